@@ -0,0 +1,120 @@
+//! `cargo bp adopt`: registering a battery pack for dependencies a project
+//! already has.
+//!
+//! The pack's features are chosen to explain as much of the existing
+//! dependency set as possible while pulling in as few crates the project
+//! doesn't have. Only the choice is made here; the command reports the
+//! differences (through [`crate::drift`]) and writes the metadata.
+
+#[cfg(test)]
+mod tests;
+
+use bphelper_manifest::BatteryPackSpec;
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+/// What adopting a pack records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Adoption {
+    /// The active features to store.
+    pub features: BTreeSet<String>,
+    /// The existing dependencies the pack takes over: its resolution for
+    /// `features`, limited to crates the project already depends on.
+    pub managed: BTreeSet<String>,
+}
+
+/// Packs with more features than this are searched greedily rather than
+/// by trying every combination.
+const EXHAUSTIVE_LIMIT: usize = 12;
+
+/// How well a feature set explains the project, best highest: crates
+/// covered, crates missing, features used, and whether `default` is one.
+type Rank = (usize, Reverse<usize>, Reverse<usize>, bool);
+
+/// Pick the features that best explain `present` (the crates the project
+/// depends on). Returns `None` when the pack curates none of them.
+///
+/// The best set covers the most existing crates, then pulls in the fewest
+/// crates the project lacks, then has the fewest features, preferring
+/// `default` on a tie.
+// [impl cli.adopt.features]
+pub(crate) fn choose_features(
+    spec: &BatteryPackSpec,
+    present: &BTreeSet<String>,
+) -> Option<Adoption> {
+    let mut candidates: BTreeSet<&str> = spec.features.keys().map(String::as_str).collect();
+    candidates.insert("default");
+    let candidates: Vec<&str> = candidates.into_iter().collect();
+
+    let rank = |features: &BTreeSet<String>| -> Rank {
+        let resolved = spec.resolve_for_features(features);
+        let covered = resolved.keys().filter(|c| present.contains(*c)).count();
+        (
+            covered,
+            Reverse(resolved.len() - covered),
+            Reverse(features.len()),
+            features.contains("default"),
+        )
+    };
+
+    let chosen = if candidates.len() <= EXHAUSTIVE_LIMIT {
+        (1u32..1 << candidates.len())
+            .map(|mask| {
+                candidates
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, f)| f.to_string())
+                    .collect::<BTreeSet<String>>()
+            })
+            .max_by_key(|features| rank(features))?
+    } else {
+        greedy(&candidates, rank)
+    };
+    if rank(&chosen).0 == 0 {
+        return None;
+    }
+
+    let managed = spec
+        .resolve_for_features(&chosen)
+        .into_keys()
+        .filter(|c| present.contains(c))
+        .collect();
+    Some(Adoption {
+        features: chosen,
+        managed,
+    })
+}
+
+/// Add the feature that ranks best until none improves the coverage, then
+/// drop features the others make redundant.
+fn greedy(candidates: &[&str], rank: impl Fn(&BTreeSet<String>) -> Rank) -> BTreeSet<String> {
+    let mut chosen = BTreeSet::new();
+    let mut covered = 0;
+    loop {
+        let best = candidates
+            .iter()
+            .filter(|f| !chosen.contains(**f))
+            .map(|f| {
+                let mut with = chosen.clone();
+                with.insert(f.to_string());
+                with
+            })
+            .max_by_key(|with| rank(with));
+        match best {
+            Some(with) if rank(&with).0 > covered => {
+                covered = rank(&with).0;
+                chosen = with;
+            }
+            _ => break,
+        }
+    }
+    for feature in chosen.clone() {
+        let mut without = chosen.clone();
+        without.remove(&feature);
+        if !without.is_empty() && rank(&without).0 == covered {
+            chosen = without;
+        }
+    }
+    chosen
+}
@@ -0,0 +1,69 @@
+//! Tests for choosing the features an adopted pack gets.
+
+use super::*;
+
+const PACK: &str = r#"
+[package]
+name = "demo-battery-pack"
+version = "0.2.0"
+keywords = ["battery-pack"]
+
+[dependencies]
+anyhow = { version = "1", optional = true }
+clap = { version = "4", optional = true }
+tracing = { version = "0.1", optional = true }
+tracing-subscriber = { version = "0.3", optional = true }
+indicatif = { version = "0.17", optional = true }
+
+[features]
+default = ["anyhow", "clap"]
+logging = ["tracing", "tracing-subscriber"]
+progress = ["indicatif"]
+everything = ["anyhow", "clap", "tracing", "tracing-subscriber", "indicatif"]
+"#;
+
+fn spec() -> BatteryPackSpec {
+    bphelper_manifest::parse_battery_pack(PACK).unwrap()
+}
+
+fn set(items: &[&str]) -> BTreeSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+fn adopt(present: &[&str]) -> Option<Adoption> {
+    choose_features(&spec(), &set(present))
+}
+
+// [verify cli.adopt.features]
+#[test]
+fn default_crates_adopt_default() {
+    assert_eq!(
+        adopt(&["anyhow", "clap", "serde"]),
+        Some(Adoption {
+            features: set(&["default"]),
+            managed: set(&["anyhow", "clap"]),
+        })
+    );
+}
+
+// [verify cli.adopt.features]
+#[test]
+fn features_are_added_for_crates_default_leaves_out() {
+    let adoption = adopt(&["anyhow", "clap", "tracing"]).unwrap();
+    assert_eq!(adoption.features, set(&["default", "logging"]));
+    assert_eq!(adoption.managed, set(&["anyhow", "clap", "tracing"]));
+}
+
+// [verify cli.adopt.features]
+#[test]
+fn the_narrowest_feature_wins_when_coverage_ties() {
+    // `everything` also covers indicatif, but drags in four missing crates.
+    let adoption = adopt(&["indicatif"]).unwrap();
+    assert_eq!(adoption.features, set(&["progress"]));
+    assert_eq!(adoption.managed, set(&["indicatif"]));
+}
+
+#[test]
+fn unrelated_dependencies_adopt_nothing() {
+    assert_eq!(adopt(&["serde", "tokio"]), None);
+}
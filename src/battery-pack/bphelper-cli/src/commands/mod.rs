@@ -9,17 +9,22 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 
+use crate::drift::{DriftKind, Finding, Hold, Outcome, PackState, ProjectDeps, Severity};
 use crate::manifest::{
-    MetadataLocation, add_dep_to_table, dep_kind_section, find_installed_bp_names,
-    find_user_manifest, find_workspace_manifest, read_active_features_from, read_managed_deps_from,
-    remove_deps_by_kind, resolve_metadata_location, should_upgrade_version, sync_dep_in_table,
-    write_bp_features_to_doc, write_deps_by_kind, write_workspace_refs_by_kind,
+    ManifestTransaction, MetadataLocation, SyncPolicy, add_dep_to_table, dep_kind_section,
+    find_installed_bp_names, find_user_manifest, find_workspace_manifest,
+    read_active_features_from, read_managed_deps_from, read_managed_features_from,
+    read_metadata_bp_names, read_pinned_from, remove_dep_features, remove_deps_by_kind,
+    resolve_metadata_location, write_bp_features_to_doc, write_deps_by_kind,
+    write_managed_features_to_doc, write_workspace_refs_by_kind,
 };
+use crate::output::MessageFormat;
 use crate::registry::{
-    CrateSource, InstalledPack, TemplateConfig, fetch_battery_pack_detail,
-    fetch_battery_pack_detail_from_source, fetch_battery_pack_list, fetch_bp_spec,
-    load_installed_bp_spec, resolve_crate_name, short_name,
+    CrateSource, InstalledPack, ListQuery, ListSort, TemplateConfig, fetch_all,
+    fetch_battery_pack_detail, fetch_battery_pack_detail_from_source, fetch_battery_pack_list,
+    fetch_bp_spec, format_downloads, load_installed_bp_specs, resolve_crate_name, short_name,
 };
+use crate::workspace::{Member, MemberSelection};
 
 // [impl cli.bare.help]
 #[derive(Parser)]
@@ -73,6 +78,16 @@ pub(crate) enum BpCommands {
         /// Set a template placeholder value (e.g., -d description="My project")
         #[arg(long = "define", short = 'd', value_parser = parse_define)]
         define: Vec<(String, String)>,
+
+        /// Build the new battery pack from this project's dependencies
+        /// (only with `cargo bp new battery-pack`)
+        #[arg(long, value_name = "PROJECT")]
+        from: Option<PathBuf>,
+
+        /// Group extracted crates into a feature (e.g., --group fmt=serde,serde_json);
+        /// crates not named by any group go into `default`
+        #[arg(long, value_name = "FEATURE=CRATES", requires = "from", value_parser = crate::extract::parse_group)]
+        group: Vec<(String, Vec<String>)>,
     },
 
     /// Add a battery pack and sync its dependencies.
@@ -81,6 +96,7 @@ pub(crate) enum BpCommands {
     /// With a battery pack name, adds that specific pack (with an interactive picker
     /// for choosing crates if the pack has features or many dependencies).
     /// Re-running on an already-installed pack lets you edit the selection.
+    /// Name more packs (e.g. `cli logging error:backtrace`) to add them together.
     #[command(visible_alias = "edit")]
     Add {
         /// Name of the battery pack (e.g., "cli" resolves to "cli-battery-pack").
@@ -88,13 +104,15 @@ pub(crate) enum BpCommands {
         #[arg(add = clap_complete::ArgValueCompleter::new(crate::completions::registry_and_local_packs))]
         battery_pack: Option<String>,
 
-        /// Specific crates to add from the battery pack (ignores defaults/features)
+        /// Specific crates to add from the battery pack (ignores defaults/features),
+        /// or more battery packs to add, as `pack` or `pack:feature,...`
         #[arg(add = clap_complete::ArgValueCompleter::new(crate::completions::pack_crates))]
         crates: Vec<String>,
 
         // [impl cli.add.features]
         // [impl cli.add.features-multiple]
-        /// Named features to enable (comma-separated or repeated)
+        /// Named features to enable (comma-separated or repeated); `pack/feature`
+        /// enables a feature on another pack, adding it too
         #[arg(long = "features", short = 'F', value_delimiter = ',', add = clap_complete::ArgValueCompleter::new(crate::completions::pack_features))]
         features: Vec<String>,
 
@@ -129,6 +147,10 @@ pub(crate) enum BpCommands {
         /// Overwrite existing files without prompting (TOML and YAML files are always merged, never overwritten)
         #[arg(long)]
         overwrite: bool,
+
+        // [impl cli.workspace.select]
+        #[command(flatten)]
+        members: MemberSelection,
     },
 
     /// Update dependencies from installed battery packs
@@ -137,6 +159,26 @@ pub(crate) enum BpCommands {
         /// Use a local path instead of downloading from crates.io
         #[arg(long)]
         path: Option<String>,
+
+        /// Show a diff of what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Exit with an error if sync would change anything (for CI)
+        #[arg(long, conflicts_with = "dry_run")]
+        check: bool,
+
+        /// Also remove crates the battery packs no longer curate (asks first)
+        #[arg(long)]
+        prune: bool,
+
+        /// With --prune, also remove Cargo features the packs no longer curate
+        #[arg(long, requires = "prune")]
+        prune_features: bool,
+
+        // [impl cli.workspace.select]
+        #[command(flatten)]
+        members: MemberSelection,
     },
 
     /// Remove a battery pack from the current project
@@ -153,6 +195,90 @@ pub(crate) enum BpCommands {
         /// Keep all dependencies (don't prompt)
         #[arg(long)]
         keep_deps: bool,
+
+        // [impl cli.workspace.select]
+        #[command(flatten)]
+        members: MemberSelection,
+    },
+
+    /// Enable features of an installed battery pack and add their crates
+    Enable {
+        /// Name of the installed battery pack (e.g., "cli" resolves to "cli-battery-pack")
+        #[arg(add = clap_complete::ArgValueCompleter::new(crate::completions::installed_packs))]
+        battery_pack: String,
+
+        /// Features to enable
+        #[arg(required = true, add = clap_complete::ArgValueCompleter::new(crate::completions::pack_features))]
+        features: Vec<String>,
+
+        /// Use a local path instead of downloading from crates.io
+        #[arg(long)]
+        path: Option<String>,
+    },
+
+    /// Disable features of an installed battery pack and remove crates no
+    /// remaining feature needs
+    Disable {
+        /// Name of the installed battery pack (e.g., "cli" resolves to "cli-battery-pack")
+        #[arg(add = clap_complete::ArgValueCompleter::new(crate::completions::installed_packs))]
+        battery_pack: String,
+
+        /// Features to disable
+        #[arg(required = true, add = clap_complete::ArgValueCompleter::new(crate::completions::pack_features))]
+        features: Vec<String>,
+
+        /// Use a local path instead of downloading from crates.io
+        #[arg(long)]
+        path: Option<String>,
+    },
+
+    /// Upgrade installed battery packs to newer releases, then sync
+    Upgrade {
+        /// Battery pack to upgrade (defaults to every installed pack)
+        #[arg(add = clap_complete::ArgValueCompleter::new(crate::completions::installed_packs))]
+        battery_pack: Option<String>,
+
+        /// Also move to releases outside the registered version requirement
+        #[arg(long)]
+        breaking: bool,
+    },
+
+    /// Register a battery pack for dependencies the project already has,
+    /// without changing them
+    Adopt {
+        /// Name of the battery pack (e.g., "cli" resolves to "cli-battery-pack")
+        #[arg(add = clap_complete::ArgValueCompleter::new(crate::completions::registry_and_local_packs))]
+        battery_pack: String,
+
+        /// Also bring the adopted dependencies in line with the pack, as
+        /// `cargo bp sync` does
+        #[arg(long)]
+        sync: bool,
+
+        /// Use a local path instead of downloading from crates.io
+        #[arg(long)]
+        path: Option<String>,
+    },
+
+    /// Find and repair inconsistent battery-pack metadata
+    Doctor {
+        /// Apply every available fix without asking
+        #[arg(long)]
+        fix: bool,
+
+        /// Use a local path instead of downloading from crates.io
+        #[arg(long)]
+        path: Option<String>,
+    },
+
+    /// Restore the files changed by an earlier cargo bp command
+    Undo {
+        /// Snapshot to restore (defaults to the most recent; see --list)
+        snapshot: Option<String>,
+
+        /// List the saved snapshots instead of restoring one
+        #[arg(long, conflicts_with = "snapshot")]
+        list: bool,
     },
 
     /// List available battery packs on crates.io
@@ -160,6 +286,20 @@ pub(crate) enum BpCommands {
     List {
         /// Filter by name (omit to list all battery packs)
         filter: Option<String>,
+
+        /// Sort order
+        // [impl cli.list.sort]
+        #[arg(long, value_enum, default_value_t = ListSort::Downloads)]
+        sort: ListSort,
+
+        /// Only list battery packs with this keyword
+        // [impl cli.list.keyword]
+        #[arg(long)]
+        keyword: Option<String>,
+        /// Output format
+        // [impl cli.json.flag]
+        #[arg(long, value_enum, default_value_t)]
+        message_format: MessageFormat,
     },
 
     /// Show detailed information about a battery pack
@@ -177,6 +317,11 @@ pub(crate) enum BpCommands {
         /// Use a local path instead of downloading from crates.io
         #[arg(long)]
         path: Option<String>,
+
+        /// Output format
+        // [impl cli.json.flag]
+        #[arg(long, value_enum, default_value_t)]
+        message_format: MessageFormat,
     },
 
     /// Show status of installed battery packs and version warnings
@@ -186,6 +331,15 @@ pub(crate) enum BpCommands {
         /// Use a local path instead of downloading from crates.io
         #[arg(long)]
         path: Option<String>,
+
+        /// Output format
+        // [impl cli.json.flag]
+        #[arg(long, value_enum, default_value_t)]
+        message_format: MessageFormat,
+
+        // [impl cli.workspace.select]
+        #[command(flatten)]
+        members: MemberSelection,
     },
 
     /// Check that installed battery packs match project dependencies
@@ -193,6 +347,37 @@ pub(crate) enum BpCommands {
         /// Use a local path instead of downloading from crates.io
         #[arg(long)]
         path: Option<String>,
+
+        /// Output format
+        // [impl cli.json.flag]
+        #[arg(long, value_enum, default_value_t)]
+        message_format: MessageFormat,
+
+        // [impl cli.workspace.select]
+        #[command(flatten)]
+        members: MemberSelection,
+    },
+
+    /// Explain which battery packs and features bring in a dependency
+    Why {
+        /// Name of the dependency to explain
+        #[arg(value_name = "CRATE")]
+        crate_name: String,
+
+        /// Use a local path instead of downloading from crates.io
+        #[arg(long)]
+        path: Option<String>,
+    },
+
+    /// Show installed battery packs, their active features and crates as a tree
+    Tree {
+        /// Use a local path instead of downloading from crates.io
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: crate::tree::TreeFormat,
     },
 
     /// Validate that the current battery pack is well-formed
@@ -200,6 +385,11 @@ pub(crate) enum BpCommands {
         /// Path to the battery pack crate (defaults to current directory)
         #[arg(long)]
         path: Option<String>,
+
+        /// Output format
+        // [impl cli.json.flag]
+        #[arg(long, value_enum, default_value_t)]
+        message_format: MessageFormat,
     },
 
     /// Print the one-line shell configuration to enable native shell completions
@@ -208,10 +398,45 @@ pub(crate) enum BpCommands {
         shell: Option<String>,
     },
 
+    /// Inspect and edit cargo-bp configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
     #[command(hide = true)]
     UpdateCache,
 }
 
+// [impl cli.config.get]
+// [impl cli.config.set]
+// [impl cli.config.list]
+#[derive(Subcommand)]
+pub(crate) enum ConfigCommands {
+    /// Print the effective value of a configuration key
+    Get {
+        /// Key to read (e.g., "target", "sync.policy", "aliases.web")
+        key: String,
+    },
+
+    /// Set a configuration key in the project's .cargo-bp.toml
+    Set {
+        /// Key to write (e.g., "target", "sync.policy", "aliases.web")
+        key: String,
+
+        /// Value to store
+        value: String,
+
+        /// Write to the user config (~/.config/cargo-bp/config.toml) instead
+        #[arg(long)]
+        global: bool,
+    },
+
+    /// List all effective configuration values and where they come from
+    #[command(visible_alias = "ls")]
+    List,
+}
+
 // [impl cli.add.target]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub(crate) enum AddTarget {
@@ -235,11 +460,37 @@ pub fn main() -> Result<()> {
             non_interactive,
             command,
         } => {
-            let source = match crate_source {
+            // [impl cli.config.layers]
+            // Config files and env vars fill in whatever the flags leave unset.
+            let config = match crate::config::Config::load(&project_dir) {
+                Ok(config) => config,
+                // `cargo bp config` must still run, so a broken file can be
+                // fixed with `config set`.
+                // [impl cli.config.broken]
+                Err(err) if matches!(command, BpCommands::Config { .. }) => {
+                    eprintln!("warning: {:#}", err);
+                    crate::config::Config::default()
+                }
+                Err(err) => return Err(err),
+            };
+            crate::registry::configure_endpoints(
+                config.registry.api.as_deref(),
+                config.registry.download.as_deref(),
+            );
+            crate::forge::configure_hosts(config.forges.clone());
+            let source = match crate_source.or_else(|| config.crate_source.clone()) {
                 Some(path) => CrateSource::Local(path),
                 None => CrateSource::Registry,
             };
             let interactive = interactive && !non_interactive;
+            // [impl cli.undo.snapshot]
+            // Undo writes through the same code paths, so it isn't recorded.
+            let _recording = (!matches!(command, BpCommands::Undo { .. }))
+                .then(|| {
+                    let args: Vec<String> = std::env::args().skip(1).collect();
+                    crate::history::begin(&project_dir, format!("cargo {}", args.join(" ")))
+                })
+                .flatten();
             match command {
                 BpCommands::New {
                     battery_pack,
@@ -247,15 +498,23 @@ pub fn main() -> Result<()> {
                     template,
                     path,
                     define,
-                } => new_from_battery_pack(NewFromBpOpts {
-                    battery_pack: &battery_pack,
-                    name,
-                    template,
-                    path_override: path,
-                    source: &source,
-                    define: &define,
-                    interactive,
-                }),
+                    from,
+                    group,
+                } => {
+                    let opts = NewFromBpOpts {
+                        battery_pack: &config.resolve_alias(&battery_pack),
+                        name,
+                        template,
+                        path_override: path,
+                        source: &source,
+                        define: &config.defines_with(&define),
+                        interactive,
+                    };
+                    match from {
+                        Some(from) => extract_battery_pack(opts, &from, &group),
+                        None => new_from_battery_pack(opts).map(drop),
+                    }
+                }
                 BpCommands::Add {
                     battery_pack,
                     crates,
@@ -267,7 +526,14 @@ pub fn main() -> Result<()> {
                     template,
                     define,
                     overwrite,
-                } => match (battery_pack, template) {
+                    members,
+                } => match (battery_pack.map(|n| config.resolve_alias(&n)), template) {
+                    (Some(_), Some(_)) | (None, _) if members.is_explicit() => {
+                        bail!(
+                            "--workspace and --package apply to adding a battery pack, \
+                             not to templates or the interactive manager"
+                        )
+                    }
                     // Template merge: cargo bp add <pack> -t <template>
                     (Some(name), Some(tmpl)) => add_template(AddTemplateOpts {
                         battery_pack: &name,
@@ -275,61 +541,238 @@ pub fn main() -> Result<()> {
                         path_override: path.as_deref(),
                         source: &source,
                         project_dir: &project_dir,
-                        defines: define.into_iter().collect(),
+                        defines: config.defines_with(&define).into_iter().collect(),
                         overwrite,
                         interactive,
                     }),
-                    // Normal add: cargo bp add <pack>
-                    (Some(name), None) => add_battery_pack(
-                        &name,
-                        &features,
-                        no_default_features,
-                        all_features,
-                        &crates,
-                        target,
-                        path.as_deref(),
-                        &source,
-                        &project_dir,
-                    ),
+                    // Normal add: cargo bp add <pack> [<crate>...] [<pack>...]
+                    (Some(name), None) => {
+                        let requests = group_add_args(
+                            &name,
+                            &crates,
+                            &features,
+                            path.as_deref(),
+                            &source,
+                            |n| config.resolve_alias(n),
+                        )?;
+                        if let [request] = requests.as_slice() {
+                            return for_each_member(
+                                &project_dir,
+                                &members,
+                                |_| true,
+                                "",
+                                |dir| {
+                                    add_battery_pack(
+                                        &request.name,
+                                        &request.features,
+                                        no_default_features,
+                                        all_features,
+                                        &request.crates,
+                                        target.or(config.target),
+                                        path.as_deref(),
+                                        &source,
+                                        dir,
+                                    )
+                                },
+                            );
+                        }
+                        let packs = resolve_added_packs(
+                            &requests,
+                            no_default_features,
+                            all_features,
+                            &source,
+                        )?;
+                        if !confirm_added_packs(&packs, interactive) {
+                            println!("Cancelled.");
+                            return Ok(());
+                        }
+                        for_each_member(
+                            &project_dir,
+                            &members,
+                            |_| true,
+                            "",
+                            |dir| {
+                                write_added_packs(
+                                    &packs,
+                                    target.or(config.target),
+                                    None,
+                                    &source,
+                                    dir,
+                                )
+                            },
+                        )
+                    }
                     (None, _) => show_add_help(&project_dir),
                 },
-                BpCommands::Sync { path } => {
-                    sync_battery_packs(&project_dir, path.as_deref(), &source)
+                BpCommands::Sync {
+                    path,
+                    dry_run,
+                    check,
+                    prune,
+                    prune_features,
+                    members,
+                } => {
+                    let prune = match (prune, prune_features) {
+                        (false, _) => Prune::Off,
+                        (true, false) => Prune::Crates,
+                        (true, true) => Prune::CratesAndFeatures,
+                    };
+                    let mode = if check {
+                        SyncMode::Check
+                    } else if dry_run {
+                        SyncMode::DryRun
+                    } else {
+                        SyncMode::Write
+                    };
+                    // Every member is synced (or checked) before `--check`
+                    // fails, so one run reports them all.
+                    let mut out_of_sync = 0;
+                    for_each_member(
+                        &project_dir,
+                        &members,
+                        |packs| !packs.is_empty(),
+                        "battery packs",
+                        |dir| {
+                            let in_sync = sync_manifests(
+                                dir,
+                                path.as_deref(),
+                                &source,
+                                config.sync_policy,
+                                mode,
+                                prune,
+                                interactive,
+                            )?;
+                            out_of_sync += usize::from(!in_sync);
+                            Ok(())
+                        },
+                    )?;
+                    // [impl cli.sync.check]
+                    // [impl cli.workspace.combined]
+                    if mode == SyncMode::Check && out_of_sync > 0 {
+                        if members.is_explicit() {
+                            bail!(
+                                "{} workspace member(s) are out of sync; run `cargo bp sync` \
+                                 with the same selection to update",
+                                out_of_sync
+                            );
+                        }
+                        bail!(
+                            "battery pack dependencies are out of sync; run `cargo bp sync` to update"
+                        );
+                    }
+                    Ok(())
                 }
                 BpCommands::Rm {
                     battery_pack,
                     remove_deps,
                     keep_deps,
-                } => remove_battery_pack(
-                    &battery_pack,
-                    remove_deps,
-                    keep_deps,
-                    interactive,
+                    members,
+                } => {
+                    let name = config.resolve_alias(&battery_pack);
+                    let crate_name = resolve_crate_name(&name);
+                    for_each_member(
+                        &project_dir,
+                        &members,
+                        |packs| packs.contains(&crate_name),
+                        &crate_name,
+                        |dir| remove_battery_pack(&name, remove_deps, keep_deps, interactive, dir),
+                    )
+                }
+                BpCommands::Enable {
+                    battery_pack,
+                    features,
+                    path,
+                } => toggle_features(
+                    &config.resolve_alias(&battery_pack),
+                    &features,
+                    FeatureToggle::Enable,
+                    path.as_deref(),
+                    &source,
+                    &project_dir,
+                ),
+                BpCommands::Disable {
+                    battery_pack,
+                    features,
+                    path,
+                } => toggle_features(
+                    &config.resolve_alias(&battery_pack),
+                    &features,
+                    FeatureToggle::Disable,
+                    path.as_deref(),
+                    &source,
+                    &project_dir,
+                ),
+                BpCommands::Upgrade {
+                    battery_pack,
+                    breaking,
+                } => upgrade_battery_packs(
                     &project_dir,
+                    battery_pack.as_deref(),
+                    breaking,
+                    &source,
+                    config.sync_policy,
                 ),
-                BpCommands::List { filter } => {
+                BpCommands::Adopt {
+                    battery_pack,
+                    sync,
+                    path,
+                } => adopt_battery_pack(AdoptOpts {
+                    name: &config.resolve_alias(&battery_pack),
+                    sync,
+                    path: path.as_deref(),
+                    source: &source,
+                    policy: config.sync_policy,
+                    project_dir: &project_dir,
+                    interactive,
+                }),
+                BpCommands::Doctor { fix, path } => {
+                    run_doctor(&project_dir, fix, path.as_deref(), &source, interactive)
+                }
+                BpCommands::Undo { snapshot, list } => {
+                    if list {
+                        list_snapshots(&project_dir)
+                    } else {
+                        undo_snapshot(&project_dir, snapshot.as_deref())
+                    }
+                }
+                BpCommands::List {
+                    filter,
+                    sort,
+                    keyword,
+                    message_format,
+                } => {
+                    let query = ListQuery {
+                        filter,
+                        keyword,
+                        sort,
+                    };
                     // [impl cli.list.interactive]
                     // [impl cli.list.non-interactive]
-                    if interactive {
-                        crate::tui::run_list(source, filter)
+                    if interactive && !message_format.is_json() {
+                        crate::tui::run_list(source, query)
                     } else {
                         // [impl cli.list.query]
                         // [impl cli.list.filter]
-                        print_battery_pack_list(&source, filter.as_deref())
+                        print_battery_pack_list(&source, &query, message_format)
                     }
                 }
                 BpCommands::Show {
                     battery_pack,
                     template,
                     path,
+                    message_format,
                 } => {
+                    let battery_pack = config.resolve_alias(&battery_pack);
                     let show_opts = crate::tui::ShowOpts {
                         battery_pack: &battery_pack,
                         template: template.as_deref(),
                         path: path.as_deref(),
                         source,
                     };
-                    if interactive {
+                    if message_format.is_json() && show_opts.template.is_some() {
+                        bail!("--template cannot be combined with --message-format json");
+                    }
+                    if interactive && !message_format.is_json() {
                         // [impl cli.show.interactive]
                         // [impl cli.show.template-preview]
                         crate::tui::run_show(show_opts)
@@ -348,18 +791,60 @@ pub fn main() -> Result<()> {
                             show_opts.path,
                             &show_opts.source,
                             &project_dir,
+                            message_format,
                         )
                     }
                 }
-                BpCommands::Status { path } => {
-                    status_battery_packs(&project_dir, path.as_deref(), &source)
-                }
-                BpCommands::Check { path } => {
-                    check_battery_packs(&project_dir, path.as_deref(), &source)
+                BpCommands::Status {
+                    path,
+                    message_format,
+                    members,
+                } => report_members(&project_dir, &members, "status", message_format, |dir| {
+                    status_battery_packs(
+                        dir,
+                        path.as_deref(),
+                        &source,
+                        config.sync_policy,
+                        message_format,
+                    )
+                })
+                .map(|_| ()),
+                BpCommands::Why { crate_name, path } => {
+                    why_dependency(&project_dir, &crate_name, path.as_deref(), &source)
                 }
-                BpCommands::Validate { path } => {
-                    crate::validate::validate_battery_pack_cmd(path.as_deref())
+                BpCommands::Tree { path, format } => tree_battery_packs(
+                    &project_dir,
+                    path.as_deref(),
+                    &source,
+                    config.sync_policy,
+                    format,
+                ),
+                BpCommands::Check {
+                    path,
+                    message_format,
+                    members,
+                } => {
+                    let outcome =
+                        report_members(&project_dir, &members, "check", message_format, |dir| {
+                            check_battery_packs(
+                                dir,
+                                path.as_deref(),
+                                &source,
+                                config.sync_policy,
+                                message_format,
+                            )
+                        })?;
+                    // [impl cli.drift.exit-codes]
+                    if outcome != Outcome::Clean {
+                        std::io::Write::flush(&mut std::io::stdout())?;
+                        std::process::exit(outcome.exit_code());
+                    }
+                    Ok(())
                 }
+                BpCommands::Validate {
+                    path,
+                    message_format,
+                } => crate::validate::validate_battery_pack_cmd(path.as_deref(), message_format),
                 BpCommands::Completions { shell } => {
                     let shell_name = shell.unwrap_or_else(|| {
                         std::env::var("SHELL")
@@ -370,6 +855,13 @@ pub fn main() -> Result<()> {
                     println!("source <(COMPLETE={} cargo-bp)", shell_name);
                     Ok(())
                 }
+                BpCommands::Config { command } => match command {
+                    ConfigCommands::Get { key } => crate::config::config_get(&config, &key),
+                    ConfigCommands::Set { key, value, global } => {
+                        crate::config::config_set(&project_dir, &key, &value, global)
+                    }
+                    ConfigCommands::List => crate::config::config_list(&config),
+                },
                 BpCommands::UpdateCache => {
                     let _ = crate::registry::update_cache();
                     Ok(())
@@ -399,7 +891,7 @@ struct NewFromBpOpts<'a> {
 // [impl cli.new.name-prompt]
 // [impl cli.path.flag]
 // [impl cli.source.replace]
-fn new_from_battery_pack(opts: NewFromBpOpts<'_>) -> Result<()> {
+fn new_from_battery_pack(opts: NewFromBpOpts<'_>) -> Result<PathBuf> {
     if !opts.interactive && opts.name.is_none() {
         bail!("--name is required in non-interactive mode");
     }
@@ -432,6 +924,130 @@ fn new_from_battery_pack(opts: NewFromBpOpts<'_>) -> Result<()> {
     generate_from_path(new_opts, &resolved.dir, &template_path)
 }
 
+/// `cargo bp new battery-pack --from <project>`: generate a battery pack
+/// from the template, curating the project's dependencies.
+// [impl cli.new.from]
+// [impl cli.new.from.pick]
+fn extract_battery_pack(
+    opts: NewFromBpOpts<'_>,
+    from: &Path,
+    groups: &[(String, Vec<String>)],
+) -> Result<()> {
+    if resolve_crate_name(opts.battery_pack) != "battery-pack" {
+        bail!("--from only works with `cargo bp new battery-pack`");
+    }
+    let mut deps = crate::extract::read_dependencies(from)?;
+    if deps.is_empty() {
+        bail!("{} has no registry dependencies to extract", from.display());
+    }
+
+    let groups = if opts.interactive {
+        deps = pick_extracted_crates(deps)?;
+        if groups.is_empty() {
+            group_extracted_crates(&deps)?
+        } else {
+            groups.to_vec()
+        }
+    } else {
+        groups.to_vec()
+    };
+    let features = crate::extract::features(&deps, &groups)?;
+
+    let project_dir = new_from_battery_pack(opts)?;
+    let manifest_path = project_dir.join("Cargo.toml");
+    let template = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let content = crate::extract::write_pack_manifest(&template, &deps, &features)?;
+    std::fs::write(&manifest_path, content)
+        .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+
+    println!(
+        "Extracted {} crate(s) into {}",
+        deps.len(),
+        project_dir.display()
+    );
+    for (feature, crates) in &features {
+        if !crates.is_empty() {
+            let crates: Vec<&str> = crates.iter().map(String::as_str).collect();
+            println!("  {}: {}", feature, crates.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Let the user choose which of the extracted crates the pack curates.
+fn pick_extracted_crates(
+    deps: BTreeMap<String, crate::extract::ExtractedDep>,
+) -> Result<BTreeMap<String, crate::extract::ExtractedDep>> {
+    let labels: Vec<String> = deps
+        .iter()
+        .map(|(name, dep)| match dep.kind {
+            bphelper_manifest::DepKind::Normal => format!("{} {}", name, dep.version),
+            kind => format!(
+                "{} {} [{}]",
+                name,
+                dep.version,
+                crate::manifest::dep_kind_section(kind)
+            ),
+        })
+        .collect();
+    let chosen = dialoguer::MultiSelect::new()
+        .with_prompt("Crates to curate (space to toggle, enter to confirm)")
+        .items(&labels)
+        .defaults(&vec![true; labels.len()])
+        .interact()
+        .context("prompt failed")?;
+    if chosen.is_empty() {
+        bail!("No crates selected");
+    }
+    Ok(deps
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| chosen.contains(i))
+        .map(|(_, dep)| dep)
+        .collect())
+}
+
+/// Ask for features one at a time until the user enters an empty name.
+fn group_extracted_crates(
+    deps: &BTreeMap<String, crate::extract::ExtractedDep>,
+) -> Result<Vec<(String, Vec<String>)>> {
+    let mut ungrouped: Vec<&String> = deps
+        .iter()
+        .filter(|(_, dep)| dep.kind == bphelper_manifest::DepKind::Normal)
+        .map(|(name, _)| name)
+        .collect();
+    let mut groups = Vec::new();
+    while !ungrouped.is_empty() {
+        let feature: String = dialoguer::Input::new()
+            .with_prompt("Feature name (empty to put the rest in default)")
+            .allow_empty(true)
+            .interact_text()
+            .context("prompt failed")?;
+        let feature = feature.trim();
+        if feature.is_empty() {
+            break;
+        }
+        let chosen = dialoguer::MultiSelect::new()
+            .with_prompt(format!("Crates in '{}'", feature))
+            .items(&ungrouped)
+            .interact()
+            .context("prompt failed")?;
+        if chosen.is_empty() {
+            continue;
+        }
+        let crates: Vec<String> = chosen.iter().map(|&i| ungrouped[i].clone()).collect();
+        ungrouped = ungrouped
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !chosen.contains(i))
+            .map(|(_, name)| name)
+            .collect();
+        groups.push((feature.to_string(), crates));
+    }
+    Ok(groups)
+}
+
 /// Result of resolving which crates to add from a battery pack.
 pub(crate) enum ResolvedAdd {
     /// Resolved to a concrete set of crates (no interactive picker needed).
@@ -571,7 +1187,7 @@ fn check_git_clean(project_dir: &Path, interactive: bool, overwrite: bool) -> Re
 
     eprintln!(
         "warning: git working tree has uncommitted changes. \
-         Template merge may be hard to undo."
+         `cargo bp undo` can revert the merge, but git won't tell its edits from yours."
     );
 
     if !interactive {
@@ -762,6 +1378,132 @@ pub(crate) fn add_battery_pack(
     }
 
     // Step 3: Now write everything — build-dep, workspace deps, crate deps, metadata.
+    write_added_packs(
+        &[AddedPack {
+            crate_name,
+            bp_version,
+            bp_spec,
+            active_features,
+            crates: crates_to_sync,
+        }],
+        target,
+        path,
+        source,
+        project_dir,
+    )
+}
+
+/// Group `cargo bp add`'s arguments by battery pack (see
+/// [`crate::multi_add`]), fetching each candidate pack's spec once.
+// [impl cli.add.multiple]
+fn group_add_args(
+    first: &str,
+    args: &[String],
+    features: &[String],
+    path: Option<&str>,
+    source: &CrateSource,
+    resolve_alias: impl Fn(&str) -> String,
+) -> Result<Vec<crate::multi_add::PackRequest>> {
+    let first_name = resolve_alias(first.split(':').next().unwrap_or(first));
+    let requests = crate::multi_add::group(first, args, features, resolve_alias, |name| {
+        let spec = match path {
+            Some(local) if name == first_name => {
+                crate::registry::load_installed_bp_spec(name, Some(local), source).ok()
+            }
+            _ => fetch_bp_spec(source, name).ok().map(|(_, spec)| spec),
+        };
+        spec.map(|spec| spec.crates.into_keys().collect())
+    })?;
+    if requests.len() > 1 && path.is_some() {
+        bail!("--path can only be used when adding a single battery pack");
+    }
+    Ok(requests)
+}
+
+/// Resolve several requested packs for `cargo bp add` without a picker:
+/// each gets its named features and crates, or its defaults.
+// [impl cli.add.multiple]
+fn resolve_added_packs(
+    requests: &[crate::multi_add::PackRequest],
+    no_default_features: bool,
+    all_features: bool,
+    source: &CrateSource,
+) -> Result<Vec<AddedPack>> {
+    requests
+        .iter()
+        .map(|request| {
+            let crate_name = resolve_crate_name(&request.name);
+            let (bp_version, bp_spec) = fetch_bp_spec(source, &request.name)?;
+            let (active_features, crates) = match resolve_add_crates(
+                &bp_spec,
+                &crate_name,
+                &request.features,
+                no_default_features,
+                all_features,
+                &request.crates,
+            ) {
+                ResolvedAdd::Crates {
+                    active_features,
+                    crates,
+                } => (active_features, crates),
+                ResolvedAdd::Interactive => (
+                    BTreeSet::from(["default".to_string()]),
+                    bp_spec.resolve_crates(&["default"]),
+                ),
+            };
+            Ok(AddedPack {
+                crate_name,
+                bp_version,
+                bp_spec,
+                active_features,
+                crates,
+            })
+        })
+        .collect()
+}
+
+/// Show what adding several packs will do and, when interactive, ask once.
+fn confirm_added_packs(packs: &[AddedPack], interactive: bool) -> bool {
+    println!("Adding {} battery packs:", packs.len());
+    for pack in packs {
+        let features: Vec<&str> = pack.active_features.iter().map(String::as_str).collect();
+        let crates: Vec<&str> = pack.crates.keys().map(String::as_str).collect();
+        let label = if features.is_empty() {
+            pack.crate_name.clone()
+        } else {
+            format!("{} ({})", pack.crate_name, features.join(", "))
+        };
+        println!("  {}: {}", label, crates.join(", "));
+    }
+    if !interactive {
+        return true;
+    }
+    dialoguer::Confirm::new()
+        .with_prompt("Add these battery packs?")
+        .default(true)
+        .interact()
+        .unwrap_or(false)
+}
+
+/// A battery pack resolved for `cargo bp add`, ready to be written.
+pub(crate) struct AddedPack {
+    pub crate_name: String,
+    pub bp_version: Option<String>,
+    pub bp_spec: bphelper_manifest::BatteryPackSpec,
+    pub active_features: BTreeSet<String>,
+    pub crates: BTreeMap<String, bphelper_manifest::CrateSpec>,
+}
+
+/// Write resolved battery packs into the project — registration, crate
+/// dependencies and metadata — planned together and written once.
+// [impl cli.add.multiple]
+fn write_added_packs(
+    added: &[AddedPack],
+    target: Option<AddTarget>,
+    path: Option<&str>,
+    source: &CrateSource,
+    project_dir: &Path,
+) -> Result<()> {
     let user_manifest_path = find_user_manifest(project_dir)?;
     let user_manifest_content =
         std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
@@ -774,7 +1516,7 @@ pub(crate) fn add_battery_pack(
     let workspace_manifest = find_workspace_manifest(&user_manifest_path)?;
 
     // [impl manifest.deps.workspace]
-    // Add crate dependencies + workspace deps (including the battery pack itself).
+    // Add crate dependencies + workspace deps (including the battery packs themselves).
     // Load workspace doc once; both deps and metadata are written to it before a
     // single flush at the end (avoids a double read-modify-write).
     let mut ws_doc: Option<toml_edit::DocumentMut> = if let Some(ref ws_path) = workspace_manifest {
@@ -793,128 +1535,197 @@ pub(crate) fn add_battery_pack(
         let ws_deps = doc["workspace"]["dependencies"]
             .or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
         if let Some(ws_table) = ws_deps.as_table_mut() {
-            // Add the battery pack itself to workspace deps
-            if let Some(local_path) = path {
-                let mut dep = toml_edit::InlineTable::new();
-                dep.insert("path", toml_edit::Value::from(local_path));
-                ws_table.insert(
-                    &crate_name,
-                    toml_edit::Item::Value(toml_edit::Value::InlineTable(dep)),
-                );
-            } else {
-                let version = bp_version
-                    .as_ref()
-                    .context("battery pack version not available (--path without workspace)")?;
-                ws_table.insert(&crate_name, toml_edit::value(version));
-            }
-            // Add the resolved crate dependencies
-            for (dep_name, dep_spec) in &crates_to_sync {
-                add_dep_to_table(ws_table, dep_name, dep_spec);
+            // Add the battery packs themselves to workspace deps
+            for pack in added {
+                if let Some(local_path) = path {
+                    let mut dep = toml_edit::InlineTable::new();
+                    dep.insert("path", toml_edit::Value::from(local_path));
+                    ws_table.insert(
+                        &pack.crate_name,
+                        toml_edit::Item::Value(toml_edit::Value::InlineTable(dep)),
+                    );
+                } else {
+                    let version = pack
+                        .bp_version
+                        .as_ref()
+                        .context("battery pack version not available (--path without workspace)")?;
+                    ws_table.insert(&pack.crate_name, toml_edit::value(version));
+                }
             }
         }
-
-        // [impl cli.add.dep-kind]
-        write_workspace_refs_by_kind(&mut user_doc, &crates_to_sync, false);
-    } else {
-        // [impl manifest.deps.no-workspace]
-        // [impl cli.add.dep-kind]
-        write_deps_by_kind(&mut user_doc, &crates_to_sync, false);
     }
 
-    // [impl manifest.register.location]
-    // [impl manifest.register.format]
-    // [impl manifest.features.storage]
-    // [impl cli.add.target]
-    // Edit semantics: remove deselected crates from previous installation
+    // [impl manifest.merge.plan]
+    // Plan against every installed pack, so crates these packs share with
+    // others keep the widest version, features and dependency kinds.
     let metadata_location = resolve_metadata_location(&user_manifest_path)?;
-    let prev_managed =
-        read_managed_deps_from(&metadata_location, &user_manifest_content, &crate_name);
-    let new_crate_names: BTreeSet<String> = crates_to_sync.keys().cloned().collect();
-    let mut removed_count = 0;
-
-    if let Some(prev) = &prev_managed {
-        // Find crates that were previously managed but are no longer selected
-        let to_remove: BTreeMap<String, bphelper_manifest::CrateSpec> = prev
+    let adding: BTreeSet<String> = added.iter().map(|p| p.crate_name.clone()).collect();
+    let others =
+        other_pack_resolutions(&user_manifest_content, &metadata_location, &adding, source);
+    let pinned: Vec<BTreeSet<String>> = added
+        .iter()
+        .map(|pack| read_pinned_from(&metadata_location, &user_manifest_content, &pack.crate_name))
+        .collect();
+    let dep_plan = crate::plan::DepPlan::new(
+        others
             .iter()
-            .filter(|name| !new_crate_names.contains(name.as_str()))
-            .filter_map(|name| {
-                bp_spec
-                    .crates
-                    .get(name)
-                    .map(|spec| (name.clone(), spec.clone()))
-            })
-            .collect();
-
-        if !to_remove.is_empty() {
-            if let Some(ref mut doc) = ws_doc {
-                // Remove from workspace deps
-                let ws_deps = doc["workspace"]["dependencies"].as_table_mut();
-                if let Some(ws_table) = ws_deps {
-                    for name in to_remove.keys() {
-                        ws_table.remove(name);
-                    }
-                }
-            }
-            removed_count = remove_deps_by_kind(&mut user_doc, &to_remove);
-        }
-    }
+            .map(|(expected, pinned)| (expected, pinned))
+            .chain(added.iter().map(|pack| &pack.crates).zip(&pinned)),
+    );
+    let new_crate_names: BTreeSet<String> = added
+        .iter()
+        .flat_map(|pack| pack.crates.keys().cloned())
+        .collect();
+    // Crates the manifest already declares stay in the sections they are in.
+    dep_plan.apply(
+        &mut user_doc,
+        ws_doc.as_mut(),
+        SyncPolicy::default(),
+        Some(&new_crate_names),
+        &BTreeSet::new(),
+    );
 
+    // [impl cli.add.target]
     // Record active features — location depends on --target flag
-    let managed_deps = new_crate_names;
     let use_workspace_metadata = match target {
         Some(AddTarget::Workspace) => true,
         Some(AddTarget::Package) => false,
         Some(AddTarget::Default) | None => workspace_manifest.is_some(),
     };
+    if use_workspace_metadata && ws_doc.is_none() {
+        bail!("--target=workspace requires a workspace, but none was found");
+    }
+
+    let mut removed_counts = Vec::new();
+    for pack in added {
+        // [impl manifest.register.location]
+        // [impl manifest.register.format]
+        // [impl manifest.features.storage]
+        // Edit semantics: remove deselected crates from previous installation
+        let prev_managed =
+            read_managed_deps_from(&metadata_location, &user_manifest_content, &pack.crate_name);
+        let mut removed_count = 0;
+
+        if let Some(prev) = &prev_managed {
+            // Find crates that were previously managed but are no longer
+            // selected, and that no other pack still uses
+            let to_remove: BTreeMap<String, bphelper_manifest::CrateSpec> = prev
+                .iter()
+                .filter(|name| !dep_plan.contains(name))
+                .filter_map(|name| {
+                    pack.bp_spec
+                        .crates
+                        .get(name)
+                        .map(|spec| (name.clone(), spec.clone()))
+                })
+                .collect();
+
+            if !to_remove.is_empty() {
+                if let Some(ref mut doc) = ws_doc {
+                    // Remove from workspace deps
+                    let ws_deps = doc["workspace"]["dependencies"].as_table_mut();
+                    if let Some(ws_table) = ws_deps {
+                        for name in to_remove.keys() {
+                            ws_table.remove(name);
+                        }
+                    }
+                }
+                removed_count = remove_deps_by_kind(&mut user_doc, &to_remove);
+            }
+        }
+        removed_counts.push(removed_count);
 
-    if use_workspace_metadata {
-        if let Some(ref mut doc) = ws_doc {
-            write_bp_features_to_doc(
+        let managed_deps: BTreeSet<String> = pack.crates.keys().cloned().collect();
+        match ws_doc.as_mut() {
+            Some(doc) if use_workspace_metadata => write_bp_features_to_doc(
                 doc,
                 &["workspace", "metadata"],
-                &crate_name,
-                &active_features,
+                &pack.crate_name,
+                &pack.active_features,
                 Some(&managed_deps),
-            );
-        } else {
-            bail!("--target=workspace requires a workspace, but none was found");
+            ),
+            _ => write_bp_features_to_doc(
+                &mut user_doc,
+                &["package", "metadata"],
+                &pack.crate_name,
+                &pack.active_features,
+                Some(&managed_deps),
+            ),
         }
-    } else {
-        write_bp_features_to_doc(
-            &mut user_doc,
-            &["package", "metadata"],
-            &crate_name,
-            &active_features,
-            Some(&managed_deps),
-        );
     }
 
-    // Write workspace Cargo.toml once (deps + metadata combined)
+    // Write the workspace and package manifests together (deps + metadata combined)
+    // [impl manifest.toml.preserve]
+    // [impl manifest.toml.atomic]
+    let mut txn = ManifestTransaction::new();
     if let (Some(ws_path), Some(doc)) = (&workspace_manifest, &ws_doc) {
-        // [impl manifest.toml.preserve]
-        std::fs::write(ws_path, doc.to_string()).context("Failed to write workspace Cargo.toml")?;
+        txn.stage(ws_path, doc.to_string());
     }
+    txn.stage(&user_manifest_path, user_doc.to_string());
+    txn.commit(crate::history::before_write)?;
 
-    // Write the final Cargo.toml
-    // [impl manifest.toml.preserve]
-    std::fs::write(&user_manifest_path, user_doc.to_string())
-        .context("Failed to write Cargo.toml")?;
-
-    println!(
-        "Added {} with {} crate(s)",
-        crate_name,
-        crates_to_sync.len()
-    );
-    for dep_name in crates_to_sync.keys() {
-        println!("  + {}", dep_name);
-    }
-    if removed_count > 0 {
-        println!("Removed {} deselected crate(s)", removed_count);
+    for (pack, removed_count) in added.iter().zip(removed_counts) {
+        println!(
+            "Added {} with {} crate(s)",
+            pack.crate_name,
+            pack.crates.len()
+        );
+        for dep_name in pack.crates.keys() {
+            println!("  + {}", dep_name);
+        }
+        if removed_count > 0 {
+            println!("Removed {} deselected crate(s)", removed_count);
+        }
     }
 
     Ok(())
 }
 
+/// Resolve every installed pack not in `skip` for its active features,
+/// with its pins. A pack that can't be loaded is left out with a warning:
+/// `add` then plans without it, as it did before packs were merged.
+fn other_pack_resolutions(
+    user_manifest_content: &str,
+    metadata_location: &MetadataLocation,
+    skip: &BTreeSet<String>,
+    source: &CrateSource,
+) -> Vec<(
+    BTreeMap<String, bphelper_manifest::CrateSpec>,
+    BTreeSet<String>,
+)> {
+    let others: Vec<String> = find_installed_bp_names(user_manifest_content)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| !skip.contains(name))
+        .collect();
+    if others.is_empty() {
+        return Vec::new();
+    }
+    let specs = match load_installed_bp_specs(&others, None, source) {
+        Ok(specs) => specs,
+        Err(e) => {
+            eprintln!("warning: could not resolve installed battery packs: {e:#}");
+            return Vec::new();
+        }
+    };
+    others
+        .iter()
+        .zip(specs)
+        .filter_map(|(bp_name, spec)| match spec {
+            Ok(spec) => {
+                let active =
+                    read_active_features_from(metadata_location, user_manifest_content, bp_name);
+                let pinned = read_pinned_from(metadata_location, user_manifest_content, bp_name);
+                Some((spec.resolve_for_features(&active), pinned))
+            }
+            Err(e) => {
+                eprintln!("warning: could not load {bp_name}: {e:#}");
+                None
+            }
+        })
+        .collect()
+}
+
 /// Show a helpful message when `cargo bp add` is run without arguments.
 /// Determine which managed deps are safe to remove (not shared with other packs).
 pub(crate) fn deps_safe_to_remove(
@@ -1009,6 +1820,9 @@ fn remove_battery_pack(
         table.remove(&crate_name);
     }
 
+    // Both manifests are written together at the end.
+    let mut txn = ManifestTransaction::new();
+
     // Remove managed deps if confirmed
     if should_remove_deps && let Some(ref managed) = managed_deps {
         let safe = deps_safe_to_remove(
@@ -1059,8 +1873,7 @@ fn remove_battery_pack(
                 bp_table.remove(&crate_name);
             }
 
-            std::fs::write(ws_path, ws_doc.to_string())
-                .context("Failed to write workspace Cargo.toml")?;
+            txn.stage(ws_path, ws_doc.to_string());
         }
 
         if !safe.is_empty() {
@@ -1079,8 +1892,9 @@ fn remove_battery_pack(
         bp_table.remove(&crate_name);
     }
 
-    std::fs::write(&user_manifest_path, user_doc.to_string())
-        .context("Failed to write Cargo.toml")?;
+    // [impl manifest.toml.atomic]
+    txn.stage(&user_manifest_path, user_doc.to_string());
+    txn.commit(crate::history::before_write)?;
 
     // Clean up build.rs
     let build_rs_path = user_manifest_path
@@ -1093,6 +1907,223 @@ fn remove_battery_pack(
     Ok(())
 }
 
+// ============================================================================
+// Feature toggling: cargo bp enable / disable
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FeatureToggle {
+    Enable,
+    Disable,
+}
+
+// [impl cli.features.enable]
+// [impl cli.features.disable]
+// [impl cli.features.shared]
+pub(crate) fn toggle_features(
+    name: &str,
+    features: &[String],
+    toggle: FeatureToggle,
+    path: Option<&str>,
+    source: &CrateSource,
+    project_dir: &Path,
+) -> Result<()> {
+    let crate_name = resolve_crate_name(name);
+    let user_manifest_path = find_user_manifest(project_dir)?;
+    let user_manifest_content =
+        std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
+
+    // A pack counts as installed when it's registered or has metadata; both
+    // matter when deciding which crates another pack still manages.
+    let metadata_location = resolve_metadata_location(&user_manifest_path)?;
+    let mut bp_names = find_installed_bp_names(&user_manifest_content)?;
+    for name in read_metadata_bp_names(&metadata_location, &user_manifest_content) {
+        if !bp_names.contains(&name) {
+            bp_names.push(name);
+        }
+    }
+    if !bp_names.contains(&crate_name) {
+        bail!("Battery pack '{}' is not installed", crate_name);
+    }
+    let bp_spec = crate::registry::load_installed_bp_spec(&crate_name, path, source)?;
+
+    // [impl cli.features.unknown]
+    for feature in features {
+        if feature != "default" && feature != "all" && !bp_spec.features.contains_key(feature) {
+            let available: Vec<&str> = bp_spec.features.keys().map(String::as_str).collect();
+            bail!(
+                "{} has no feature '{}' (available: {})",
+                crate_name,
+                feature,
+                available.join(", ")
+            );
+        }
+    }
+
+    let old_features =
+        read_active_features_from(&metadata_location, &user_manifest_content, &crate_name);
+    let mut new_features = old_features.clone();
+    match toggle {
+        FeatureToggle::Enable => new_features.extend(features.iter().cloned()),
+        FeatureToggle::Disable => {
+            if old_features.contains("all") && features.iter().any(|f| f != "all") {
+                bail!(
+                    "all features of {} are enabled; disable 'all' first",
+                    crate_name
+                );
+            }
+            for feature in features {
+                new_features.remove(feature);
+            }
+            if new_features.is_empty() {
+                bail!(
+                    "disabling {} would leave {} with no features; use `cargo bp rm` to remove it",
+                    features.join(", "),
+                    crate_name
+                );
+            }
+        }
+    }
+    if new_features == old_features {
+        let state = match toggle {
+            FeatureToggle::Enable => "enabled",
+            FeatureToggle::Disable => "not enabled",
+        };
+        println!(
+            "{} already {} for {}",
+            features.join(", "),
+            state,
+            crate_name
+        );
+        return Ok(());
+    }
+
+    let old_crates = bp_spec.resolve_for_features(&old_features);
+    let new_crates = bp_spec.resolve_for_features(&new_features);
+    let added: BTreeMap<String, bphelper_manifest::CrateSpec> = new_crates
+        .iter()
+        .filter(|(name, _)| !old_crates.contains_key(*name))
+        .map(|(name, spec)| (name.clone(), spec.clone()))
+        .collect();
+    let dropped: BTreeSet<String> = old_crates
+        .keys()
+        .filter(|name| !new_crates.contains_key(*name))
+        .cloned()
+        .collect();
+
+    // Only crates the pack manages are removed, and only those no other
+    // installed pack manages too. Without managed-deps (old metadata) we
+    // can't tell the user's own crates apart, so nothing is removed.
+    let prev_managed =
+        read_managed_deps_from(&metadata_location, &user_manifest_content, &crate_name);
+    let dropped_managed: BTreeSet<String> = match &prev_managed {
+        Some(managed) => dropped.intersection(managed).cloned().collect(),
+        None => BTreeSet::new(),
+    };
+    let removable = deps_safe_to_remove(
+        &dropped_managed,
+        &bp_names,
+        &crate_name,
+        &metadata_location,
+        &user_manifest_content,
+    );
+    let to_remove: BTreeMap<String, bphelper_manifest::CrateSpec> = removable
+        .iter()
+        .filter_map(|name| {
+            old_crates
+                .get(name)
+                .map(|spec| (name.clone(), spec.clone()))
+        })
+        .collect();
+    let managed_deps: BTreeSet<String> = prev_managed
+        .unwrap_or_default()
+        .difference(&dropped)
+        .cloned()
+        .chain(new_crates.keys().cloned())
+        .collect();
+
+    // [impl manifest.toml.preserve]
+    let mut user_doc: toml_edit::DocumentMut = user_manifest_content
+        .parse()
+        .context("Failed to parse Cargo.toml")?;
+    let workspace_manifest = find_workspace_manifest(&user_manifest_path)?;
+    let mut ws_doc: Option<toml_edit::DocumentMut> = match &workspace_manifest {
+        Some(ws_path) => Some(
+            std::fs::read_to_string(ws_path)
+                .context("Failed to read workspace Cargo.toml")?
+                .parse()
+                .context("Failed to parse workspace Cargo.toml")?,
+        ),
+        None => None,
+    };
+
+    if let Some(doc) = ws_doc.as_mut() {
+        let ws_deps = doc["workspace"]["dependencies"]
+            .or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
+        if let Some(ws_table) = ws_deps.as_table_mut() {
+            for (dep_name, dep_spec) in &added {
+                if !ws_table.contains_key(dep_name) {
+                    add_dep_to_table(ws_table, dep_name, dep_spec);
+                }
+            }
+            for name in to_remove.keys() {
+                ws_table.remove(name);
+            }
+        }
+        write_workspace_refs_by_kind(&mut user_doc, &added, true);
+    } else {
+        write_deps_by_kind(&mut user_doc, &added, true);
+    }
+    remove_deps_by_kind(&mut user_doc, &to_remove);
+
+    match (&metadata_location, ws_doc.as_mut()) {
+        (MetadataLocation::Workspace { .. }, Some(doc)) => write_bp_features_to_doc(
+            doc,
+            &["workspace", "metadata"],
+            &crate_name,
+            &new_features,
+            Some(&managed_deps),
+        ),
+        _ => write_bp_features_to_doc(
+            &mut user_doc,
+            &["package", "metadata"],
+            &crate_name,
+            &new_features,
+            Some(&managed_deps),
+        ),
+    }
+
+    // [impl manifest.toml.atomic]
+    let mut txn = ManifestTransaction::new();
+    if let (Some(ws_path), Some(doc)) = (&workspace_manifest, &ws_doc) {
+        txn.stage(ws_path, doc.to_string());
+    }
+    txn.stage(&user_manifest_path, user_doc.to_string());
+    txn.commit(crate::history::before_write)?;
+
+    let verb = match toggle {
+        FeatureToggle::Enable => "Enabled",
+        FeatureToggle::Disable => "Disabled",
+    };
+    println!("{} {} for {}", verb, features.join(", "), crate_name);
+    for dep_name in added.keys() {
+        println!("  + {}", dep_name);
+    }
+    for dep_name in to_remove.keys() {
+        println!("  - {}", dep_name);
+    }
+    for dep_name in dropped.difference(&removable) {
+        let reason = if dropped_managed.contains(dep_name) {
+            "another battery pack also manages it"
+        } else {
+            "not added by this battery pack"
+        };
+        println!("  = {} (kept: {})", dep_name, reason);
+    }
+
+    Ok(())
+}
+
 /// Remove a validate() call from build.rs. If the file becomes an empty main,
 /// delete it entirely.
 fn cleanup_build_rs(build_rs_path: &Path, crate_name: &str) -> Result<()> {
@@ -1117,6 +2148,7 @@ fn cleanup_build_rs(build_rs_path: &Path, crate_name: &str) -> Result<()> {
 
     // Check if the remaining content is just an empty main
     let trimmed = new_content.replace(char::is_whitespace, "");
+    crate::history::before_write(build_rs_path);
     if trimmed == "fnmain(){}" {
         std::fs::remove_file(build_rs_path).context("Failed to delete build.rs")?;
     } else {
@@ -1160,7 +2192,68 @@ fn show_add_help(project_dir: &Path) -> Result<()> {
 // [impl cli.sync.add-crates]
 // [impl cli.source.subcommands]
 
-fn sync_battery_packs(project_dir: &Path, path: Option<&str>, source: &CrateSource) -> Result<()> {
+/// Whether `cargo bp sync` writes its changes or only reports them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum SyncMode {
+    /// Write the updated manifests.
+    #[default]
+    Write,
+    /// Print a diff of what would change; write nothing.
+    DryRun,
+    /// Like `DryRun`, but fail when anything would change.
+    Check,
+}
+
+/// What `cargo bp sync --prune` may remove.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Prune {
+    /// Only add and upgrade.
+    #[default]
+    Off,
+    /// Remove crates the packs no longer curate.
+    Crates,
+    /// Also remove Cargo features the packs no longer curate.
+    CratesAndFeatures,
+}
+
+/// One installed pack, resolved for sync.
+struct SyncedPack {
+    active_features: BTreeSet<String>,
+    expected: BTreeMap<String, bphelper_manifest::CrateSpec>,
+    managed_deps: Option<BTreeSet<String>>,
+    managed_features: BTreeMap<String, BTreeSet<String>>,
+    pinned: BTreeSet<String>,
+}
+
+pub(crate) fn sync_battery_packs(
+    project_dir: &Path,
+    path: Option<&str>,
+    source: &CrateSource,
+    policy: SyncPolicy,
+    mode: SyncMode,
+    prune: Prune,
+    interactive: bool,
+) -> Result<()> {
+    let in_sync = sync_manifests(project_dir, path, source, policy, mode, prune, interactive)?;
+    // [impl cli.sync.check]
+    if mode == SyncMode::Check && !in_sync {
+        bail!("battery pack dependencies are out of sync; run `cargo bp sync` to update");
+    }
+    Ok(())
+}
+
+/// Sync (or, for `--dry-run` and `--check`, diff) one project's manifests.
+/// Returns whether they were already in sync.
+// [impl cli.config.sync-policy]
+fn sync_manifests(
+    project_dir: &Path,
+    path: Option<&str>,
+    source: &CrateSource,
+    policy: SyncPolicy,
+    mode: SyncMode,
+    prune: Prune,
+    interactive: bool,
+) -> Result<bool> {
     let user_manifest_path = find_user_manifest(project_dir)?;
     let user_manifest_content =
         std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
@@ -1169,7 +2262,7 @@ fn sync_battery_packs(project_dir: &Path, path: Option<&str>, source: &CrateSour
 
     if bp_names.is_empty() {
         println!("No battery packs installed.");
-        return Ok(());
+        return Ok(true);
     }
 
     // [impl manifest.toml.preserve]
@@ -1181,925 +2274,2516 @@ fn sync_battery_packs(project_dir: &Path, path: Option<&str>, source: &CrateSour
     let metadata_location = resolve_metadata_location(&user_manifest_path)?;
     let mut total_changes = 0;
 
-    for bp_name in &bp_names {
-        // Get the battery pack spec
-        let bp_spec = load_installed_bp_spec(bp_name, path, source)?;
-
-        // Read active features from the correct metadata location
-        let active_features =
-            read_active_features_from(&metadata_location, &user_manifest_content, bp_name);
-
-        // [impl format.hidden.effect]
-        let expected = bp_spec.resolve_for_features(&active_features);
-
-        // Compute managed-deps: migrate old-format or merge new crates
-        let existing_managed =
-            read_managed_deps_from(&metadata_location, &user_manifest_content, bp_name);
-        let expected_names: BTreeSet<String> = expected.keys().cloned().collect();
-        let managed_deps = match existing_managed {
-            None => expected_names, // migration: populate from resolved crates
-            Some(mut set) => {
-                set.extend(expected_names);
-                set
-            }
-        };
-
-        // [impl manifest.deps.workspace]
-        // Sync each crate
-        if let Some(ref ws_path) = workspace_manifest {
+    // The workspace manifest is shared by every pack, so read it once and
+    // write it once at the end.
+    let mut workspace = match &workspace_manifest {
+        Some(ws_path) => {
             let ws_content =
                 std::fs::read_to_string(ws_path).context("Failed to read workspace Cargo.toml")?;
             // [impl manifest.toml.preserve]
-            let mut ws_doc: toml_edit::DocumentMut = ws_content
+            let ws_doc: toml_edit::DocumentMut = ws_content
                 .parse()
                 .context("Failed to parse workspace Cargo.toml")?;
+            Some((ws_path.as_path(), ws_content, ws_doc))
+        }
+        None => None,
+    };
 
-            let ws_deps = ws_doc["workspace"]["dependencies"]
-                .or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
-            if let Some(ws_table) = ws_deps.as_table_mut() {
-                for (dep_name, dep_spec) in &expected {
-                    if sync_dep_in_table(ws_table, dep_name, dep_spec) {
-                        total_changes += 1;
-                        println!("  ~ {} (updated in workspace)", dep_name);
-                    }
-                }
-            }
+    let bp_specs = load_installed_bp_specs(&bp_names, path, source)?
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
 
-            // Write managed-deps to workspace metadata if that's where it lives
-            if matches!(metadata_location, MetadataLocation::Workspace { .. }) {
-                write_bp_features_to_doc(
-                    &mut ws_doc,
-                    &["workspace", "metadata"],
+    // Resolve every pack up front: what one pack may prune depends on what
+    // the others still use.
+    let packs: Vec<SyncedPack> = bp_names
+        .iter()
+        .zip(&bp_specs)
+        .map(|(bp_name, bp_spec)| {
+            let active_features =
+                read_active_features_from(&metadata_location, &user_manifest_content, bp_name);
+            SyncedPack {
+                // [impl format.hidden.effect]
+                expected: bp_spec.resolve_for_features(&active_features),
+                active_features,
+                managed_deps: read_managed_deps_from(
+                    &metadata_location,
+                    &user_manifest_content,
                     bp_name,
-                    &active_features,
-                    Some(&managed_deps),
-                );
+                ),
+                managed_features: read_managed_features_from(
+                    &metadata_location,
+                    &user_manifest_content,
+                    bp_name,
+                ),
+                // [impl cli.pins.metadata]
+                pinned: read_pinned_from(&metadata_location, &user_manifest_content, bp_name),
             }
+        })
+        .collect();
+    let plans = prune_plans(
+        &bp_names,
+        &packs,
+        prune,
+        interactive && mode == SyncMode::Write,
+    );
 
-            // [impl manifest.toml.preserve]
-            std::fs::write(ws_path, ws_doc.to_string())
-                .context("Failed to write workspace Cargo.toml")?;
+    // [impl cli.sync.prune]
+    for plan in plans.iter().flatten() {
+        total_changes += apply_prune(&mut user_doc, workspace.as_mut(), plan);
+    }
 
-            // Ensure crate-level references exist in the correct sections
-            // [impl cli.add.dep-kind]
-            let refs_added = write_workspace_refs_by_kind(&mut user_doc, &expected, true);
-            total_changes += refs_added;
-        } else {
-            // [impl manifest.deps.no-workspace]
-            // [impl cli.add.dep-kind]
-            for (dep_name, dep_spec) in &expected {
-                let section = dep_kind_section(dep_spec.dep_kind);
-                let table =
-                    user_doc[section].or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
-                if let Some(table) = table.as_table_mut() {
-                    if !table.contains_key(dep_name) {
-                        add_dep_to_table(table, dep_name, dep_spec);
-                        total_changes += 1;
-                        println!("  + {}", dep_name);
-                    } else if sync_dep_in_table(table, dep_name, dep_spec) {
-                        total_changes += 1;
-                        println!("  ~ {}", dep_name);
-                    }
-                }
+    // [impl manifest.merge.plan]
+    let dep_plan = crate::plan::DepPlan::new(packs.iter().map(|p| (&p.expected, &p.pinned)));
+    // Only crates a pack recorded as its own are moved between sections.
+    let relocate: BTreeSet<String> = packs
+        .iter()
+        .flat_map(|p| p.managed_deps.iter().flatten())
+        .cloned()
+        .collect();
+    let features_before =
+        declared_features(&user_doc, workspace.as_ref().map(|(_, _, ws_doc)| ws_doc))?;
+    for change in dep_plan.apply(
+        &mut user_doc,
+        workspace.as_mut().map(|(_, _, ws_doc)| ws_doc),
+        policy,
+        None,
+        &relocate,
+    ) {
+        total_changes += 1;
+        println!("  {}", change);
+    }
+    // Only what sync itself added is the packs' to prune later.
+    let inserted: BTreeMap<String, BTreeSet<String>> =
+        declared_features(&user_doc, workspace.as_ref().map(|(_, _, ws_doc)| ws_doc))?
+            .into_iter()
+            .map(|(name, after)| {
+                let before = features_before.get(&name);
+                let added = after
+                    .into_iter()
+                    .filter(|f| before.is_none_or(|b| !b.contains(f)))
+                    .collect();
+                (name, added)
+            })
+            .collect();
+
+    for ((bp_name, pack), plan) in bp_names.iter().zip(&packs).zip(&plans) {
+        // Compute managed-deps: migrate old-format or merge new crates,
+        // dropping whatever was pruned.
+        let expected_names: BTreeSet<String> = pack.expected.keys().cloned().collect();
+        let managed_deps = match &pack.managed_deps {
+            None => expected_names, // migration: populate from resolved crates
+            Some(set) => {
+                let mut set: BTreeSet<String> = set
+                    .iter()
+                    .filter(|name| !plan.iter().flat_map(|p| p.stale()).any(|s| s == *name))
+                    .cloned()
+                    .collect();
+                set.extend(expected_names);
+                set
             }
-        }
+        };
+        let managed_features = crate::prune::managed_features(
+            &pack.managed_features,
+            &pack.expected,
+            &inserted,
+            plan.as_ref(),
+        );
 
-        // Write managed-deps to package metadata if that's where it lives
-        if matches!(metadata_location, MetadataLocation::Package) {
-            write_bp_features_to_doc(
-                &mut user_doc,
-                &["package", "metadata"],
-                bp_name,
-                &active_features,
-                Some(&managed_deps),
-            );
-        }
+        let (doc, prefix) = match (&metadata_location, workspace.as_mut()) {
+            (MetadataLocation::Workspace { .. }, Some((_, _, ws_doc))) => {
+                (ws_doc, ["workspace", "metadata"])
+            }
+            _ => (&mut user_doc, ["package", "metadata"]),
+        };
+        write_bp_features_to_doc(
+            doc,
+            &prefix,
+            bp_name,
+            &pack.active_features,
+            Some(&managed_deps),
+        );
+        write_managed_features_to_doc(doc, &prefix, bp_name, &managed_features);
     }
 
-    // [impl manifest.toml.preserve]
-    std::fs::write(&user_manifest_path, user_doc.to_string())
-        .context("Failed to write Cargo.toml")?;
-
-    if total_changes == 0 {
-        println!("All dependencies are up to date.");
-    } else {
-        println!("Synced {} change(s).", total_changes);
+    // Compare rendered text, not just the change counter: metadata
+    // bookkeeping (e.g. migrating managed-deps) also changes the files.
+    let mut updates = Vec::new();
+    if let Some((ws_path, ws_content, ws_doc)) = &workspace {
+        let new_content = ws_doc.to_string();
+        if new_content != *ws_content {
+            updates.push((*ws_path, ws_content.as_str(), new_content));
+        }
+    }
+    let new_user_content = user_doc.to_string();
+    if new_user_content != user_manifest_content {
+        updates.push((
+            user_manifest_path.as_path(),
+            user_manifest_content.as_str(),
+            new_user_content,
+        ));
     }
 
-    Ok(())
-}
+    if mode == SyncMode::Write {
+        // [impl manifest.toml.preserve]
+        // [impl manifest.toml.atomic]
+        let mut txn = ManifestTransaction::new();
+        for (manifest_path, _, new_content) in &updates {
+            txn.stage(*manifest_path, new_content.as_str());
+        }
+        txn.commit(crate::history::before_write)?;
 
-// ============================================================================
-// Interactive crate picker
-// ============================================================================
+        if total_changes == 0 {
+            println!("All dependencies are up to date.");
+        } else {
+            println!("Synced {} change(s).", total_changes);
+        }
+        return Ok(updates.is_empty());
+    }
 
-/// Compute which crates from a battery pack are already present in the project.
-///
-/// Returns an empty set when the pack is not yet installed (fresh install),
-/// which causes the picker to fall back to the pack's default feature set.
-fn compute_pre_selection(
-    bp_spec: &bphelper_manifest::BatteryPackSpec,
-    project_dir: &Path,
-) -> BTreeSet<String> {
-    let Ok(manifest_path) = find_user_manifest(project_dir) else {
-        return BTreeSet::new();
-    };
-    let Ok(content) = std::fs::read_to_string(&manifest_path) else {
-        return BTreeSet::new();
-    };
-    let Ok(versions) = collect_user_dep_versions(&manifest_path, &content) else {
-        return BTreeSet::new();
-    };
+    // [impl cli.sync.dry-run]
+    if updates.is_empty() {
+        println!("All dependencies are up to date.");
+        return Ok(true);
+    }
+    for (manifest_path, old_content, new_content) in &updates {
+        let display = manifest_path
+            .strip_prefix(project_dir)
+            .unwrap_or(manifest_path)
+            .display()
+            .to_string();
+        println!();
+        print!(
+            "{}",
+            crate::merge::unified_diff(old_content, new_content, &display)
+        );
+    }
+    println!();
 
-    // A crate is pre-selected if it appears in the project's dependencies
-    bp_spec
-        .crates
-        .keys()
-        .filter(|name| versions.contains_key(name.as_str()))
-        .cloned()
-        .collect()
+    if mode == SyncMode::DryRun {
+        println!("Dry run: nothing was written. Run `cargo bp sync` to apply these changes.");
+    }
+    Ok(false)
 }
 
-/// Represents the result of an interactive crate selection.
-pub(crate) struct PickerResult {
-    /// The resolved crates to install (name -> dep spec with merged features).
-    pub crates: BTreeMap<String, bphelper_manifest::CrateSpec>,
-    /// Which feature names are fully selected (for metadata recording).
-    pub active_features: BTreeSet<String>,
+/// The features each crate is declared with in the (unsaved) manifests.
+fn declared_features(
+    user_doc: &toml_edit::DocumentMut,
+    ws_doc: Option<&toml_edit::DocumentMut>,
+) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let user: toml::Value =
+        toml::from_str(&user_doc.to_string()).context("Failed to parse Cargo.toml")?;
+    let workspace = ws_doc
+        .map(|doc| toml::from_str::<toml::Value>(&doc.to_string()))
+        .transpose()
+        .context("Failed to parse workspace Cargo.toml")?;
+    Ok(crate::drift::ProjectDeps::from_manifests(&user, workspace.as_ref()).features())
 }
 
-/// An item in the picker — either a feature or an individual crate.
-enum PickerItem {
-    Feature(String), // feature name
-    Crate(String),   // crate name
+/// Plan `--prune` for each pack (lined up with `bp_names`), asking first
+/// when interactive. Returns `None` for packs with nothing to prune.
+// [impl cli.sync.prune]
+fn prune_plans(
+    bp_names: &[String],
+    packs: &[SyncedPack],
+    prune: Prune,
+    confirm: bool,
+) -> Vec<Option<crate::prune::PackPrune>> {
+    if prune == Prune::Off {
+        return vec![None; packs.len()];
+    }
+    let records: Vec<crate::prune::PackRecord<'_>> = packs
+        .iter()
+        .map(|pack| crate::prune::PackRecord {
+            expected: &pack.expected,
+            managed_deps: pack.managed_deps.as_ref(),
+            managed_features: &pack.managed_features,
+        })
+        .collect();
+    let plans: Vec<Option<crate::prune::PackPrune>> = crate::prune::plan(&records)
+        .into_iter()
+        .map(|mut plan| {
+            if prune == Prune::Crates {
+                plan.features.clear();
+            }
+            (!plan.is_empty()).then_some(plan)
+        })
+        .collect();
+
+    if confirm
+        && plans.iter().any(|p| {
+            p.as_ref()
+                .is_some_and(|p| !p.remove.is_empty() || !p.features.is_empty())
+        })
+    {
+        println!("Battery packs no longer curate:");
+        for (bp_name, plan) in bp_names.iter().zip(&plans) {
+            let Some(plan) = plan else { continue };
+            for name in &plan.remove {
+                println!("  {} (from {})", name, bp_name);
+            }
+            for (name, features) in &plan.features {
+                let features: Vec<&str> = features.iter().map(String::as_str).collect();
+                println!(
+                    "  {} features: {} (from {})",
+                    name,
+                    features.join(", "),
+                    bp_name
+                );
+            }
+        }
+        let proceed = dialoguer::Confirm::new()
+            .with_prompt("Remove these from Cargo.toml?")
+            .default(true)
+            .interact()
+            .unwrap_or(false);
+        if !proceed {
+            return vec![None; packs.len()];
+        }
+    }
+    plans
 }
 
-/// Show an interactive multi-select picker for choosing which crates to install.
-///
-/// Features are listed first, then individual crates. `pre_selected` contains
-/// crate names already present in the project (for edit mode); when empty,
-/// the pack's default feature set is used for initial selection.
-///
-/// Returns `None` if the user cancels.
-fn pick_crates_interactive(
-    bp_spec: &bphelper_manifest::BatteryPackSpec,
-    pre_selected: &BTreeSet<String>,
-) -> Result<Option<PickerResult>> {
+/// Remove what a pack no longer curates from the manifest. Crate entries
+/// go from every dependency section of the package; the workspace's own
+/// `[workspace.dependencies]` entries are left for other members. Returns
+/// the number of changes.
+// [impl cli.sync.prune]
+// [impl cli.sync.prune-features]
+fn apply_prune(
+    user_doc: &mut toml_edit::DocumentMut,
+    workspace: Option<&mut (&Path, String, toml_edit::DocumentMut)>,
+    plan: &crate::prune::PackPrune,
+) -> usize {
+    let mut changes = 0;
+    for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        let Some(table) = user_doc.get_mut(section).and_then(|t| t.as_table_mut()) else {
+            continue;
+        };
+        for name in &plan.remove {
+            if table.remove(name).is_some() {
+                changes += 1;
+                println!("  - {}", name);
+            }
+        }
+        for (name, features) in &plan.features {
+            if remove_dep_features(table, name, features) {
+                changes += 1;
+                println!("  ~ {} (features pruned)", name);
+            }
+        }
+    }
+    if let Some(ws_table) = workspace.and_then(|(_, _, ws_doc)| {
+        ws_doc
+            .get_mut("workspace")
+            .and_then(|w| w.get_mut("dependencies"))
+            .and_then(|d| d.as_table_mut())
+    }) {
+        for (name, features) in &plan.features {
+            if remove_dep_features(ws_table, name, features) {
+                changes += 1;
+                println!("  ~ {} (features pruned in workspace)", name);
+            }
+        }
+    }
+    changes
+}
+
+// ============================================================================
+// Upgrade command
+// ============================================================================
+
+/// What `cargo bp upgrade` found for one pack.
+enum PlannedUpgrade {
+    UpToDate {
+        current: semver::Version,
+        /// A newer release outside the registered requirement, when
+        /// `--breaking` wasn't given.
+        breaking: Option<semver::Version>,
+    },
+    Upgrade {
+        from: semver::Version,
+        to: semver::Version,
+        changes: Vec<bphelper_manifest::CrateChange>,
+        /// CHANGELOG.md sections between the two releases; empty if the
+        /// pack ships no changelog.
+        changelog: String,
+    },
+}
+
+fn plan_upgrade(
+    bp_name: &str,
+    registered: Option<&str>,
+    installed: &bphelper_manifest::BatteryPackSpec,
+    breaking: bool,
+    source: &CrateSource,
+) -> Result<PlannedUpgrade> {
+    let registered = registered.with_context(|| {
+        format!(
+            "{} is registered without a version (a path dependency?); nothing to upgrade",
+            bp_name
+        )
+    })?;
+    let published = crate::upgrade::available_versions(source, bp_name)?;
+    let candidates = crate::upgrade::upgrade_candidates(registered, &installed.version, &published)
+        .with_context(|| {
+            format!(
+                "{} has an unsupported version requirement '{}'",
+                bp_name, registered
+            )
+        })?;
+
+    let Some(target) = candidates.target(breaking).cloned() else {
+        return Ok(PlannedUpgrade::UpToDate {
+            current: candidates.current,
+            breaking: candidates.latest.filter(|_| !breaking),
+        });
+    };
+
+    let release = crate::upgrade::fetch_release(source, bp_name, &target.to_string())?;
+    let changelog = release
+        .changelog
+        .as_deref()
+        .map(|c| crate::upgrade::changelog_between(c, &candidates.current, &target))
+        .unwrap_or_default();
+    Ok(PlannedUpgrade::Upgrade {
+        changes: installed.diff_crates(&release.spec),
+        from: candidates.current,
+        to: target,
+        changelog,
+    })
+}
+
+// [impl cli.upgrade.sync]
+fn upgrade_battery_packs(
+    project_dir: &Path,
+    battery_pack: Option<&str>,
+    breaking: bool,
+    source: &CrateSource,
+    policy: SyncPolicy,
+) -> Result<()> {
     use console::style;
-    use dialoguer::MultiSelect;
 
-    // Collect non-default features with their member crates
-    let features: Vec<(&String, &BTreeSet<String>)> = bp_spec
-        .features
-        .iter()
-        .filter(|(name, _)| name.as_str() != "default")
-        .collect();
+    let user_manifest_path = find_user_manifest(project_dir)?;
+    let user_manifest_content =
+        std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
 
-    // Collect all visible crates
-    let visible_crates: Vec<(&String, &bphelper_manifest::CrateSpec)> = bp_spec
-        .crates
+    let installed = find_installed_bp_names(&user_manifest_content)?;
+    let bp_names = match battery_pack {
+        Some(name) => {
+            let crate_name = resolve_crate_name(name);
+            if !installed.contains(&crate_name) {
+                bail!("Battery pack '{}' is not installed", crate_name);
+            }
+            vec![crate_name]
+        }
+        None => installed,
+    };
+    if bp_names.is_empty() {
+        println!("No battery packs installed.");
+        return Ok(());
+    }
+
+    // Plan every pack before touching any manifest, so a failed lookup
+    // leaves the project as it was.
+    let registered: Vec<Option<String>> = bp_names
         .iter()
-        .filter(|(name, _)| !bp_spec.is_hidden(name))
+        .map(|bp_name| {
+            registered_pack_version(&user_manifest_path, &user_manifest_content, bp_name)
+        })
+        .collect::<Result<_>>()?;
+    let installed_specs = load_installed_bp_specs(&bp_names, None, source)?
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    let packs: Vec<(&String, Option<&str>, &bphelper_manifest::BatteryPackSpec)> = bp_names
+        .iter()
+        .zip(&registered)
+        .zip(&installed_specs)
+        .map(|((bp_name, registered), spec)| (bp_name, registered.as_deref(), spec))
         .collect();
+    let plans = fetch_all(
+        "Checking published versions",
+        &packs,
+        |(bp_name, registered, spec)| plan_upgrade(bp_name, *registered, spec, breaking, source),
+    )
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?;
+
+    let mut upgrades = Vec::new();
+    for (bp_name, plan) in bp_names.iter().zip(&plans) {
+        match plan {
+            PlannedUpgrade::UpToDate { current, breaking } => {
+                println!(
+                    "{} {} is up to date",
+                    style(short_name(bp_name)).bold(),
+                    current
+                );
+                if let Some(latest) = breaking {
+                    println!(
+                        "  {} {} is available; run {} to upgrade across the breaking change",
+                        style("⚠").yellow(),
+                        style(latest).green(),
+                        style(format!(
+                            "cargo bp upgrade {} --breaking",
+                            short_name(bp_name)
+                        ))
+                        .bold(),
+                    );
+                }
+            }
+            PlannedUpgrade::Upgrade {
+                from,
+                to,
+                changes,
+                changelog,
+            } => {
+                // [impl cli.upgrade.changes]
+                println!(
+                    "{} {} → {}",
+                    style(short_name(bp_name)).bold(),
+                    style(from).red(),
+                    style(to).green(),
+                );
+                print_crate_changes(changes);
+                // [impl cli.upgrade.changelog]
+                if !changelog.is_empty() {
+                    println!();
+                    for line in changelog.lines() {
+                        println!("    {}", line);
+                    }
+                    println!();
+                }
+                upgrades.push((bp_name, to.to_string()));
+            }
+        }
+    }
 
-    if visible_crates.is_empty() {
-        bail!("Battery pack has no crates to add");
+    if upgrades.is_empty() {
+        return Ok(());
     }
 
-    let use_defaults = pre_selected.is_empty();
-    let default_crates: BTreeSet<String> = if use_defaults {
-        bp_spec
-            .resolve_crates(&["default"])
-            .keys()
-            .cloned()
-            .collect()
-    } else {
-        BTreeSet::new()
+    // [impl cli.upgrade.registration]
+    // [impl manifest.toml.preserve]
+    let mut user_doc: toml_edit::DocumentMut = user_manifest_content
+        .parse()
+        .context("Failed to parse Cargo.toml")?;
+    let workspace_manifest = find_workspace_manifest(&user_manifest_path)?;
+    let mut ws_doc: Option<toml_edit::DocumentMut> = match &workspace_manifest {
+        Some(ws_path) => Some(
+            std::fs::read_to_string(ws_path)
+                .context("Failed to read workspace Cargo.toml")?
+                .parse()
+                .context("Failed to parse workspace Cargo.toml")?,
+        ),
+        None => None,
     };
+    let mut ws_changed = false;
+    for (bp_name, version) in &upgrades {
+        let updated = crate::manifest::set_registered_pack_version(
+            &mut user_doc,
+            ws_doc.as_mut(),
+            bp_name,
+            version,
+        )?;
+        ws_changed |= updated == crate::manifest::RegistrationUpdate::Workspace;
+    }
+    // [impl manifest.toml.atomic]
+    let mut txn = ManifestTransaction::new();
+    if let (Some(ws_path), Some(doc)) = (&workspace_manifest, &ws_doc)
+        && ws_changed
+    {
+        txn.stage(ws_path, doc.to_string());
+    }
+    txn.stage(&user_manifest_path, user_doc.to_string());
+    txn.commit(crate::history::before_write)?;
+    println!("Upgraded {} battery pack(s).", upgrades.len());
+
+    sync_battery_packs(
+        project_dir,
+        None,
+        source,
+        policy,
+        SyncMode::Write,
+        Prune::Off,
+        false,
+    )
+}
 
-    // Build picker items: features first, then crates
-    let mut items: Vec<PickerItem> = Vec::new();
-    let mut labels: Vec<String> = Vec::new();
-    let mut defaults: Vec<bool> = Vec::new();
+/// Input options for [`adopt_battery_pack`].
+struct AdoptOpts<'a> {
+    name: &'a str,
+    sync: bool,
+    path: Option<&'a str>,
+    source: &'a CrateSource,
+    policy: SyncPolicy,
+    project_dir: &'a Path,
+    interactive: bool,
+}
 
-    for (feat_name, feat_crates) in &features {
-        let member_list = feat_crates
-            .iter()
-            .filter(|c| !bp_spec.is_hidden(c))
-            .cloned()
-            .collect::<Vec<_>>()
-            .join(", ");
-        labels.push(format!(
-            "✦ {} {}",
-            feat_name,
-            style(format!("[{}]", member_list)).dim()
-        ));
-        let checked = if use_defaults {
-            // Feature is checked if all its visible members are in defaults
-            feat_crates
-                .iter()
-                .filter(|c| !bp_spec.is_hidden(c))
-                .all(|c| default_crates.contains(c))
-        } else {
-            // Feature is checked if all its visible members are pre-selected
-            feat_crates
-                .iter()
-                .filter(|c| !bp_spec.is_hidden(c))
-                .all(|c| pre_selected.contains(c))
-        };
-        defaults.push(checked);
-        items.push(PickerItem::Feature(feat_name.to_string()));
+// [impl cli.adopt.register]
+// [impl cli.adopt.report]
+// [impl cli.adopt.sync]
+fn adopt_battery_pack(opts: AdoptOpts<'_>) -> Result<()> {
+    let crate_name = resolve_crate_name(opts.name);
+    let user_manifest_path = find_user_manifest(opts.project_dir)?;
+    let user_manifest_content =
+        std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
+    let metadata_location = resolve_metadata_location(&user_manifest_path)?;
+    if read_metadata_bp_names(&metadata_location, &user_manifest_content).contains(&crate_name) {
+        bail!(
+            "{} is already installed; use `cargo bp sync` or `cargo bp enable` instead",
+            crate_name
+        );
     }
 
-    for (crate_name, spec) in &visible_crates {
-        let version_info = if spec.features.is_empty() {
-            format!("({})", spec.version)
-        } else {
-            format!(
-                "({}, features: {})",
-                spec.version,
-                spec.features
-                    .iter()
-                    .map(|s| s.as_str())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            )
-        };
-        labels.push(format!("  {} {}", crate_name, style(&version_info).dim()));
-        let checked = if use_defaults {
-            default_crates.contains(crate_name.as_str())
-        } else {
-            pre_selected.contains(crate_name.as_str())
-        };
-        defaults.push(checked);
-        items.push(PickerItem::Crate(crate_name.to_string()));
+    // [impl cli.path.flag]
+    // [impl cli.source.replace]
+    let (bp_version, bp_spec, local_dir) = match (opts.path, opts.source) {
+        (Some(local_path), _) => {
+            let spec = crate::registry::load_installed_bp_spec(
+                &crate_name,
+                Some(local_path),
+                opts.source,
+            )?;
+            (None, spec, Some(PathBuf::from(local_path)))
+        }
+        (None, CrateSource::Local(workspace_dir)) => {
+            let dir = crate::registry::find_local_battery_pack_dir(workspace_dir, &crate_name)?;
+            let (_, spec) = fetch_bp_spec(opts.source, opts.name)?;
+            (None, spec, Some(dir))
+        }
+        (None, CrateSource::Registry) => {
+            let (version, spec) = fetch_bp_spec(opts.source, opts.name)?;
+            (version, spec, None)
+        }
+    };
+
+    let project = crate::drift::ProjectDeps::read(&user_manifest_path, &user_manifest_content)?;
+    let present: BTreeSet<String> = project.deps.keys().cloned().collect();
+    let Some(adoption) = crate::adopt::choose_features(&bp_spec, &present) else {
+        bail!(
+            "The project doesn't depend on any crate {} curates; use `cargo bp add {}`",
+            crate_name,
+            opts.name
+        );
+    };
+
+    let features: Vec<&str> = adoption.features.iter().map(String::as_str).collect();
+    println!("Adopting {} ({})", crate_name, features.join(", "));
+    for name in &adoption.managed {
+        println!("  = {}", name);
     }
 
-    // Show the picker
-    println!();
-    println!(
-        "  {} v{}",
-        style(&bp_spec.name).green().bold(),
+    // [impl cli.adopt.report]
+    let findings = crate::drift::analyze(
+        &project,
+        &crate::drift::PackState {
+            name: &crate_name,
+            spec: &bp_spec,
+            active_features: &adoption.features,
+            managed_deps: Some(&adoption.managed),
+            pinned: &BTreeSet::new(),
+        },
+        opts.policy,
+    );
+    if !findings.is_empty() {
+        println!("Differences from {}:", crate_name);
+        for finding in &findings {
+            println!("  {}", finding.message);
+        }
+    }
+
+    // [impl cli.adopt.register]
+    let mut user_doc: toml_edit::DocumentMut = user_manifest_content
+        .parse()
+        .context("Failed to parse Cargo.toml")?;
+    let workspace_manifest = find_workspace_manifest(&user_manifest_path)?;
+    let mut ws_doc: Option<toml_edit::DocumentMut> = workspace_manifest
+        .as_ref()
+        .map(|ws_path| -> Result<_> {
+            std::fs::read_to_string(ws_path)
+                .context("Failed to read workspace Cargo.toml")?
+                .parse()
+                .context("Failed to parse workspace Cargo.toml")
+        })
+        .transpose()?;
+
+    let registration = match (&local_dir, &bp_version) {
+        (Some(dir), _) => {
+            // Absolute, since the entry may land in the workspace manifest.
+            let dir = std::path::absolute(dir)?;
+            let mut dep = toml_edit::InlineTable::new();
+            dep.insert(
+                "path",
+                toml_edit::Value::from(dir.to_string_lossy().as_ref()),
+            );
+            toml_edit::value(dep)
+        }
+        (None, Some(version)) => toml_edit::value(version.as_str()),
+        (None, None) => bail!("battery pack version not available"),
+    };
+    let build_deps = user_doc["build-dependencies"]
+        .or_insert(toml_edit::Item::Table(toml_edit::Table::new()))
+        .as_table_mut()
+        .context("[build-dependencies] is not a table")?;
+    if !build_deps.contains_key(&crate_name) {
+        match ws_doc.as_mut() {
+            Some(doc) => {
+                crate::manifest::add_workspace_ref(build_deps, &crate_name);
+                let ws_deps = doc["workspace"]["dependencies"]
+                    .or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
+                if let Some(ws_table) = ws_deps.as_table_mut()
+                    && !ws_table.contains_key(&crate_name)
+                {
+                    ws_table.insert(&crate_name, registration);
+                }
+            }
+            None => {
+                build_deps.insert(&crate_name, registration);
+            }
+        }
+    }
+
+    // [impl manifest.features.storage]
+    match ws_doc.as_mut() {
+        Some(doc) => write_bp_features_to_doc(
+            doc,
+            &["workspace", "metadata"],
+            &crate_name,
+            &adoption.features,
+            Some(&adoption.managed),
+        ),
+        None => write_bp_features_to_doc(
+            &mut user_doc,
+            &["package", "metadata"],
+            &crate_name,
+            &adoption.features,
+            Some(&adoption.managed),
+        ),
+    }
+
+    // [impl manifest.toml.atomic]
+    let mut txn = ManifestTransaction::new();
+    if let (Some(ws_path), Some(doc)) = (&workspace_manifest, &ws_doc) {
+        txn.stage(ws_path, doc.to_string());
+    }
+    txn.stage(&user_manifest_path, user_doc.to_string());
+    txn.commit(crate::history::before_write)?;
+    println!(
+        "Adopted {} with {} crate(s)",
+        crate_name,
+        adoption.managed.len()
+    );
+
+    // [impl cli.adopt.sync]
+    if opts.sync {
+        return sync_battery_packs(
+            opts.project_dir,
+            opts.path,
+            opts.source,
+            opts.policy,
+            SyncMode::Write,
+            Prune::Off,
+            opts.interactive,
+        );
+    }
+    if findings.iter().any(|f| f.kind.fixed_by_sync()) {
+        println!("Run `cargo bp sync` (or adopt with --sync) to apply the pack's recommendations.");
+    }
+    Ok(())
+}
+
+// [impl cli.doctor.checks]
+// [impl cli.doctor.fix]
+fn run_doctor(
+    project_dir: &Path,
+    fix: bool,
+    path: Option<&str>,
+    source: &CrateSource,
+    interactive: bool,
+) -> Result<()> {
+    let user_manifest_path = find_user_manifest(project_dir)?;
+    let user_content =
+        std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
+    let workspace_manifest = find_workspace_manifest(&user_manifest_path)?;
+    let ws_content = workspace_manifest
+        .as_ref()
+        .map(|ws| std::fs::read_to_string(ws).context("Failed to read workspace Cargo.toml"))
+        .transpose()?;
+    let build_rs_path = user_manifest_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("build.rs");
+    let build_rs = std::fs::read_to_string(&build_rs_path).ok();
+
+    let user: toml::Value = toml::from_str(&user_content).context("Failed to parse Cargo.toml")?;
+    let ws: Option<toml::Value> = ws_content
+        .as_deref()
+        .map(|c| toml::from_str(c).context("Failed to parse workspace Cargo.toml"))
+        .transpose()?;
+    let problems = crate::doctor::diagnose(&crate::doctor::Project {
+        user: &user,
+        workspace: ws.as_ref(),
+        build_rs: build_rs.as_deref(),
+    });
+    if problems.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+
+    // [impl cli.doctor.confirm]
+    let mut chosen = Vec::new();
+    for problem in &problems {
+        println!("problem: {problem}");
+        println!("    fix: {}", problem.remedy());
+        let apply = if fix {
+            true
+        } else if interactive {
+            dialoguer::Confirm::new()
+                .with_prompt("Apply this fix?")
+                .default(true)
+                .interact()
+                .context("prompt failed")?
+        } else {
+            false
+        };
+        if apply {
+            chosen.push(problem);
+        }
+    }
+
+    let mut user_doc: toml_edit::DocumentMut =
+        user_content.parse().context("Failed to parse Cargo.toml")?;
+    let mut ws_doc: Option<toml_edit::DocumentMut> = ws_content
+        .as_deref()
+        .map(|c| c.parse().context("Failed to parse workspace Cargo.toml"))
+        .transpose()?;
+    let metadata_location = resolve_metadata_location(&user_manifest_path)?;
+    let mut fixed = 0;
+    let mut stale_validate = Vec::new();
+    for &problem in &chosen {
+        match problem {
+            crate::doctor::Problem::NoManagedDeps { pack } => {
+                let spec = match crate::registry::load_installed_bp_spec(pack, path, source) {
+                    Ok(spec) => spec,
+                    Err(e) => {
+                        eprintln!("warning: could not resolve {pack}, so not fixing it: {e:#}");
+                        continue;
+                    }
+                };
+                let active = read_active_features_from(&metadata_location, &user_content, pack);
+                let curated = spec.resolve_for_features(&active).into_keys().collect();
+                crate::doctor::backfill_managed_deps(
+                    &mut user_doc,
+                    ws_doc.as_mut(),
+                    pack,
+                    &curated,
+                );
+            }
+            crate::doctor::Problem::StaleValidate { pack } => stale_validate.push(pack),
+            _ => {
+                crate::doctor::fix(problem, &mut user_doc, ws_doc.as_mut());
+            }
+        }
+        fixed += 1;
+    }
+
+    // [impl manifest.toml.atomic]
+    let mut txn = crate::manifest::ManifestTransaction::new();
+    txn.stage(&user_manifest_path, user_doc.to_string());
+    if let (Some(ws_path), Some(ws_doc)) = (&workspace_manifest, &ws_doc) {
+        txn.stage(ws_path, ws_doc.to_string());
+    }
+    txn.commit(crate::history::before_write)?;
+    for pack in stale_validate {
+        cleanup_build_rs(&build_rs_path, pack)?;
+    }
+
+    if fixed > 0 {
+        println!("Fixed {fixed} of {} problem(s).", problems.len());
+    }
+    if fixed < problems.len() {
+        bail!(
+            "{} problem(s) left unfixed; run `cargo bp doctor --fix` to repair them",
+            problems.len() - fixed
+        );
+    }
+    Ok(())
+}
+
+// [impl cli.undo.list]
+fn list_snapshots(project_dir: &Path) -> Result<()> {
+    let snapshots = crate::history::list(&crate::history::history_dir(project_dir)?)?;
+    if snapshots.is_empty() {
+        println!("No cargo bp snapshots in this project.");
+        return Ok(());
+    }
+    for snapshot in snapshots {
+        println!(
+            "{}  {}  {}",
+            snapshot.id,
+            crate::history::format_time(snapshot.journal.time),
+            snapshot.journal.command
+        );
+        for file in &snapshot.journal.files {
+            let change = match (&file.before, &file.after) {
+                (None, _) => "created",
+                (_, None) => "removed",
+                _ => "modified",
+            };
+            println!("    {change:<8}  {}", file.path.display());
+        }
+    }
+    Ok(())
+}
+
+// [impl cli.undo.restore]
+fn undo_snapshot(project_dir: &Path, snapshot: Option<&str>) -> Result<()> {
+    let history = crate::history::history_dir(project_dir)?;
+    let undone = crate::history::undo(&history, snapshot)?;
+    println!(
+        "Undid `{}` ({} file{} restored)",
+        undone.journal.command,
+        undone.journal.files.len(),
+        if undone.journal.files.len() == 1 {
+            ""
+        } else {
+            "s"
+        }
+    );
+    Ok(())
+}
+
+/// Print added, removed and re-versioned curated crates.
+fn print_crate_changes(changes: &[bphelper_manifest::CrateChange]) {
+    use console::style;
+
+    for change in changes {
+        match change {
+            bphelper_manifest::CrateChange::Added { name, version } => {
+                println!("      {} {} {}", style("+").green(), name, version)
+            }
+            bphelper_manifest::CrateChange::Removed { name, version } => {
+                println!("      {} {} {}", style("-").red(), name, version)
+            }
+            bphelper_manifest::CrateChange::VersionChanged { name, from, to } => {
+                println!("      {} {}: {} → {}", style("~").yellow(), name, from, to)
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Interactive crate picker
+// ============================================================================
+
+/// Compute which crates from a battery pack are already present in the project.
+///
+/// Returns an empty set when the pack is not yet installed (fresh install),
+/// which causes the picker to fall back to the pack's default feature set.
+fn compute_pre_selection(
+    bp_spec: &bphelper_manifest::BatteryPackSpec,
+    project_dir: &Path,
+) -> BTreeSet<String> {
+    let Ok(manifest_path) = find_user_manifest(project_dir) else {
+        return BTreeSet::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&manifest_path) else {
+        return BTreeSet::new();
+    };
+    let Ok(versions) = collect_user_dep_versions(&manifest_path, &content) else {
+        return BTreeSet::new();
+    };
+
+    // A crate is pre-selected if it appears in the project's dependencies
+    bp_spec
+        .crates
+        .keys()
+        .filter(|name| versions.contains_key(name.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Represents the result of an interactive crate selection.
+pub(crate) struct PickerResult {
+    /// The resolved crates to install (name -> dep spec with merged features).
+    pub crates: BTreeMap<String, bphelper_manifest::CrateSpec>,
+    /// Which feature names are fully selected (for metadata recording).
+    pub active_features: BTreeSet<String>,
+}
+
+/// An item in the picker — either a feature or an individual crate.
+enum PickerItem {
+    Feature(String), // feature name
+    Crate(String),   // crate name
+}
+
+/// Show an interactive multi-select picker for choosing which crates to install.
+///
+/// Features are listed first, then individual crates. `pre_selected` contains
+/// crate names already present in the project (for edit mode); when empty,
+/// the pack's default feature set is used for initial selection.
+///
+/// Returns `None` if the user cancels.
+fn pick_crates_interactive(
+    bp_spec: &bphelper_manifest::BatteryPackSpec,
+    pre_selected: &BTreeSet<String>,
+) -> Result<Option<PickerResult>> {
+    use console::style;
+    use dialoguer::MultiSelect;
+
+    // Collect non-default features with their member crates
+    let features: Vec<(&String, &BTreeSet<String>)> = bp_spec
+        .features
+        .iter()
+        .filter(|(name, _)| name.as_str() != "default")
+        .collect();
+
+    // Collect all visible crates
+    let visible_crates: Vec<(&String, &bphelper_manifest::CrateSpec)> = bp_spec
+        .crates
+        .iter()
+        .filter(|(name, _)| !bp_spec.is_hidden(name))
+        .collect();
+
+    if visible_crates.is_empty() {
+        bail!("Battery pack has no crates to add");
+    }
+
+    let use_defaults = pre_selected.is_empty();
+    let default_crates: BTreeSet<String> = if use_defaults {
+        bp_spec
+            .resolve_crates(&["default"])
+            .keys()
+            .cloned()
+            .collect()
+    } else {
+        BTreeSet::new()
+    };
+
+    // Build picker items: features first, then crates
+    let mut items: Vec<PickerItem> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+    let mut defaults: Vec<bool> = Vec::new();
+
+    for (feat_name, feat_crates) in &features {
+        let member_list = feat_crates
+            .iter()
+            .filter(|c| !bp_spec.is_hidden(c))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        labels.push(format!(
+            "✦ {} {}",
+            feat_name,
+            style(format!("[{}]", member_list)).dim()
+        ));
+        let checked = if use_defaults {
+            // Feature is checked if all its visible members are in defaults
+            feat_crates
+                .iter()
+                .filter(|c| !bp_spec.is_hidden(c))
+                .all(|c| default_crates.contains(c))
+        } else {
+            // Feature is checked if all its visible members are pre-selected
+            feat_crates
+                .iter()
+                .filter(|c| !bp_spec.is_hidden(c))
+                .all(|c| pre_selected.contains(c))
+        };
+        defaults.push(checked);
+        items.push(PickerItem::Feature(feat_name.to_string()));
+    }
+
+    for (crate_name, spec) in &visible_crates {
+        let version_info = if spec.features.is_empty() {
+            format!("({})", spec.version)
+        } else {
+            format!(
+                "({}, features: {})",
+                spec.version,
+                spec.features
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        labels.push(format!("  {} {}", crate_name, style(&version_info).dim()));
+        let checked = if use_defaults {
+            default_crates.contains(crate_name.as_str())
+        } else {
+            pre_selected.contains(crate_name.as_str())
+        };
+        defaults.push(checked);
+        items.push(PickerItem::Crate(crate_name.to_string()));
+    }
+
+    // Show the picker
+    println!();
+    println!(
+        "  {} v{}",
+        style(&bp_spec.name).green().bold(),
         style(&bp_spec.version).dim()
     );
     println!();
 
-    let selections = MultiSelect::new()
-        .with_prompt("Select features and crates")
-        .items(&labels)
-        .defaults(&defaults)
-        .interact_opt()
-        .context("Failed to show crate picker")?;
+    let selections = MultiSelect::new()
+        .with_prompt("Select features and crates")
+        .items(&labels)
+        .defaults(&defaults)
+        .interact_opt()
+        .context("Failed to show crate picker")?;
+
+    let Some(selected_indices) = selections else {
+        return Ok(None);
+    };
+
+    // Determine which features and crates are selected
+    let selected_set: BTreeSet<usize> = selected_indices.into_iter().collect();
+    let mut selected_crates: BTreeSet<String> = BTreeSet::new();
+
+    for (i, item) in items.iter().enumerate() {
+        if !selected_set.contains(&i) {
+            continue;
+        }
+        match item {
+            PickerItem::Feature(feat_name) => {
+                if let Some(members) = bp_spec.features.get(feat_name) {
+                    for c in members {
+                        if !bp_spec.is_hidden(c) {
+                            selected_crates.insert(c.clone());
+                        }
+                    }
+                }
+            }
+            PickerItem::Crate(name) => {
+                selected_crates.insert(name.clone());
+            }
+        }
+    }
+
+    // Build the result
+    let mut crates = BTreeMap::new();
+    for name in &selected_crates {
+        if let Some(spec) = bp_spec.crates.get(name) {
+            crates.insert(name.clone(), spec.clone());
+        }
+    }
+
+    // Determine which features are fully selected
+    let mut active_features = BTreeSet::new();
+    // Check if all default crates are selected
+    let default_members = bp_spec.features.get("default");
+    if default_members.is_some_and(|members| {
+        members
+            .iter()
+            .filter(|c| !bp_spec.is_hidden(c))
+            .all(|c| selected_crates.contains(c))
+    }) {
+        active_features.insert("default".to_string());
+    }
+    for (feat_name, feat_crates) in &bp_spec.features {
+        if feat_name == "default" {
+            continue;
+        }
+        if feat_crates
+            .iter()
+            .filter(|c| !bp_spec.is_hidden(c))
+            .all(|c| selected_crates.contains(c))
+        {
+            active_features.insert(feat_name.clone());
+        }
+    }
+
+    Ok(Some(PickerResult {
+        crates,
+        active_features,
+    }))
+}
+
+// ============================================================================
+// build.rs manipulation
+// ============================================================================
+
+/// Shared options for `cargo bp new` generation.
+struct NewOpts {
+    battery_pack: String,
+    name: Option<String>,
+    defines: BTreeMap<String, String>,
+    interactive: bool,
+}
+
+fn generate_from_local(
+    opts: NewOpts,
+    local_path: &str,
+    template: Option<String>,
+) -> Result<PathBuf> {
+    let local_path = Path::new(local_path);
+
+    // Read local Cargo.toml
+    let manifest_path = local_path.join("Cargo.toml");
+    let manifest_content = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+
+    let crate_name = local_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown");
+    let templates = parse_template_metadata(&manifest_content, crate_name)?;
+    let template_path = resolve_template(&templates, template.as_deref(), opts.interactive)?;
+
+    generate_from_path(opts, local_path, &template_path)
+}
+
+/// Prompt for a project name if not provided.
+fn prompt_project_name(name: Option<String>) -> Result<String> {
+    match name {
+        Some(n) => Ok(n),
+        None => dialoguer::Input::<String>::new()
+            .with_prompt("Project name")
+            .interact_text()
+            .context("Failed to read project name"),
+    }
+}
+
+/// Ensure a project name ends with `-battery-pack`.
+fn ensure_battery_pack_suffix(name: String) -> String {
+    if name.ends_with("-battery-pack") {
+        name
+    } else {
+        let fixed = format!("{}-battery-pack", name);
+        println!("Renaming project to: {}", fixed);
+        fixed
+    }
+}
+
+fn generate_from_path(opts: NewOpts, crate_path: &Path, template_path: &str) -> Result<PathBuf> {
+    let raw = prompt_project_name(opts.name)?;
+    let project_name = if opts.battery_pack == "battery-pack" {
+        ensure_battery_pack_suffix(raw)
+    } else {
+        raw
+    };
+
+    let interactive_override = if opts.interactive { None } else { Some(false) };
+
+    let gen_opts = crate::template_engine::GenerateOpts {
+        render: crate::template_engine::RenderOpts {
+            crate_root: crate_path.to_path_buf(),
+            template_path: template_path.to_string(),
+            project_name,
+            defines: opts.defines,
+            interactive_override,
+        },
+        destination: None,
+        git_init: true,
+    };
+
+    crate::template_engine::generate(gen_opts)
+}
+
+/// Parse a `key=value` string for clap's `value_parser`.
+fn parse_define(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) => Ok((key.to_string(), value.to_string())),
+        None => Ok((s.to_string(), "true".to_string())),
+    }
+}
+
+fn parse_template_metadata(
+    manifest_content: &str,
+    crate_name: &str,
+) -> Result<BTreeMap<String, TemplateConfig>> {
+    let spec = bphelper_manifest::parse_battery_pack(manifest_content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse Cargo.toml: {}", e))?;
+
+    if spec.templates.is_empty() {
+        bail!(
+            "Battery pack '{}' has no templates defined in [package.metadata.battery.templates]",
+            crate_name
+        );
+    }
+
+    Ok(spec.templates)
+}
+
+// [impl format.templates.selection]
+// [impl cli.new.template-select]
+pub(crate) fn resolve_template(
+    templates: &BTreeMap<String, TemplateConfig>,
+    requested: Option<&str>,
+    interactive: bool,
+) -> Result<String> {
+    match requested {
+        Some(name) => {
+            let config = templates.get(name).ok_or_else(|| {
+                let available: Vec<_> = templates.keys().map(|s| s.as_str()).collect();
+                anyhow::anyhow!(
+                    "Template '{}' not found. Available templates: {}",
+                    name,
+                    available.join(", ")
+                )
+            })?;
+            Ok(config.path.clone())
+        }
+        None => {
+            if templates.len() == 1 {
+                let (_, config) = templates.iter().next().unwrap();
+                Ok(config.path.clone())
+            } else if let Some(config) = templates.get("default") {
+                Ok(config.path.clone())
+            } else {
+                prompt_for_template(templates, interactive)
+            }
+        }
+    }
+}
+
+fn prompt_for_template(
+    templates: &BTreeMap<String, TemplateConfig>,
+    interactive: bool,
+) -> Result<String> {
+    use dialoguer::{Select, theme::ColorfulTheme};
+
+    // Build display items with descriptions
+    let items: Vec<String> = templates
+        .iter()
+        .map(|(name, config)| {
+            if let Some(desc) = &config.description {
+                format!("{} - {}", name, desc)
+            } else {
+                name.clone()
+            }
+        })
+        .collect();
+
+    // Check if we're in a TTY for interactive mode
+    if !interactive || !std::io::stdout().is_terminal() {
+        // Non-interactive: list templates and bail
+        println!("Available templates:");
+        for item in &items {
+            println!("  {}", item);
+        }
+        bail!("Multiple templates available. Please specify one with --template <name>");
+    }
+
+    // Interactive: show selector
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select a template")
+        .items(&items)
+        .default(0)
+        .interact()
+        .context("Failed to select template")?;
+
+    // Get the selected template's path
+    let (_, config) = templates
+        .iter()
+        .nth(selection)
+        .ok_or_else(|| anyhow::anyhow!("Invalid template selection"))?;
+    Ok(config.path.clone())
+}
+
+fn print_battery_pack_list(
+    source: &CrateSource,
+    query: &ListQuery,
+    format: MessageFormat,
+) -> Result<()> {
+    use console::style;
+
+    let battery_packs = fetch_battery_pack_list(source, query)?;
+
+    // [impl cli.json.list]
+    if format.is_json() {
+        return crate::output::print(
+            "list",
+            &crate::output::ListOutput {
+                packs: battery_packs.iter().map(Into::into).collect(),
+            },
+        );
+    }
+
+    if battery_packs.is_empty() {
+        match query.describe_filters() {
+            Some(filters) => println!("No battery packs found {}", filters),
+            None => println!("No battery packs found"),
+        }
+        return Ok(());
+    }
+
+    // Find the longest name for alignment
+    let max_name_len = battery_packs
+        .iter()
+        .map(|c| c.short_name.len())
+        .max()
+        .unwrap_or(0);
+
+    let max_version_len = battery_packs
+        .iter()
+        .map(|c| c.version.len())
+        .max()
+        .unwrap_or(0);
+
+    // Local sources have no download counts, so skip the column entirely.
+    let show_downloads = battery_packs.iter().any(|bp| bp.downloads > 0);
+    let downloads: Vec<String> = battery_packs
+        .iter()
+        .map(|bp| match query.sort {
+            ListSort::Recent => format_downloads(bp.recent_downloads.unwrap_or(0)),
+            _ => format_downloads(bp.downloads),
+        })
+        .collect();
+    let max_downloads_len = downloads.iter().map(|d| d.len()).max().unwrap_or(0);
+
+    println!();
+    for (bp, dl) in battery_packs.iter().zip(&downloads) {
+        let desc = bp.description.lines().next().unwrap_or("");
+
+        // Pad strings manually, then apply colors (ANSI codes break width formatting)
+        let name_padded = format!("{:<width$}", bp.short_name, width = max_name_len);
+        let ver_padded = format!("{:<width$}", bp.version, width = max_version_len);
+
+        if show_downloads {
+            let dl_padded = format!("{:>width$}", dl, width = max_downloads_len);
+            println!(
+                "  {}  {}  {}  {}",
+                style(name_padded).green().bold(),
+                style(ver_padded).dim(),
+                style(format!("↓{}", dl_padded)).cyan(),
+                desc,
+            );
+        } else {
+            println!(
+                "  {}  {}  {}",
+                style(name_padded).green().bold(),
+                style(ver_padded).dim(),
+                desc,
+            );
+        }
+        if let Some(meta) = summary_metadata_line(bp) {
+            println!(
+                "  {:width$}  {}",
+                "",
+                style(meta).dim(),
+                width = max_name_len
+            );
+        }
+    }
+    println!();
+
+    println!(
+        "{}",
+        style(format!(
+            "Found {} battery pack(s), sorted by {}",
+            battery_packs.len(),
+            query.sort.as_str()
+        ))
+        .dim()
+    );
+
+    Ok(())
+}
+
+/// Secondary line for `cargo bp list`: keywords, categories, and last update.
+fn summary_metadata_line(bp: &crate::registry::BatteryPackSummary) -> Option<String> {
+    let mut meta = Vec::new();
+    match &bp.keywords {
+        Some(keywords) => {
+            let tags: Vec<&str> = keywords
+                .iter()
+                .map(String::as_str)
+                .filter(|k| *k != "battery-pack")
+                .collect();
+            if !tags.is_empty() {
+                meta.push(format!("keywords: {}", tags.join(", ")));
+            }
+        }
+        None => meta.push("keywords unknown".to_string()),
+    }
+    match &bp.categories {
+        Some(categories) if !categories.is_empty() => {
+            meta.push(format!("categories: {}", categories.join(", ")));
+        }
+        Some(_) => {}
+        None => meta.push("categories unknown".to_string()),
+    }
+    if let Some(updated) = &bp.updated_at {
+        // Only the date part of the RFC 3339 timestamp is interesting here.
+        meta.push(format!("updated {}", updated.get(..10).unwrap_or(updated)));
+    }
+    (!meta.is_empty()).then(|| meta.join(" · "))
+}
+
+/// Read installed state (managed-deps and active features) for a battery pack.
+/// Returns empty sets if not in a project or pack not installed.
+fn read_installed_state(
+    project_dir: &Path,
+    crate_name: &str,
+) -> (BTreeSet<String>, BTreeSet<String>) {
+    let empty = (BTreeSet::new(), BTreeSet::new());
+    let Ok(manifest_path) = find_user_manifest(project_dir) else {
+        return empty;
+    };
+    let Ok(content) = std::fs::read_to_string(&manifest_path) else {
+        return empty;
+    };
+    let Ok(location) = resolve_metadata_location(&manifest_path) else {
+        return empty;
+    };
+    let managed = read_managed_deps_from(&location, &content, crate_name).unwrap_or_default();
+    let features = read_active_features_from(&location, &content, crate_name);
+    (managed, features)
+}
+
+fn print_battery_pack_detail(
+    name: &str,
+    path: Option<&str>,
+    source: &CrateSource,
+    project_dir: &Path,
+    format: MessageFormat,
+) -> Result<()> {
+    use console::style;
+
+    // --path takes precedence over --crate-source
+    let detail = if path.is_some() {
+        fetch_battery_pack_detail(name, path)?
+    } else {
+        fetch_battery_pack_detail_from_source(source, name)?
+    };
+    for warning in &detail.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    // Read installed state from the project (if available)
+    let crate_name = resolve_crate_name(name);
+    let (managed_deps, active_features) = read_installed_state(project_dir, &crate_name);
 
-    let Some(selected_indices) = selections else {
-        return Ok(None);
-    };
+    // [impl cli.json.show]
+    if format.is_json() {
+        return crate::output::print(
+            "show",
+            &crate::output::ShowOutput::new(&detail, &managed_deps, &active_features),
+        );
+    }
 
-    // Determine which features and crates are selected
-    let selected_set: BTreeSet<usize> = selected_indices.into_iter().collect();
-    let mut selected_crates: BTreeSet<String> = BTreeSet::new();
+    // Header
+    println!();
+    println!(
+        "{} {}",
+        style(&detail.name).green().bold(),
+        style(&detail.version).dim()
+    );
+    if !detail.description.is_empty() {
+        println!("{}", detail.description);
+    }
 
-    for (i, item) in items.iter().enumerate() {
-        if !selected_set.contains(&i) {
-            continue;
-        }
-        match item {
-            PickerItem::Feature(feat_name) => {
-                if let Some(members) = bp_spec.features.get(feat_name) {
-                    for c in members {
-                        if !bp_spec.is_hidden(c) {
-                            selected_crates.insert(c.clone());
-                        }
-                    }
-                }
-            }
-            PickerItem::Crate(name) => {
-                selected_crates.insert(name.clone());
+    // Authors
+    if !detail.owners.is_empty() {
+        println!();
+        println!("{}", style("Authors:").bold());
+        for owner in &detail.owners {
+            if let Some(name) = &owner.name {
+                println!("  {} ({})", name, owner.login);
+            } else {
+                println!("  {}", owner.login);
             }
         }
     }
 
-    // Build the result
-    let mut crates = BTreeMap::new();
-    for name in &selected_crates {
-        if let Some(spec) = bp_spec.crates.get(name) {
-            crates.insert(name.clone(), spec.clone());
+    // Crates
+    if !detail.crates.is_empty() {
+        println!();
+        println!("{}", style("Crates:").bold());
+        for dep in &detail.crates {
+            let marker = if managed_deps.contains(dep) {
+                format!(" {}", style("✓").green())
+            } else {
+                String::new()
+            };
+            println!("  {}{}", dep, marker);
         }
     }
 
-    // Determine which features are fully selected
-    let mut active_features = BTreeSet::new();
-    // Check if all default crates are selected
-    let default_members = bp_spec.features.get("default");
-    if default_members.is_some_and(|members| {
-        members
-            .iter()
-            .filter(|c| !bp_spec.is_hidden(c))
-            .all(|c| selected_crates.contains(c))
-    }) {
-        active_features.insert("default".to_string());
+    // Features
+    if !detail.features.is_empty() {
+        println!();
+        println!("{}", style("Features:").bold());
+        for (feat_name, members) in &detail.features {
+            let marker = if active_features.contains(feat_name) {
+                format!(" {}", style("✓").green())
+            } else {
+                String::new()
+            };
+            println!(
+                "  {} → {}{}",
+                style(feat_name).cyan(),
+                members.join(", "),
+                marker
+            );
+        }
     }
-    for (feat_name, feat_crates) in &bp_spec.features {
-        if feat_name == "default" {
-            continue;
+
+    // Extends
+    if !detail.extends.is_empty() {
+        println!();
+        println!("{}", style("Extends:").bold());
+        for dep in &detail.extends {
+            println!("  {}", dep);
         }
-        if feat_crates
+    }
+
+    // Templates
+    if !detail.templates.is_empty() {
+        println!();
+        println!("{}", style("Templates:").bold());
+        let max_name_len = detail
+            .templates
             .iter()
-            .filter(|c| !bp_spec.is_hidden(c))
-            .all(|c| selected_crates.contains(c))
-        {
-            active_features.insert(feat_name.clone());
+            .map(|t| t.name.len())
+            .max()
+            .unwrap_or(0);
+        for tmpl in &detail.templates {
+            let name_padded = format!("{:<width$}", tmpl.name, width = max_name_len);
+            if let Some(desc) = &tmpl.description {
+                println!("  {}  {}", style(name_padded).cyan(), desc);
+            } else {
+                println!("  {}", style(name_padded).cyan());
+            }
         }
     }
 
-    Ok(Some(PickerResult {
-        crates,
-        active_features,
-    }))
-}
+    // [impl format.examples.browsable]
+    // Examples
+    if !detail.examples.is_empty() {
+        println!();
+        println!("{}", style("Examples:").bold());
+        let max_name_len = detail
+            .examples
+            .iter()
+            .map(|e| e.name.len())
+            .max()
+            .unwrap_or(0);
+        for example in &detail.examples {
+            let name_padded = format!("{:<width$}", example.name, width = max_name_len);
+            if let Some(desc) = &example.description {
+                println!("  {}  {}", style(name_padded).magenta(), desc);
+            } else {
+                println!("  {}", style(name_padded).magenta());
+            }
+        }
+    }
 
-// ============================================================================
-// build.rs manipulation
-// ============================================================================
+    // Install hints
+    println!();
+    println!("{}", style("Install:").bold());
+    println!("  cargo bp add {}", detail.short_name);
+    println!("  cargo bp new {}", detail.short_name);
+    println!();
 
-/// Shared options for `cargo bp new` generation.
-struct NewOpts {
-    battery_pack: String,
-    name: Option<String>,
-    defines: BTreeMap<String, String>,
-    interactive: bool,
+    Ok(())
 }
 
-fn generate_from_local(opts: NewOpts, local_path: &str, template: Option<String>) -> Result<()> {
-    let local_path = Path::new(local_path);
-
-    // Read local Cargo.toml
-    let manifest_path = local_path.join("Cargo.toml");
-    let manifest_content = std::fs::read_to_string(&manifest_path)
-        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+// [impl cli.show.template-preview]
+fn print_template_preview(opts: &crate::template_engine::PreviewOpts<'_>) -> Result<()> {
+    let (_crate_name, files) = crate::template_engine::preview_template(opts)?;
 
-    let crate_name = local_path
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
-    let templates = parse_template_metadata(&manifest_content, crate_name)?;
-    let template_path = resolve_template(&templates, template.as_deref(), opts.interactive)?;
+    for file in &files {
+        println!("── {} ──", file.path);
+        println!("{}", file.content);
+        println!();
+    }
 
-    generate_from_path(opts, local_path, &template_path)
+    Ok(())
 }
 
-/// Prompt for a project name if not provided.
-fn prompt_project_name(name: Option<String>) -> Result<String> {
-    match name {
-        Some(n) => Ok(n),
-        None => dialoguer::Input::<String>::new()
-            .with_prompt("Project name")
-            .interact_text()
-            .context("Failed to read project name"),
-    }
-}
+// ============================================================================
+// Workspace runs
+// ============================================================================
 
-/// Ensure a project name ends with `-battery-pack`.
-fn ensure_battery_pack_suffix(name: String) -> String {
-    if name.ends_with("-battery-pack") {
-        name
-    } else {
-        let fixed = format!("{}-battery-pack", name);
-        println!("Renaming project to: {}", fixed);
-        fixed
+/// Battery packs registered in a member's manifest, plus those with
+/// package-level metadata. Workspace-level metadata is shared by every
+/// member, so it doesn't say which members use a pack.
+fn member_packs(member_dir: &Path) -> Result<BTreeSet<String>> {
+    let manifest_path = find_user_manifest(member_dir)?;
+    let content = std::fs::read_to_string(&manifest_path).context("Failed to read Cargo.toml")?;
+    let mut names: BTreeSet<String> = find_installed_bp_names(&content)?.into_iter().collect();
+    let location = resolve_metadata_location(&manifest_path)?;
+    if matches!(location, MetadataLocation::Package) {
+        names.extend(read_metadata_bp_names(&location, &content));
     }
+    Ok(names)
 }
 
-fn generate_from_path(opts: NewOpts, crate_path: &Path, template_path: &str) -> Result<()> {
-    let raw = prompt_project_name(opts.name)?;
-    let project_name = if opts.battery_pack == "battery-pack" {
-        ensure_battery_pack_suffix(raw)
-    } else {
-        raw
-    };
-
-    let interactive_override = if opts.interactive { None } else { Some(false) };
+/// Introduce a member's section of a combined report.
+fn print_member_header(member: &Member, project_dir: &Path) {
+    use console::style;
 
-    let gen_opts = crate::template_engine::GenerateOpts {
-        render: crate::template_engine::RenderOpts {
-            crate_root: crate_path.to_path_buf(),
-            template_path: template_path.to_string(),
-            project_name,
-            defines: opts.defines,
-            interactive_override,
-        },
-        destination: None,
-        git_init: true,
+    let dir = match member.dir.strip_prefix(project_dir) {
+        Ok(dir) if dir.as_os_str().is_empty() => Path::new("."),
+        Ok(dir) => dir,
+        Err(_) => &member.dir,
     };
-
-    crate::template_engine::generate(gen_opts)?;
-
-    Ok(())
-}
-
-/// Parse a `key=value` string for clap's `value_parser`.
-fn parse_define(s: &str) -> Result<(String, String), String> {
-    match s.split_once('=') {
-        Some((key, value)) => Ok((key.to_string(), value.to_string())),
-        None => Ok((s.to_string(), "true".to_string())),
-    }
+    println!(
+        "{} {}",
+        style(&member.name).bold().underlined(),
+        style(format!("({})", dir.display())).dim()
+    );
 }
 
-fn parse_template_metadata(
-    manifest_content: &str,
-    crate_name: &str,
-) -> Result<BTreeMap<String, TemplateConfig>> {
-    let spec = bphelper_manifest::parse_battery_pack(manifest_content)
-        .map_err(|e| anyhow::anyhow!("Failed to parse Cargo.toml: {}", e))?;
-
-    if spec.templates.is_empty() {
-        bail!(
-            "Battery pack '{}' has no templates defined in [package.metadata.battery.templates]",
-            crate_name
-        );
-    }
-
-    Ok(spec.templates)
-}
+/// Run a command that changes manifests on each selected member, or on
+/// `project_dir` alone when no selection flags were given. Members whose
+/// installed packs `applies` rejects are skipped, described as lacking
+/// `needs`; the run stops at the first member that fails.
+// [impl cli.workspace.each]
+fn for_each_member(
+    project_dir: &Path,
+    selection: &MemberSelection,
+    applies: impl Fn(&BTreeSet<String>) -> bool,
+    needs: &str,
+    mut run: impl FnMut(&Path) -> Result<()>,
+) -> Result<()> {
+    if !selection.is_explicit() {
+        return run(project_dir);
+    }
 
-// [impl format.templates.selection]
-// [impl cli.new.template-select]
-pub(crate) fn resolve_template(
-    templates: &BTreeMap<String, TemplateConfig>,
-    requested: Option<&str>,
-    interactive: bool,
-) -> Result<String> {
-    match requested {
-        Some(name) => {
-            let config = templates.get(name).ok_or_else(|| {
-                let available: Vec<_> = templates.keys().map(|s| s.as_str()).collect();
-                anyhow::anyhow!(
-                    "Template '{}' not found. Available templates: {}",
-                    name,
-                    available.join(", ")
-                )
-            })?;
-            Ok(config.path.clone())
+    let mut ran = 0;
+    let mut skipped = 0;
+    for member in selection.resolve(project_dir)? {
+        if !applies(&member_packs(&member.dir)?) {
+            skipped += 1;
+            continue;
         }
-        None => {
-            if templates.len() == 1 {
-                let (_, config) = templates.iter().next().unwrap();
-                Ok(config.path.clone())
-            } else if let Some(config) = templates.get("default") {
-                Ok(config.path.clone())
-            } else {
-                prompt_for_template(templates, interactive)
-            }
+        if ran > 0 {
+            println!();
         }
+        print_member_header(&member, project_dir);
+        run(&member.dir).with_context(|| format!("in workspace member '{}'", member.name))?;
+        ran += 1;
+    }
+    if ran == 0 {
+        bail!("no selected workspace member has {}", needs);
+    }
+    if skipped > 0 {
+        println!("\nSkipped {} member(s) without {}.", skipped, needs);
     }
+    Ok(())
 }
 
-fn prompt_for_template(
-    templates: &BTreeMap<String, TemplateConfig>,
-    interactive: bool,
-) -> Result<String> {
-    use dialoguer::{Select, theme::ColorfulTheme};
+/// Run `status` or `check` on each selected member and combine the results
+/// into one report. Returns the worst outcome across members.
+// [impl cli.workspace.combined]
+fn report_members<T: serde::Serialize>(
+    project_dir: &Path,
+    selection: &MemberSelection,
+    kind: &str,
+    format: MessageFormat,
+    mut run: impl FnMut(&Path) -> Result<(Outcome, Option<T>)>,
+) -> Result<Outcome> {
+    use console::style;
 
-    // Build display items with descriptions
-    let items: Vec<String> = templates
-        .iter()
-        .map(|(name, config)| {
-            if let Some(desc) = &config.description {
-                format!("{} - {}", name, desc)
-            } else {
-                name.clone()
-            }
-        })
-        .collect();
+    if !selection.is_explicit() {
+        let (outcome, output) = run(project_dir)?;
+        if let Some(output) = output {
+            crate::output::print(kind, &output)?;
+        }
+        return Ok(outcome);
+    }
 
-    // Check if we're in a TTY for interactive mode
-    if !interactive || !std::io::stdout().is_terminal() {
-        // Non-interactive: list templates and bail
-        println!("Available templates:");
-        for item in &items {
-            println!("  {}", item);
+    let mut worst = Outcome::Clean;
+    let mut tally: BTreeMap<Outcome, usize> = BTreeMap::new();
+    let mut members = Vec::new();
+    let mut skipped = Vec::new();
+    for member in selection.resolve(project_dir)? {
+        if member_packs(&member.dir)?.is_empty() {
+            skipped.push(member.name);
+            continue;
+        }
+        if !format.is_json() {
+            if !tally.is_empty() {
+                println!();
+            }
+            print_member_header(&member, project_dir);
+        }
+        let (outcome, output) =
+            run(&member.dir).with_context(|| format!("in workspace member '{}'", member.name))?;
+        worst = worst.max(outcome);
+        *tally.entry(outcome).or_default() += 1;
+        if let Some(report) = output {
+            members.push(crate::output::MemberOutput {
+                manifest_path: member.dir.join("Cargo.toml").display().to_string(),
+                package: member.name,
+                report,
+            });
         }
-        bail!("Multiple templates available. Please specify one with --template <name>");
     }
 
-    // Interactive: show selector
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a template")
-        .items(&items)
-        .default(0)
-        .interact()
-        .context("Failed to select template")?;
+    if format.is_json() {
+        crate::output::print(
+            kind,
+            &crate::output::WorkspaceOutput {
+                ok: worst == Outcome::Clean,
+                status: worst.into(),
+                members,
+                skipped,
+            },
+        )?;
+        return Ok(worst);
+    }
 
-    // Get the selected template's path
-    let (_, config) = templates
-        .iter()
-        .nth(selection)
-        .ok_or_else(|| anyhow::anyhow!("Invalid template selection"))?;
-    Ok(config.path.clone())
+    let counts: Vec<String> = [
+        (Outcome::Clean, "clean"),
+        (Outcome::Warnings, "with warnings"),
+        (Outcome::Errors, "with errors"),
+    ]
+    .into_iter()
+    .filter_map(|(outcome, label)| tally.get(&outcome).map(|n| format!("{} {}", n, label)))
+    .collect();
+    let total: usize = tally.values().sum();
+    print!("\n{} {} member(s)", style("Workspace:").bold(), total);
+    if !counts.is_empty() {
+        print!(": {}", counts.join(", "));
+    }
+    if !skipped.is_empty() {
+        print!(" ({} without battery packs skipped)", skipped.len());
+    }
+    println!();
+    Ok(worst)
 }
 
-fn print_battery_pack_list(source: &CrateSource, filter: Option<&str>) -> Result<()> {
+// ============================================================================
+// Status command
+// ============================================================================
+
+// [impl cli.status.list]
+// [impl cli.status.version-warn]
+// [impl cli.status.no-project]
+// [impl cli.source.subcommands]
+// [impl cli.path.subcommands]
+///
+/// In JSON mode the document is returned rather than printed, so a
+/// workspace run can combine members into one report.
+fn status_battery_packs(
+    project_dir: &Path,
+    path: Option<&str>,
+    source: &CrateSource,
+    policy: SyncPolicy,
+    format: MessageFormat,
+) -> Result<(Outcome, Option<crate::output::StatusOutput>)> {
     use console::style;
 
-    let battery_packs = fetch_battery_pack_list(source, filter)?;
+    // [impl cli.status.no-project]
+    let user_manifest_path =
+        find_user_manifest(project_dir).context("are you inside a Rust project?")?;
+    let user_manifest_content =
+        std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
 
-    if battery_packs.is_empty() {
-        match filter {
-            Some(q) => println!("No battery packs found matching '{}'", q),
-            None => println!("No battery packs found"),
-        }
-        return Ok(());
+    // Inline the load_installed_packs logic to avoid re-reading the manifest.
+    let bp_names = find_installed_bp_names(&user_manifest_content)?;
+    let metadata_location = resolve_metadata_location(&user_manifest_path)?;
+    let specs = load_installed_bp_specs(&bp_names, path, source)?;
+    let packs: Vec<(String, InstalledPack)> = bp_names
+        .iter()
+        .cloned()
+        .zip(specs)
+        .map(|(bp_name, spec)| {
+            let spec = spec?;
+            let active_features =
+                read_active_features_from(&metadata_location, &user_manifest_content, &bp_name);
+            let registered =
+                registered_pack_version(&user_manifest_path, &user_manifest_content, &bp_name)?;
+            let pack = InstalledPack {
+                short_name: short_name(&bp_name).to_string(),
+                version: registered.unwrap_or_else(|| spec.version.clone()),
+                spec,
+                active_features,
+            };
+            Ok((bp_name, pack))
+        })
+        .collect::<Result<_>>()?;
+    let unregistered = unregistered_packs(
+        &user_manifest_path,
+        &metadata_location,
+        &user_manifest_content,
+        &bp_names,
+    )?;
+
+    if packs.is_empty() && unregistered.is_empty() && !format.is_json() {
+        println!("No battery packs installed.");
+        return Ok((Outcome::Clean, None));
     }
 
-    // Find the longest name for alignment
-    let max_name_len = battery_packs
+    // [impl cli.drift.unified]
+    let project = ProjectDeps::read(&user_manifest_path, &user_manifest_content)?;
+    let (findings, holds): (Vec<Vec<Finding>>, Vec<Vec<Hold>>) = packs
         .iter()
-        .map(|c| c.short_name.len())
-        .max()
-        .unwrap_or(0);
+        .map(|(bp_name, pack)| {
+            pack_findings(
+                &project,
+                &metadata_location,
+                &user_manifest_content,
+                bp_name,
+                &pack.spec,
+                &pack.active_features,
+                policy,
+            )
+        })
+        .unzip();
+
+    // Pack-level checks only make sense against the registry; a local
+    // source or --path has no release history.
+    let releases: Vec<Option<Result<PackRelease>>> =
+        if path.is_none() && matches!(source, CrateSource::Registry) {
+            fetch_all("Checking published versions", &packs, |(bp_name, pack)| {
+                Some(pack_release(bp_name, pack))
+            })
+        } else {
+            packs.iter().map(|_| None).collect()
+        };
 
-    let max_version_len = battery_packs
-        .iter()
-        .map(|c| c.version.len())
-        .max()
-        .unwrap_or(0);
+    let status = Outcome::of(findings.iter().flatten().chain(&unregistered));
 
-    println!();
-    for bp in &battery_packs {
-        let desc = bp.description.lines().next().unwrap_or("");
+    // [impl cli.json.status]
+    if format.is_json() {
+        let packs = packs
+            .iter()
+            .zip(releases)
+            .zip(findings.iter().zip(&holds))
+            .map(|(((bp_name, pack), release), (findings, holds))| {
+                let expected = pack.spec.resolve_for_features(&pack.active_features);
+                crate::output::PackStatus {
+                    name: bp_name.clone(),
+                    short_name: pack.short_name.clone(),
+                    registered_version: pack.version.clone(),
+                    installed_version: pack.spec.version.clone(),
+                    active_features: pack.active_features.iter().cloned().collect(),
+                    release: release.map(|release| match release {
+                        Ok(release) => crate::output::PackRelease {
+                            latest_version: release.status.latest,
+                            yanked: release.status.yanked,
+                            outdated: release.status.outdated,
+                            changes: release.changes.iter().map(Into::into).collect(),
+                            error: None,
+                        },
+                        Err(e) => crate::output::PackRelease {
+                            latest_version: None,
+                            yanked: false,
+                            outdated: false,
+                            changes: Vec::new(),
+                            error: Some(format!("{:#}", e)),
+                        },
+                    }),
+                    crates: crate::output::crate_statuses(&expected, &project, findings),
+                    findings: crate::output::findings(findings),
+                    held: crate::output::holds(holds),
+                }
+            })
+            .collect();
+        let output = crate::output::StatusOutput {
+            status: status.into(),
+            packs,
+            unregistered: crate::output::findings(&unregistered),
+        };
+        return Ok((status, Some(output)));
+    }
 
-        // Pad strings manually, then apply colors (ANSI codes break width formatting)
-        let name_padded = format!("{:<width$}", bp.short_name, width = max_name_len);
-        let ver_padded = format!("{:<width$}", bp.version, width = max_version_len);
+    let mut any_outdated = false;
 
+    for (((_, pack), release), (findings, holds)) in
+        packs.iter().zip(releases).zip(findings.iter().zip(&holds))
+    {
+        // [impl cli.status.list]
         println!(
-            "  {}  {}  {}",
-            style(name_padded).green().bold(),
-            style(ver_padded).dim(),
-            desc,
+            "{} ({})",
+            style(&pack.short_name).bold(),
+            style(&pack.version).dim(),
         );
+
+        match release {
+            Some(Ok(release)) => any_outdated |= print_pack_release(pack, &release),
+            Some(Err(e)) => println!(
+                "  {} could not check published versions: {}",
+                style("⚠").yellow(),
+                e
+            ),
+            None => {}
+        }
+
+        // [impl cli.status.version-warn]
+        if findings.is_empty() {
+            println!("  {} all dependencies up to date", style("✓").green());
+        } else {
+            findings.iter().for_each(print_finding);
+        }
+        // [impl cli.pins.report]
+        holds.iter().for_each(print_hold);
     }
-    println!();
 
-    println!(
-        "{}",
-        style(format!("Found {} battery pack(s)", battery_packs.len())).dim()
-    );
+    for finding in &unregistered {
+        println!(
+            "{} ({})",
+            style(short_name(&finding.pack)).bold(),
+            style("not registered").dim(),
+        );
+        print_finding(finding);
+    }
 
-    Ok(())
+    print_drift_hints(findings.iter().flatten().chain(&unregistered), any_outdated);
+
+    Ok((status, None))
 }
 
-/// Read installed state (managed-deps and active features) for a battery pack.
-/// Returns empty sets if not in a project or pack not installed.
-fn read_installed_state(
+// ============================================================================
+// Why command
+// ============================================================================
+
+// [impl cli.why.packs]
+// [impl cli.why.winner]
+// [impl cli.why.ownership]
+fn why_dependency(
     project_dir: &Path,
     crate_name: &str,
-) -> (BTreeSet<String>, BTreeSet<String>) {
-    let empty = (BTreeSet::new(), BTreeSet::new());
-    let Ok(manifest_path) = find_user_manifest(project_dir) else {
-        return empty;
-    };
-    let Ok(content) = std::fs::read_to_string(&manifest_path) else {
-        return empty;
-    };
-    let Ok(location) = resolve_metadata_location(&manifest_path) else {
-        return empty;
-    };
-    let managed = read_managed_deps_from(&location, &content, crate_name).unwrap_or_default();
-    let features = read_active_features_from(&location, &content, crate_name);
-    (managed, features)
-}
-
-fn print_battery_pack_detail(
-    name: &str,
     path: Option<&str>,
     source: &CrateSource,
-    project_dir: &Path,
 ) -> Result<()> {
+    use crate::why::{Ownership, Reason};
     use console::style;
 
-    // --path takes precedence over --crate-source
-    let detail = if path.is_some() {
-        fetch_battery_pack_detail(name, path)?
-    } else {
-        fetch_battery_pack_detail_from_source(source, name)?
-    };
-
-    // Read installed state from the project (if available)
-    let crate_name = resolve_crate_name(name);
-    let (managed_deps, active_features) = read_installed_state(project_dir, &crate_name);
-
-    // Header
-    println!();
-    println!(
-        "{} {}",
-        style(&detail.name).green().bold(),
-        style(&detail.version).dim()
-    );
-    if !detail.description.is_empty() {
-        println!("{}", detail.description);
-    }
+    let user_manifest_path =
+        find_user_manifest(project_dir).context("are you inside a Rust project?")?;
+    let user_manifest_content =
+        std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
 
-    // Authors
-    if !detail.owners.is_empty() {
-        println!();
-        println!("{}", style("Authors:").bold());
-        for owner in &detail.owners {
-            if let Some(name) = &owner.name {
-                println!("  {} ({})", name, owner.login);
-            } else {
-                println!("  {}", owner.login);
-            }
-        }
-    }
+    let bp_names = find_installed_bp_names(&user_manifest_content)?;
+    let metadata_location = resolve_metadata_location(&user_manifest_path)?;
+    let specs = load_installed_bp_specs(&bp_names, path, source)?
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    let features: Vec<BTreeSet<String>> = bp_names
+        .iter()
+        .map(|bp_name| {
+            read_active_features_from(&metadata_location, &user_manifest_content, bp_name)
+        })
+        .collect();
+    let managed: Vec<Option<BTreeSet<String>>> = bp_names
+        .iter()
+        .map(|bp_name| read_managed_deps_from(&metadata_location, &user_manifest_content, bp_name))
+        .collect();
+    // Pins only affect versions, which `why` reports as declared.
+    let no_pins = BTreeSet::new();
+    let packs: Vec<PackState<'_>> = bp_names
+        .iter()
+        .zip(&specs)
+        .zip(features.iter().zip(&managed))
+        .map(
+            |((name, spec), (active_features, managed_deps))| PackState {
+                name,
+                spec,
+                active_features,
+                managed_deps: managed_deps.as_ref(),
+                pinned: &no_pins,
+            },
+        )
+        .collect();
 
-    // Crates
-    if !detail.crates.is_empty() {
-        println!();
-        println!("{}", style("Crates:").bold());
-        for dep in &detail.crates {
-            let marker = if managed_deps.contains(dep) {
-                format!(" {}", style("✓").green())
-            } else {
-                String::new()
-            };
-            println!("  {}{}", dep, marker);
+    let project = ProjectDeps::read(&user_manifest_path, &user_manifest_content)?;
+    let why = crate::why::explain(crate_name, &project, &packs);
+    if why.declared.is_empty() && why.recommendations.is_empty() {
+        bail!(
+            "'{}' is not a dependency of this project and no installed battery pack curates it",
+            crate_name
+        );
+    }
+
+    println!("{}", style(&why.crate_name).bold());
+    for declared in &why.declared {
+        println!(
+            "  declared in [{}]: {}",
+            dep_kind_section(declared.kind),
+            declared.version.as_deref().unwrap_or("(no version)")
+        );
+    }
+    match &why.ownership {
+        Ownership::Managed(packs) => {
+            let names: Vec<&str> = packs.iter().map(|p| short_name(p)).collect();
+            println!("  managed by {}", names.join(", "));
         }
+        Ownership::User => println!("  user-owned: no battery pack manages it"),
+        Ownership::Absent => println!("  not in Cargo.toml"),
     }
 
-    // Features
-    if !detail.features.is_empty() {
-        println!();
-        println!("{}", style("Features:").bold());
-        for (feat_name, members) in &detail.features {
-            let marker = if active_features.contains(feat_name) {
-                format!(" {}", style("✓").green())
-            } else {
-                String::new()
+    for rec in &why.recommendations {
+        let label = format!("{} {}", short_name(&rec.pack), rec.pack_version);
+        if !rec.is_active() {
+            let hint = match rec.available_via.as_slice() {
+                [] => String::new(),
+                features => format!(" (enable {} to add it)", features.join(", ")),
             };
             println!(
-                "  {} → {}{}",
-                style(feat_name).cyan(),
-                members.join(", "),
-                marker
+                "  {} curates {}, not enabled{}",
+                style(label).dim(),
+                rec.spec.version,
+                hint
             );
+            continue;
         }
-    }
-
-    // Extends
-    if !detail.extends.is_empty() {
-        println!();
-        println!("{}", style("Extends:").bold());
-        for dep in &detail.extends {
-            println!("  {}", dep);
-        }
-    }
-
-    // Templates
-    if !detail.templates.is_empty() {
-        println!();
-        println!("{}", style("Templates:").bold());
-        let max_name_len = detail
-            .templates
+        let reasons: Vec<String> = rec
+            .reasons
             .iter()
-            .map(|t| t.name.len())
-            .max()
-            .unwrap_or(0);
-        for tmpl in &detail.templates {
-            let name_padded = format!("{:<width$}", tmpl.name, width = max_name_len);
-            if let Some(desc) = &tmpl.description {
-                println!("  {}  {}", style(name_padded).cyan(), desc);
+            .map(|reason| match reason {
+                Reason::Feature(feature) => format!("feature {}", feature),
+                Reason::AllFeatures => "all features".to_string(),
+                Reason::Always(kind) => format!("[{}], always included", dep_kind_section(*kind)),
+            })
+            .collect();
+        let cargo_features = if rec.spec.features.is_empty() {
+            String::new()
+        } else {
+            let list: Vec<&str> = rec.spec.features.iter().map(String::as_str).collect();
+            format!(" with {}", list.join(", "))
+        };
+        println!(
+            "  {} recommends {}{} in [{}] via {}{}",
+            label,
+            rec.spec.version,
+            cargo_features,
+            dep_kind_section(rec.spec.dep_kind),
+            reasons.join(", "),
+            if rec.wins {
+                style(" ✓").green().to_string()
             } else {
-                println!("  {}", style(name_padded).cyan());
-            }
-        }
+                String::new()
+            },
+        );
     }
 
-    // [impl format.examples.browsable]
-    // Examples
-    if !detail.examples.is_empty() {
-        println!();
-        println!("{}", style("Examples:").bold());
-        let max_name_len = detail
-            .examples
+    if let Some(merged) = &why.merged {
+        let winners: Vec<&str> = why
+            .recommendations
             .iter()
-            .map(|e| e.name.len())
-            .max()
-            .unwrap_or(0);
-        for example in &detail.examples {
-            let name_padded = format!("{:<width$}", example.name, width = max_name_len);
-            if let Some(desc) = &example.description {
-                println!("  {}  {}", style(name_padded).magenta(), desc);
-            } else {
-                println!("  {}", style(name_padded).magenta());
-            }
-        }
-    }
-
-    // Install hints
-    println!();
-    println!("{}", style("Install:").bold());
-    println!("  cargo bp add {}", detail.short_name);
-    println!("  cargo bp new {}", detail.short_name);
-    println!();
-
-    Ok(())
-}
-
-// [impl cli.show.template-preview]
-fn print_template_preview(opts: &crate::template_engine::PreviewOpts<'_>) -> Result<()> {
-    let (_crate_name, files) = crate::template_engine::preview_template(opts)?;
-
-    for file in &files {
-        println!("── {} ──", file.path);
-        println!("{}", file.content);
-        println!();
+            .filter(|r| r.wins)
+            .map(|r| short_name(&r.pack))
+            .collect();
+        let sections: Vec<String> = merged
+            .dep_kinds
+            .iter()
+            .map(|kind| format!("[{}]", dep_kind_section(*kind)))
+            .collect();
+        println!(
+            "  → {} in {} (from {})",
+            style(&merged.version).bold(),
+            sections.join(" and "),
+            winners.join(", ")
+        );
     }
 
     Ok(())
 }
 
 // ============================================================================
-// Status command
+// Tree command
 // ============================================================================
 
-// [impl cli.status.list]
-// [impl cli.status.version-warn]
-// [impl cli.status.no-project]
-// [impl cli.source.subcommands]
-// [impl cli.path.subcommands]
-fn status_battery_packs(
+// [impl cli.tree.structure]
+// [impl cli.tree.formats]
+fn tree_battery_packs(
     project_dir: &Path,
     path: Option<&str>,
     source: &CrateSource,
+    policy: SyncPolicy,
+    format: crate::tree::TreeFormat,
 ) -> Result<()> {
-    use console::style;
+    use crate::tree::TreeFormat;
 
-    // [impl cli.status.no-project]
     let user_manifest_path =
         find_user_manifest(project_dir).context("are you inside a Rust project?")?;
     let user_manifest_content =
         std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
 
-    // Inline the load_installed_packs logic to avoid re-reading the manifest.
     let bp_names = find_installed_bp_names(&user_manifest_content)?;
     let metadata_location = resolve_metadata_location(&user_manifest_path)?;
-    let packs: Vec<InstalledPack> = bp_names
-        .into_iter()
-        .map(|bp_name| {
-            let spec = load_installed_bp_spec(&bp_name, path, source)?;
+    let specs = load_installed_bp_specs(&bp_names, path, source)?;
+    let project = ProjectDeps::read(&user_manifest_path, &user_manifest_content)?;
+    let declared = collect_user_dep_versions(&user_manifest_path, &user_manifest_content)?;
+    // Nested packs come from the source even with --path, which only
+    // names the installed pack.
+    let load_nested = |name: &str| crate::registry::load_installed_bp_spec(name, None, source);
+
+    let packs = bp_names
+        .iter()
+        .zip(specs)
+        .map(|(bp_name, spec)| {
+            let spec = spec?;
             let active_features =
-                read_active_features_from(&metadata_location, &user_manifest_content, &bp_name);
-            Ok(InstalledPack {
-                short_name: short_name(&bp_name).to_string(),
-                version: spec.version.clone(),
-                spec,
-                active_features,
-            })
+                read_active_features_from(&metadata_location, &user_manifest_content, bp_name);
+            let registered =
+                registered_pack_version(&user_manifest_path, &user_manifest_content, bp_name)?;
+            // [impl cli.tree.drift]
+            let (findings, _) = pack_findings(
+                &project,
+                &metadata_location,
+                &user_manifest_content,
+                bp_name,
+                &spec,
+                &active_features,
+                policy,
+            );
+            Ok(crate::tree::build(
+                &crate::tree::InstalledNode {
+                    name: bp_name,
+                    version: registered.as_deref().unwrap_or(&spec.version),
+                    spec: &spec,
+                    active_features: &active_features,
+                    findings: &findings,
+                },
+                &declared,
+                &load_nested,
+            ))
         })
-        .collect::<Result<_>>()?;
+        .collect::<Result<Vec<_>>>()?;
+
+    match format {
+        TreeFormat::Text if packs.is_empty() => println!("No battery packs installed."),
+        TreeFormat::Text => print!("{}", crate::tree::render_text(&packs)),
+        TreeFormat::Dot => print!("{}", crate::tree::render_dot(&packs)),
+        TreeFormat::Json => crate::output::print(
+            "tree",
+            &crate::output::TreeOutput {
+                packs: packs.iter().map(Into::into).collect(),
+            },
+        )?,
+    }
+    Ok(())
+}
 
-    if packs.is_empty() {
-        println!("No battery packs installed.");
-        return Ok(());
+/// Run the drift analysis for one installed pack: its drift findings, and
+/// the crates whose versions pins or `policy` hold back.
+fn pack_findings(
+    project: &ProjectDeps,
+    metadata_location: &MetadataLocation,
+    user_manifest_content: &str,
+    bp_name: &str,
+    spec: &bphelper_manifest::BatteryPackSpec,
+    active_features: &BTreeSet<String>,
+    policy: SyncPolicy,
+) -> (Vec<Finding>, Vec<Hold>) {
+    let managed_deps = read_managed_deps_from(metadata_location, user_manifest_content, bp_name);
+    let pinned = read_pinned_from(metadata_location, user_manifest_content, bp_name);
+    let pack = PackState {
+        name: bp_name,
+        spec,
+        active_features,
+        managed_deps: managed_deps.as_ref(),
+        pinned: &pinned,
+    };
+    (
+        crate::drift::analyze(project, &pack, policy),
+        crate::drift::holds(project, &pack, policy),
+    )
+}
+
+/// Packs with battery-pack metadata but no `[build-dependencies]` entry.
+///
+/// With workspace-level metadata, a pack the workspace lists in
+/// `[workspace.dependencies]` counts as registered: other members may be
+/// the ones using it.
+fn unregistered_packs(
+    user_manifest_path: &Path,
+    metadata_location: &MetadataLocation,
+    user_manifest_content: &str,
+    bp_names: &[String],
+) -> Result<Vec<Finding>> {
+    let mut registered = bp_names.to_vec();
+    if let Some(ws_path) = find_workspace_manifest(user_manifest_path)?
+        && matches!(metadata_location, MetadataLocation::Workspace { .. })
+    {
+        let ws_content =
+            std::fs::read_to_string(&ws_path).context("Failed to read workspace Cargo.toml")?;
+        let ws_raw: toml::Value =
+            toml::from_str(&ws_content).context("Failed to parse workspace Cargo.toml")?;
+        if let Some(ws_deps) = ws_raw
+            .get("workspace")
+            .and_then(|w| w.get("dependencies"))
+            .and_then(|d| d.as_table())
+        {
+            registered.extend(ws_deps.keys().cloned());
+        }
     }
+    let metadata_names = read_metadata_bp_names(metadata_location, user_manifest_content);
+    Ok(crate::drift::unregistered(&metadata_names, &registered))
+}
 
-    // Build a map of the user's actual dependency versions so we can compare.
-    let user_versions = collect_user_dep_versions(&user_manifest_path, &user_manifest_content)?;
+/// Print one held crate, set apart from drift findings.
+fn print_hold(hold: &Hold) {
+    use console::style;
 
-    let mut any_warnings = false;
+    println!("  {} {}", style("=").cyan(), hold.message());
+}
 
-    for pack in &packs {
-        // [impl cli.status.list]
-        println!(
-            "{} ({})",
-            style(&pack.short_name).bold(),
-            style(&pack.version).dim(),
-        );
+/// Print one finding, marked as a warning or an error.
+fn print_finding(finding: &Finding) {
+    use console::style;
+
+    let marker = match finding.severity {
+        Severity::Warning => style("⚠").yellow(),
+        Severity::Error => style("✗").red(),
+    };
+    println!("  {} {}", marker, finding.message);
+}
 
-        // Resolve which crates are expected for this pack's active features.
-        let expected = pack.spec.resolve_for_features(&pack.active_features);
+/// Print what to run next, given every finding of a `status` or `check` run.
+fn print_drift_hints<'a>(findings: impl IntoIterator<Item = &'a Finding>, any_outdated: bool) {
+    use console::style;
 
-        let mut pack_warnings = Vec::new();
-        for (dep_name, dep_spec) in &expected {
-            if dep_spec.version.is_empty() {
-                continue;
-            }
-            if let Some(user_version) = user_versions.get(dep_name.as_str()) {
-                // [impl cli.status.version-warn]
-                if should_upgrade_version(user_version, &dep_spec.version) {
-                    pack_warnings.push((
-                        dep_name.as_str(),
-                        user_version.as_str(),
-                        dep_spec.version.as_str(),
-                    ));
-                }
-            }
+    let mut hints = Vec::new();
+    let mut needs_sync = false;
+    let mut needs_add = false;
+    for finding in findings {
+        needs_sync |= finding.kind.fixed_by_sync();
+        needs_add |= finding.kind == DriftKind::NotRegistered;
+    }
+    if needs_sync {
+        hints.push(format!("Run {} to update.", style("cargo bp sync").bold()));
+    }
+    if needs_add {
+        hints.push(format!(
+            "Run {} to register a pack, or remove its metadata.",
+            style("cargo bp add <pack>").bold()
+        ));
+    }
+    if any_outdated {
+        hints.push(format!(
+            "Update the battery pack version in {} to pick up newer releases.",
+            style("[build-dependencies]").bold()
+        ));
+    }
+    if !hints.is_empty() {
+        println!();
+        for hint in hints {
+            println!("{}", hint);
         }
+    }
+}
 
-        if pack_warnings.is_empty() {
-            println!("  {} all dependencies up to date", style("✓").green());
-        } else {
-            any_warnings = true;
-            for (dep, current, recommended) in &pack_warnings {
-                println!(
-                    "  {} {}: {} → {} recommended",
-                    style("⚠").yellow(),
-                    dep,
-                    style(current).red(),
-                    style(recommended).green(),
-                );
-            }
+/// What the registry says about an installed pack's release.
+struct PackRelease {
+    status: crate::registry::PackVersionStatus,
+    /// Crate changes from the installed to the latest release, when outdated.
+    changes: Vec<bphelper_manifest::CrateChange>,
+}
+
+fn pack_release(bp_name: &str, pack: &InstalledPack) -> Result<PackRelease> {
+    let published = crate::registry::fetch_published_versions(bp_name)?;
+    let status =
+        crate::registry::pack_version_status(&pack.version, &pack.spec.version, &published);
+    let changes = match status.latest.as_deref().filter(|_| status.outdated) {
+        Some(latest) => {
+            let latest_spec = crate::registry::fetch_bp_spec_at_version(bp_name, latest)?;
+            pack.spec.diff_crates(&latest_spec)
         }
-    }
+        None => Vec::new(),
+    };
+    Ok(PackRelease { status, changes })
+}
 
-    if any_warnings {
-        println!();
-        println!("Run {} to update.", style("cargo bp sync").bold());
+/// Print yanked/outdated warnings for one pack, plus what changed in the
+/// latest release. Returns whether the pack is outdated.
+fn print_pack_release(pack: &InstalledPack, release: &PackRelease) -> bool {
+    use console::style;
+
+    if release.status.yanked {
+        println!(
+            "  {} {} {} has been yanked",
+            style("⚠").yellow(),
+            pack.short_name,
+            style(&pack.spec.version).red(),
+        );
     }
 
-    Ok(())
+    let Some(latest) = release
+        .status
+        .latest
+        .as_deref()
+        .filter(|_| release.status.outdated)
+    else {
+        return false;
+    };
+    println!(
+        "  {} {} {} → {} available",
+        style("⚠").yellow(),
+        pack.short_name,
+        style(&pack.spec.version).red(),
+        style(latest).green(),
+    );
+
+    // [impl cli.status.pack-diff]
+    print_crate_changes(&release.changes);
+
+    true
 }
 
+/// A loaded pack in `check`: its spec, active features and findings.
+type CheckedPack = (
+    bphelper_manifest::BatteryPackSpec,
+    BTreeSet<String>,
+    Vec<Finding>,
+    Vec<Hold>,
+);
+
+// [impl cli.check.purpose]
+// [impl cli.check.version-drift]
+// [impl cli.check.output]
+// [impl cli.check.path]
+///
+/// As with `status`, the JSON document is returned rather than printed.
 fn check_battery_packs(
     project_dir: &Path,
-    _path: Option<&str>,
+    path: Option<&str>,
     source: &CrateSource,
-) -> Result<()> {
+    policy: SyncPolicy,
+    format: MessageFormat,
+) -> Result<(Outcome, Option<crate::output::CheckOutput>)> {
     let user_manifest_path = find_user_manifest(project_dir)?;
     let user_manifest_content =
         std::fs::read_to_string(&user_manifest_path).context("Failed to read Cargo.toml")?;
 
-    // For now, use build-dependencies to find battery packs (this will be updated when metadata reading is improved)
     let bp_names = find_installed_bp_names(&user_manifest_content)?;
-
-    if bp_names.is_empty() {
+    let metadata_location = resolve_metadata_location(&user_manifest_path)?;
+    let unregistered = unregistered_packs(
+        &user_manifest_path,
+        &metadata_location,
+        &user_manifest_content,
+        &bp_names,
+    )?;
+
+    // [impl cli.check.no-packs]
+    if bp_names.is_empty() && unregistered.is_empty() && !format.is_json() {
         println!("No battery packs installed.");
-        return Ok(());
+        return Ok((Outcome::Clean, None));
     }
 
-    println!("Checking {} installed battery pack(s)...", bp_names.len());
+    if !format.is_json() {
+        println!("Checking {} installed battery pack(s)...", bp_names.len());
+    }
+
+    // [impl cli.drift.unified]
+    let project = ProjectDeps::read(&user_manifest_path, &user_manifest_content)?;
+    let results: Vec<Result<CheckedPack>> = bp_names
+        .iter()
+        .zip(load_installed_bp_specs(&bp_names, path, source)?)
+        .map(|(bp_name, spec)| {
+            let spec = spec?;
+            let active_features =
+                read_active_features_from(&metadata_location, &user_manifest_content, bp_name);
+            let (findings, holds) = pack_findings(
+                &project,
+                &metadata_location,
+                &user_manifest_content,
+                bp_name,
+                &spec,
+                &active_features,
+                policy,
+            );
+            Ok((spec, active_features, findings, holds))
+        })
+        .collect();
 
-    // Get user's current dependency versions
-    let user_versions = collect_user_dep_versions(&user_manifest_path, &user_manifest_content)?;
+    let load_failed = results.iter().any(|r| r.is_err());
+    let outcome = Outcome::of(
+        results
+            .iter()
+            .flatten()
+            .flat_map(|(_, _, findings, _)| findings)
+            .chain(&unregistered),
+    )
+    .max(if load_failed {
+        Outcome::Errors
+    } else {
+        Outcome::Clean
+    });
 
-    let mut all_valid = true;
+    // [impl cli.json.check]
+    if format.is_json() {
+        let packs: Vec<crate::output::PackCheck> = bp_names
+            .iter()
+            .zip(&results)
+            .map(|(bp_name, result)| match result {
+                Ok((spec, active_features, findings, holds)) => crate::output::PackCheck {
+                    name: bp_name.clone(),
+                    version: Some(spec.version.clone()),
+                    error: None,
+                    crates: crate::output::crate_statuses(
+                        &spec.resolve_for_features(active_features),
+                        &project,
+                        findings,
+                    ),
+                    findings: crate::output::findings(findings),
+                    held: crate::output::holds(holds),
+                },
+                Err(e) => crate::output::PackCheck {
+                    name: bp_name.clone(),
+                    version: None,
+                    error: Some(format!("{:#}", e)),
+                    crates: Vec::new(),
+                    findings: Vec::new(),
+                    held: Vec::new(),
+                },
+            })
+            .collect();
+        let output = crate::output::CheckOutput {
+            ok: outcome == Outcome::Clean,
+            status: outcome.into(),
+            packs,
+            unregistered: crate::output::findings(&unregistered),
+        };
+        return Ok((outcome, Some(output)));
+    }
 
-    for bp_name in &bp_names {
+    for (bp_name, result) in bp_names.iter().zip(&results) {
         print!("  {} ... ", bp_name);
 
-        // Get the battery pack spec
-        let (_version, spec) = match crate::registry::fetch_bp_spec(source, bp_name) {
-            Ok(result) => result,
+        let (findings, holds) = match result {
+            Ok((_, _, findings, holds)) => (findings, holds),
             Err(e) => {
-                println!("❌ Failed to load spec: {}", e);
-                all_valid = false;
+                println!("❌ Failed to load spec: {:#}", e);
                 continue;
             }
         };
 
-        // Check for version drift
-        let mut warnings = Vec::new();
-        for (crate_name, crate_spec) in &spec.crates {
-            if let Some(user_version) = user_versions.get(crate_name)
-                && is_older_version(user_version, &crate_spec.version)
-            {
-                warnings.push(format!(
-                    "{}: {} → {}",
-                    crate_name, user_version, crate_spec.version
-                ));
-            }
+        match Outcome::of(findings) {
+            Outcome::Clean => println!("✅ OK"),
+            Outcome::Warnings => println!("⚠️  Drift found:"),
+            Outcome::Errors => println!("❌ Drift found:"),
         }
-
-        if warnings.is_empty() {
-            println!("✅ OK");
-        } else {
-            println!("⚠️  Outdated versions:");
-            for warning in warnings {
-                println!("    {}", warning);
-            }
-            all_valid = false;
+        for finding in findings {
+            print!("  ");
+            print_finding(finding);
+        }
+        // [impl cli.pins.report]
+        for hold in holds {
+            print!("  ");
+            print_hold(hold);
         }
     }
-
-    if all_valid {
-        println!("\nAll battery packs are up to date! ✅");
-    } else {
-        println!("\nSome dependencies are outdated. Run `cargo bp sync` to update. ⚠️");
+    for finding in &unregistered {
+        println!("  {} ... ❌ Not registered:", finding.pack);
+        print!("  ");
+        print_finding(finding);
     }
 
-    Ok(())
-}
-
-fn is_older_version(user_version: &str, recommended_version: &str) -> bool {
-    // Simple version comparison - parse as semver if possible
-    match (
-        semver::Version::parse(user_version),
-        semver::Version::parse(recommended_version),
-    ) {
-        (Ok(user), Ok(recommended)) => user < recommended,
-        _ => false, // If we can't parse, assume it's fine
+    match outcome {
+        Outcome::Clean => println!("\nAll battery packs are up to date! ✅"),
+        Outcome::Warnings => println!("\nSome dependencies have drifted. ⚠️"),
+        Outcome::Errors => println!("\nSome battery packs have errors. ❌"),
     }
+    print_drift_hints(
+        results
+            .iter()
+            .flatten()
+            .flat_map(|(_, _, findings, _)| findings)
+            .chain(&unregistered),
+        false,
+    );
+
+    Ok((outcome, None))
 }
 
 /// Collect the user's actual dependency versions from Cargo.toml (and workspace deps if applicable).
@@ -2152,6 +4836,41 @@ pub(crate) fn collect_user_dep_versions(
     Ok(versions)
 }
 
+/// Read the version requirement a project registered for a battery pack.
+///
+/// Looks at the `[build-dependencies]` entry, following `workspace = true`
+/// into the workspace's `[workspace.dependencies]`. Returns `None` when the
+/// entry carries no version (e.g. a path-only dependency).
+pub(crate) fn registered_pack_version(
+    user_manifest_path: &Path,
+    user_manifest_content: &str,
+    bp_name: &str,
+) -> Result<Option<String>> {
+    let raw: toml::Value =
+        toml::from_str(user_manifest_content).context("Failed to parse Cargo.toml")?;
+    let Some(entry) = raw.get("build-dependencies").and_then(|d| d.get(bp_name)) else {
+        return Ok(None);
+    };
+    if let Some(version) = extract_version_from_dep(entry) {
+        return Ok(Some(version));
+    }
+    if !is_workspace_ref(entry) {
+        return Ok(None);
+    }
+    let Some(ws_path) = find_workspace_manifest(user_manifest_path)? else {
+        return Ok(None);
+    };
+    let ws_content =
+        std::fs::read_to_string(&ws_path).context("Failed to read workspace Cargo.toml")?;
+    let ws_raw: toml::Value =
+        toml::from_str(&ws_content).context("Failed to parse workspace Cargo.toml")?;
+    Ok(ws_raw
+        .get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|d| d.get(bp_name))
+        .and_then(extract_version_from_dep))
+}
+
 /// Extract version strings from a TOML dependency table.
 fn extract_versions_from_table(
     table: Option<&toml::map::Map<String, toml::Value>>,
@@ -532,6 +532,7 @@ struct ParsedAdd {
     _template: Option<String>,
     _define: Vec<(String, String)>,
     _overwrite: bool,
+    members: crate::workspace::MemberSelection,
 }
 
 /// Parse args as `cargo bp add ...` and return all Add fields.
@@ -550,6 +551,7 @@ fn parse_add_command(args: &[&str]) -> ParsedAdd {
             template,
             define,
             overwrite,
+            members,
         } => ParsedAdd {
             _battery_pack: battery_pack,
             crates,
@@ -561,11 +563,21 @@ fn parse_add_command(args: &[&str]) -> ParsedAdd {
             _template: template,
             _define: define,
             _overwrite: overwrite,
+            members,
         },
         other => panic!("expected Add, got {:?}", std::mem::discriminant(&other)),
     }
 }
 
+// [verify cli.workspace.select]
+#[test]
+fn add_package_flags_are_not_crates() {
+    let parsed = parse_add_command(&["cargo", "bp", "add", "cli", "-p", "app", "-p", "tool"]);
+    assert!(parsed.crates.is_empty());
+    assert_eq!(parsed.members.package, ["app", "tool"]);
+    assert!(!parsed.members.workspace);
+}
+
 // ============================================================================
 // cli.add.features — -F/--features flag parsing
 // ============================================================================
@@ -1092,6 +1104,36 @@ fn add(
     .unwrap();
 }
 
+// [verify manifest.deps.existing]
+// [verify manifest.merge.plan]
+#[test]
+fn add_keeps_existing_entries_and_only_adds_what_is_missing() {
+    let tmp = make_temp_project();
+    let manifest = tmp.path().join("Cargo.toml");
+    let mut content = std::fs::read_to_string(&manifest).unwrap();
+    content.push_str(
+        "\n[dependencies]\nanyhow = { version = \"1.0.80\", features = [\"backtrace\"] }\n",
+    );
+    std::fs::write(&manifest, content).unwrap();
+
+    add(
+        "basic",
+        "basic-battery-pack",
+        &["default"],
+        FeatureMode::Default,
+        &[],
+        None,
+        tmp.path(),
+    );
+
+    let content = read_cargo_toml(&tmp);
+    assert!(
+        content.contains("anyhow = { version = \"1.0.80\", features = [\"backtrace\"] }"),
+        "{content}"
+    );
+    assert!(content.contains("thiserror = \"2\""), "{content}");
+}
+
 // ============================================================================
 // cli.add.register — battery pack appears in [build-dependencies]
 // ============================================================================
@@ -1801,6 +1843,407 @@ fn collect_versions_errors_on_invalid_toml() {
     assert!(result.is_err());
 }
 
+// ---------------------------------------------------------------------------
+// registered_pack_version — the pack version the project asked for
+// ---------------------------------------------------------------------------
+
+// [verify cli.status.list]
+#[test]
+fn registered_pack_version_reads_build_dependencies() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest_path = dir.path().join("Cargo.toml");
+    let content = r#"
+[package]
+name = "test"
+version = "0.1.0"
+
+[build-dependencies]
+cli-battery-pack = "0.4"
+error-battery-pack = { version = "0.2.1", features = ["default"] }
+local-battery-pack = { path = "../local" }
+"#;
+    std::fs::write(&manifest_path, content).unwrap();
+
+    let version = |name| super::registered_pack_version(&manifest_path, content, name).unwrap();
+    assert_eq!(version("cli-battery-pack").as_deref(), Some("0.4"));
+    assert_eq!(version("error-battery-pack").as_deref(), Some("0.2.1"));
+    assert_eq!(version("local-battery-pack"), None);
+    assert_eq!(version("missing-battery-pack"), None);
+}
+
+// [verify cli.status.list]
+#[test]
+fn registered_pack_version_follows_workspace_dependencies() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("Cargo.toml"),
+        r#"
+[workspace]
+members = ["app"]
+
+[workspace.dependencies]
+cli-battery-pack = "0.5.0"
+"#,
+    )
+    .unwrap();
+    let app = dir.path().join("app");
+    std::fs::create_dir(&app).unwrap();
+    let content = r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[build-dependencies]
+cli-battery-pack.workspace = true
+"#;
+    std::fs::write(app.join("Cargo.toml"), content).unwrap();
+
+    let version =
+        super::registered_pack_version(&app.join("Cargo.toml"), content, "cli-battery-pack")
+            .unwrap();
+    assert_eq!(version.as_deref(), Some("0.5.0"));
+}
+
+// ============================================================================
+// sync --dry-run / --check
+// ============================================================================
+
+/// A project registered with the basic fixture, with `anyhow` behind the
+/// pack's recommendation and `thiserror` missing.
+fn make_out_of_sync_project() -> tempfile::TempDir {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tmp.path().join("Cargo.toml"),
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "0.9"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+"#,
+    )
+    .unwrap();
+    tmp
+}
+
+fn sync_with_mode(project_dir: &std::path::Path, mode: super::SyncMode) -> anyhow::Result<()> {
+    let fixture = fixtures_dir().join("basic-battery-pack");
+    super::sync_battery_packs(
+        project_dir,
+        Some(fixture.to_str().unwrap()),
+        &crate::registry::CrateSource::Registry,
+        crate::manifest::SyncPolicy::default(),
+        mode,
+        super::Prune::Off,
+        false,
+    )
+}
+
+// [verify cli.sync.dry-run]
+#[test]
+fn sync_dry_run_writes_nothing() {
+    let tmp = make_out_of_sync_project();
+    let before = read_cargo_toml(&tmp);
+
+    sync_with_mode(tmp.path(), super::SyncMode::DryRun).unwrap();
+
+    assert_eq!(read_cargo_toml(&tmp), before);
+}
+
+// [verify cli.sync.check]
+#[test]
+fn sync_check_fails_when_out_of_sync() {
+    let tmp = make_out_of_sync_project();
+    let before = read_cargo_toml(&tmp);
+
+    let Err(err) = sync_with_mode(tmp.path(), super::SyncMode::Check) else {
+        panic!("expected --check to fail");
+    };
+    assert!(err.to_string().contains("out of sync"), "{err:#}");
+    assert_eq!(read_cargo_toml(&tmp), before);
+}
+
+// [verify cli.sync.check]
+#[test]
+fn sync_check_passes_after_sync() {
+    let tmp = make_out_of_sync_project();
+
+    sync_with_mode(tmp.path(), super::SyncMode::Write).unwrap();
+    let synced = read_cargo_toml(&tmp);
+    assert!(synced.contains("thiserror"), "{synced}");
+
+    sync_with_mode(tmp.path(), super::SyncMode::Check).unwrap();
+}
+
+// [verify cli.sync.dry-run]
+#[test]
+fn sync_dry_run_and_check_are_parsed() {
+    let cli = super::Cli::try_parse_from(["cargo", "bp", "sync", "--dry-run"]).unwrap();
+    assert!(matches!(
+        unwrap_bp_command(cli),
+        super::BpCommands::Sync {
+            dry_run: true,
+            check: false,
+            ..
+        }
+    ));
+    assert!(super::Cli::try_parse_from(["cargo", "bp", "sync", "--dry-run", "--check"]).is_err());
+}
+
+// [verify cli.sync.dep-kind]
+#[test]
+fn sync_moves_crates_whose_dep_kind_changed() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tmp.path().join("Cargo.toml"),
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+clap = { version = "4", features = ["derive"] }
+dialoguer = "0.11"
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+assert_cmd = "2.0" # for tests
+
+[dev-dependencies]
+predicates = "3.0"
+
+[build-dependencies]
+fancy-battery-pack = "0.2"
+cc = "1.0"
+
+[package.metadata.battery-pack.fancy-battery-pack]
+features = ["default"]
+managed-deps = ["assert_cmd", "clap", "dialoguer", "predicates"]
+"#,
+    )
+    .unwrap();
+    let fixture = fixtures_dir().join("fancy-battery-pack");
+    super::sync_battery_packs(
+        tmp.path(),
+        Some(fixture.to_str().unwrap()),
+        &crate::registry::CrateSource::Registry,
+        crate::manifest::SyncPolicy::default(),
+        super::SyncMode::Write,
+        super::Prune::Off,
+        false,
+    )
+    .unwrap();
+
+    let manifest = std::fs::read_to_string(tmp.path().join("Cargo.toml")).unwrap();
+    let doc: toml_edit::DocumentMut = manifest.parse().unwrap();
+    assert!(
+        !doc["dependencies"]
+            .as_table()
+            .unwrap()
+            .contains_key("assert_cmd"),
+        "{manifest}"
+    );
+    assert!(
+        manifest.contains(
+            "[dev-dependencies]\npredicates = \"3.0\"\nassert_cmd = \"2.0\" # for tests\n"
+        ),
+        "{manifest}"
+    );
+}
+
+// [verify cli.sync.dep-kind]
+#[test]
+fn add_leaves_crates_the_user_declared_in_place() {
+    let tmp = make_temp_project();
+    let cargo_toml = tmp.path().join("Cargo.toml");
+    let mut content = read_cargo_toml(&tmp);
+    content.push_str("\n[dependencies]\ninsta = \"1.34\"\n");
+    std::fs::write(&cargo_toml, content).unwrap();
+
+    add(
+        "managed",
+        "managed-battery-pack",
+        &[],
+        FeatureMode::Default,
+        &[],
+        None,
+        tmp.path(),
+    );
+
+    let manifest = read_cargo_toml(&tmp);
+    assert!(
+        extract_section(&manifest, "[dependencies]").contains("insta = \"1.34\""),
+        "{manifest}"
+    );
+}
+
+// ============================================================================
+// cargo bp enable / disable
+// ============================================================================
+
+fn toggle(
+    pack_name: &str,
+    fixture: &str,
+    features: &[&str],
+    toggle: super::FeatureToggle,
+    project_dir: &std::path::Path,
+) -> anyhow::Result<()> {
+    let fixture_path = fixtures_dir().join(fixture);
+    let features: Vec<String> = features.iter().map(|s| s.to_string()).collect();
+    super::toggle_features(
+        pack_name,
+        &features,
+        toggle,
+        Some(fixture_path.to_str().unwrap()),
+        &crate::registry::CrateSource::Registry,
+        project_dir,
+    )
+}
+
+fn active_features(tmp: &tempfile::TempDir, bp_name: &str) -> BTreeSet<String> {
+    crate::manifest::read_active_features(&read_cargo_toml(tmp), bp_name)
+}
+
+// [verify cli.features.enable]
+// [verify cli.features.disable]
+#[test]
+fn enable_then_disable_round_trips_crates() {
+    let tmp = make_temp_project();
+    add(
+        "fancy",
+        "fancy-battery-pack",
+        &[],
+        FeatureMode::Default,
+        &[],
+        None,
+        tmp.path(),
+    );
+    let fixture = "fancy-battery-pack";
+
+    toggle(
+        "fancy",
+        fixture,
+        &["indicators"],
+        super::FeatureToggle::Enable,
+        tmp.path(),
+    )
+    .unwrap();
+    let deps = extract_section(&read_cargo_toml(&tmp), "[dependencies]");
+    assert!(deps.contains("indicatif"), "{deps}");
+    assert!(deps.contains("console"), "{deps}");
+    assert_eq!(
+        active_features(&tmp, "fancy-battery-pack"),
+        BTreeSet::from(["default".to_string(), "indicators".to_string()])
+    );
+
+    toggle(
+        "fancy",
+        fixture,
+        &["indicators"],
+        super::FeatureToggle::Disable,
+        tmp.path(),
+    )
+    .unwrap();
+    let manifest = read_cargo_toml(&tmp);
+    let deps = extract_section(&manifest, "[dependencies]");
+    assert!(!deps.contains("indicatif"), "{deps}");
+    assert!(!deps.contains("console"), "{deps}");
+    assert!(deps.contains("clap"), "{deps}");
+    let managed = crate::manifest::read_managed_deps_from(
+        &crate::manifest::MetadataLocation::Package,
+        &manifest,
+        "fancy-battery-pack",
+    )
+    .unwrap();
+    assert!(!managed.contains("indicatif"), "{managed:?}");
+}
+
+// [verify cli.features.shared]
+#[test]
+fn disable_keeps_crates_another_pack_manages() {
+    let tmp = make_temp_project();
+    add(
+        "fancy",
+        "fancy-battery-pack",
+        &[],
+        FeatureMode::Default,
+        &[],
+        None,
+        tmp.path(),
+    );
+    add(
+        "managed",
+        "managed-battery-pack",
+        &[],
+        FeatureMode::Default,
+        &[],
+        None,
+        tmp.path(),
+    );
+    let fixture = "fancy-battery-pack";
+    toggle(
+        "fancy",
+        fixture,
+        &["indicators"],
+        super::FeatureToggle::Enable,
+        tmp.path(),
+    )
+    .unwrap();
+
+    toggle(
+        "fancy",
+        fixture,
+        &["default"],
+        super::FeatureToggle::Disable,
+        tmp.path(),
+    )
+    .unwrap();
+    let deps = extract_section(&read_cargo_toml(&tmp), "[dependencies]");
+    assert!(!deps.contains("dialoguer"), "{deps}");
+    assert!(deps.contains("clap"), "clap is shared with managed: {deps}");
+}
+
+// [verify cli.features.unknown]
+// [verify cli.features.disable]
+#[test]
+fn toggle_rejects_unknown_and_last_features() {
+    let tmp = make_temp_project();
+    add(
+        "fancy",
+        "fancy-battery-pack",
+        &[],
+        FeatureMode::Default,
+        &[],
+        None,
+        tmp.path(),
+    );
+    let before = read_cargo_toml(&tmp);
+
+    let err = toggle(
+        "fancy",
+        "fancy-battery-pack",
+        &["nope"],
+        super::FeatureToggle::Enable,
+        tmp.path(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no feature 'nope'"), "{err:#}");
+
+    let err = toggle(
+        "fancy",
+        "fancy-battery-pack",
+        &["default"],
+        super::FeatureToggle::Disable,
+        tmp.path(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("cargo bp rm"), "{err:#}");
+    assert_eq!(read_cargo_toml(&tmp), before);
+}
+
 // ============================================================================
 // infer_project_name
 // ============================================================================
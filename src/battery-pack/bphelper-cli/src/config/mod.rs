@@ -0,0 +1,488 @@
+//! Layered user and project configuration.
+//!
+//! Settings are read from the user config (`~/.config/cargo-bp/config.toml`),
+//! then the nearest project `.cargo-bp.toml`, then `CARGO_BP_*` environment
+//! variables. Later layers override earlier ones; command-line flags override
+//! everything and are applied by the command handlers.
+//! Depends on `manifest` (for `SyncPolicy`), `forge` (for `ForgeKind`) and the
+//! `AddTarget` CLI type.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::commands::AddTarget;
+use crate::forge::ForgeKind;
+use crate::manifest::SyncPolicy;
+
+/// File name of the project-level config.
+pub(crate) const PROJECT_CONFIG_FILE: &str = ".cargo-bp.toml";
+
+/// Scalar keys accepted by `cargo bp config get/set`.
+///
+/// `aliases.<name>`, `defines.<name>` and `forges.<host>` are accepted in
+/// addition to these.
+const SCALAR_KEYS: &[&str] = &[
+    "target",
+    "crate-source",
+    "registry.api",
+    "registry.download",
+    "sync.policy",
+];
+
+/// Table keys whose entries are free-form `<table>.<name>` pairs.
+const MAP_TABLES: &[&str] = &["aliases", "defines", "forges"];
+
+// ============================================================================
+// On-disk format
+// ============================================================================
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ConfigFile {
+    target: Option<String>,
+    crate_source: Option<PathBuf>,
+    #[serde(default)]
+    registry: RegistryFile,
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    defines: BTreeMap<String, String>,
+    #[serde(default)]
+    forges: BTreeMap<String, String>,
+    #[serde(default)]
+    sync: SyncFile,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RegistryFile {
+    api: Option<String>,
+    download: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct SyncFile {
+    policy: Option<SyncPolicy>,
+}
+
+// ============================================================================
+// Effective configuration
+// ============================================================================
+
+/// Where an effective config value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ConfigOrigin {
+    /// A config file (user or project).
+    File(PathBuf),
+    /// A `CARGO_BP_*` environment variable.
+    Env(&'static str),
+}
+
+impl std::fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigOrigin::File(path) => write!(f, "{}", path.display()),
+            ConfigOrigin::Env(var) => write!(f, "${}", var),
+        }
+    }
+}
+
+/// Registry endpoint overrides.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RegistryConfig {
+    /// crates.io-compatible API base (e.g. `https://crates.io/api/v1/crates`).
+    pub api: Option<String>,
+    /// Crate tarball download base (e.g. `https://static.crates.io/crates`).
+    pub download: Option<String>,
+}
+
+/// The merged configuration from all layers.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Config {
+    /// Default for `cargo bp add --target`.
+    pub target: Option<AddTarget>,
+    /// Default for `cargo bp --crate-source`.
+    pub crate_source: Option<PathBuf>,
+    pub registry: RegistryConfig,
+    /// Short pack names that expand to full crate names (`web` → `acme-web-battery-pack`).
+    pub aliases: BTreeMap<String, String>,
+    /// Default template placeholder values, overridden by `--define`.
+    pub defines: BTreeMap<String, String>,
+    /// Self-hosted forge hosts (`git.example.com` → GitLab) for repository links.
+    pub forges: BTreeMap<String, ForgeKind>,
+    /// How `cargo bp sync` treats outdated versions.
+    pub sync_policy: SyncPolicy,
+    /// Origin of each flattened key, for `cargo bp config list`.
+    origins: BTreeMap<String, ConfigOrigin>,
+}
+
+/// Environment variables consulted after the config files.
+const ENV_VARS: &[(&str, &str)] = &[
+    ("CARGO_BP_TARGET", "target"),
+    ("CARGO_BP_CRATE_SOURCE", "crate-source"),
+    ("CARGO_BP_REGISTRY_API", "registry.api"),
+    ("CARGO_BP_REGISTRY_DOWNLOAD", "registry.download"),
+    ("CARGO_BP_SYNC_POLICY", "sync.policy"),
+];
+
+/// Return the path of the user-level config file, if a home directory is known.
+///
+/// Honors `$XDG_CONFIG_HOME`, falling back to `~/.config`.
+pub(crate) fn user_config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("cargo-bp").join("config.toml"))
+}
+
+/// Find the nearest `.cargo-bp.toml`, walking up from `start`.
+pub(crate) fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+impl Config {
+    /// Load the effective configuration for a command run in `project_dir`.
+    // [impl cli.config.layers]
+    pub(crate) fn load(project_dir: &Path) -> Result<Self> {
+        Self::load_from(
+            user_config_path().as_deref(),
+            find_project_config(project_dir).as_deref(),
+            |var| std::env::var(var).ok(),
+        )
+    }
+
+    /// Load from explicit file locations and an environment lookup.
+    ///
+    /// Missing files are skipped. Empty environment variables are ignored.
+    pub(crate) fn load_from(
+        user_config: Option<&Path>,
+        project_config: Option<&Path>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut config = Config::default();
+
+        for path in [user_config, project_config].into_iter().flatten() {
+            if !path.is_file() {
+                continue;
+            }
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let file: ConfigFile = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            let base_dir = path.parent().unwrap_or(Path::new("."));
+            config
+                .apply_file(file, base_dir, &ConfigOrigin::File(path.to_path_buf()))
+                .with_context(|| format!("Invalid configuration in {}", path.display()))?;
+        }
+
+        for (var, key) in ENV_VARS {
+            if let Some(value) = env(var).filter(|v| !v.is_empty()) {
+                config
+                    .set_key(key, &value, Path::new("."), ConfigOrigin::Env(var))
+                    .with_context(|| format!("Invalid value in ${}", var))?;
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn apply_file(
+        &mut self,
+        file: ConfigFile,
+        base_dir: &Path,
+        origin: &ConfigOrigin,
+    ) -> Result<()> {
+        if let Some(target) = file.target {
+            self.set_key("target", &target, base_dir, origin.clone())?;
+        }
+        if let Some(path) = file.crate_source {
+            self.crate_source = Some(base_dir.join(path));
+            self.origins
+                .insert("crate-source".to_string(), origin.clone());
+        }
+        if let Some(api) = file.registry.api {
+            self.set_key("registry.api", &api, base_dir, origin.clone())?;
+        }
+        if let Some(download) = file.registry.download {
+            self.set_key("registry.download", &download, base_dir, origin.clone())?;
+        }
+        if let Some(policy) = file.sync.policy {
+            self.sync_policy = policy;
+            self.origins
+                .insert("sync.policy".to_string(), origin.clone());
+        }
+        for (name, target) in file.aliases {
+            self.set_key(
+                &format!("aliases.{name}"),
+                &target,
+                base_dir,
+                origin.clone(),
+            )?;
+        }
+        for (name, value) in file.defines {
+            self.set_key(&format!("defines.{name}"), &value, base_dir, origin.clone())?;
+        }
+        for (host, forge) in file.forges {
+            self.set_key(&format!("forges.{host}"), &forge, base_dir, origin.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Set a single flattened key, validating its value.
+    fn set_key(
+        &mut self,
+        key: &str,
+        value: &str,
+        base_dir: &Path,
+        origin: ConfigOrigin,
+    ) -> Result<()> {
+        match key {
+            "target" => self.target = Some(parse_target(value)?),
+            "crate-source" => self.crate_source = Some(base_dir.join(value)),
+            "registry.api" => self.registry.api = Some(value.to_string()),
+            "registry.download" => self.registry.download = Some(value.to_string()),
+            "sync.policy" => self.sync_policy = parse_sync_policy(value)?,
+            _ => match split_map_key(key) {
+                Some(("aliases", name)) => {
+                    self.aliases.insert(name.to_string(), value.to_string());
+                }
+                Some(("defines", name)) => {
+                    self.defines.insert(name.to_string(), value.to_string());
+                }
+                Some(("forges", host)) => {
+                    self.forges
+                        .insert(host.to_ascii_lowercase(), parse_forge(value)?);
+                }
+                _ => bail!("unknown configuration key '{}'", key),
+            },
+        }
+        self.origins.insert(key.to_string(), origin);
+        Ok(())
+    }
+
+    /// Expand a pack name through the configured aliases.
+    ///
+    /// Names without an alias are returned unchanged, so the usual
+    /// `-battery-pack` suffix resolution still applies afterwards.
+    // [impl cli.config.aliases]
+    pub(crate) fn resolve_alias(&self, name: &str) -> String {
+        self.aliases
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Merge `--define` flags over the configured default defines.
+    // [impl cli.config.defines]
+    pub(crate) fn defines_with(&self, flags: &[(String, String)]) -> Vec<(String, String)> {
+        let mut merged = self.defines.clone();
+        merged.extend(flags.iter().cloned());
+        merged.into_iter().collect()
+    }
+
+    /// Return every effective value as flattened `key → value` pairs.
+    pub(crate) fn entries(&self) -> BTreeMap<String, String> {
+        let mut entries = BTreeMap::new();
+        if let Some(target) = self.target {
+            entries.insert("target".to_string(), target_name(target).to_string());
+        }
+        if let Some(path) = &self.crate_source {
+            entries.insert("crate-source".to_string(), path.display().to_string());
+        }
+        if let Some(api) = &self.registry.api {
+            entries.insert("registry.api".to_string(), api.clone());
+        }
+        if let Some(download) = &self.registry.download {
+            entries.insert("registry.download".to_string(), download.clone());
+        }
+        entries.insert(
+            "sync.policy".to_string(),
+            self.sync_policy.as_str().to_string(),
+        );
+        for (name, target) in &self.aliases {
+            entries.insert(format!("aliases.{name}"), target.clone());
+        }
+        for (name, value) in &self.defines {
+            entries.insert(format!("defines.{name}"), value.clone());
+        }
+        for (host, forge) in &self.forges {
+            entries.insert(format!("forges.{host}"), forge.as_str().to_string());
+        }
+        entries
+    }
+
+    /// Where a flattened key's effective value came from (`None` for defaults).
+    pub(crate) fn origin(&self, key: &str) -> Option<&ConfigOrigin> {
+        self.origins.get(key)
+    }
+}
+
+fn parse_target(value: &str) -> Result<AddTarget> {
+    <AddTarget as clap::ValueEnum>::from_str(value, false).map_err(|_| {
+        anyhow::anyhow!(
+            "invalid target '{}' (expected workspace, package, or default)",
+            value
+        )
+    })
+}
+
+fn target_name(target: AddTarget) -> &'static str {
+    match target {
+        AddTarget::Workspace => "workspace",
+        AddTarget::Package => "package",
+        AddTarget::Default => "default",
+    }
+}
+
+fn parse_sync_policy(value: &str) -> Result<SyncPolicy> {
+    SyncPolicy::parse(value).ok_or_else(|| {
+        let names: Vec<_> = SyncPolicy::ALL.iter().map(|p| p.as_str()).collect();
+        anyhow::anyhow!(
+            "invalid sync policy '{}' (expected one of: {})",
+            value,
+            names.join(", ")
+        )
+    })
+}
+
+// [impl cli.config.forges]
+fn parse_forge(value: &str) -> Result<ForgeKind> {
+    ForgeKind::parse(value).ok_or_else(|| {
+        let names: Vec<_> = ForgeKind::ALL.iter().map(|k| k.as_str()).collect();
+        anyhow::anyhow!(
+            "invalid forge '{}' (expected one of: {})",
+            value,
+            names.join(", ")
+        )
+    })
+}
+
+/// Split `aliases.web` into `("aliases", "web")`.
+fn split_map_key(key: &str) -> Option<(&str, &str)> {
+    let (table, name) = key.split_once('.')?;
+    (MAP_TABLES.contains(&table) && !name.is_empty()).then_some((table, name))
+}
+
+/// Fail with the list of valid keys unless `key` is a known configuration key.
+fn check_known_key(key: &str) -> Result<()> {
+    if SCALAR_KEYS.contains(&key) || split_map_key(key).is_some() {
+        Ok(())
+    } else {
+        bail!(
+            "unknown configuration key '{}' (expected one of: {}, aliases.<name>, defines.<name>, forges.<host>)",
+            key,
+            SCALAR_KEYS.join(", ")
+        )
+    }
+}
+
+/// Validate a key and value before writing them to a config file.
+fn validate_key_value(key: &str, value: &str) -> Result<()> {
+    check_known_key(key)?;
+    match key {
+        "target" => parse_target(value).map(|_| ()),
+        "sync.policy" => parse_sync_policy(value).map(|_| ()),
+        _ if key.starts_with("forges.") => parse_forge(value).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// Write `key = value` into the config file at `path`, creating it if needed.
+///
+/// Existing formatting and comments in the file are preserved.
+// [impl cli.config.set]
+pub(crate) fn set_in_file(path: &Path, key: &str, value: &str) -> Result<()> {
+    validate_key_value(key, value)?;
+
+    let content = if path.exists() {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    match key.split_once('.') {
+        Some((table, name)) => {
+            let item = doc[table].or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
+            let Some(table) = item.as_table_like_mut() else {
+                bail!("'{}' in {} is not a table", table, path.display());
+            };
+            table.insert(name, toml_edit::value(value));
+        }
+        None => {
+            doc[key] = toml_edit::value(value);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    crate::history::before_write(path);
+    std::fs::write(path, doc.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+// ============================================================================
+// `cargo bp config` subcommands
+// ============================================================================
+
+/// Print the effective value of a single key.
+// [impl cli.config.get]
+pub(crate) fn config_get(config: &Config, key: &str) -> Result<()> {
+    check_known_key(key)?;
+    match config.entries().get(key) {
+        Some(value) => {
+            println!("{}", value);
+            Ok(())
+        }
+        None => bail!("configuration key '{}' is not set", key),
+    }
+}
+
+/// Write a key to the project config (or the user config with `global`).
+// [impl cli.config.set]
+pub(crate) fn config_set(project_dir: &Path, key: &str, value: &str, global: bool) -> Result<()> {
+    let path = if global {
+        user_config_path().context("Could not determine the user config directory")?
+    } else {
+        find_project_config(project_dir).unwrap_or_else(|| project_dir.join(PROJECT_CONFIG_FILE))
+    };
+    set_in_file(&path, key, value)?;
+    println!("Set {} = {:?} in {}", key, value, path.display());
+    Ok(())
+}
+
+/// Print every effective value with its origin.
+// [impl cli.config.list]
+pub(crate) fn config_list(config: &Config) -> Result<()> {
+    use console::style;
+
+    for (key, value) in config.entries() {
+        let origin = match config.origin(&key) {
+            Some(origin) => origin.to_string(),
+            None => "default".to_string(),
+        };
+        println!(
+            "{} = {:?} {}",
+            key,
+            value,
+            style(format!("({})", origin)).dim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,358 @@
+//! Tests for layered configuration loading and `cargo bp config`.
+
+use super::{Config, ConfigOrigin};
+use crate::commands::AddTarget;
+use crate::forge::ForgeKind;
+use crate::manifest::SyncPolicy;
+use std::path::Path;
+
+fn no_env(_: &str) -> Option<String> {
+    None
+}
+
+fn write(dir: &Path, name: &str, content: &str) -> std::path::PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+// ============================================================================
+// cli.config.layers — user < project < env
+// ============================================================================
+
+// [verify cli.config.layers]
+#[test]
+fn missing_files_yield_defaults() {
+    let tmp = tempfile::tempdir().unwrap();
+    let config = Config::load_from(
+        Some(&tmp.path().join("nope.toml")),
+        Some(&tmp.path().join(".cargo-bp.toml")),
+        no_env,
+    )
+    .unwrap();
+
+    assert_eq!(config.target, None);
+    assert_eq!(config.crate_source, None);
+    assert_eq!(config.sync_policy, SyncPolicy::AllowBreaking);
+    assert!(config.aliases.is_empty());
+    assert!(config.defines.is_empty());
+}
+
+// [verify cli.config.layers]
+#[test]
+fn project_config_overrides_user_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let user = write(
+        tmp.path(),
+        "user.toml",
+        r#"
+target = "package"
+
+[aliases]
+web = "acme-web-battery-pack"
+db = "acme-db-battery-pack"
+
+[defines]
+ci_platform = "gitlab"
+license = "MIT"
+"#,
+    );
+    let project = write(
+        tmp.path(),
+        ".cargo-bp.toml",
+        r#"
+target = "workspace"
+
+[aliases]
+db = "other-db-battery-pack"
+
+[defines]
+ci_platform = "github"
+"#,
+    );
+
+    let config = Config::load_from(Some(&user), Some(&project), no_env).unwrap();
+
+    assert_eq!(config.target, Some(AddTarget::Workspace));
+    assert_eq!(config.aliases["web"], "acme-web-battery-pack");
+    assert_eq!(config.aliases["db"], "other-db-battery-pack");
+    assert_eq!(config.defines["ci_platform"], "github");
+    assert_eq!(config.defines["license"], "MIT");
+    assert_eq!(
+        config.origin("target"),
+        Some(&ConfigOrigin::File(project.clone()))
+    );
+    assert_eq!(
+        config.origin("aliases.web"),
+        Some(&ConfigOrigin::File(user))
+    );
+}
+
+// [verify cli.config.layers]
+#[test]
+fn env_overrides_config_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = write(
+        tmp.path(),
+        ".cargo-bp.toml",
+        r#"
+target = "workspace"
+
+[sync]
+policy = "never-upgrade"
+"#,
+    );
+
+    let env = |var: &str| match var {
+        "CARGO_BP_TARGET" => Some("package".to_string()),
+        "CARGO_BP_SYNC_POLICY" => Some("compatible-only".to_string()),
+        "CARGO_BP_REGISTRY_API" => Some(String::new()),
+        _ => None,
+    };
+    let config = Config::load_from(None, Some(&project), env).unwrap();
+
+    assert_eq!(config.target, Some(AddTarget::Package));
+    assert_eq!(config.sync_policy, SyncPolicy::CompatibleOnly);
+    assert_eq!(
+        config.origin("sync.policy"),
+        Some(&ConfigOrigin::Env("CARGO_BP_SYNC_POLICY"))
+    );
+    // Empty env vars are ignored.
+    assert_eq!(config.registry.api, None);
+}
+
+// [verify cli.config.layers]
+#[test]
+fn crate_source_is_relative_to_config_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = write(
+        tmp.path(),
+        ".cargo-bp.toml",
+        "crate-source = \"../packs\"\n",
+    );
+
+    let config = Config::load_from(None, Some(&project), no_env).unwrap();
+
+    assert_eq!(config.crate_source, Some(tmp.path().join("../packs")));
+}
+
+// [verify cli.config.layers]
+#[test]
+fn invalid_values_are_reported_with_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = write(tmp.path(), ".cargo-bp.toml", "target = \"everywhere\"\n");
+
+    let err = Config::load_from(None, Some(&project), no_env).unwrap_err();
+    let msg = format!("{:#}", err);
+
+    assert!(msg.contains(".cargo-bp.toml"), "{msg}");
+    assert!(msg.contains("invalid target 'everywhere'"), "{msg}");
+}
+
+// [verify cli.config.layers]
+#[test]
+fn unknown_keys_are_rejected() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = write(tmp.path(), ".cargo-bp.toml", "colour = \"always\"\n");
+
+    assert!(Config::load_from(None, Some(&project), no_env).is_err());
+}
+
+// [verify cli.config.layers]
+#[test]
+fn project_config_is_found_in_ancestors() {
+    let tmp = tempfile::tempdir().unwrap();
+    let nested = tmp.path().join("crates/app");
+    std::fs::create_dir_all(&nested).unwrap();
+    let project = write(tmp.path(), ".cargo-bp.toml", "");
+
+    assert_eq!(super::find_project_config(&nested), Some(project));
+}
+
+// ============================================================================
+// cli.config.aliases / cli.config.defines
+// ============================================================================
+
+// [verify cli.config.aliases]
+#[test]
+fn aliases_expand_known_names_only() {
+    let mut config = Config::default();
+    config
+        .aliases
+        .insert("web".to_string(), "acme-web-battery-pack".to_string());
+
+    assert_eq!(config.resolve_alias("web"), "acme-web-battery-pack");
+    assert_eq!(config.resolve_alias("cli"), "cli");
+}
+
+// [verify cli.config.defines]
+#[test]
+fn define_flags_override_configured_defines() {
+    let mut config = Config::default();
+    config
+        .defines
+        .insert("ci_platform".to_string(), "gitlab".to_string());
+    config
+        .defines
+        .insert("license".to_string(), "MIT".to_string());
+
+    let merged = config.defines_with(&[("ci_platform".to_string(), "github".to_string())]);
+
+    assert_eq!(
+        merged,
+        vec![
+            ("ci_platform".to_string(), "github".to_string()),
+            ("license".to_string(), "MIT".to_string()),
+        ]
+    );
+}
+
+// [verify cli.config.forges]
+#[test]
+fn forges_map_hosts_to_forge_kinds() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = write(
+        tmp.path(),
+        ".cargo-bp.toml",
+        r#"
+[forges]
+"Git.Example.com" = "gitlab"
+"code.example.org" = "forgejo"
+"#,
+    );
+
+    let config = Config::load_from(None, Some(&project), no_env).unwrap();
+
+    assert_eq!(config.forges["git.example.com"], ForgeKind::GitLab);
+    assert_eq!(config.forges["code.example.org"], ForgeKind::Gitea);
+    assert_eq!(config.entries()["forges.code.example.org"], "gitea");
+
+    let bad = write(tmp.path(), "bad.toml", "[forges]\n\"x.org\" = \"svn\"\n");
+    let err = Config::load_from(None, Some(&bad), no_env).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("invalid forge 'svn'"),
+        "{err:#}"
+    );
+}
+
+// [verify cli.config.forges]
+#[test]
+fn set_writes_forge_hosts_as_quoted_keys() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join(".cargo-bp.toml");
+
+    super::set_in_file(&path, "forges.git.example.com", "gitlab").unwrap();
+    assert!(super::set_in_file(&path, "forges.other.org", "cvs").is_err());
+
+    let config = Config::load_from(None, Some(&path), no_env).unwrap();
+    assert_eq!(config.forges["git.example.com"], ForgeKind::GitLab);
+}
+
+// ============================================================================
+// cli.config.set — writes keys, preserving the rest of the file
+// ============================================================================
+
+// [verify cli.config.set]
+#[test]
+fn set_creates_file_and_round_trips() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("nested/config.toml");
+
+    super::set_in_file(&path, "target", "workspace").unwrap();
+    super::set_in_file(&path, "aliases.web", "acme-web-battery-pack").unwrap();
+    super::set_in_file(&path, "sync.policy", "compatible-only").unwrap();
+
+    let config = Config::load_from(Some(&path), None, no_env).unwrap();
+    assert_eq!(config.target, Some(AddTarget::Workspace));
+    assert_eq!(config.aliases["web"], "acme-web-battery-pack");
+    assert_eq!(config.sync_policy, SyncPolicy::CompatibleOnly);
+}
+
+// [verify cli.config.set]
+#[test]
+fn set_preserves_comments() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = write(
+        tmp.path(),
+        ".cargo-bp.toml",
+        "# team defaults\ntarget = \"package\"\n",
+    );
+
+    super::set_in_file(&path, "target", "workspace").unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with("# team defaults\n"), "{content}");
+    assert!(content.contains("target = \"workspace\""), "{content}");
+}
+
+// [verify cli.config.set]
+#[test]
+fn set_rejects_unknown_keys_and_bad_values() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join(".cargo-bp.toml");
+
+    assert!(super::set_in_file(&path, "colour", "always").is_err());
+    assert!(super::set_in_file(&path, "aliases.", "x").is_err());
+    assert!(super::set_in_file(&path, "sync.policy", "yolo").is_err());
+    assert!(!path.exists(), "nothing should be written on error");
+}
+
+// ============================================================================
+// cli.config.list — flattened effective values
+// ============================================================================
+
+// [verify cli.config.list]
+#[test]
+fn entries_flatten_all_values() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = write(
+        tmp.path(),
+        ".cargo-bp.toml",
+        r#"
+target = "default"
+
+[registry]
+api = "https://registry.example.com/api/v1/crates"
+
+[aliases]
+web = "acme-web-battery-pack"
+"#,
+    );
+
+    let config = Config::load_from(None, Some(&project), no_env).unwrap();
+    let keys: Vec<String> = config.entries().into_keys().collect();
+
+    assert_eq!(
+        keys,
+        vec!["aliases.web", "registry.api", "sync.policy", "target"]
+    );
+    assert_eq!(config.origin("sync.policy"), None, "policy is a default");
+}
+
+// [verify cli.config.get]
+#[test]
+fn parses_config_subcommands() {
+    use clap::Parser;
+
+    let cli = crate::commands::Cli::try_parse_from([
+        "cargo",
+        "bp",
+        "config",
+        "set",
+        "aliases.web",
+        "acme-web-battery-pack",
+        "--global",
+    ])
+    .unwrap();
+    let crate::commands::Commands::Bp { command, .. } = cli.command;
+    match command {
+        crate::commands::BpCommands::Config {
+            command: crate::commands::ConfigCommands::Set { key, value, global },
+        } => {
+            assert_eq!(key, "aliases.web");
+            assert_eq!(value, "acme-web-battery-pack");
+            assert!(global);
+        }
+        _ => panic!("expected config set"),
+    }
+}
@@ -0,0 +1,367 @@
+//! `cargo bp doctor`: finding and repairing battery-pack metadata that has
+//! drifted out of step with the manifests.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+const PACKAGE: [&str; 2] = ["package", "metadata"];
+const WORKSPACE: [&str; 2] = ["workspace", "metadata"];
+
+/// One inconsistency in a project's battery-pack metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Problem {
+    /// The pack has a metadata entry but no `[build-dependencies]` entry.
+    Unregistered { pack: String },
+    /// The pack has a `package.metadata` entry while the metadata lives in
+    /// `workspace.metadata`, so the package entry is ignored. `duplicate`
+    /// is set when the workspace has an entry for the pack too.
+    Shadowed { pack: String, duplicate: bool },
+    /// `managed-deps` lists crates the manifest doesn't depend on.
+    MissingManagedDeps {
+        pack: String,
+        crates: BTreeSet<String>,
+    },
+    /// The pack's metadata predates `managed-deps`.
+    NoManagedDeps { pack: String },
+    /// `build.rs` still calls `validate()` on a pack that isn't registered.
+    StaleValidate { pack: String },
+}
+
+impl Problem {
+    pub(crate) fn pack(&self) -> &str {
+        match self {
+            Problem::Unregistered { pack }
+            | Problem::Shadowed { pack, .. }
+            | Problem::MissingManagedDeps { pack, .. }
+            | Problem::NoManagedDeps { pack }
+            | Problem::StaleValidate { pack } => pack,
+        }
+    }
+
+    /// What fixing the problem does.
+    pub(crate) fn remedy(&self) -> String {
+        match self {
+            Problem::Unregistered { .. } => "remove its metadata entry".to_string(),
+            Problem::Shadowed {
+                duplicate: true, ..
+            } => "remove the package-level entry".to_string(),
+            Problem::Shadowed {
+                duplicate: false, ..
+            } => "move the entry to workspace.metadata".to_string(),
+            Problem::MissingManagedDeps { crates, .. } => {
+                format!("drop {} from managed-deps", join(crates))
+            }
+            Problem::NoManagedDeps { .. } => {
+                "record the pack's crates the manifest has as managed-deps".to_string()
+            }
+            Problem::StaleValidate { pack } => {
+                format!(
+                    "remove `{}::validate();` from build.rs",
+                    pack.replace('-', "_")
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Unregistered { pack } => write!(
+                f,
+                "{pack} has battery-pack metadata but isn't in [build-dependencies]"
+            ),
+            Problem::Shadowed {
+                pack,
+                duplicate: true,
+            } => write!(
+                f,
+                "{pack} has metadata in both package.metadata and workspace.metadata; \
+                 the workspace entry is used"
+            ),
+            Problem::Shadowed {
+                pack,
+                duplicate: false,
+            } => write!(
+                f,
+                "{pack}'s package.metadata entry is ignored because battery-pack \
+                 metadata lives in workspace.metadata"
+            ),
+            Problem::MissingManagedDeps { pack, crates } => write!(
+                f,
+                "{pack}'s managed-deps lists {}, which the manifest doesn't depend on",
+                join(crates)
+            ),
+            Problem::NoManagedDeps { pack } => write!(
+                f,
+                "{pack}'s metadata has no managed-deps (written by an older cargo-bp)"
+            ),
+            Problem::StaleValidate { pack } => write!(
+                f,
+                "build.rs calls {}::validate() but {pack} isn't in [build-dependencies]",
+                pack.replace('-', "_")
+            ),
+        }
+    }
+}
+
+fn join(crates: &BTreeSet<String>) -> String {
+    crates
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The parsed files `doctor` looks at.
+pub(crate) struct Project<'a> {
+    pub user: &'a toml::Value,
+    pub workspace: Option<&'a toml::Value>,
+    pub build_rs: Option<&'a str>,
+}
+
+/// Find every problem in `project`, ordered by pack.
+// [impl cli.doctor.checks]
+pub(crate) fn diagnose(project: &Project<'_>) -> Vec<Problem> {
+    let registered = registered_packs(project);
+    let package = metadata_table(project.user, &PACKAGE);
+    let workspace = project
+        .workspace
+        .and_then(|ws| metadata_table(ws, &WORKSPACE));
+
+    let mut problems = Vec::new();
+    // Mirrors `resolve_metadata_location`: any workspace-level table wins.
+    let mut present = dependency_names(project.user);
+    let effective = match (workspace, package) {
+        (Some(ws), package) => {
+            for pack in package.into_iter().flat_map(|p| p.keys()) {
+                problems.push(Problem::Shadowed {
+                    pack: pack.clone(),
+                    duplicate: ws.contains_key(pack),
+                });
+            }
+            // Workspace-level metadata covers crates other members use.
+            present.extend(
+                project
+                    .workspace
+                    .and_then(|ws| ws.get("workspace")?.get("dependencies")?.as_table())
+                    .into_iter()
+                    .flat_map(|t| t.keys().map(String::as_str)),
+            );
+            Some(ws)
+        }
+        (None, package) => package,
+    };
+
+    for (pack, entry) in effective.into_iter().flatten() {
+        let Some(entry) = entry.as_table() else {
+            continue;
+        };
+        if !registered.contains(pack) {
+            problems.push(Problem::Unregistered { pack: pack.clone() });
+        }
+        match entry.get("managed-deps").and_then(|d| d.as_array()) {
+            None => problems.push(Problem::NoManagedDeps { pack: pack.clone() }),
+            Some(managed) => {
+                let crates: BTreeSet<String> = managed
+                    .iter()
+                    .filter_map(|d| d.as_str())
+                    .filter(|d| !present.contains(*d))
+                    .map(String::from)
+                    .collect();
+                if !crates.is_empty() {
+                    problems.push(Problem::MissingManagedDeps {
+                        pack: pack.clone(),
+                        crates,
+                    });
+                }
+            }
+        }
+    }
+
+    for pack in validated_packs(project.build_rs.unwrap_or_default()) {
+        if !registered.contains(&pack) {
+            problems.push(Problem::StaleValidate { pack });
+        }
+    }
+
+    problems.sort_by(|a, b| a.pack().cmp(b.pack()));
+    problems
+}
+
+/// Fix `problem` in the parsed manifests. Problems that take more than a
+/// metadata edit ([`Problem::NoManagedDeps`] and [`Problem::StaleValidate`])
+/// are left alone.
+// [impl cli.doctor.fix]
+pub(crate) fn fix(
+    problem: &Problem,
+    user: &mut toml_edit::DocumentMut,
+    workspace: Option<&mut toml_edit::DocumentMut>,
+) {
+    match problem {
+        Problem::Unregistered { pack } => {
+            let (doc, prefix) = metadata_home(user, workspace);
+            remove_entry(doc, prefix, pack);
+        }
+        Problem::Shadowed { pack, duplicate } => {
+            let entry = metadata_table_mut(user, &PACKAGE).and_then(|t| t.get(pack).cloned());
+            if !duplicate
+                && let (Some(entry), Some(ws)) = (entry, workspace)
+                && let Some(table) = metadata_table_mut(ws, &WORKSPACE)
+            {
+                table.insert(pack, entry);
+            }
+            remove_entry(user, &PACKAGE, pack);
+        }
+        Problem::MissingManagedDeps { pack, crates } => {
+            let (doc, prefix) = metadata_home(user, workspace);
+            if let Some(managed) = metadata_table_mut(doc, prefix)
+                .and_then(|t| t.get_mut(pack))
+                .and_then(|e| e.get_mut("managed-deps"))
+                .and_then(|d| d.as_array_mut())
+            {
+                managed.retain(|d| d.as_str().is_none_or(|d| !crates.contains(d)));
+            }
+        }
+        Problem::NoManagedDeps { .. } | Problem::StaleValidate { .. } => {}
+    }
+}
+
+/// Fix [`Problem::NoManagedDeps`]: record as the pack's `managed-deps`
+/// the crates of `curated` (its current resolution) that the manifest
+/// depends on. Sync would otherwise adopt them all at its next run.
+// [impl cli.doctor.fix]
+pub(crate) fn backfill_managed_deps(
+    user: &mut toml_edit::DocumentMut,
+    workspace: Option<&mut toml_edit::DocumentMut>,
+    pack: &str,
+    curated: &BTreeSet<String>,
+) {
+    let present: BTreeSet<String> = ["dependencies", "dev-dependencies", "build-dependencies"]
+        .iter()
+        .filter_map(|section| user.get(section)?.as_table_like())
+        .flat_map(|t| t.iter().map(|(name, _)| name.to_string()))
+        .collect();
+    let (doc, prefix) = metadata_home(user, workspace);
+    let Some(entry) = metadata_table_mut(doc, prefix)
+        .and_then(|t| t.get_mut(pack))
+        .and_then(|e| e.as_table_like_mut())
+    else {
+        return;
+    };
+    let managed: toml_edit::Array = curated.intersection(&present).map(String::as_str).collect();
+    entry.insert("managed-deps", toml_edit::value(managed));
+}
+
+/// Battery packs in the member's `[build-dependencies]` or the
+/// workspace's `[workspace.dependencies]`.
+fn registered_packs(project: &Project<'_>) -> BTreeSet<String> {
+    let is_pack = |name: &String| name.ends_with("-battery-pack") || name == "battery-pack";
+    let member = project
+        .user
+        .get("build-dependencies")
+        .and_then(|d| d.as_table())
+        .into_iter()
+        .flat_map(|t| t.keys());
+    let workspace = project
+        .workspace
+        .and_then(|ws| ws.get("workspace"))
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|d| d.as_table())
+        .into_iter()
+        .flat_map(|t| t.keys());
+    member
+        .chain(workspace)
+        .filter(|n| is_pack(n))
+        .cloned()
+        .collect()
+}
+
+/// Crates in any of the manifest's dependency sections.
+fn dependency_names(manifest: &toml::Value) -> BTreeSet<&str> {
+    ["dependencies", "dev-dependencies", "build-dependencies"]
+        .iter()
+        .filter_map(|section| manifest.get(section)?.as_table())
+        .flat_map(|t| t.keys().map(String::as_str))
+        .collect()
+}
+
+/// Packs whose `validate()` `build.rs` calls, as crate names.
+fn validated_packs(build_rs: &str) -> BTreeSet<String> {
+    build_rs
+        .lines()
+        .filter_map(|line| line.trim().strip_suffix("::validate();"))
+        .filter(|ident| *ident == "battery_pack" || ident.ends_with("_battery_pack"))
+        .filter(|ident| ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .map(|ident| ident.replace('_', "-"))
+        .collect()
+}
+
+fn metadata_table<'a>(
+    manifest: &'a toml::Value,
+    prefix: &[&str; 2],
+) -> Option<&'a toml::map::Map<String, toml::Value>> {
+    manifest
+        .get(prefix[0])?
+        .get(prefix[1])?
+        .get("battery-pack")?
+        .as_table()
+}
+
+/// The document and prefix battery-pack metadata lives under, as
+/// `resolve_metadata_location` decides it.
+fn metadata_home<'a>(
+    user: &'a mut toml_edit::DocumentMut,
+    workspace: Option<&'a mut toml_edit::DocumentMut>,
+) -> (&'a mut toml_edit::DocumentMut, &'static [&'static str; 2]) {
+    match workspace {
+        Some(ws)
+            if ws
+                .get(WORKSPACE[0])
+                .and_then(|w| w.get(WORKSPACE[1]))
+                .and_then(|m| m.get("battery-pack"))
+                .is_some() =>
+        {
+            (ws, &WORKSPACE)
+        }
+        _ => (user, &PACKAGE),
+    }
+}
+
+fn metadata_table_mut<'a>(
+    doc: &'a mut toml_edit::DocumentMut,
+    prefix: &[&str; 2],
+) -> Option<&'a mut dyn toml_edit::TableLike> {
+    doc.get_mut(prefix[0])?
+        .get_mut(prefix[1])?
+        .get_mut("battery-pack")?
+        .as_table_like_mut()
+}
+
+/// Remove a pack's metadata entry, and the tables above it that it leaves
+/// empty.
+fn remove_entry(doc: &mut toml_edit::DocumentMut, prefix: &[&str; 2], pack: &str) {
+    let Some(table) = metadata_table_mut(doc, prefix) else {
+        return;
+    };
+    table.remove(pack);
+    if !table.is_empty() {
+        return;
+    }
+    let Some(metadata) = doc
+        .get_mut(prefix[0])
+        .and_then(|t| t.get_mut(prefix[1]))
+        .and_then(|m| m.as_table_like_mut())
+    else {
+        return;
+    };
+    metadata.remove("battery-pack");
+    if metadata.is_empty()
+        && let Some(parent) = doc.get_mut(prefix[0]).and_then(|t| t.as_table_like_mut())
+    {
+        parent.remove(prefix[1]);
+    }
+}
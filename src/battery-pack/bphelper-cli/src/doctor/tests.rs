@@ -0,0 +1,200 @@
+//! Tests for `cargo bp doctor` diagnosis and fixes.
+
+use super::*;
+
+fn parse(content: &str) -> toml::Value {
+    toml::from_str(content).unwrap()
+}
+
+fn diagnose_str(user: &str, workspace: Option<&str>, build_rs: Option<&str>) -> Vec<Problem> {
+    let user = parse(user);
+    let workspace = workspace.map(parse);
+    diagnose(&Project {
+        user: &user,
+        workspace: workspace.as_ref(),
+        build_rs,
+    })
+}
+
+const HEALTHY: &str = r#"[package]
+name = "app"
+
+[dependencies]
+anyhow = "1"
+
+[build-dependencies]
+cli-battery-pack = "0.5"
+
+[package.metadata.battery-pack.cli-battery-pack]
+features = ["default"]
+managed-deps = ["anyhow"]
+"#;
+
+// [verify cli.doctor.checks]
+#[test]
+fn healthy_project_has_no_problems() {
+    let build_rs = "fn main() {\n    cli_battery_pack::validate();\n}\n";
+    assert!(diagnose_str(HEALTHY, None, Some(build_rs)).is_empty());
+}
+
+// [verify cli.doctor.checks]
+#[test]
+fn package_level_problems_are_found() {
+    let user = r#"[package]
+name = "app"
+
+[dependencies]
+anyhow = "1"
+
+[build-dependencies]
+cli-battery-pack = "0.5"
+
+[package.metadata.battery-pack.cli-battery-pack]
+features = ["default"]
+managed-deps = ["anyhow", "clap"]
+
+[package.metadata.battery-pack.error-battery-pack]
+features = ["default"]
+"#;
+    let build_rs = "fn main() {\n    cli_battery_pack::validate();\n    \
+                    old_battery_pack::validate();\n}\n";
+    assert_eq!(
+        diagnose_str(user, None, Some(build_rs)),
+        [
+            Problem::MissingManagedDeps {
+                pack: "cli-battery-pack".into(),
+                crates: BTreeSet::from(["clap".to_string()]),
+            },
+            Problem::Unregistered {
+                pack: "error-battery-pack".into(),
+            },
+            Problem::NoManagedDeps {
+                pack: "error-battery-pack".into(),
+            },
+            Problem::StaleValidate {
+                pack: "old-battery-pack".into(),
+            },
+        ]
+    );
+}
+
+const WORKSPACE_ROOT: &str = r#"[workspace]
+members = ["app"]
+
+[workspace.dependencies]
+anyhow = "1"
+clap = "4"
+cli-battery-pack = "0.5"
+
+[workspace.metadata.battery-pack.cli-battery-pack]
+features = ["default"]
+managed-deps = ["anyhow", "clap"]
+"#;
+
+// [verify cli.doctor.checks]
+#[test]
+fn package_entries_shadowed_by_workspace_metadata_are_found() {
+    let user = r#"[package]
+name = "app"
+
+[dependencies]
+anyhow = { workspace = true }
+
+[build-dependencies]
+cli-battery-pack = { workspace = true }
+
+[package.metadata.battery-pack.cli-battery-pack]
+features = ["default"]
+managed-deps = ["anyhow"]
+
+[package.metadata.battery-pack.error-battery-pack]
+features = ["default"]
+managed-deps = []
+"#;
+    // `clap` is only in [workspace.dependencies]: another member may use it.
+    assert_eq!(
+        diagnose_str(user, Some(WORKSPACE_ROOT), None),
+        [
+            Problem::Shadowed {
+                pack: "cli-battery-pack".into(),
+                duplicate: true,
+            },
+            Problem::Shadowed {
+                pack: "error-battery-pack".into(),
+                duplicate: false,
+            },
+        ]
+    );
+}
+
+// [verify cli.doctor.fix]
+#[test]
+fn fixes_edit_the_metadata_in_place() {
+    let user = r#"[package]
+name = "app"
+
+[dependencies]
+anyhow = { workspace = true }
+
+[package.metadata.battery-pack.cli-battery-pack]
+features = ["default"]
+
+[package.metadata.battery-pack.error-battery-pack]
+features = ["default"]
+managed-deps = []
+
+[package.metadata.other]
+keep = true
+"#;
+    let mut user_doc: toml_edit::DocumentMut = user.parse().unwrap();
+    let mut ws_doc: toml_edit::DocumentMut = WORKSPACE_ROOT.parse().unwrap();
+    let problems = [
+        Problem::Shadowed {
+            pack: "cli-battery-pack".into(),
+            duplicate: true,
+        },
+        Problem::Shadowed {
+            pack: "error-battery-pack".into(),
+            duplicate: false,
+        },
+        Problem::MissingManagedDeps {
+            pack: "cli-battery-pack".into(),
+            crates: BTreeSet::from(["clap".to_string()]),
+        },
+    ];
+    for problem in &problems {
+        fix(problem, &mut user_doc, Some(&mut ws_doc));
+    }
+
+    let user = user_doc.to_string();
+    assert!(!user.contains("battery-pack"), "{user}");
+    assert!(user.contains("[package.metadata.other]"), "{user}");
+    let ws = ws_doc.to_string();
+    assert!(ws.contains("managed-deps = [\"anyhow\"]"), "{ws}");
+    assert!(
+        ws.contains("[workspace.metadata.battery-pack.error-battery-pack]"),
+        "{ws}"
+    );
+}
+
+// [verify cli.doctor.fix]
+#[test]
+fn backfilled_managed_deps_only_name_present_crates() {
+    let user = r#"[package]
+name = "app"
+
+[dependencies]
+anyhow = "1"
+serde = "1"
+
+[package.metadata.battery-pack.cli-battery-pack]
+features = ["default"]
+"#;
+    let mut doc: toml_edit::DocumentMut = user.parse().unwrap();
+    let curated = BTreeSet::from(["anyhow".to_string(), "clap".to_string()]);
+    backfill_managed_deps(&mut doc, None, "cli-battery-pack", &curated);
+    assert!(
+        doc.to_string().contains("managed-deps = [\"anyhow\"]"),
+        "{doc}"
+    );
+}
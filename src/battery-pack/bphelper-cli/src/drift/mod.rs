@@ -0,0 +1,516 @@
+//! Drift analysis: how a project's manifests differ from its battery packs.
+//!
+//! `status` and `check` both run the same analysis. It compares each
+//! installed pack's resolved spec with what the project actually declares:
+//! dependency versions, Cargo features, dependency sections, and the pack
+//! registration itself. It then reports categorized [`Finding`]s. The
+//! callers decide how to render them and what exit status they map to.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result};
+use bphelper_manifest::{BatteryPackSpec, DepKind};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::manifest::{
+    SyncPolicy, dep_kind_section, find_workspace_manifest, is_exact_requirement,
+    should_upgrade_version,
+};
+
+// ============================================================================
+// Findings
+// ============================================================================
+
+/// A category of drift.
+// [impl cli.drift.categories]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum DriftKind {
+    /// The pack has metadata in the project but no `[build-dependencies]` entry.
+    NotRegistered,
+    /// A crate from an active feature is not a dependency.
+    MissingCrate,
+    /// The crate is declared in a different dependency section.
+    WrongSection,
+    /// The declared version is older than the pack recommends.
+    Outdated,
+    /// The dependency lacks Cargo features the pack enables.
+    MissingFeature,
+    /// A crate the pack used to manage is no longer part of the pack.
+    NoLongerCurated,
+}
+
+impl DriftKind {
+    pub(crate) fn severity(self) -> Severity {
+        match self {
+            DriftKind::NotRegistered | DriftKind::MissingCrate => Severity::Error,
+            DriftKind::WrongSection
+            | DriftKind::Outdated
+            | DriftKind::MissingFeature
+            | DriftKind::NoLongerCurated => Severity::Warning,
+        }
+    }
+
+    /// Whether `cargo bp sync` resolves this kind of drift.
+    pub(crate) fn fixed_by_sync(self) -> bool {
+        matches!(
+            self,
+            DriftKind::MissingCrate | DriftKind::Outdated | DriftKind::MissingFeature
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Warning,
+    Error,
+}
+
+/// One difference between a pack and the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Finding {
+    pub pack: String,
+    pub kind: DriftKind,
+    pub severity: Severity,
+    /// The crate concerned; `None` for pack-level findings.
+    pub crate_name: Option<String>,
+    /// What the pack expects: a version, a section name, or the missing
+    /// features as a comma-separated list.
+    pub expected: Option<String>,
+    /// What the project has, in the same terms as `expected`.
+    pub actual: Option<String>,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(
+        pack: &str,
+        kind: DriftKind,
+        crate_name: Option<&str>,
+        expected: Option<String>,
+        actual: Option<String>,
+        message: String,
+    ) -> Self {
+        Self {
+            pack: pack.to_string(),
+            kind,
+            severity: kind.severity(),
+            crate_name: crate_name.map(String::from),
+            expected,
+            actual,
+            message,
+        }
+    }
+}
+
+/// Overall result of an analysis, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Outcome {
+    Clean,
+    Warnings,
+    Errors,
+}
+
+impl Outcome {
+    /// Process exit status for `cargo bp check`. `1` is left for the CLI
+    /// failing outright (no project, unreadable manifest, ...).
+    pub(crate) fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Clean => 0,
+            Outcome::Warnings => 2,
+            Outcome::Errors => 3,
+        }
+    }
+
+    pub(crate) fn of<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> Self {
+        findings
+            .into_iter()
+            .map(|f| match f.severity {
+                Severity::Warning => Outcome::Warnings,
+                Severity::Error => Outcome::Errors,
+            })
+            .max()
+            .unwrap_or(Outcome::Clean)
+    }
+}
+
+// ============================================================================
+// Project dependencies
+// ============================================================================
+
+/// One declaration of a dependency in the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DeclaredDep {
+    pub kind: DepKind,
+    /// The version requirement, resolved through `workspace = true`.
+    pub version: Option<String>,
+    /// Enabled features, including those inherited from the workspace entry.
+    pub features: BTreeSet<String>,
+}
+
+/// Everything the analysis needs to know about the project's manifests.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProjectDeps {
+    /// Declarations by crate name; a crate can appear in several sections.
+    pub deps: BTreeMap<String, Vec<DeclaredDep>>,
+}
+
+impl ProjectDeps {
+    /// Read the member manifest, resolving workspace references through the
+    /// enclosing workspace manifest, if any.
+    pub(crate) fn read(user_manifest_path: &Path, user_manifest_content: &str) -> Result<Self> {
+        let user: toml::Value =
+            toml::from_str(user_manifest_content).context("Failed to parse Cargo.toml")?;
+        let workspace = match find_workspace_manifest(user_manifest_path)? {
+            Some(ws_path) => {
+                let ws_content = std::fs::read_to_string(&ws_path)
+                    .context("Failed to read workspace Cargo.toml")?;
+                Some(
+                    toml::from_str::<toml::Value>(&ws_content)
+                        .context("Failed to parse workspace Cargo.toml")?,
+                )
+            }
+            None => None,
+        };
+        Ok(Self::from_manifests(&user, workspace.as_ref()))
+    }
+
+    pub(crate) fn from_manifests(user: &toml::Value, workspace: Option<&toml::Value>) -> Self {
+        let ws_deps = workspace
+            .and_then(|ws| ws.get("workspace"))
+            .and_then(|w| w.get("dependencies"))
+            .and_then(|d| d.as_table());
+
+        let mut deps: BTreeMap<String, Vec<DeclaredDep>> = BTreeMap::new();
+        for kind in [DepKind::Normal, DepKind::Dev, DepKind::Build] {
+            let Some(table) = user.get(dep_kind_section(kind)).and_then(|d| d.as_table()) else {
+                continue;
+            };
+            for (name, value) in table {
+                let mut declared = DeclaredDep {
+                    kind,
+                    version: dep_version(value),
+                    features: dep_features(value),
+                };
+                if is_workspace_ref(value)
+                    && let Some(ws_value) = ws_deps.and_then(|t| t.get(name))
+                {
+                    declared.version = dep_version(ws_value);
+                    declared.features.extend(dep_features(ws_value));
+                }
+                deps.entry(name.clone()).or_default().push(declared);
+            }
+        }
+        Self { deps }
+    }
+
+    /// The declaration of `name` in `kind`'s section, else its first one.
+    fn declaration(&self, name: &str, kind: DepKind) -> Option<(&DeclaredDep, bool)> {
+        let declared = self.deps.get(name)?;
+        declared
+            .iter()
+            .find(|d| d.kind == kind)
+            .map(|d| (d, true))
+            .or_else(|| declared.first().map(|d| (d, false)))
+    }
+
+    /// The declared version of `name`, preferring the section `kind`.
+    pub(crate) fn version_of(&self, name: &str, kind: DepKind) -> Option<&str> {
+        self.declaration(name, kind)
+            .and_then(|(d, _)| d.version.as_deref())
+    }
+
+    /// Every feature each crate is declared with, across its sections.
+    pub(crate) fn features(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.deps
+            .iter()
+            .map(|(name, declared)| {
+                let features = declared.iter().flat_map(|d| d.features.clone()).collect();
+                (name.clone(), features)
+            })
+            .collect()
+    }
+}
+
+fn dep_version(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Table(t) => t.get("version").and_then(|v| v.as_str()).map(String::from),
+        _ => None,
+    }
+}
+
+fn dep_features(value: &toml::Value) -> BTreeSet<String> {
+    value
+        .get("features")
+        .and_then(|f| f.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn is_workspace_ref(value: &toml::Value) -> bool {
+    value
+        .get("workspace")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+// ============================================================================
+// Analysis
+// ============================================================================
+
+/// What the project records about one installed pack.
+pub(crate) struct PackState<'a> {
+    pub name: &'a str,
+    pub spec: &'a BatteryPackSpec,
+    pub active_features: &'a BTreeSet<String>,
+    /// `managed-deps` from the pack's metadata, if recorded.
+    pub managed_deps: Option<&'a BTreeSet<String>>,
+    /// `pinned` from the pack's metadata: crates whose version sync leaves alone.
+    pub pinned: &'a BTreeSet<String>,
+}
+
+/// Findings for packs that have battery-pack metadata in the project but are
+/// missing from `registered` (the packs with a build-dependency).
+pub(crate) fn unregistered<'a>(
+    metadata_names: impl IntoIterator<Item = &'a String>,
+    registered: &[String],
+) -> Vec<Finding> {
+    metadata_names
+        .into_iter()
+        .filter(|name| !registered.contains(name))
+        .map(|name| {
+            Finding::new(
+                name,
+                DriftKind::NotRegistered,
+                None,
+                None,
+                None,
+                format!(
+                    "{} has battery-pack metadata but is not in [build-dependencies]",
+                    name
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Compare one pack's resolved spec with the project. Versions held back
+/// by a pin or by `policy` are not drift; see [`holds`].
+// [impl cli.drift.unified]
+pub(crate) fn analyze(
+    project: &ProjectDeps,
+    pack: &PackState<'_>,
+    policy: SyncPolicy,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (name, expected) in pack.spec.resolve_for_features(pack.active_features) {
+        let expected_section = dep_kind_section(expected.dep_kind);
+        let Some((declared, in_expected_section)) = project.declaration(&name, expected.dep_kind)
+        else {
+            findings.push(Finding::new(
+                pack.name,
+                DriftKind::MissingCrate,
+                Some(&name),
+                Some(expected.version.clone()).filter(|v| !v.is_empty()),
+                None,
+                format!("{}: missing from [{}]", name, expected_section),
+            ));
+            continue;
+        };
+
+        if !in_expected_section {
+            let actual_section = dep_kind_section(declared.kind);
+            findings.push(Finding::new(
+                pack.name,
+                DriftKind::WrongSection,
+                Some(&name),
+                Some(expected_section.to_string()),
+                Some(actual_section.to_string()),
+                format!(
+                    "{}: in [{}], expected in [{}]",
+                    name, actual_section, expected_section
+                ),
+            ));
+        }
+
+        if let Some(actual) = &declared.version
+            && is_behind(actual, &expected.version)
+            && hold_reason(pack, &name, actual, &expected.version, policy).is_none()
+        {
+            findings.push(Finding::new(
+                pack.name,
+                DriftKind::Outdated,
+                Some(&name),
+                Some(expected.version.clone()),
+                Some(actual.clone()),
+                format!("{}: {} → {} recommended", name, actual, expected.version),
+            ));
+        }
+
+        let missing: Vec<&str> = expected
+            .features
+            .iter()
+            .filter(|f| !declared.features.contains(*f))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            findings.push(Finding::new(
+                pack.name,
+                DriftKind::MissingFeature,
+                Some(&name),
+                Some(missing.join(", ")),
+                Some(
+                    declared
+                        .features
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                format!("{}: missing features {}", name, missing.join(", ")),
+            ));
+        }
+    }
+
+    if let Some(managed) = pack.managed_deps {
+        for name in managed {
+            if !pack.spec.crates.contains_key(name) && project.deps.contains_key(name) {
+                findings.push(Finding::new(
+                    pack.name,
+                    DriftKind::NoLongerCurated,
+                    Some(name),
+                    None,
+                    None,
+                    format!("{}: no longer part of {}", name, pack.name),
+                ));
+            }
+        }
+    }
+
+    findings
+}
+
+// ============================================================================
+// Holds
+// ============================================================================
+
+/// Why a crate's version is exempt from the pack's recommendation.
+// [impl cli.pins.report]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HoldReason {
+    /// Listed in the pack's `pinned` metadata.
+    Pinned,
+    /// Declared with an exact (`=`) requirement.
+    Exact,
+    /// Behind the recommendation, but the sync policy forbids the upgrade.
+    Policy(SyncPolicy),
+}
+
+/// A crate whose version `sync` leaves alone, reported apart from drift.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Hold {
+    pub pack: String,
+    pub crate_name: String,
+    pub reason: HoldReason,
+    /// The declared requirement.
+    pub actual: String,
+    /// The pack's recommendation.
+    pub recommended: String,
+}
+
+impl Hold {
+    /// Whether the recommendation is newer than what the project declares.
+    pub(crate) fn is_behind(&self) -> bool {
+        is_behind(&self.actual, &self.recommended)
+    }
+
+    pub(crate) fn message(&self) -> String {
+        let held = match self.reason {
+            HoldReason::Pinned => format!("{}: pinned at {}", self.crate_name, self.actual),
+            HoldReason::Exact => format!("{}: exact requirement {}", self.crate_name, self.actual),
+            HoldReason::Policy(policy) => format!(
+                "{}: kept at {} by sync policy {}",
+                self.crate_name,
+                self.actual,
+                policy.as_str()
+            ),
+        };
+        if self.is_behind() {
+            format!("{} ({} recommended)", held, self.recommended)
+        } else {
+            held
+        }
+    }
+}
+
+/// Crates of one pack whose versions are pinned, or behind but held back by
+/// `policy`. Pins are reported whether or not they're behind, so they don't
+/// go unnoticed once the pack moves on.
+// [impl cli.pins.report]
+pub(crate) fn holds(project: &ProjectDeps, pack: &PackState<'_>, policy: SyncPolicy) -> Vec<Hold> {
+    pack.spec
+        .resolve_for_features(pack.active_features)
+        .into_iter()
+        .filter_map(|(name, expected)| {
+            let (declared, _) = project.declaration(&name, expected.dep_kind)?;
+            let actual = declared.version.clone()?;
+            let reason = hold_reason(pack, &name, &actual, &expected.version, policy)?;
+            Some(Hold {
+                pack: pack.name.to_string(),
+                crate_name: name,
+                reason,
+                actual,
+                recommended: expected.version,
+            })
+        })
+        .collect()
+}
+
+// [impl cli.pins.metadata]
+// [impl cli.pins.exact]
+fn hold_reason(
+    pack: &PackState<'_>,
+    name: &str,
+    actual: &str,
+    recommended: &str,
+    policy: SyncPolicy,
+) -> Option<HoldReason> {
+    if pack.pinned.contains(name) {
+        Some(HoldReason::Pinned)
+    } else if is_exact_requirement(actual) {
+        Some(HoldReason::Exact)
+    } else if is_behind(actual, recommended) && !policy.allows_upgrade(actual, recommended) {
+        Some(HoldReason::Policy(policy))
+    } else {
+        None
+    }
+}
+
+/// Whether `recommended` is newer than the declared requirement. An exact
+/// requirement compares by the version it names.
+fn is_behind(actual: &str, recommended: &str) -> bool {
+    let actual = actual.trim_start().trim_start_matches('=').trim_start();
+    !recommended.is_empty() && should_upgrade_version(actual, recommended)
+}
+
+/// The most significant finding kind for each crate, for per-crate views.
+pub(crate) fn worst_by_crate(findings: &[Finding]) -> BTreeMap<&str, DriftKind> {
+    let mut worst: BTreeMap<&str, DriftKind> = BTreeMap::new();
+    for finding in findings {
+        let Some(name) = finding.crate_name.as_deref() else {
+            continue;
+        };
+        worst
+            .entry(name)
+            .and_modify(|k| *k = (*k).min(finding.kind))
+            .or_insert(finding.kind);
+    }
+    worst
+}
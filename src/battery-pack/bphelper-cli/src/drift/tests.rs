@@ -0,0 +1,316 @@
+//! Tests for the drift analysis.
+
+use super::*;
+
+const PACK: &str = r#"
+[package]
+name = "demo-battery-pack"
+version = "0.2.0"
+keywords = ["battery-pack"]
+
+[dependencies]
+anyhow = { version = "1.0.80", optional = true }
+serde = { version = "1.0", features = ["derive"], optional = true }
+tracing = { version = "0.1", optional = true }
+
+[dev-dependencies]
+insta = { version = "1.39", optional = true }
+
+[features]
+default = ["anyhow", "serde", "insta"]
+logging = ["tracing"]
+"#;
+
+fn spec() -> BatteryPackSpec {
+    bphelper_manifest::parse_battery_pack(PACK).unwrap()
+}
+
+fn project(manifest: &str) -> ProjectDeps {
+    ProjectDeps::from_manifests(&toml::from_str(manifest).unwrap(), None)
+}
+
+fn set(items: &[&str]) -> BTreeSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+fn run(project: &ProjectDeps, features: &[&str], managed: Option<&[&str]>) -> Vec<Finding> {
+    let spec = spec();
+    let active = set(features);
+    let managed = managed.map(set);
+    analyze(
+        project,
+        &PackState {
+            name: "demo-battery-pack",
+            spec: &spec,
+            active_features: &active,
+            managed_deps: managed.as_ref(),
+            pinned: &BTreeSet::new(),
+        },
+        SyncPolicy::default(),
+    )
+}
+
+fn kinds(findings: &[Finding]) -> Vec<(Option<&str>, DriftKind)> {
+    findings
+        .iter()
+        .map(|f| (f.crate_name.as_deref(), f.kind))
+        .collect()
+}
+
+// [verify cli.drift.unified]
+#[test]
+fn matching_project_is_clean() {
+    let project = project(
+        r#"
+[dependencies]
+anyhow = "1.0.86"
+serde = { version = "1.0.200", features = ["derive", "rc"] }
+
+[dev-dependencies]
+insta = "1.39"
+"#,
+    );
+    let findings = run(&project, &["default"], None);
+    assert!(findings.is_empty(), "{findings:?}");
+    assert_eq!(Outcome::of(&findings), Outcome::Clean);
+}
+
+// [verify cli.drift.categories]
+#[test]
+fn reports_each_category() {
+    let project = project(
+        r#"
+[dependencies]
+anyhow = "1.0.70"
+serde = "1.0"
+insta = "1.39"
+old-crate = "0.3"
+"#,
+    );
+    let findings = run(
+        &project,
+        &["default", "logging"],
+        Some(&["anyhow", "old-crate"]),
+    );
+    assert_eq!(
+        kinds(&findings),
+        [
+            (Some("anyhow"), DriftKind::Outdated),
+            (Some("insta"), DriftKind::WrongSection),
+            (Some("serde"), DriftKind::MissingFeature),
+            (Some("tracing"), DriftKind::MissingCrate),
+            (Some("old-crate"), DriftKind::NoLongerCurated),
+        ]
+    );
+
+    let serde = &findings[2];
+    assert_eq!(serde.expected.as_deref(), Some("derive"));
+    let insta = &findings[1];
+    assert_eq!(insta.expected.as_deref(), Some("dev-dependencies"));
+    assert_eq!(insta.actual.as_deref(), Some("dependencies"));
+    assert_eq!(Outcome::of(&findings), Outcome::Errors);
+}
+
+// [verify cli.drift.categories]
+#[test]
+fn newer_versions_are_not_drift() {
+    let project = project(
+        r#"
+[dependencies]
+anyhow = "1.1"
+serde = { version = "1.0", features = ["derive"] }
+
+[dev-dependencies]
+insta = "1.40"
+"#,
+    );
+    assert!(run(&project, &["default"], None).is_empty());
+}
+
+#[test]
+fn managed_crates_still_in_the_pack_are_not_uncurated() {
+    let project = project(
+        r#"
+[dependencies]
+anyhow = "1.0.80"
+serde = { version = "1.0", features = ["derive"] }
+tracing = "0.1"
+
+[dev-dependencies]
+insta = "1.39"
+"#,
+    );
+    // `tracing` is managed but its feature is off: not drift either way.
+    let findings = run(&project, &["default"], Some(&["anyhow", "tracing"]));
+    assert!(findings.is_empty(), "{findings:?}");
+}
+
+#[test]
+fn workspace_refs_resolve_version_and_features() {
+    let user: toml::Value = toml::from_str(
+        r#"
+[dependencies]
+anyhow = { workspace = true }
+serde = { workspace = true, features = ["rc"] }
+
+[dev-dependencies]
+insta.workspace = true
+"#,
+    )
+    .unwrap();
+    let ws: toml::Value = toml::from_str(
+        r#"
+[workspace.dependencies]
+anyhow = "1.0.70"
+serde = { version = "1.0", features = ["derive"] }
+insta = "1.39"
+"#,
+    )
+    .unwrap();
+    let project = ProjectDeps::from_manifests(&user, Some(&ws));
+
+    assert_eq!(
+        project.version_of("anyhow", DepKind::Normal),
+        Some("1.0.70")
+    );
+    let findings = run(&project, &["default"], None);
+    assert_eq!(kinds(&findings), [(Some("anyhow"), DriftKind::Outdated)]);
+}
+
+// [verify cli.drift.categories]
+#[test]
+fn metadata_without_registration_is_an_error() {
+    let metadata = set(&["cli-battery-pack", "demo-battery-pack"]);
+    let findings = unregistered(&metadata, &["demo-battery-pack".to_string()]);
+
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].pack, "cli-battery-pack");
+    assert_eq!(findings[0].kind, DriftKind::NotRegistered);
+    assert_eq!(findings[0].severity, Severity::Error);
+}
+
+// [verify cli.drift.exit-codes]
+#[test]
+fn outcome_is_the_worst_severity() {
+    let project = project(
+        r#"
+[dependencies]
+anyhow = "1.0.70"
+serde = { version = "1.0", features = ["derive"] }
+
+[dev-dependencies]
+insta = "1.39"
+"#,
+    );
+    let findings = run(&project, &["default"], None);
+    assert_eq!(Outcome::of(&findings), Outcome::Warnings);
+
+    assert_eq!(Outcome::Clean.exit_code(), 0);
+    assert_eq!(Outcome::Warnings.exit_code(), 2);
+    assert_eq!(Outcome::Errors.exit_code(), 3);
+}
+
+#[test]
+fn worst_by_crate_prefers_errors() {
+    let project = project(
+        r#"
+[dev-dependencies]
+anyhow = "1.0.70"
+"#,
+    );
+    let findings = run(&project, &["default"], None);
+    let worst = worst_by_crate(&findings);
+    assert_eq!(worst.get("anyhow"), Some(&DriftKind::WrongSection));
+    assert_eq!(worst.get("serde"), Some(&DriftKind::MissingCrate));
+}
+
+fn run_held(
+    project: &ProjectDeps,
+    pinned: &[&str],
+    policy: SyncPolicy,
+) -> (Vec<Finding>, Vec<Hold>) {
+    let spec = spec();
+    let active = set(&["default"]);
+    let pinned = set(pinned);
+    let pack = PackState {
+        name: "demo-battery-pack",
+        spec: &spec,
+        active_features: &active,
+        managed_deps: None,
+        pinned: &pinned,
+    };
+    (
+        analyze(project, &pack, policy),
+        holds(project, &pack, policy),
+    )
+}
+
+const BEHIND: &str = r#"
+[dependencies]
+anyhow = "=1.0.70"
+serde = { version = "0.9", features = ["derive"] }
+
+[dev-dependencies]
+insta = "1.30"
+"#;
+
+// [verify cli.pins.metadata]
+// [verify cli.pins.exact]
+// [verify cli.pins.report]
+#[test]
+fn pinned_and_exact_crates_are_held_not_drift() {
+    let (findings, holds) = run_held(&project(BEHIND), &["insta"], SyncPolicy::AllowBreaking);
+
+    assert_eq!(kinds(&findings), [(Some("serde"), DriftKind::Outdated)]);
+    let held: Vec<(&str, HoldReason)> = holds
+        .iter()
+        .map(|h| (h.crate_name.as_str(), h.reason))
+        .collect();
+    assert_eq!(
+        held,
+        [("anyhow", HoldReason::Exact), ("insta", HoldReason::Pinned)]
+    );
+    assert!(holds.iter().all(Hold::is_behind));
+    assert_eq!(
+        holds[0].message(),
+        "anyhow: exact requirement =1.0.70 (1.0.80 recommended)"
+    );
+}
+
+// [verify cli.pins.policy]
+#[test]
+fn policy_holds_back_upgrades_it_forbids() {
+    let (findings, holds) = run_held(&project(BEHIND), &[], SyncPolicy::CompatibleOnly);
+
+    // insta 1.30 → 1.39 is compatible, so it's still drift; serde 0.9 → 1.0 isn't.
+    assert_eq!(kinds(&findings), [(Some("insta"), DriftKind::Outdated)]);
+    let serde = holds.iter().find(|h| h.crate_name == "serde").unwrap();
+    assert_eq!(serde.reason, HoldReason::Policy(SyncPolicy::CompatibleOnly));
+    assert_eq!(
+        serde.message(),
+        "serde: kept at 0.9 by sync policy compatible-only (1.0 recommended)"
+    );
+
+    let (findings, holds) = run_held(&project(BEHIND), &[], SyncPolicy::NeverUpgrade);
+    assert!(findings.is_empty(), "{findings:?}");
+    assert_eq!(holds.len(), 3);
+}
+
+#[test]
+fn pins_that_are_current_are_still_reported() {
+    let project = project(
+        r#"
+[dependencies]
+anyhow = "1.0.86"
+serde = { version = "1.0", features = ["derive"] }
+
+[dev-dependencies]
+insta = "1.39"
+"#,
+    );
+    let (findings, holds) = run_held(&project, &["anyhow"], SyncPolicy::NeverUpgrade);
+    assert!(findings.is_empty(), "{findings:?}");
+    assert_eq!(holds.len(), 1);
+    assert!(!holds[0].is_behind());
+    assert_eq!(holds[0].message(), "anyhow: pinned at 1.0.86");
+}
@@ -0,0 +1,250 @@
+//! `cargo bp new battery-pack --from <project>`: turning a project's
+//! dependencies into a new battery pack.
+//!
+//! The pack itself is generated from the `battery-pack` template as usual;
+//! this module reads the source project's dependencies and rewrites the
+//! generated `Cargo.toml` to curate the chosen ones.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result, bail};
+use bphelper_manifest::DepKind;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::manifest::dep_kind_section;
+
+/// A dependency of the source project, as the pack will curate it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExtractedDep {
+    pub kind: DepKind,
+    /// The version requirement, e.g. `1.0` (Cargo's implicit `^` dropped).
+    pub version: String,
+    pub features: BTreeSet<String>,
+    pub default_features: bool,
+}
+
+/// Read the dependencies of the package at `project` (or of every member,
+/// for a virtual workspace), with `workspace = true` entries resolved.
+/// Path dependencies and battery packs are left out, as are
+/// target-specific ones, which a pack can't curate; those are reported.
+// [impl cli.new.from.read]
+pub(crate) fn read_dependencies(project: &Path) -> Result<BTreeMap<String, ExtractedDep>> {
+    let manifest_path = if project.is_file() {
+        project.to_path_buf()
+    } else {
+        project.join("Cargo.toml")
+    };
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .manifest_path(&manifest_path)
+        .no_deps()
+        .exec()
+        .with_context(|| format!("Failed to run `cargo metadata` on {}", project.display()))?;
+
+    let manifest_path = manifest_path.canonicalize().unwrap_or(manifest_path);
+    let packages: Vec<&cargo_metadata::Package> = match metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.manifest_path.as_std_path() == manifest_path)
+    {
+        Some(package) => vec![package],
+        None => metadata.workspace_packages(),
+    };
+
+    let mut deps: BTreeMap<String, ExtractedDep> = BTreeMap::new();
+    for package in packages {
+        for dep in &package.dependencies {
+            let name = dep.name.to_string();
+            if dep.path.is_some() || name == "battery-pack" || name.ends_with("-battery-pack") {
+                continue;
+            }
+            if let Some(target) = &dep.target {
+                eprintln!(
+                    "warning: skipping '{}' from {}: it only applies to `{}`",
+                    name, package.name, target
+                );
+                continue;
+            }
+            let kind = match dep.kind {
+                cargo_metadata::DependencyKind::Development => DepKind::Dev,
+                cargo_metadata::DependencyKind::Build => DepKind::Build,
+                _ => DepKind::Normal,
+            };
+            let extracted = ExtractedDep {
+                kind,
+                version: version_requirement(&dep.req),
+                features: dep.features.iter().cloned().collect(),
+                default_features: dep.uses_default_features,
+            };
+            match deps.get_mut(&name) {
+                // Members can list a crate differently: keep the highest
+                // requirement, every feature, and a regular dependency over
+                // a dev or build one.
+                Some(existing) => {
+                    if requirement_floor(&dep.req) > floor_of(&existing.version) {
+                        existing.version = extracted.version;
+                    }
+                    existing.features.extend(extracted.features);
+                    existing.default_features |= extracted.default_features;
+                    if kind == DepKind::Normal {
+                        existing.kind = kind;
+                    }
+                }
+                None => {
+                    deps.insert(name, extracted);
+                }
+            }
+        }
+    }
+    Ok(deps)
+}
+
+/// Write a requirement the way it would appear in a manifest: `^1.2`
+/// becomes `1.2`.
+fn version_requirement(req: &semver::VersionReq) -> String {
+    req.comparators
+        .iter()
+        .map(|c| {
+            let c = c.to_string();
+            c.strip_prefix('^').map(String::from).unwrap_or(c)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The lowest version `req` admits, to compare requirements by:
+/// `1.2` and `>=1.2, <2` both give `1.2.0`.
+fn requirement_floor(req: &semver::VersionReq) -> (u64, u64, u64) {
+    req.comparators
+        .first()
+        .map(|c| (c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0)))
+        .unwrap_or_default()
+}
+
+fn floor_of(version: &str) -> (u64, u64, u64) {
+    semver::VersionReq::parse(version)
+        .map(|req| requirement_floor(&req))
+        .unwrap_or_default()
+}
+
+/// Parse a `--group` value: `feature=crate,crate`.
+pub(crate) fn parse_group(s: &str) -> Result<(String, Vec<String>), String> {
+    let (feature, crates) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected FEATURE=CRATE,..., got '{s}'"))?;
+    let crates: Vec<String> = crates
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(String::from)
+        .collect();
+    if feature.is_empty() || crates.is_empty() {
+        return Err(format!("expected FEATURE=CRATE,..., got '{s}'"));
+    }
+    Ok((feature.to_string(), crates))
+}
+
+/// The pack's `[features]`: each group as a named feature, and `default`
+/// holding the regular dependencies no group claims.
+// [impl cli.new.from.features]
+pub(crate) fn features(
+    deps: &BTreeMap<String, ExtractedDep>,
+    groups: &[(String, Vec<String>)],
+) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let mut features: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (feature, crates) in groups {
+        if feature == "default" || feature == "all" {
+            bail!("'{}' can't be used as a feature name here", feature);
+        }
+        for name in crates {
+            match deps.get(name) {
+                None => bail!("'{}' is not one of the extracted crates", name),
+                Some(dep) if dep.kind != DepKind::Normal => bail!(
+                    "'{}' is in [{}]; only [dependencies] can be grouped into features",
+                    name,
+                    dep_kind_section(dep.kind)
+                ),
+                Some(_) => {}
+            }
+        }
+        features
+            .entry(feature.clone())
+            .or_default()
+            .extend(crates.iter().cloned());
+    }
+    let grouped: BTreeSet<&String> = features.values().flatten().collect();
+    let default: BTreeSet<String> = deps
+        .iter()
+        .filter(|(name, dep)| dep.kind == DepKind::Normal && !grouped.contains(name))
+        .map(|(name, _)| name.clone())
+        .collect();
+    features.insert("default".to_string(), default);
+    Ok(features)
+}
+
+/// Rewrite the template's `Cargo.toml` to curate `deps`, grouped into
+/// `features`. Regular dependencies become optional; the template's example
+/// comments are dropped.
+// [impl cli.new.from.layout]
+pub(crate) fn write_pack_manifest(
+    template: &str,
+    deps: &BTreeMap<String, ExtractedDep>,
+    features: &BTreeMap<String, BTreeSet<String>>,
+) -> Result<String> {
+    let mut doc: toml_edit::DocumentMut = template
+        .parse()
+        .context("Failed to parse the generated Cargo.toml")?;
+    drop_comments(doc.as_table_mut());
+
+    for (name, dep) in deps {
+        let mut entry = toml_edit::InlineTable::new();
+        entry.insert("version", dep.version.as_str().into());
+        if !dep.features.is_empty() {
+            let features: toml_edit::Array = dep.features.iter().map(String::as_str).collect();
+            entry.insert("features", toml_edit::Value::Array(features));
+        }
+        if !dep.default_features {
+            entry.insert("default-features", false.into());
+        }
+        if dep.kind == DepKind::Normal {
+            entry.insert("optional", true.into());
+        }
+        let section = dep_kind_section(dep.kind);
+        doc[section]
+            .or_insert(toml_edit::Item::Table(toml_edit::Table::new()))
+            .as_table_mut()
+            .with_context(|| format!("[{}] is not a table", section))?
+            .insert(name, toml_edit::value(entry));
+    }
+
+    let mut table = toml_edit::Table::new();
+    // `default` first, as in the hand-written packs.
+    for (feature, crates) in features
+        .iter()
+        .filter(|(f, _)| *f == "default")
+        .chain(features.iter().filter(|(f, _)| *f != "default"))
+    {
+        let crates: toml_edit::Array = crates.iter().map(String::as_str).collect();
+        table.insert(feature, toml_edit::value(crates));
+    }
+    doc.insert("features", toml_edit::Item::Table(table));
+    Ok(doc.to_string())
+}
+
+/// Remove comment lines above every table header.
+fn drop_comments(table: &mut toml_edit::Table) {
+    for (_, item) in table.iter_mut() {
+        if let Some(table) = item.as_table_mut() {
+            if table
+                .decor()
+                .prefix()
+                .and_then(|p| p.as_str())
+                .is_some_and(|p| p.contains('#'))
+            {
+                table.decor_mut().set_prefix("\n");
+            }
+            drop_comments(table);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Tests for turning extracted dependencies into a pack manifest.
+
+use super::*;
+
+const TEMPLATE: &str = r#"[package]
+name = "demo-battery-pack"
+version = "0.1.0"
+edition = "2024"
+description = "Demo"
+keywords = ["battery-pack"]
+
+[dependencies]
+battery-pack = "0.4"
+# Add your curated dependencies here:
+# clap = { version = "4", features = ["derive"] }
+
+[dev-dependencies]
+battery-pack = { version = "0.4", features = ["cli"] }
+
+[build-dependencies]
+battery-pack = { version = "0.4", features = ["build"] }
+
+# Optional: narrow the default set and define named features
+# [features]
+# default = ["clap", "serde"]
+
+[package.metadata.battery-pack]
+hidden = ["battery-pack"]
+"#;
+
+fn dep(kind: DepKind, version: &str) -> ExtractedDep {
+    ExtractedDep {
+        kind,
+        version: version.to_string(),
+        features: BTreeSet::new(),
+        default_features: true,
+    }
+}
+
+fn deps() -> BTreeMap<String, ExtractedDep> {
+    let mut serde = dep(DepKind::Normal, "1");
+    serde.features.insert("derive".to_string());
+    let mut tokio = dep(DepKind::Normal, "1.40");
+    tokio.default_features = false;
+    BTreeMap::from([
+        ("anyhow".to_string(), dep(DepKind::Normal, "1")),
+        ("serde".to_string(), serde),
+        ("serde_json".to_string(), dep(DepKind::Normal, "1")),
+        ("tokio".to_string(), tokio),
+        ("insta".to_string(), dep(DepKind::Dev, "1")),
+    ])
+}
+
+fn groups(items: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+    items
+        .iter()
+        .map(|(f, crates)| {
+            (
+                f.to_string(),
+                crates.iter().map(|c| c.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+fn set(items: &[&str]) -> BTreeSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn requirements_compare_by_the_lowest_version_they_admit() {
+    let floor = |req: &str| requirement_floor(&semver::VersionReq::parse(req).unwrap());
+    assert_eq!(floor("1"), (1, 0, 0));
+    assert!(floor("1.0.100") > floor("1"));
+    assert!(floor(">=1.2, <2") > floor("~1.1.9"));
+    assert_eq!(floor_of("1.40"), (1, 40, 0));
+    assert_eq!(floor_of("not a requirement"), (0, 0, 0));
+}
+
+#[test]
+fn parse_group_splits_crates() {
+    assert_eq!(
+        parse_group("json=serde, serde_json").unwrap(),
+        (
+            "json".to_string(),
+            vec!["serde".to_string(), "serde_json".to_string()]
+        )
+    );
+    assert!(parse_group("json").is_err());
+    assert!(parse_group("json=").is_err());
+    assert!(parse_group("=serde").is_err());
+}
+
+// [verify cli.new.from.features]
+#[test]
+fn ungrouped_regular_crates_are_default() {
+    let features = features(&deps(), &groups(&[("json", &["serde", "serde_json"])])).unwrap();
+    assert_eq!(features["default"], set(&["anyhow", "tokio"]));
+    assert_eq!(features["json"], set(&["serde", "serde_json"]));
+    assert_eq!(features.len(), 2);
+}
+
+// [verify cli.new.from.features]
+#[test]
+fn grouping_rejects_unknown_and_dev_crates() {
+    let err = features(&deps(), &groups(&[("x", &["clap"])])).unwrap_err();
+    assert!(err.to_string().contains("clap"), "{err}");
+    let err = features(&deps(), &groups(&[("x", &["insta"])])).unwrap_err();
+    assert!(err.to_string().contains("dev-dependencies"), "{err}");
+    assert!(features(&deps(), &groups(&[("default", &["anyhow"])])).is_err());
+}
+
+// [verify cli.new.from.layout]
+#[test]
+fn manifest_curates_deps_and_validates() {
+    let deps = deps();
+    let features = features(&deps, &groups(&[("json", &["serde", "serde_json"])])).unwrap();
+    let content = write_pack_manifest(TEMPLATE, &deps, &features).unwrap();
+
+    assert!(!content.contains('#'), "template comments kept:\n{content}");
+    let doc: toml::Value = toml::from_str(&content).unwrap();
+    assert_eq!(
+        doc["dependencies"]["serde"]["features"],
+        toml::Value::Array(vec!["derive".into()])
+    );
+    assert_eq!(doc["dependencies"]["serde"]["optional"], true.into());
+    assert_eq!(
+        doc["dependencies"]["tokio"]["default-features"],
+        false.into()
+    );
+    assert_eq!(doc["dev-dependencies"]["insta"]["version"], "1".into());
+    assert!(doc["dev-dependencies"]["insta"].get("optional").is_none());
+
+    let spec = bphelper_manifest::parse_battery_pack(&content).unwrap();
+    let report = spec.validate_spec();
+    assert!(!report.has_errors(), "{:?}", report.diagnostics);
+    assert_eq!(spec.features["default"], set(&["anyhow", "tokio"]));
+    assert_eq!(spec.features["json"], set(&["serde", "serde_json"]));
+    assert!(spec.is_hidden("battery-pack"));
+}
@@ -0,0 +1,167 @@
+//! Source forge detection and repository link building.
+//!
+//! Battery packs only tell us their `repository` URL. To link to a template
+//! directory or an example file we need to know which forge hosts it, since
+//! GitHub, GitLab, Gitea/Forgejo and sourcehut all lay out their tree and
+//! blob URLs differently. Well-known hosts are recognized out of the box;
+//! self-hosted instances are mapped with the `forges.<host>` config key.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// A kind of source forge, which determines the URL layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ForgeKind {
+    GitHub,
+    GitLab,
+    /// Gitea and its fork Forgejo (e.g. Codeberg).
+    Gitea,
+    SourceHut,
+}
+
+impl ForgeKind {
+    pub(crate) const ALL: &[ForgeKind] = &[
+        ForgeKind::GitHub,
+        ForgeKind::GitLab,
+        ForgeKind::Gitea,
+        ForgeKind::SourceHut,
+    ];
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "github",
+            ForgeKind::GitLab => "gitlab",
+            ForgeKind::Gitea => "gitea",
+            ForgeKind::SourceHut => "sourcehut",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "forgejo" => Some(ForgeKind::Gitea),
+            _ => Self::ALL.iter().copied().find(|k| k.as_str() == value),
+        }
+    }
+}
+
+/// Hosts recognized without any configuration.
+const KNOWN_HOSTS: &[(&str, ForgeKind)] = &[
+    ("github.com", ForgeKind::GitHub),
+    ("gitlab.com", ForgeKind::GitLab),
+    ("codeberg.org", ForgeKind::Gitea),
+    ("gitea.com", ForgeKind::Gitea),
+    ("git.sr.ht", ForgeKind::SourceHut),
+];
+
+static CONFIGURED_HOSTS: OnceLock<BTreeMap<String, ForgeKind>> = OnceLock::new();
+
+/// Register additional host → forge mappings for this process.
+///
+/// Must be called before the first link is built; later calls are ignored.
+// [impl cli.config.forges]
+pub(crate) fn configure_hosts(hosts: BTreeMap<String, ForgeKind>) {
+    let _ = CONFIGURED_HOSTS.set(hosts);
+}
+
+/// Identify the forge for `host`: configured mappings first, then well-known
+/// hosts, then a guess from the host name (`gitlab.example.com`).
+fn forge_for_host(host: &str, configured: &BTreeMap<String, ForgeKind>) -> Option<ForgeKind> {
+    let host = host.to_ascii_lowercase();
+    if let Some(kind) = configured.get(&host) {
+        return Some(*kind);
+    }
+    if let Some((_, kind)) = KNOWN_HOSTS.iter().find(|(h, _)| *h == host) {
+        return Some(*kind);
+    }
+    let first_label = host.split('.').next().unwrap_or("");
+    match first_label {
+        "gitlab" => Some(ForgeKind::GitLab),
+        "gitea" | "forgejo" => Some(ForgeKind::Gitea),
+        _ => None,
+    }
+}
+
+/// A repository on a known forge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Repository {
+    pub forge: ForgeKind,
+    /// Scheme and host, e.g. `https://gitlab.example.com`.
+    base: String,
+    /// Project path on the forge, e.g. `owner/repo` or `~user/repo`.
+    project: String,
+}
+
+impl Repository {
+    /// Parse a `repository` URL using the configured host mappings.
+    pub(crate) fn parse(url: &str) -> Option<Self> {
+        static EMPTY: BTreeMap<String, ForgeKind> = BTreeMap::new();
+        Self::parse_with(url, CONFIGURED_HOSTS.get().unwrap_or(&EMPTY))
+    }
+
+    /// Parse a `repository` URL with an explicit host mapping.
+    pub(crate) fn parse_with(url: &str, hosts: &BTreeMap<String, ForgeKind>) -> Option<Self> {
+        let url = url.trim();
+        let url = url.strip_prefix("git+").unwrap_or(url);
+        let (scheme, rest) = url.split_once("://")?;
+        if scheme != "https" && scheme != "http" {
+            return None;
+        }
+        let (host, path) = rest.split_once('/')?;
+        let forge = forge_for_host(host, hosts)?;
+
+        let path = path.trim_end_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let project = match forge {
+            // GitLab groups nest arbitrarily deep; everything before `/-/`
+            // is the project path.
+            ForgeKind::GitLab => path.split("/-/").next().unwrap_or(path).to_string(),
+            _ => path.splitn(3, '/').take(2).collect::<Vec<_>>().join("/"),
+        };
+        if project.split('/').filter(|s| !s.is_empty()).count() < 2 {
+            return None;
+        }
+
+        Some(Repository {
+            forge,
+            base: format!("{}://{}", scheme, host),
+            project,
+        })
+    }
+
+    /// The repository's home page.
+    pub(crate) fn home_url(&self) -> String {
+        format!("{}/{}", self.base, self.project)
+    }
+
+    /// Link to a directory at `commit` (or the default branch).
+    pub(crate) fn tree_url(&self, commit: Option<&str>, path: &str) -> String {
+        self.browse_url("tree", commit, path)
+    }
+
+    /// Link to a file at `commit` (or the default branch).
+    pub(crate) fn blob_url(&self, commit: Option<&str>, path: &str) -> String {
+        self.browse_url("blob", commit, path)
+    }
+
+    /// Tree and blob URLs differ only in one path segment on GitHub and
+    /// GitLab; Gitea and sourcehut use one route for both.
+    fn browse_url(&self, kind: &str, commit: Option<&str>, path: &str) -> String {
+        let home = self.home_url();
+        let path = path.trim_start_matches('/');
+        // `HEAD` resolves to the default branch everywhere except Gitea,
+        // whose URLs need an explicit branch or commit.
+        let git_ref = commit.unwrap_or("HEAD");
+        match self.forge {
+            ForgeKind::GitHub => format!("{home}/{kind}/{git_ref}/{path}"),
+            ForgeKind::GitLab => format!("{home}/-/{kind}/{git_ref}/{path}"),
+            ForgeKind::Gitea => match commit {
+                Some(sha) => format!("{home}/src/commit/{sha}/{path}"),
+                None => home,
+            },
+            ForgeKind::SourceHut => format!("{home}/tree/{git_ref}/item/{path}"),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Tests for forge detection and repository link shapes.
+
+use super::{ForgeKind, Repository};
+use std::collections::BTreeMap;
+
+fn parse(url: &str) -> Option<Repository> {
+    Repository::parse_with(url, &BTreeMap::new())
+}
+
+const SHA: &str = "0123abcd";
+
+// [verify tui.browse.forge-links]
+#[test]
+fn known_hosts_are_detected() {
+    let cases = [
+        ("https://github.com/o/r", ForgeKind::GitHub),
+        ("https://gitlab.com/o/r", ForgeKind::GitLab),
+        ("https://codeberg.org/o/r", ForgeKind::Gitea),
+        ("https://gitea.com/o/r", ForgeKind::Gitea),
+        ("https://git.sr.ht/~o/r", ForgeKind::SourceHut),
+        ("https://gitlab.example.com/o/r", ForgeKind::GitLab),
+        ("https://GitHub.com/o/r", ForgeKind::GitHub),
+    ];
+    for (url, forge) in cases {
+        assert_eq!(parse(url).map(|r| r.forge), Some(forge), "{url}");
+    }
+    assert_eq!(parse("https://example.com/o/r"), None);
+    assert_eq!(parse("ssh://git@github.com/o/r"), None);
+    assert_eq!(parse("https://github.com/o"), None);
+}
+
+// [verify tui.browse.forge-links]
+#[test]
+fn repository_urls_are_normalized() {
+    for url in [
+        "https://github.com/o/r",
+        "https://github.com/o/r/",
+        "https://github.com/o/r.git",
+        "git+https://github.com/o/r.git",
+        "https://github.com/o/r/tree/main/crates/x",
+    ] {
+        assert_eq!(
+            parse(url).unwrap().home_url(),
+            "https://github.com/o/r",
+            "{url}"
+        );
+    }
+    assert_eq!(
+        parse("https://gitlab.com/group/sub/r/-/tree/main")
+            .unwrap()
+            .home_url(),
+        "https://gitlab.com/group/sub/r"
+    );
+}
+
+// [verify tui.browse.forge-links]
+#[test]
+fn github_link_shapes() {
+    let repo = parse("https://github.com/o/r").unwrap();
+    assert_eq!(
+        repo.tree_url(Some(SHA), "t/simple"),
+        "https://github.com/o/r/tree/0123abcd/t/simple"
+    );
+    assert_eq!(
+        repo.blob_url(None, "examples/a.rs"),
+        "https://github.com/o/r/blob/HEAD/examples/a.rs"
+    );
+}
+
+// [verify tui.browse.forge-links]
+#[test]
+fn gitlab_link_shapes() {
+    let repo = parse("https://gitlab.com/g/sub/r").unwrap();
+    assert_eq!(
+        repo.tree_url(Some(SHA), "t/simple"),
+        "https://gitlab.com/g/sub/r/-/tree/0123abcd/t/simple"
+    );
+    assert_eq!(
+        repo.blob_url(None, "a.rs"),
+        "https://gitlab.com/g/sub/r/-/blob/HEAD/a.rs"
+    );
+}
+
+// [verify tui.browse.forge-links]
+#[test]
+fn gitea_link_shapes() {
+    let repo = parse("https://codeberg.org/o/r").unwrap();
+    assert_eq!(
+        repo.tree_url(Some(SHA), "t/simple"),
+        "https://codeberg.org/o/r/src/commit/0123abcd/t/simple"
+    );
+    // Gitea has no symbolic default-branch ref, so fall back to the repo.
+    assert_eq!(repo.blob_url(None, "a.rs"), "https://codeberg.org/o/r");
+}
+
+// [verify tui.browse.forge-links]
+#[test]
+fn sourcehut_link_shapes() {
+    let repo = parse("https://git.sr.ht/~o/r").unwrap();
+    assert_eq!(
+        repo.tree_url(Some(SHA), "t/simple"),
+        "https://git.sr.ht/~o/r/tree/0123abcd/item/t/simple"
+    );
+    assert_eq!(
+        repo.blob_url(Some(SHA), "a.rs"),
+        "https://git.sr.ht/~o/r/tree/0123abcd/item/a.rs"
+    );
+}
+
+// [verify cli.config.forges]
+#[test]
+fn configured_hosts_override_detection() {
+    let mut hosts = BTreeMap::new();
+    hosts.insert("git.example.com".to_string(), ForgeKind::GitLab);
+    hosts.insert("gitlab.example.org".to_string(), ForgeKind::Gitea);
+
+    let repo = Repository::parse_with("https://git.example.com/team/r", &hosts).unwrap();
+    assert_eq!(
+        repo.tree_url(Some(SHA), "x"),
+        "https://git.example.com/team/r/-/tree/0123abcd/x"
+    );
+    let repo = Repository::parse_with("https://gitlab.example.org/team/r", &hosts).unwrap();
+    assert_eq!(repo.forge, ForgeKind::Gitea);
+}
+
+// [verify cli.config.forges]
+#[test]
+fn forge_names_parse() {
+    for kind in ForgeKind::ALL {
+        assert_eq!(ForgeKind::parse(kind.as_str()), Some(*kind));
+    }
+    assert_eq!(ForgeKind::parse("forgejo"), Some(ForgeKind::Gitea));
+    assert_eq!(ForgeKind::parse("bitbucket"), None);
+}
@@ -0,0 +1,340 @@
+//! Operation snapshots behind `cargo bp undo`.
+//!
+//! While a command runs, every file it is about to modify is copied into
+//! `target/bp-history/<timestamp>/` under the project (or workspace) root,
+//! next to a `journal.toml` naming the command and the files. When the
+//! command ends the journal also records each file's new contents, so
+//! `undo` can tell whether anything changed since and refuse rather than
+//! clobber later edits. Snapshots where nothing actually changed are
+//! discarded.
+//!
+//! Writers don't need to know whether a recording is active: they call
+//! [`before_write`], or hand it to
+//! [`ManifestTransaction::commit`](crate::manifest::ManifestTransaction::commit),
+//! and it is a no-op outside one.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+const JOURNAL: &str = "journal.toml";
+
+/// What a snapshot records about one command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Journal {
+    /// The command line, e.g. `cargo bp add cli`.
+    pub command: String,
+    /// When the command ran, in seconds since the Unix epoch.
+    pub time: u64,
+    #[serde(default)]
+    pub files: Vec<JournalFile>,
+}
+
+/// One file a command modified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct JournalFile {
+    /// The file, relative to the project root.
+    pub path: PathBuf,
+    /// The saved copy of the file before the command, if it existed.
+    pub before: Option<String>,
+    /// The saved copy of the file after the command, if it still exists.
+    pub after: Option<String>,
+}
+
+/// A snapshot on disk.
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+    pub id: String,
+    pub dir: PathBuf,
+    pub journal: Journal,
+}
+
+/// The recording for the command in progress.
+struct Operation {
+    root: PathBuf,
+    dir: Option<PathBuf>,
+    journal: Journal,
+    seen: BTreeSet<PathBuf>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Operation>> = const { RefCell::new(None) };
+}
+
+/// Records the files modified while it is alive; dropping it saves the
+/// snapshot.
+pub(crate) struct Recording(());
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        if let Some(op) = CURRENT.with(|current| current.borrow_mut().take())
+            && let Err(e) = op.finish()
+        {
+            eprintln!("warning: could not save undo history: {e:#}");
+        }
+    }
+}
+
+/// The directory snapshots for `project_dir` live in: `target/bp-history`
+/// under the workspace root, or under the package if it isn't in one.
+pub(crate) fn history_dir(project_dir: &Path) -> Result<PathBuf> {
+    let manifest = crate::manifest::find_user_manifest(project_dir)?;
+    let root = match crate::manifest::find_workspace_manifest(&manifest)? {
+        Some(ws) => ws.parent().unwrap_or(project_dir).to_path_buf(),
+        None => project_dir.to_path_buf(),
+    };
+    Ok(root.join("target").join("bp-history"))
+}
+
+/// Start recording for `command`. Returns `None` outside a Rust project.
+// [impl cli.undo.snapshot]
+pub(crate) fn begin(project_dir: &Path, command: impl Into<String>) -> Option<Recording> {
+    let history = history_dir(project_dir).ok()?;
+    let root = history.parent()?.parent()?.to_path_buf();
+    let op = Operation {
+        root,
+        dir: None,
+        journal: Journal {
+            command: command.into(),
+            time: now(),
+            files: Vec::new(),
+        },
+        seen: BTreeSet::new(),
+    };
+    CURRENT.with(|current| *current.borrow_mut() = Some(op));
+    Some(Recording(()))
+}
+
+/// Snapshot `path` before it is written or removed. Only the first call
+/// for a path during a command keeps a copy.
+// [impl cli.undo.snapshot]
+pub(crate) fn before_write(path: &Path) {
+    CURRENT.with(|current| {
+        if let Some(op) = current.borrow_mut().as_mut()
+            && let Err(e) = op.save_before(path)
+        {
+            eprintln!("warning: could not snapshot {}: {e:#}", path.display());
+        }
+    });
+}
+
+impl Operation {
+    fn save_before(&mut self, path: &Path) -> Result<()> {
+        let path = std::path::absolute(path)?;
+        if !self.seen.insert(path.clone()) {
+            return Ok(());
+        }
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => {
+                let dir = new_snapshot_dir(&self.root.join("target").join("bp-history"))?;
+                self.dir = Some(dir.clone());
+                dir
+            }
+        };
+        let n = self.journal.files.len();
+        let before = match read_optional(&path)? {
+            Some(content) => {
+                let name = format!("{n}.before");
+                std::fs::write(dir.join(&name), content)?;
+                Some(name)
+            }
+            None => None,
+        };
+        self.journal.files.push(JournalFile {
+            path: path
+                .strip_prefix(&self.root)
+                .map(Path::to_path_buf)
+                .unwrap_or(path),
+            before,
+            after: None,
+        });
+        write_journal(&dir, &self.journal)
+    }
+
+    /// Record each file's new contents, dropping files the command left
+    /// as they were; a snapshot with nothing left is removed.
+    fn finish(mut self) -> Result<()> {
+        let Some(dir) = self.dir else {
+            return Ok(());
+        };
+        let mut kept = Vec::new();
+        for (n, mut file) in self.journal.files.into_iter().enumerate() {
+            let now = read_optional(&self.root.join(&file.path))?;
+            let before = saved(&dir, file.before.as_deref())?;
+            if now == before {
+                if let Some(name) = &file.before {
+                    std::fs::remove_file(dir.join(name))?;
+                }
+                continue;
+            }
+            if let Some(content) = now {
+                let name = format!("{n}.after");
+                std::fs::write(dir.join(&name), content)?;
+                file.after = Some(name);
+            }
+            kept.push(file);
+        }
+        if kept.is_empty() {
+            return std::fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to remove {}", dir.display()));
+        }
+        self.journal.files = kept;
+        write_journal(&dir, &self.journal)
+    }
+}
+
+/// Saved snapshots in `history`, newest first.
+pub(crate) fn list(history: &Path) -> Result<Vec<Snapshot>> {
+    let entries = match std::fs::read_dir(history) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", history.display())),
+    };
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let dir = entry?.path();
+        let Ok(content) = std::fs::read_to_string(dir.join(JOURNAL)) else {
+            continue;
+        };
+        let journal: Journal = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", dir.join(JOURNAL).display()))?;
+        let id = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        snapshots.push(Snapshot { id, dir, journal });
+    }
+    snapshots.sort_by(|a, b| (b.journal.time, &b.id).cmp(&(a.journal.time, &a.id)));
+    Ok(snapshots)
+}
+
+/// Restore the files of the snapshot `id` (or the newest one) and delete
+/// the snapshot. Refuses if any of its files changed since the command
+/// that made it.
+// [impl cli.undo.restore]
+// [impl cli.undo.refuse-changed]
+pub(crate) fn undo(history: &Path, id: Option<&str>) -> Result<Snapshot> {
+    let snapshots = list(history)?;
+    let snapshot = match id {
+        Some(id) => snapshots
+            .into_iter()
+            .find(|s| s.id == id)
+            .with_context(|| format!("No snapshot '{}'; see `cargo bp undo --list`", id))?,
+        None => snapshots
+            .into_iter()
+            .next()
+            .context("Nothing to undo: no cargo bp snapshots in this project")?,
+    };
+    let root = history
+        .parent()
+        .and_then(Path::parent)
+        .context("history directory has no project root")?;
+
+    let mut txn = crate::manifest::ManifestTransaction::new();
+    for file in &snapshot.journal.files {
+        let path = root.join(&file.path);
+        if read_optional(&path)? != saved(&snapshot.dir, file.after.as_deref())? {
+            bail!(
+                "{} has changed since `{}`; not undoing it",
+                file.path.display(),
+                snapshot.journal.command
+            );
+        }
+        match saved(&snapshot.dir, file.before.as_deref())? {
+            Some(content) => {
+                let content = String::from_utf8(content).with_context(|| {
+                    format!("{} is not UTF-8 and can't be restored", file.path.display())
+                })?;
+                txn.stage(path, content)
+            }
+            None => txn.stage_removal(path),
+        }
+    }
+    txn.commit(before_write)?;
+    std::fs::remove_dir_all(&snapshot.dir)
+        .with_context(|| format!("Failed to remove {}", snapshot.dir.display()))?;
+    Ok(snapshot)
+}
+
+/// Format a Unix time as UTC, e.g. `2026-10-18 09:30:00`.
+pub(crate) fn format_time(secs: u64) -> String {
+    let (y, m, d, hh, mm, ss) = civil(secs);
+    format!("{y:04}-{m:02}-{d:02} {hh:02}:{mm:02}:{ss:02}")
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Create a fresh `<timestamp>` directory in `history`.
+fn new_snapshot_dir(history: &Path) -> Result<PathBuf> {
+    let (y, m, d, hh, mm, ss) = civil(now());
+    let stamp = format!("{y:04}{m:02}{d:02}T{hh:02}{mm:02}{ss:02}Z");
+    std::fs::create_dir_all(history)
+        .with_context(|| format!("Failed to create {}", history.display()))?;
+    for n in 0.. {
+        let id = if n == 0 {
+            stamp.clone()
+        } else {
+            format!("{stamp}-{n}")
+        };
+        let dir = history.join(id);
+        match std::fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to create {}", dir.display()));
+            }
+        }
+    }
+    unreachable!()
+}
+
+fn write_journal(dir: &Path, journal: &Journal) -> Result<()> {
+    let content = toml::to_string(journal).context("Failed to serialize the undo journal")?;
+    std::fs::write(dir.join(JOURNAL), content)
+        .with_context(|| format!("Failed to write {}", dir.join(JOURNAL).display()))
+}
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn saved(dir: &Path, name: Option<&str>) -> Result<Option<Vec<u8>>> {
+    name.map(|name| {
+        std::fs::read(dir.join(name))
+            .with_context(|| format!("Failed to read {}", dir.join(name).display()))
+    })
+    .transpose()
+}
+
+/// Split a Unix time into UTC calendar fields.
+fn civil(secs: u64) -> (i64, u32, u32, u64, u64, u64) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's
+    // `civil_from_days`).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d, rem / 3_600, rem % 3_600 / 60, rem % 60)
+}
@@ -0,0 +1,130 @@
+//! Tests for operation snapshots and `undo`.
+
+use super::*;
+
+const ORIGINAL: &str = "[package]\nname = \"demo\"\n";
+
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("Cargo.toml"), ORIGINAL).unwrap();
+    dir
+}
+
+fn history(project: &tempfile::TempDir) -> PathBuf {
+    history_dir(project.path()).unwrap()
+}
+
+/// Run `edit` the way a command would: inside a recording for `command`.
+fn record(project: &tempfile::TempDir, command: &str, edit: impl FnOnce(&Path)) {
+    let _recording = begin(project.path(), command).unwrap();
+    edit(project.path());
+}
+
+fn write(path: &Path, content: &str) {
+    before_write(path);
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn civil_time_matches_known_dates() {
+    assert_eq!(format_time(0), "1970-01-01 00:00:00");
+    assert_eq!(format_time(951_782_400), "2000-02-29 00:00:00");
+    assert_eq!(format_time(1_792_315_845), "2026-10-18 09:30:45");
+}
+
+// [verify cli.undo.snapshot]
+#[test]
+fn recording_keeps_before_and_after_copies() {
+    let project = project();
+    record(&project, "cargo bp add basic", |dir| {
+        write(&dir.join("Cargo.toml"), "edited once");
+        write(&dir.join("Cargo.toml"), "edited twice");
+        write(&dir.join("build.rs"), "fn main() {}");
+    });
+
+    let snapshots = list(&history(&project)).unwrap();
+    assert_eq!(snapshots.len(), 1);
+    let snapshot = &snapshots[0];
+    assert_eq!(snapshot.journal.command, "cargo bp add basic");
+    let files: Vec<_> = snapshot.journal.files.iter().map(|f| &f.path).collect();
+    assert_eq!(files, [Path::new("Cargo.toml"), Path::new("build.rs")]);
+
+    let manifest = &snapshot.journal.files[0];
+    let before = saved(&snapshot.dir, manifest.before.as_deref()).unwrap();
+    let after = saved(&snapshot.dir, manifest.after.as_deref()).unwrap();
+    assert_eq!(before.as_deref(), Some(ORIGINAL.as_bytes()));
+    assert_eq!(after.as_deref(), Some(&b"edited twice"[..]));
+    assert!(snapshot.journal.files[1].before.is_none());
+}
+
+#[test]
+fn recording_that_changes_nothing_leaves_no_snapshot() {
+    let project = project();
+    record(&project, "cargo bp sync", |dir| {
+        write(&dir.join("Cargo.toml"), ORIGINAL);
+    });
+    assert!(list(&history(&project)).unwrap().is_empty());
+}
+
+#[test]
+fn writes_outside_a_recording_are_not_snapshotted() {
+    let project = project();
+    write(&project.path().join("Cargo.toml"), "edited");
+    assert!(list(&history(&project)).unwrap().is_empty());
+}
+
+// [verify cli.undo.restore]
+#[test]
+fn undo_restores_the_newest_snapshot_and_removes_created_files() {
+    let project = project();
+    record(&project, "cargo bp add basic", |dir| {
+        write(&dir.join("Cargo.toml"), "first");
+    });
+    // Snapshot ids are per second; make sure the second one sorts after.
+    let mut first = list(&history(&project)).unwrap().remove(0);
+    first.journal.time -= 10;
+    write_journal(&first.dir, &first.journal).unwrap();
+    record(&project, "cargo bp add fancy", |dir| {
+        write(&dir.join("Cargo.toml"), "second");
+        write(&dir.join("build.rs"), "fn main() {}");
+    });
+
+    let commands: Vec<_> = list(&history(&project))
+        .unwrap()
+        .into_iter()
+        .map(|s| s.journal.command)
+        .collect();
+    assert_eq!(commands, ["cargo bp add fancy", "cargo bp add basic"]);
+
+    let undone = undo(&history(&project), None).unwrap();
+    assert_eq!(undone.journal.command, "cargo bp add fancy");
+    let manifest = std::fs::read_to_string(project.path().join("Cargo.toml")).unwrap();
+    assert_eq!(manifest, "first");
+    assert!(!project.path().join("build.rs").exists());
+
+    undo(&history(&project), Some(&first.id)).unwrap();
+    let manifest = std::fs::read_to_string(project.path().join("Cargo.toml")).unwrap();
+    assert_eq!(manifest, ORIGINAL);
+    assert!(list(&history(&project)).unwrap().is_empty());
+}
+
+// [verify cli.undo.refuse-changed]
+#[test]
+fn undo_refuses_when_a_file_changed_since() {
+    let project = project();
+    record(&project, "cargo bp sync", |dir| {
+        write(&dir.join("Cargo.toml"), "synced");
+        write(&dir.join("build.rs"), "fn main() {}");
+    });
+    std::fs::remove_file(project.path().join("build.rs")).unwrap();
+
+    let err = undo(&history(&project), None).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("build.rs has changed since `cargo bp sync`"),
+        "{err}"
+    );
+    let manifest = std::fs::read_to_string(project.path().join("Cargo.toml")).unwrap();
+    assert_eq!(manifest, "synced");
+    assert_eq!(list(&history(&project)).unwrap().len(), 1);
+}
@@ -1,13 +1,28 @@
 //! CLI for battery-pack: create and manage battery packs.
 
+mod adopt;
 mod commands;
 mod completions;
+pub(crate) mod config;
+mod doctor;
+pub(crate) mod drift;
+mod extract;
+pub(crate) mod forge;
+mod history;
 pub(crate) mod manifest;
 pub(crate) mod merge;
+mod multi_add;
+pub(crate) mod output;
+mod plan;
+mod prune;
 pub(crate) mod registry;
 pub(crate) mod template_engine;
+mod tree;
 mod tui;
+mod upgrade;
 mod validate;
+mod why;
+mod workspace;
 
 // The only true public API
 pub use commands::main;
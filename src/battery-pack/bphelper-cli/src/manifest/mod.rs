@@ -8,6 +8,10 @@ use anyhow::{Context, Result, bail};
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
+mod transaction;
+
+pub(crate) use transaction::ManifestTransaction;
+
 // ============================================================================
 // Cargo.toml location helpers
 // ============================================================================
@@ -133,18 +137,23 @@ pub(crate) fn write_workspace_refs_by_kind(
         if let Some(table) = table.as_table_mut()
             && (!if_missing || !table.contains_key(dep_name))
         {
-            let mut dep = toml_edit::InlineTable::new();
-            dep.insert("workspace", toml_edit::Value::from(true));
-            table.insert(
-                dep_name,
-                toml_edit::Item::Value(toml_edit::Value::InlineTable(dep)),
-            );
+            add_workspace_ref(table, dep_name);
             written += 1;
         }
     }
     written
 }
 
+/// Add a `name = { workspace = true }` reference to a dependency table.
+pub(crate) fn add_workspace_ref(table: &mut toml_edit::Table, name: &str) {
+    let mut dep = toml_edit::InlineTable::new();
+    dep.insert("workspace", toml_edit::Value::from(true));
+    table.insert(
+        name,
+        toml_edit::Item::Value(toml_edit::Value::InlineTable(dep)),
+    );
+}
+
 /// Add a dependency to a toml_edit table (non-workspace mode).
 // [impl manifest.deps.add]
 // [impl manifest.deps.version-features]
@@ -191,6 +200,129 @@ pub(crate) fn remove_deps_by_kind(
     removed
 }
 
+/// Sections a dependency was moved between by [`relocate_dep`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Relocation {
+    /// Sections the entry was removed from.
+    pub from: Vec<bphelper_manifest::DepKind>,
+    /// Sections the entry was added to.
+    pub to: Vec<bphelper_manifest::DepKind>,
+}
+
+impl std::fmt::Display for Relocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |kinds: &[bphelper_manifest::DepKind]| {
+            kinds
+                .iter()
+                .map(|k| format!("[{}]", k))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if self.from.is_empty() {
+            write!(f, "also in {}", join(&self.to))
+        } else if self.to.is_empty() {
+            write!(f, "removed from {}", join(&self.from))
+        } else {
+            write!(f, "{} → {}", join(&self.from), join(&self.to))
+        }
+    }
+}
+
+/// Move a dependency that lives in the wrong sections into the sections for
+/// `kinds`, e.g. after a battery pack moved it from `[dependencies]` to
+/// `[dev-dependencies]`. The existing entry is moved as-is, so inline,
+/// table and `workspace = true` forms keep their formatting.
+///
+/// An entry already in every wanted section only loses its copies in the
+/// other sections; a crate the manifest doesn't have at all is left alone.
+/// Returns what was moved.
+// [impl cli.sync.dep-kind]
+// [impl manifest.toml.preserve]
+pub(crate) fn relocate_dep(
+    doc: &mut toml_edit::DocumentMut,
+    name: &str,
+    kinds: &[bphelper_manifest::DepKind],
+) -> Option<Relocation> {
+    use bphelper_manifest::DepKind;
+
+    let in_section = |doc: &toml_edit::DocumentMut, kind: DepKind| {
+        doc.get(dep_kind_section(kind))
+            .and_then(|t| t.as_table_like())
+            .is_some_and(|t| t.contains_key(name))
+    };
+    let present: Vec<DepKind> = [DepKind::Normal, DepKind::Dev, DepKind::Build]
+        .into_iter()
+        .filter(|kind| in_section(doc, *kind))
+        .collect();
+    let missing: Vec<DepKind> = kinds
+        .iter()
+        .copied()
+        .filter(|kind| !present.contains(kind))
+        .collect();
+    let stale: Vec<DepKind> = present
+        .iter()
+        .copied()
+        .filter(|kind| !kinds.contains(kind))
+        .collect();
+    if present.is_empty() || (missing.is_empty() && stale.is_empty()) {
+        return None;
+    }
+    // Leave hand-written inline sections (`dependencies = { .. }`) alone.
+    let not_a_table = |kind: &DepKind| {
+        doc.get(dep_kind_section(*kind))
+            .is_some_and(|t| !t.is_table())
+    };
+    if present.iter().chain(&missing).any(not_a_table) {
+        return None;
+    }
+
+    // Take the entry from a stale section if there is one (dropping every
+    // stale copy), otherwise copy it from a section that stays.
+    let mut entry = None;
+    for kind in stale.iter().chain(&present) {
+        let Some(table) = doc
+            .get_mut(dep_kind_section(*kind))
+            .and_then(|t| t.as_table_mut())
+        else {
+            continue;
+        };
+        if stale.contains(kind) {
+            let removed = table.remove_entry(name);
+            entry = entry.or(removed);
+        } else if entry.is_none() {
+            entry = table
+                .get_key_value(name)
+                .map(|(k, v)| (k.clone(), v.clone()));
+        }
+    }
+    let (key, item) = entry?;
+
+    for kind in &missing {
+        let table = doc[dep_kind_section(*kind)]
+            .or_insert(toml_edit::Item::Table(toml_edit::Table::new()))
+            .as_table_mut()?;
+        let mut item = item.clone();
+        // A `[dependencies.name]` table belongs under its new parent.
+        if let (toml_edit::Item::Table(moved), Some(position)) = (&mut item, table.position()) {
+            moved.set_position(position);
+        }
+        table.insert_formatted(&key, item);
+    }
+
+    Some(Relocation {
+        from: stale,
+        to: missing,
+    })
+}
+
+/// Parse a version string as semver, padding short forms like `"1"` or `"1.2"`.
+fn parse_version_lenient(version: &str) -> Option<semver::Version> {
+    semver::Version::parse(version)
+        .or_else(|_| semver::Version::parse(&format!("{}.0", version)))
+        .or_else(|_| semver::Version::parse(&format!("{}.0.0", version)))
+        .ok()
+}
+
 /// Return true when `recommended` is strictly newer than `current` (semver).
 ///
 /// Falls back to string equality when either side is not a valid semver
@@ -198,20 +330,131 @@ pub(crate) fn remove_deps_by_kind(
 /// differ.
 pub(crate) fn should_upgrade_version(current: &str, recommended: &str) -> bool {
     match (
-        semver::Version::parse(current)
-            .or_else(|_| semver::Version::parse(&format!("{}.0", current)))
-            .or_else(|_| semver::Version::parse(&format!("{}.0.0", current))),
-        semver::Version::parse(recommended)
-            .or_else(|_| semver::Version::parse(&format!("{}.0", recommended)))
-            .or_else(|_| semver::Version::parse(&format!("{}.0.0", recommended))),
+        parse_version_lenient(current),
+        parse_version_lenient(recommended),
     ) {
         // [impl manifest.sync.version-bump]
-        (Ok(cur), Ok(rec)) => rec > cur,
+        (Some(cur), Some(rec)) => rec > cur,
         // Non-parsable: fall back to "update if different"
         _ => current != recommended,
     }
 }
 
+/// Return true when `recommended` is semver-compatible with `current`
+/// under Cargo's caret rules (same major, or same minor for `0.x`).
+///
+/// Non-parsable versions are treated as incompatible.
+pub(crate) fn is_compatible_version(current: &str, recommended: &str) -> bool {
+    let (Some(cur), Some(rec)) = (
+        parse_version_lenient(current),
+        parse_version_lenient(recommended),
+    ) else {
+        return false;
+    };
+    if cur.major != rec.major {
+        false
+    } else if cur.major > 0 {
+        true
+    } else if cur.minor != rec.minor {
+        false
+    } else {
+        cur.minor > 0 || cur.patch == rec.patch
+    }
+}
+
+/// How `cargo bp sync` treats dependency versions that are behind the
+/// battery pack's recommendation.
+// [impl cli.config.sync-policy]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SyncPolicy {
+    /// Upgrade to the recommended version, even across major versions.
+    #[default]
+    AllowBreaking,
+    /// Upgrade only when the recommended version is semver-compatible.
+    CompatibleOnly,
+    /// Never change versions; only add missing crates and features.
+    NeverUpgrade,
+}
+
+impl SyncPolicy {
+    pub(crate) const ALL: [SyncPolicy; 3] = [
+        SyncPolicy::AllowBreaking,
+        SyncPolicy::CompatibleOnly,
+        SyncPolicy::NeverUpgrade,
+    ];
+
+    /// The kebab-case name used in config files and on the command line.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            SyncPolicy::AllowBreaking => "allow-breaking",
+            SyncPolicy::CompatibleOnly => "compatible-only",
+            SyncPolicy::NeverUpgrade => "never-upgrade",
+        }
+    }
+
+    /// Parse a policy from its kebab-case name.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == s)
+    }
+
+    /// Return true when this policy allows moving `current` to `recommended`.
+    /// An exact (`=`) requirement is a pin, which no policy moves.
+    // [impl cli.pins.exact]
+    pub(crate) fn allows_upgrade(self, current: &str, recommended: &str) -> bool {
+        if is_exact_requirement(current) {
+            return false;
+        }
+        match self {
+            SyncPolicy::AllowBreaking => should_upgrade_version(current, recommended),
+            SyncPolicy::CompatibleOnly => {
+                should_upgrade_version(current, recommended)
+                    && is_compatible_version(current, recommended)
+            }
+            SyncPolicy::NeverUpgrade => false,
+        }
+    }
+}
+
+/// Return true for an exact (`=`) version requirement, which battery-pack
+/// treats as a pin.
+pub(crate) fn is_exact_requirement(version: &str) -> bool {
+    version.trim_start().starts_with('=')
+}
+
+/// Remove `features` from a dependency entry, keeping its formatting.
+/// Returns true if any were removed.
+// [impl cli.sync.prune-features]
+pub(crate) fn remove_dep_features(
+    table: &mut toml_edit::Table,
+    name: &str,
+    features: &BTreeSet<String>,
+) -> bool {
+    let Some(array) = table
+        .get_mut(name)
+        .and_then(|item| item.as_table_like_mut())
+        .and_then(|dep| dep.get_mut("features"))
+        .and_then(|f| f.as_array_mut())
+    else {
+        return false;
+    };
+    let before = array.len();
+    // Removing the first element must not take the spacing after "[" with it.
+    let lead = array
+        .get(0)
+        .and_then(|v| v.decor().prefix())
+        .and_then(|p| p.as_str())
+        .map(str::to_owned);
+    array.retain(|v| v.as_str().is_none_or(|f| !features.contains(f)));
+    if array.len() == before {
+        return false;
+    }
+    if let (Some(first), Some(lead)) = (array.get_mut(0), lead) {
+        first.decor_mut().set_prefix(lead);
+    }
+    true
+}
+
 /// Sync a dependency in-place: update version if behind, add missing features.
 /// Returns true if changes were made.
 // [impl manifest.deps.existing]
@@ -220,6 +463,16 @@ pub(crate) fn sync_dep_in_table(
     table: &mut toml_edit::Table,
     name: &str,
     spec: &bphelper_manifest::CrateSpec,
+) -> bool {
+    sync_dep_in_table_with_policy(table, name, spec, SyncPolicy::default())
+}
+
+/// Like [`sync_dep_in_table`], but version bumps are gated by `policy`.
+pub(crate) fn sync_dep_in_table_with_policy(
+    table: &mut toml_edit::Table,
+    name: &str,
+    spec: &bphelper_manifest::CrateSpec,
+    policy: SyncPolicy,
 ) -> bool {
     let Some(existing) = table.get_mut(name) else {
         // Not present — add it
@@ -233,19 +486,18 @@ pub(crate) fn sync_dep_in_table(
         toml_edit::Item::Value(toml_edit::Value::String(version_str)) => {
             let current = version_str.value().to_string();
             // [impl manifest.sync.version-bump]
-            if !spec.version.is_empty() && should_upgrade_version(&current, &spec.version) {
+            if !spec.version.is_empty() && policy.allows_upgrade(&current, &spec.version) {
                 *version_str = toml_edit::Formatted::new(spec.version.clone());
                 changed = true;
             }
             // [impl manifest.sync.feature-add]
             if !spec.features.is_empty() {
-                let keep_version = if !spec.version.is_empty()
-                    && should_upgrade_version(&current, &spec.version)
-                {
-                    spec.version.clone()
-                } else {
-                    current.clone()
-                };
+                let keep_version =
+                    if !spec.version.is_empty() && policy.allows_upgrade(&current, &spec.version) {
+                        spec.version.clone()
+                    } else {
+                        current.clone()
+                    };
                 let patched = bphelper_manifest::CrateSpec {
                     version: keep_version,
                     features: spec.features.clone(),
@@ -260,7 +512,7 @@ pub(crate) fn sync_dep_in_table(
             // [impl manifest.sync.version-bump]
             if let Some(toml_edit::Value::String(v)) = inline.get_mut("version")
                 && !spec.version.is_empty()
-                && should_upgrade_version(v.value(), &spec.version)
+                && policy.allows_upgrade(v.value(), &spec.version)
             {
                 *v = toml_edit::Formatted::new(spec.version.clone());
                 changed = true;
@@ -303,7 +555,7 @@ pub(crate) fn sync_dep_in_table(
             if let Some(toml_edit::Item::Value(toml_edit::Value::String(v))) =
                 tbl.get_mut("version")
                 && !spec.version.is_empty()
-                && should_upgrade_version(v.value(), &spec.version)
+                && policy.allows_upgrade(v.value(), &spec.version)
             {
                 *v = toml_edit::Formatted::new(spec.version.clone());
                 changed = true;
@@ -460,30 +712,52 @@ pub(crate) fn read_active_features_from(
     }
 }
 
-/// Read managed-deps from a parsed TOML value at a given path prefix.
-///
-/// Returns `None` when the key is absent (old-format metadata).
-fn read_managed_deps_at(
-    raw: &toml::Value,
-    prefix: &[&str],
+/// A battery pack's entry in the battery-pack metadata table, respecting
+/// metadata location.
+fn read_metadata_entry(
+    location: &MetadataLocation,
+    user_manifest_content: &str,
     bp_name: &str,
-) -> Option<BTreeSet<String>> {
-    let mut node = Some(raw);
+) -> Option<toml::Value> {
+    let (content, prefix): (std::borrow::Cow<'_, str>, &[&str]) = match location {
+        MetadataLocation::Package => (
+            std::borrow::Cow::Borrowed(user_manifest_content),
+            &["package", "metadata"],
+        ),
+        MetadataLocation::Workspace { ws_manifest_path } => {
+            let ws = std::fs::read_to_string(ws_manifest_path).ok()?;
+            (std::borrow::Cow::Owned(ws), &["workspace", "metadata"])
+        }
+    };
+    let raw: toml::Value = toml::from_str(&content).ok()?;
+    let mut node = Some(&raw);
     for key in prefix {
         node = node.and_then(|n| n.get(key));
     }
-    let arr = node
-        .and_then(|m| m.get("battery-pack"))
-        .and_then(|bp| bp.get(bp_name))
-        .and_then(|entry| entry.get("managed-deps"))
-        .and_then(|v| v.as_array())?;
+    node?.get("battery-pack")?.get(bp_name).cloned()
+}
+
+fn string_set(value: &toml::Value) -> Option<BTreeSet<String>> {
     Some(
-        arr.iter()
+        value
+            .as_array()?
+            .iter()
             .filter_map(|v| v.as_str().map(String::from))
             .collect(),
     )
 }
 
+/// Read a list of crate names (`managed-deps` or `pinned`) from a battery
+/// pack's metadata entry. Returns `None` when the key is absent.
+fn read_crate_list_from(
+    location: &MetadataLocation,
+    user_manifest_content: &str,
+    bp_name: &str,
+    key: &str,
+) -> Option<BTreeSet<String>> {
+    string_set(read_metadata_entry(location, user_manifest_content, bp_name)?.get(key)?)
+}
+
 /// Read managed-deps for a battery pack, respecting metadata location.
 ///
 /// Returns `None` when the key is absent (old-format / pre-migration metadata).
@@ -492,18 +766,107 @@ pub(crate) fn read_managed_deps_from(
     user_manifest_content: &str,
     bp_name: &str,
 ) -> Option<BTreeSet<String>> {
+    read_crate_list_from(location, user_manifest_content, bp_name, "managed-deps")
+}
+
+/// Read the crates a battery pack's metadata pins, respecting metadata
+/// location. Sync never changes a pinned crate's version.
+// [impl cli.pins.metadata]
+pub(crate) fn read_pinned_from(
+    location: &MetadataLocation,
+    user_manifest_content: &str,
+    bp_name: &str,
+) -> BTreeSet<String> {
+    read_crate_list_from(location, user_manifest_content, bp_name, "pinned").unwrap_or_default()
+}
+
+/// Read the Cargo features sync added to each crate on a battery pack's
+/// behalf (`managed-features`), respecting metadata location.
+// [impl cli.sync.prune-features]
+pub(crate) fn read_managed_features_from(
+    location: &MetadataLocation,
+    user_manifest_content: &str,
+    bp_name: &str,
+) -> BTreeMap<String, BTreeSet<String>> {
+    read_metadata_entry(location, user_manifest_content, bp_name)
+        .as_ref()
+        .and_then(|entry| entry.get("managed-features"))
+        .and_then(|f| f.as_table())
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(name, features)| Some((name.clone(), string_set(features)?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Record `managed-features` on a battery pack's metadata entry, which
+/// [`write_bp_features_to_doc`] must already have written. An empty map
+/// removes the key.
+pub(crate) fn write_managed_features_to_doc(
+    doc: &mut toml_edit::DocumentMut,
+    path_prefix: &[&str],
+    bp_name: &str,
+    features: &BTreeMap<String, BTreeSet<String>>,
+) {
+    let Some(entry) = doc[path_prefix[0]][path_prefix[1]]["battery-pack"][bp_name].as_table_mut()
+    else {
+        return;
+    };
+    if features.is_empty() {
+        entry.remove("managed-features");
+        return;
+    }
+    let mut table = toml_edit::InlineTable::new();
+    for (name, crate_features) in features {
+        let mut arr = toml_edit::Array::new();
+        for feature in crate_features {
+            arr.push(feature.as_str());
+        }
+        table.insert(name, toml_edit::Value::Array(arr));
+    }
+    entry.insert(
+        "managed-features",
+        toml_edit::Item::Value(toml_edit::Value::InlineTable(table)),
+    );
+}
+
+/// Names of the battery packs that have an entry in the battery-pack
+/// metadata table, respecting metadata location.
+pub(crate) fn read_metadata_bp_names(
+    location: &MetadataLocation,
+    user_manifest_content: &str,
+) -> BTreeSet<String> {
     let (content, prefix): (std::borrow::Cow<'_, str>, &[&str]) = match location {
         MetadataLocation::Package => (
             std::borrow::Cow::Borrowed(user_manifest_content),
             &["package", "metadata"],
         ),
         MetadataLocation::Workspace { ws_manifest_path } => {
-            let ws = std::fs::read_to_string(ws_manifest_path).ok()?;
+            let Ok(ws) = std::fs::read_to_string(ws_manifest_path) else {
+                return BTreeSet::new();
+            };
             (std::borrow::Cow::Owned(ws), &["workspace", "metadata"])
         }
     };
-    let raw: toml::Value = toml::from_str(&content).ok()?;
-    read_managed_deps_at(&raw, prefix, bp_name)
+    let Ok(raw) = toml::from_str::<toml::Value>(&content) else {
+        return BTreeSet::new();
+    };
+    let mut node = Some(&raw);
+    for key in prefix {
+        node = node.and_then(|n| n.get(key));
+    }
+    node.and_then(|m| m.get("battery-pack"))
+        .and_then(|bp| bp.as_table())
+        .map(|table| {
+            table
+                .iter()
+                .filter(|(_, entry)| entry.is_table())
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 /// Write features and optional managed-deps into a `toml_edit::DocumentMut`.
@@ -571,10 +934,98 @@ pub(crate) fn write_bp_features_to_doc(
             toml_edit::Item::Value(toml_edit::Value::Array(arr)),
         );
     }
+    // Keep whatever else the user recorded for the pack, such as `pinned`.
+    if let Some(existing) = bp_table.get(bp_name).and_then(|item| item.as_table_like()) {
+        for (key, item) in existing.iter() {
+            if key != "features" && key != "managed-deps" {
+                entry_table.insert(key, item.clone());
+            }
+        }
+    }
     entry_table.set_implicit(true);
     bp_table.insert(bp_name, toml_edit::Item::Table(entry_table));
 }
 
+/// Where a battery pack's version requirement was rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RegistrationUpdate {
+    /// The member's `[build-dependencies]` entry.
+    Package,
+    /// `[workspace.dependencies]`, for a `workspace = true` entry.
+    Workspace,
+}
+
+/// Point a battery pack's registration at `version`, keeping the entry's
+/// formatting and any other keys.
+///
+/// `workspace = true` entries are followed into `ws_doc`. Fails when the
+/// pack isn't registered or its entry has no version to replace (e.g. a
+/// path dependency).
+// [impl cli.upgrade.registration]
+pub(crate) fn set_registered_pack_version(
+    user_doc: &mut toml_edit::DocumentMut,
+    ws_doc: Option<&mut toml_edit::DocumentMut>,
+    bp_name: &str,
+    version: &str,
+) -> Result<RegistrationUpdate> {
+    let entry = user_doc
+        .get_mut("build-dependencies")
+        .and_then(|deps| deps.as_table_like_mut())
+        .and_then(|deps| deps.get_mut(bp_name))
+        .with_context(|| format!("{} is not in [build-dependencies]", bp_name))?;
+
+    let is_workspace_ref = entry
+        .get("workspace")
+        .and_then(|w| w.as_bool())
+        .unwrap_or(false);
+    if !is_workspace_ref {
+        return if set_dep_version(entry, version) {
+            Ok(RegistrationUpdate::Package)
+        } else {
+            bail!(
+                "{} in [build-dependencies] has no version to upgrade",
+                bp_name
+            )
+        };
+    }
+
+    let ws_entry = ws_doc
+        .and_then(|doc| doc.get_mut("workspace"))
+        .and_then(|ws| ws.as_table_like_mut())
+        .and_then(|ws| ws.get_mut("dependencies"))
+        .and_then(|deps| deps.as_table_like_mut())
+        .and_then(|deps| deps.get_mut(bp_name))
+        .with_context(|| format!("{} is not in [workspace.dependencies]", bp_name))?;
+    if set_dep_version(ws_entry, version) {
+        Ok(RegistrationUpdate::Workspace)
+    } else {
+        bail!(
+            "{} in [workspace.dependencies] has no version to upgrade",
+            bp_name
+        )
+    }
+}
+
+/// Replace the version of a dependency entry, whether it's a bare string,
+/// an inline table or a `[section.name]` table. Returns false if the entry
+/// has no version.
+fn set_dep_version(entry: &mut toml_edit::Item, version: &str) -> bool {
+    let value = match entry {
+        toml_edit::Item::Value(toml_edit::Value::String(_)) => entry.as_value_mut(),
+        _ => entry
+            .as_table_like_mut()
+            .and_then(|t| t.get_mut("version"))
+            .and_then(|v| v.as_value_mut()),
+    };
+    let Some(toml_edit::Value::String(current)) = value else {
+        return false;
+    };
+    let decor = current.decor().clone();
+    *current = toml_edit::Formatted::new(version.to_string());
+    *current.decor_mut() = decor;
+    true
+}
+
 /// Resolve the manifest path for a battery pack using `cargo metadata`.
 ///
 /// Works for any dependency source: path deps, registry deps, git deps.
@@ -598,6 +1049,30 @@ pub(crate) fn resolve_battery_pack_manifest(bp_name: &str) -> Result<PathBuf> {
     Ok(package.manifest_path.clone().into())
 }
 
+/// Resolve several battery packs with a single `cargo metadata` run.
+///
+/// Returns each pack found in the dependency graph mapped to its resolved
+/// version and manifest path. Packs that aren't in the graph are omitted.
+pub(crate) fn resolve_battery_pack_manifests(
+    bp_names: &[String],
+) -> Result<BTreeMap<String, (String, PathBuf)>> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .exec()
+        .context("Failed to run `cargo metadata`")?;
+
+    Ok(metadata
+        .packages
+        .iter()
+        .filter(|p| bp_names.iter().any(|name| *name == *p.name))
+        .map(|p| {
+            (
+                p.name.to_string(),
+                (p.version.to_string(), p.manifest_path.clone().into()),
+            )
+        })
+        .collect())
+}
+
 // ============================================================================
 // Version collection for status
 // ============================================================================
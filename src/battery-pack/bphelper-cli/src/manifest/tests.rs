@@ -1001,6 +1001,124 @@ serde = { version = "1.0", features = ["derive"] }
     );
 }
 
+// ---------------------------------------------------------------------------
+// cli.config.sync-policy — version bumps gated by the configured policy
+// ---------------------------------------------------------------------------
+
+// [verify cli.config.sync-policy]
+#[test]
+fn compatible_only_policy_skips_major_bump() {
+    let mut doc = parse_deps(
+        r#"
+[dependencies]
+clap = "3.2"
+serde = "1.0"
+"#,
+    );
+    let table = doc["dependencies"].as_table_mut().unwrap();
+    let policy = super::SyncPolicy::CompatibleOnly;
+    assert!(!super::sync_dep_in_table_with_policy(
+        table,
+        "clap",
+        &spec("4.5", &[]),
+        policy
+    ));
+    assert!(super::sync_dep_in_table_with_policy(
+        table,
+        "serde",
+        &spec("1.0.200", &[]),
+        policy
+    ));
+    assert_eq!(read_version(&doc, "clap"), "3.2");
+    assert_eq!(read_version(&doc, "serde"), "1.0.200");
+}
+
+// [verify cli.config.sync-policy]
+#[test]
+fn never_upgrade_policy_still_adds_features() {
+    let mut doc = parse_deps(
+        r#"
+[dependencies]
+serde = { version = "1.0", features = ["rc"] }
+"#,
+    );
+    let table = doc["dependencies"].as_table_mut().unwrap();
+    let changed = super::sync_dep_in_table_with_policy(
+        table,
+        "serde",
+        &spec("1.2", &["derive"]),
+        super::SyncPolicy::NeverUpgrade,
+    );
+    assert!(changed, "missing feature should still be added");
+    assert_eq!(read_version(&doc, "serde"), "1.0");
+    assert_eq!(read_features(&doc, "serde"), vec!["rc", "derive"]);
+}
+
+// [verify cli.config.sync-policy]
+#[test]
+fn compatible_version_follows_caret_rules() {
+    assert!(super::is_compatible_version("1.0", "1.9.3"));
+    assert!(!super::is_compatible_version("1.0", "2.0"));
+    assert!(super::is_compatible_version("0.11", "0.11.4"));
+    assert!(!super::is_compatible_version("0.11", "0.12"));
+    assert!(!super::is_compatible_version("0.0.1", "0.0.2"));
+    assert!(!super::is_compatible_version("git", "1.0"));
+}
+
+// ---------------------------------------------------------------------------
+// cli.pins — exact requirements and pinned metadata
+// ---------------------------------------------------------------------------
+
+// [verify cli.pins.exact]
+#[test]
+fn exact_requirements_are_never_upgraded() {
+    let mut doc = parse_deps(
+        r#"
+[dependencies]
+tokio = { version = "=1.38.0", features = ["rt"] }
+"#,
+    );
+    let table = doc["dependencies"].as_table_mut().unwrap();
+    super::sync_dep_in_table(table, "tokio", &spec("1.40", &["macros"]));
+    assert_eq!(read_version(&doc, "tokio"), "=1.38.0");
+    assert_eq!(read_features(&doc, "tokio"), vec!["rt", "macros"]);
+}
+
+// [verify cli.pins.metadata]
+#[test]
+fn rewriting_metadata_keeps_pins() {
+    let mut doc: toml_edit::DocumentMut = r#"[package]
+name = "app"
+
+[package.metadata.battery-pack.cli-battery-pack]
+features = ["default"]
+pinned = ["tokio"]
+"#
+    .parse()
+    .unwrap();
+    let features = BTreeSet::from(["default".to_string(), "progress".to_string()]);
+    super::write_bp_features_to_doc(
+        &mut doc,
+        &["package", "metadata"],
+        "cli-battery-pack",
+        &features,
+        None,
+    );
+    let content = doc.to_string();
+    assert_eq!(
+        super::read_pinned_from(
+            &super::MetadataLocation::Package,
+            &content,
+            "cli-battery-pack"
+        ),
+        BTreeSet::from(["tokio".to_string()])
+    );
+    assert!(
+        content.contains(r#"features = ["default", "progress"]"#),
+        "{content}"
+    );
+}
+
 // ---------------------------------------------------------------------------
 // manifest.sync.version-bump — must not downgrade
 // ---------------------------------------------------------------------------
@@ -1673,3 +1791,273 @@ anyhow = \"1.0.0\"
         "document should be byte-identical when nothing changed"
     );
 }
+
+// ============================================================================
+// set_registered_pack_version — rewriting the pack's own requirement
+// ============================================================================
+
+// [verify cli.upgrade.registration]
+#[test]
+fn registration_version_is_rewritten_in_place() {
+    let input = "\
+[build-dependencies]
+cli-battery-pack = \"0.4\" # pinned for now
+ci-battery-pack = { version = \"0.1\", default-features = false }
+
+[build-dependencies.error-battery-pack]
+version = \"0.2\"
+";
+    let mut doc = parse_doc(input);
+    for (name, version) in [
+        ("cli-battery-pack", "0.5.0"),
+        ("ci-battery-pack", "0.1.3"),
+        ("error-battery-pack", "0.3.0"),
+    ] {
+        let updated = super::set_registered_pack_version(&mut doc, None, name, version).unwrap();
+        assert_eq!(updated, super::RegistrationUpdate::Package);
+    }
+
+    assert_eq!(
+        doc.to_string(),
+        "\
+[build-dependencies]
+cli-battery-pack = \"0.5.0\" # pinned for now
+ci-battery-pack = { version = \"0.1.3\", default-features = false }
+
+[build-dependencies.error-battery-pack]
+version = \"0.3.0\"
+"
+    );
+}
+
+// [verify cli.upgrade.registration]
+#[test]
+fn registration_follows_workspace_refs() {
+    let mut user = parse_doc("[build-dependencies]\ncli-battery-pack.workspace = true\n");
+    let mut ws = parse_doc("[workspace.dependencies]\ncli-battery-pack = \"0.4\"\n");
+
+    let updated =
+        super::set_registered_pack_version(&mut user, Some(&mut ws), "cli-battery-pack", "0.5.0")
+            .unwrap();
+
+    assert_eq!(updated, super::RegistrationUpdate::Workspace);
+    assert_eq!(
+        ws.to_string(),
+        "[workspace.dependencies]\ncli-battery-pack = \"0.5.0\"\n"
+    );
+    assert_eq!(
+        user.to_string(),
+        "[build-dependencies]\ncli-battery-pack.workspace = true\n"
+    );
+}
+
+#[test]
+fn registration_without_version_is_an_error() {
+    let mut doc = parse_doc("[build-dependencies]\ncli-battery-pack = { path = \"../cli\" }\n");
+    let err = super::set_registered_pack_version(&mut doc, None, "cli-battery-pack", "0.5.0")
+        .unwrap_err();
+    assert!(err.to_string().contains("no version"), "{err}");
+
+    let err =
+        super::set_registered_pack_version(&mut doc, None, "ci-battery-pack", "0.5.0").unwrap_err();
+    assert!(
+        err.to_string().contains("not in [build-dependencies]"),
+        "{err}"
+    );
+}
+
+// [verify cli.sync.prune-features]
+#[test]
+fn removing_dep_features_keeps_array_formatting() {
+    let mut doc = parse_doc(
+        "[dependencies]\n\
+         anyhow = { version = \"1\", features = [\"backtrace\", \"std\"] }\n\
+         tokio = { version = \"1\", features = [\n    \"full\",\n    \"tracing\",\n] }\n",
+    );
+    let table = doc["dependencies"].as_table_mut().unwrap();
+    let stale: BTreeSet<String> = ["backtrace".to_string(), "full".to_string()].into();
+    assert!(super::remove_dep_features(table, "anyhow", &stale));
+    assert!(super::remove_dep_features(table, "tokio", &stale));
+    assert!(!super::remove_dep_features(table, "anyhow", &stale));
+    assert!(!super::remove_dep_features(table, "serde", &stale));
+    assert_eq!(
+        doc.to_string(),
+        "[dependencies]\n\
+         anyhow = { version = \"1\", features = [\"std\"] }\n\
+         tokio = { version = \"1\", features = [\n    \"tracing\",\n] }\n"
+    );
+}
+
+// [verify cli.sync.dep-kind]
+#[test]
+fn relocating_a_dep_moves_the_entry_as_written() {
+    let mut doc = parse_doc(
+        "[dependencies]\n\
+         serde = \"1\"\n\
+         insta = { version = \"1\", features = [\"yaml\"] } # snapshots\n\n\
+         [dependencies.tempfile]\n\
+         version = \"3\"\n\n\
+         [dev-dependencies]\n\
+         assert_cmd = \"2\"\n",
+    );
+    let moved = super::relocate_dep(&mut doc, "insta", &[DepKind::Dev]).unwrap();
+    assert_eq!(moved.to_string(), "[dependencies] → [dev-dependencies]");
+    assert!(super::relocate_dep(&mut doc, "tempfile", &[DepKind::Dev]).is_some());
+    assert_eq!(
+        doc.to_string(),
+        "[dependencies]\n\
+         serde = \"1\"\n\n\
+         [dev-dependencies]\n\
+         assert_cmd = \"2\"\n\
+         insta = { version = \"1\", features = [\"yaml\"] } # snapshots\n\n\
+         [dev-dependencies.tempfile]\n\
+         version = \"3\"\n"
+    );
+
+    // Already where it belongs, or not in the manifest at all.
+    assert!(super::relocate_dep(&mut doc, "insta", &[DepKind::Dev]).is_none());
+    assert!(super::relocate_dep(&mut doc, "clap", &[DepKind::Normal]).is_none());
+}
+
+// [verify cli.sync.dep-kind]
+#[test]
+fn relocating_a_workspace_ref_to_a_second_kind_copies_it() {
+    let mut doc = parse_doc("[dev-dependencies]\ncc = { workspace = true }\n");
+    let moved = super::relocate_dep(&mut doc, "cc", &[DepKind::Dev, DepKind::Build]).unwrap();
+    assert!(moved.from.is_empty());
+    assert_eq!(moved.to_string(), "also in [build-dependencies]");
+    assert_eq!(
+        doc.to_string(),
+        "[dev-dependencies]\ncc = { workspace = true }\n\n\
+         [build-dependencies]\ncc = { workspace = true }\n"
+    );
+
+    // Widening to a regular dependency drops the narrower entries.
+    let moved = super::relocate_dep(&mut doc, "cc", &[DepKind::Normal]).unwrap();
+    assert_eq!(moved.from, [DepKind::Dev, DepKind::Build]);
+    assert!(doc["dependencies"]["cc"].is_inline_table());
+    assert!(
+        !doc["dev-dependencies"]
+            .as_table()
+            .unwrap()
+            .contains_key("cc")
+    );
+    assert!(
+        !doc["build-dependencies"]
+            .as_table()
+            .unwrap()
+            .contains_key("cc")
+    );
+}
+
+// [verify cli.sync.dep-kind]
+#[test]
+fn relocating_drops_a_stale_copy_of_a_crate_already_in_place() {
+    let mut doc = parse_doc(
+        "[dependencies]\n\
+         insta.workspace = true\n\n\
+         [dev-dependencies]\n\
+         insta.workspace = true\n",
+    );
+    let moved = super::relocate_dep(&mut doc, "insta", &[DepKind::Dev]).unwrap();
+    assert_eq!(moved.to_string(), "removed from [dependencies]");
+    assert!(
+        !doc["dependencies"]
+            .as_table()
+            .unwrap()
+            .contains_key("insta")
+    );
+    assert_eq!(
+        doc["dev-dependencies"]["insta"]["workspace"].as_bool(),
+        Some(true)
+    );
+    assert!(super::relocate_dep(&mut doc, "insta", &[DepKind::Dev]).is_none());
+}
+
+// ============================================================================
+// Manifest transactions
+// ============================================================================
+
+fn leftover_temp_files(dir: &std::path::Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(".cargo-bp-"))
+        .collect()
+}
+
+// [verify manifest.toml.atomic]
+#[test]
+fn transaction_writes_every_staged_manifest() {
+    let tmp = tempfile::tempdir().unwrap();
+    let ws = tmp.path().join("Cargo.toml");
+    let member = tmp.path().join("app/Cargo.toml");
+    std::fs::create_dir_all(member.parent().unwrap()).unwrap();
+    std::fs::write(&ws, "[workspace]\n").unwrap();
+    std::fs::write(&member, "[package]\n").unwrap();
+
+    let mut txn = super::ManifestTransaction::new();
+    txn.stage(&ws, "[workspace]\nmembers = []\n");
+    txn.stage(&member, "stale");
+    txn.stage(&member, "[package]\nname = \"app\"\n");
+    txn.commit(|_| {}).unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&ws).unwrap(),
+        "[workspace]\nmembers = []\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&member).unwrap(),
+        "[package]\nname = \"app\"\n"
+    );
+    assert!(leftover_temp_files(tmp.path()).is_empty());
+}
+
+// [verify manifest.toml.atomic]
+#[test]
+fn transaction_restores_originals_when_a_write_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let ws = tmp.path().join("Cargo.toml");
+    let new_file = tmp.path().join("new.toml");
+    std::fs::write(&ws, "[workspace]\n").unwrap();
+    // A non-empty directory can't be replaced by a file.
+    let blocked = tmp.path().join("blocked");
+    std::fs::create_dir_all(blocked.join("inner")).unwrap();
+
+    let mut txn = super::ManifestTransaction::new();
+    txn.stage(&ws, "[workspace]\nmembers = [\"app\"]\n");
+    txn.stage(&new_file, "created");
+    txn.stage(&blocked, "[package]\n");
+    let err = txn.commit(|_| {}).unwrap_err();
+
+    assert!(format!("{err:#}").contains("blocked"), "{err:#}");
+    assert_eq!(std::fs::read_to_string(&ws).unwrap(), "[workspace]\n");
+    assert!(!new_file.exists());
+    assert!(blocked.join("inner").is_dir());
+    assert!(leftover_temp_files(tmp.path()).is_empty());
+}
+
+// [verify manifest.toml.atomic]
+#[test]
+fn transaction_removal_is_rolled_back_with_the_rest() {
+    let tmp = tempfile::tempdir().unwrap();
+    let build_rs = tmp.path().join("build.rs");
+    std::fs::write(&build_rs, "fn main() {}\n").unwrap();
+    let blocked = tmp.path().join("blocked");
+    std::fs::create_dir_all(blocked.join("inner")).unwrap();
+
+    let mut txn = super::ManifestTransaction::new();
+    txn.stage_removal(&build_rs);
+    txn.stage_removal(tmp.path().join("missing.rs"));
+    txn.stage(&blocked, "[package]\n");
+    txn.commit(|_| {}).unwrap_err();
+    assert_eq!(
+        std::fs::read_to_string(&build_rs).unwrap(),
+        "fn main() {}\n"
+    );
+
+    let mut txn = super::ManifestTransaction::new();
+    txn.stage_removal(&build_rs);
+    txn.commit(|_| {}).unwrap();
+    assert!(!build_rs.exists());
+}
@@ -0,0 +1,135 @@
+//! All-or-nothing writes across several manifests.
+//!
+//! `add`, `sync`, `rm` and friends usually edit the workspace `Cargo.toml`
+//! and the member's together. Writing them one after the other can leave the
+//! project half-updated if the second write fails, so commands stage every
+//! edited document in a [`ManifestTransaction`] and commit it once: each new
+//! file is written to a temp file beside its target first, then renamed over
+//! it, and if any rename fails the files already replaced get their original
+//! contents back. A transaction can also remove files, which `cargo bp undo`
+//! uses for files the undone command created.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Edited manifests waiting to be written together.
+#[derive(Debug, Default)]
+pub(crate) struct ManifestTransaction {
+    /// New content per path; `None` removes the file.
+    staged: Vec<(PathBuf, Option<String>)>,
+}
+
+impl ManifestTransaction {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stage `content` for `path`, replacing anything staged for it before.
+    pub(crate) fn stage(&mut self, path: impl Into<PathBuf>, content: impl Into<String>) {
+        self.stage_inner(path.into(), Some(content.into()));
+    }
+
+    /// Stage the removal of `path`, replacing anything staged for it before.
+    pub(crate) fn stage_removal(&mut self, path: impl Into<PathBuf>) {
+        self.stage_inner(path.into(), None);
+    }
+
+    fn stage_inner(&mut self, path: PathBuf, content: Option<String>) {
+        match self.staged.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, staged)) => *staged = content,
+            None => self.staged.push((path, content)),
+        }
+    }
+
+    /// Write every staged file, or none of them.
+    ///
+    /// Files whose content is unchanged are not rewritten; `before_write`
+    /// is called with each file that is, just before it is replaced (the
+    /// commands use it to snapshot for `cargo bp undo`). Temp files are
+    /// written next to their targets (so the rename stays on one
+    /// filesystem) and take over the original file's permissions.
+    // [impl manifest.toml.atomic]
+    pub(crate) fn commit(self, mut before_write: impl FnMut(&Path)) -> Result<()> {
+        let mut pending = Vec::new();
+        for (path, content) in self.staged {
+            let original = match std::fs::read_to_string(&path) {
+                Ok(original) if Some(&original) == content.as_ref() => continue,
+                Ok(original) => Some(original),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    if content.is_none() {
+                        continue;
+                    }
+                    None
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+                }
+            };
+            // Dropping the temp files on an early return removes them.
+            let temp = content
+                .map(|content| write_temp(&path, &content))
+                .transpose()?;
+            pending.push((path, original, temp));
+        }
+
+        let mut replaced: Vec<(PathBuf, Option<String>)> = Vec::new();
+        for (path, original, temp) in pending {
+            before_write(&path);
+            let result = match temp {
+                Some(temp) => temp.persist(&path).map(drop).map_err(|e| e.error),
+                None => std::fs::remove_file(&path),
+            };
+            if let Err(e) = result {
+                let err =
+                    anyhow::Error::new(e).context(format!("Failed to write {}", path.display()));
+                return Err(rollback(replaced, err));
+            }
+            replaced.push((path, original));
+        }
+        Ok(())
+    }
+}
+
+/// Write `content` to a temp file in `path`'s directory.
+fn write_temp(path: &Path, content: &str) -> Result<tempfile::NamedTempFile> {
+    use std::io::Write;
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut temp = tempfile::Builder::new()
+        .prefix(".cargo-bp-")
+        .suffix(".tmp")
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create a temp file in {}", dir.display()))?;
+    temp.write_all(content.as_bytes())
+        .and_then(|()| temp.as_file().sync_all())
+        .with_context(|| format!("Failed to write {}", temp.path().display()))?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let _ = std::fs::set_permissions(temp.path(), metadata.permissions());
+    }
+    Ok(temp)
+}
+
+/// Put back the files a failed commit already replaced. Returns `err`,
+/// noting any file that couldn't be restored.
+fn rollback(replaced: Vec<(PathBuf, Option<String>)>, err: anyhow::Error) -> anyhow::Error {
+    let mut err = err;
+    for (path, original) in replaced.into_iter().rev() {
+        let restored = match &original {
+            // A removed file is put back just like a rewritten one.
+            Some(original) => write_temp(&path, original)
+                .and_then(|temp| temp.persist(&path).map_err(|e| e.error.into()))
+                .map(drop),
+            None => std::fs::remove_file(&path).map_err(Into::into),
+        };
+        if let Err(e) = restored {
+            err = err.context(format!(
+                "could not restore {} ({e:#}); it has the new contents",
+                path.display()
+            ));
+        }
+    }
+    err
+}
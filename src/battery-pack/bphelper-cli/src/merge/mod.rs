@@ -168,8 +168,13 @@ fn write_new_file(dest: &Path, content: &str) -> Result<()> {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("failed to create {}", parent.display()))?;
     }
-    std::fs::write(dest, content).with_context(|| format!("failed to write {}", dest.display()))?;
-    Ok(())
+    write_file(dest, content)
+}
+
+/// Write `dest`, snapshotting it first for `cargo bp undo`.
+fn write_file(dest: &Path, content: &str) -> Result<()> {
+    crate::history::before_write(dest);
+    std::fs::write(dest, content).with_context(|| format!("failed to write {}", dest.display()))
 }
 
 /// Shared context for conflict resolution (plain and structured).
@@ -187,8 +192,7 @@ fn resolve_plain_conflict(
     batch: &mut BatchDecision,
 ) -> Result<FileResult> {
     if ctx.opts.overwrite || matches!(batch, BatchDecision::AcceptAll) {
-        std::fs::write(ctx.dest, ctx.new_content)
-            .with_context(|| format!("failed to write {}", ctx.dest.display()))?;
+        write_file(ctx.dest, ctx.new_content)?;
         return Ok(FileResult::Overwritten(ctx.rel_path.to_string()));
     }
 
@@ -205,8 +209,7 @@ fn resolve_plain_conflict(
         match read_single_key()? {
             's' => return Ok(FileResult::Skipped(ctx.rel_path.to_string())),
             'o' => {
-                std::fs::write(ctx.dest, ctx.new_content)
-                    .with_context(|| format!("failed to write {}", ctx.dest.display()))?;
+                write_file(ctx.dest, ctx.new_content)?;
                 return Ok(FileResult::Overwritten(ctx.rel_path.to_string()));
             }
             'd' => {
@@ -219,8 +222,7 @@ fn resolve_plain_conflict(
             }
             'O' => {
                 *batch = BatchDecision::AcceptAll;
-                std::fs::write(ctx.dest, ctx.new_content)
-                    .with_context(|| format!("failed to write {}", ctx.dest.display()))?;
+                write_file(ctx.dest, ctx.new_content)?;
                 return Ok(FileResult::Overwritten(ctx.rel_path.to_string()));
             }
             _ => {} // Unrecognized key, re-prompt.
@@ -249,8 +251,7 @@ fn resolve_structured_merge(
         if !diff.is_empty() {
             eprintln!("merging {}:\n{}", ctx.rel_path, diff);
         }
-        std::fs::write(ctx.dest, ctx.new_content)
-            .with_context(|| format!("failed to write {}", ctx.dest.display()))?;
+        write_file(ctx.dest, ctx.new_content)?;
         return Ok(FileResult::Merged(ctx.rel_path.to_string()));
     }
 
@@ -271,8 +272,7 @@ fn resolve_structured_merge(
         );
         match read_single_key()? {
             'a' => {
-                std::fs::write(ctx.dest, &content_to_write)
-                    .with_context(|| format!("failed to write {}", ctx.dest.display()))?;
+                write_file(ctx.dest, &content_to_write)?;
                 return Ok(FileResult::Merged(ctx.rel_path.to_string()));
             }
             's' => return Ok(FileResult::Skipped(ctx.rel_path.to_string())),
@@ -287,8 +287,7 @@ fn resolve_structured_merge(
             }
             'A' => {
                 *batch = BatchDecision::AcceptAll;
-                std::fs::write(ctx.dest, &content_to_write)
-                    .with_context(|| format!("failed to write {}", ctx.dest.display()))?;
+                write_file(ctx.dest, &content_to_write)?;
                 return Ok(FileResult::Merged(ctx.rel_path.to_string()));
             }
             'S' => {
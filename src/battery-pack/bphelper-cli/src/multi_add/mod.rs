@@ -0,0 +1,126 @@
+//! Support for adding several battery packs in one `cargo bp add`.
+//!
+//! The positional arguments are a battery pack followed by crates from it,
+//! as before, optionally followed by more packs with their own crates:
+//!
+//! ```text
+//! cargo bp add cli clap logging error:backtrace -F tracing/json
+//! ```
+//!
+//! A bare argument is a crate of the pack before it when that pack curates
+//! it, and otherwise starts a new pack if it names one; anything else stays
+//! with the pack before it, to be reported as an unknown crate. Only the
+//! packs themselves are looked up, one spec each. `pack:` or
+//! `pack:feature,...` always starts a new pack with those features, and
+//! `-F pack/feature` enables a feature on a pack, adding the pack if it
+//! isn't named already. A plain `-F feature` applies to the first pack.
+
+#[cfg(test)]
+mod tests;
+
+use crate::registry::resolve_crate_name;
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One battery pack requested on the `cargo bp add` command line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PackRequest {
+    pub name: String,
+    pub features: Vec<String>,
+    pub crates: Vec<String>,
+}
+
+/// Split `cargo bp add`'s positional arguments and `-F` values into one
+/// request per battery pack. Pack names go through `resolve_alias`;
+/// `pack_crates(name)` gives the crates a pack curates, or `None` if there
+/// is no such pack, and is asked at most once per name.
+// [impl cli.add.multiple]
+// [impl cli.add.pack-features]
+pub(crate) fn group(
+    first: &str,
+    args: &[String],
+    features: &[String],
+    resolve_alias: impl Fn(&str) -> String,
+    mut pack_crates: impl FnMut(&str) -> Option<BTreeSet<String>>,
+) -> Result<Vec<PackRequest>> {
+    let mut known: BTreeMap<String, Option<BTreeSet<String>>> = BTreeMap::new();
+    let mut crates_of = |name: &str| {
+        known
+            .entry(name.to_string())
+            .or_insert_with(|| pack_crates(name))
+            .clone()
+    };
+    let request = |arg: &str| {
+        parse_request(arg).map(|mut r| {
+            r.name = resolve_alias(&r.name);
+            r
+        })
+    };
+
+    let mut requests = vec![request(first)?];
+    for arg in args {
+        let current = &requests[requests.len() - 1].name;
+        // A pack we can't look up keeps its arguments, as crates.
+        let curated = crates_of(current).is_none_or(|crates| crates.contains(arg));
+        if arg.contains(':') || (!curated && crates_of(&resolve_alias(arg)).is_some()) {
+            requests.push(request(arg)?);
+        } else {
+            requests
+                .last_mut()
+                .expect("at least one pack")
+                .crates
+                .push(arg.clone());
+        }
+    }
+
+    for feature in features {
+        let (index, feature) = match feature.split_once('/') {
+            Some((pack, feature)) => {
+                let wanted = resolve_crate_name(&resolve_alias(pack));
+                match requests
+                    .iter()
+                    .position(|r| resolve_crate_name(&r.name) == wanted)
+                {
+                    Some(index) => (index, feature),
+                    None => {
+                        requests.push(request(pack)?);
+                        (requests.len() - 1, feature)
+                    }
+                }
+            }
+            None => (0, feature.as_str()),
+        };
+        requests[index].features.push(feature.to_string());
+    }
+
+    let mut seen = std::collections::BTreeSet::new();
+    for r in &requests {
+        if !seen.insert(resolve_crate_name(&r.name)) {
+            bail!("battery pack '{}' is given more than once", r.name);
+        }
+    }
+    Ok(requests)
+}
+
+/// Parse `pack` or `pack:feature,feature`.
+fn parse_request(arg: &str) -> Result<PackRequest> {
+    let (name, features) = match arg.split_once(':') {
+        Some((name, features)) => (
+            name,
+            features
+                .split(',')
+                .filter(|f| !f.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ),
+        None => (arg, Vec::new()),
+    };
+    if name.is_empty() {
+        bail!("'{}' does not name a battery pack", arg);
+    }
+    Ok(PackRequest {
+        name: name.to_string(),
+        features,
+        crates: Vec::new(),
+    })
+}
@@ -0,0 +1,127 @@
+//! Tests for grouping `cargo bp add` arguments by battery pack.
+
+use super::*;
+use std::cell::RefCell;
+
+fn args(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// `cli` offers clap and dialoguer; `logging` and `error` are packs too.
+fn pack_crates(name: &str) -> Option<BTreeSet<String>> {
+    match name {
+        "cli" => Some(["clap", "dialoguer"].map(String::from).into()),
+        "logging" | "error" | "error-battery-pack" => Some(BTreeSet::new()),
+        _ => None,
+    }
+}
+
+fn no_alias(name: &str) -> String {
+    name.to_string()
+}
+
+fn group_args(first: &str, positional: &[&str], features: &[&str]) -> Result<Vec<PackRequest>> {
+    group(
+        first,
+        &args(positional),
+        &args(features),
+        no_alias,
+        pack_crates,
+    )
+}
+
+fn req(name: &str, features: &[&str], crates: &[&str]) -> PackRequest {
+    PackRequest {
+        name: name.into(),
+        features: args(features),
+        crates: args(crates),
+    }
+}
+
+// [verify cli.add.multiple]
+#[test]
+fn bare_pack_names_start_a_new_pack() {
+    let requests = group_args("cli", &["logging", "error"], &[]).unwrap();
+    assert_eq!(
+        requests,
+        [
+            req("cli", &[], &[]),
+            req("logging", &[], &[]),
+            req("error", &[], &[])
+        ]
+    );
+
+    // Crates of the pack before them stay with it; unknown names too, so
+    // they're reported as unknown crates.
+    let requests = group_args("cli", &["clap", "nope", "logging"], &[]).unwrap();
+    assert_eq!(
+        requests,
+        [req("cli", &[], &["clap", "nope"]), req("logging", &[], &[])]
+    );
+}
+
+// [verify cli.add.multiple]
+#[test]
+fn each_pack_is_looked_up_once() {
+    let asked = RefCell::new(Vec::new());
+    let requests = group(
+        "cli",
+        &args(&["clap", "dialoguer", "logging", "nope", "nope"]),
+        &[],
+        no_alias,
+        |name| {
+            asked.borrow_mut().push(name.to_string());
+            pack_crates(name)
+        },
+    )
+    .unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(*asked.borrow(), ["cli", "logging", "nope"]);
+}
+
+// [verify cli.add.pack-features]
+#[test]
+fn features_go_to_the_named_pack() {
+    let requests = group_args(
+        "cli:indicators",
+        &["error:backtrace,std", "logging"],
+        &["fancy", "logging/json", "error-battery-pack/anyhow"],
+    )
+    .unwrap();
+    assert_eq!(
+        requests,
+        [
+            req("cli", &["indicators", "fancy"], &[]),
+            req("error", &["backtrace", "std", "anyhow"], &[]),
+            req("logging", &["json"], &[]),
+        ]
+    );
+}
+
+// [verify cli.add.pack-features]
+#[test]
+fn features_for_another_pack_add_it() {
+    let requests = group_args("cli", &[], &["logging/json", "logging/otel"]).unwrap();
+    assert_eq!(
+        requests,
+        [req("cli", &[], &[]), req("logging", &["json", "otel"], &[])]
+    );
+}
+
+// [verify cli.add.pack-features]
+#[test]
+fn repeated_and_empty_packs_are_rejected() {
+    let err = group_args("cli", &["cli-battery-pack:fancy"], &[]).unwrap_err();
+    assert!(err.to_string().contains("more than once"), "{err}");
+
+    // Aliases are resolved before looking for repeats.
+    let alias = |name: &str| match name {
+        "my-cli" => "cli".to_string(),
+        _ => name.to_string(),
+    };
+    let err = group("cli", &args(&["my-cli:"]), &[], alias, pack_crates).unwrap_err();
+    assert!(err.to_string().contains("more than once"), "{err}");
+
+    assert!(group_args(":json", &[], &[]).is_err());
+    assert!(group_args("cli", &[], &["/json"]).is_err());
+}
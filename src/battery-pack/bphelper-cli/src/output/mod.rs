@@ -0,0 +1,626 @@
+//! Machine-readable output for `--message-format json`.
+//!
+//! Each command that supports JSON prints exactly one object on stdout:
+//! `{"schema_version": N, "kind": "<command>", ...}`. The types here are the
+//! schema; they are deliberately separate from the registry and manifest
+//! types so internal refactors don't silently change what dashboards see.
+//! The schema is documented in `md/json-output.md`. Bump [`SCHEMA_VERSION`]
+//! whenever a field is removed, renamed or changes meaning; adding fields is
+//! not a breaking change.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::Result;
+use bphelper_manifest::CrateSpec;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::drift::ProjectDeps;
+use crate::registry::{BatteryPackDetail, BatteryPackSummary};
+
+/// Version of the JSON schema emitted by every command.
+// [impl cli.json.versioned]
+pub(crate) const SCHEMA_VERSION: u32 = 2;
+
+/// Output format for commands that support machine-readable output.
+// [impl cli.json.flag]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum MessageFormat {
+    /// Human-readable text.
+    #[default]
+    Human,
+    /// A single JSON object on stdout.
+    Json,
+}
+
+impl MessageFormat {
+    pub(crate) fn is_json(self) -> bool {
+        self == MessageFormat::Json
+    }
+}
+
+/// The envelope every JSON document shares. Flattening (rather than
+/// building a `serde_json::Map`) keeps fields in declaration order.
+#[derive(Serialize)]
+struct Document<'a, T> {
+    schema_version: u32,
+    kind: &'a str,
+    #[serde(flatten)]
+    data: &'a T,
+}
+
+/// Render `data` as a JSON document of the given `kind`. `data` must
+/// serialize as an object.
+pub(crate) fn render<T: Serialize>(kind: &str, data: &T) -> Result<String> {
+    let document = Document {
+        schema_version: SCHEMA_VERSION,
+        kind,
+        data,
+    };
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| anyhow::anyhow!("failed to render JSON output for '{}': {}", kind, e))
+}
+
+/// Print `data` as a JSON document of the given `kind` on stdout.
+pub(crate) fn print<T: Serialize>(kind: &str, data: &T) -> Result<()> {
+    println!("{}", render(kind, data)?);
+    Ok(())
+}
+
+// ============================================================================
+// list
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ListOutput {
+    pub packs: Vec<PackSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PackSummary {
+    pub name: String,
+    pub short_name: String,
+    pub version: String,
+    pub description: String,
+    pub downloads: u64,
+    pub recent_downloads: Option<u64>,
+    pub updated_at: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub categories: Option<Vec<String>>,
+}
+
+impl From<&BatteryPackSummary> for PackSummary {
+    fn from(s: &BatteryPackSummary) -> Self {
+        Self {
+            name: s.name.clone(),
+            short_name: s.short_name.clone(),
+            version: s.version.clone(),
+            description: s.description.clone(),
+            downloads: s.downloads,
+            recent_downloads: s.recent_downloads,
+            updated_at: s.updated_at.clone(),
+            keywords: s.keywords.clone(),
+            categories: s.categories.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// show
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ShowOutput {
+    pub name: String,
+    pub short_name: String,
+    pub version: String,
+    pub description: String,
+    pub repository: Option<String>,
+    pub commit: Option<String>,
+    pub owners: Vec<Owner>,
+    pub crates: Vec<ShowCrate>,
+    pub features: Vec<ShowFeature>,
+    pub extends: Vec<String>,
+    pub templates: Vec<Template>,
+    pub examples: Vec<Example>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Owner {
+    pub login: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ShowCrate {
+    pub name: String,
+    /// The crate is managed by this pack in the current project.
+    pub installed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ShowFeature {
+    pub name: String,
+    pub crates: Vec<String>,
+    /// The feature is enabled in the current project.
+    pub active: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Template {
+    pub name: String,
+    pub path: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Example {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl ShowOutput {
+    /// Describe `detail`, marking what the current project has installed.
+    pub(crate) fn new(
+        detail: &BatteryPackDetail,
+        managed_deps: &BTreeSet<String>,
+        active_features: &BTreeSet<String>,
+    ) -> Self {
+        Self {
+            name: detail.name.clone(),
+            short_name: detail.short_name.clone(),
+            version: detail.version.clone(),
+            description: detail.description.clone(),
+            repository: detail.repository.clone(),
+            commit: detail.commit.clone(),
+            owners: detail
+                .owners
+                .iter()
+                .map(|o| Owner {
+                    login: o.login.clone(),
+                    name: o.name.clone(),
+                })
+                .collect(),
+            crates: detail
+                .crates
+                .iter()
+                .map(|name| ShowCrate {
+                    name: name.clone(),
+                    installed: managed_deps.contains(name),
+                })
+                .collect(),
+            features: detail
+                .features
+                .iter()
+                .map(|(name, crates)| ShowFeature {
+                    name: name.clone(),
+                    crates: crates.clone(),
+                    active: active_features.contains(name),
+                })
+                .collect(),
+            extends: detail.extends.clone(),
+            templates: detail
+                .templates
+                .iter()
+                .map(|t| Template {
+                    name: t.name.clone(),
+                    path: t.path.clone(),
+                    description: t.description.clone(),
+                })
+                .collect(),
+            examples: detail
+                .examples
+                .iter()
+                .map(|e| Example {
+                    name: e.name.clone(),
+                    description: e.description.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// ============================================================================
+// status / check
+// ============================================================================
+
+/// How a project's dependencies differ from what a battery pack expects.
+// [impl cli.json.drift]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Drift {
+    NotRegistered,
+    MissingCrate,
+    WrongSection,
+    Outdated,
+    MissingFeature,
+    NoLongerCurated,
+}
+
+impl From<crate::drift::DriftKind> for Drift {
+    fn from(kind: crate::drift::DriftKind) -> Self {
+        use crate::drift::DriftKind;
+        match kind {
+            DriftKind::NotRegistered => Drift::NotRegistered,
+            DriftKind::MissingCrate => Drift::MissingCrate,
+            DriftKind::WrongSection => Drift::WrongSection,
+            DriftKind::Outdated => Drift::Outdated,
+            DriftKind::MissingFeature => Drift::MissingFeature,
+            DriftKind::NoLongerCurated => Drift::NoLongerCurated,
+        }
+    }
+}
+
+/// The worst finding of a run; `check` exits with a matching status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Outcome {
+    Clean,
+    Warnings,
+    Errors,
+}
+
+impl From<crate::drift::Outcome> for Outcome {
+    fn from(outcome: crate::drift::Outcome) -> Self {
+        match outcome {
+            crate::drift::Outcome::Clean => Outcome::Clean,
+            crate::drift::Outcome::Warnings => Outcome::Warnings,
+            crate::drift::Outcome::Errors => Outcome::Errors,
+        }
+    }
+}
+
+/// One difference between a battery pack and the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Finding {
+    pub pack: String,
+    pub drift: Drift,
+    pub severity: Severity,
+    /// `None` for pack-level findings such as `not-registered`.
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub message: String,
+}
+
+impl From<&crate::drift::Finding> for Finding {
+    fn from(f: &crate::drift::Finding) -> Self {
+        Self {
+            pack: f.pack.clone(),
+            drift: f.kind.into(),
+            severity: match f.severity {
+                crate::drift::Severity::Warning => Severity::Warning,
+                crate::drift::Severity::Error => Severity::Error,
+            },
+            crate_name: f.crate_name.clone(),
+            expected: f.expected.clone(),
+            actual: f.actual.clone(),
+            message: f.message.clone(),
+        }
+    }
+}
+
+/// Convert analysis findings to their output form.
+pub(crate) fn findings(findings: &[crate::drift::Finding]) -> Vec<Finding> {
+    findings.iter().map(Into::into).collect()
+}
+
+/// Why a crate's version is exempt from the pack's recommendation.
+// [impl cli.pins.report]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum HoldReason {
+    Pinned,
+    Exact,
+    Policy,
+}
+
+/// A crate whose version sync leaves alone. Holds are not drift and don't
+/// affect `status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Hold {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub reason: HoldReason,
+    /// The sync policy that holds the crate back, for `policy` holds.
+    pub policy: Option<&'static str>,
+    pub actual: String,
+    pub recommended: String,
+    /// Whether the recommendation is newer than `actual`.
+    pub behind: bool,
+    pub message: String,
+}
+
+impl From<&crate::drift::Hold> for Hold {
+    fn from(h: &crate::drift::Hold) -> Self {
+        use crate::drift::HoldReason as Reason;
+        let (reason, policy) = match h.reason {
+            Reason::Pinned => (HoldReason::Pinned, None),
+            Reason::Exact => (HoldReason::Exact, None),
+            Reason::Policy(policy) => (HoldReason::Policy, Some(policy.as_str())),
+        };
+        Self {
+            crate_name: h.crate_name.clone(),
+            reason,
+            policy,
+            actual: h.actual.clone(),
+            recommended: h.recommended.clone(),
+            behind: h.is_behind(),
+            message: h.message(),
+        }
+    }
+}
+
+/// Convert analysis holds to their output form.
+pub(crate) fn holds(holds: &[crate::drift::Hold]) -> Vec<Hold> {
+    holds.iter().map(Into::into).collect()
+}
+
+/// One curated crate: what the pack expects and what the project has.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct CrateStatus {
+    pub name: String,
+    pub expected: String,
+    /// `None` when the project doesn't depend on the crate, or its entry
+    /// carries no version (e.g. a path dependency).
+    pub actual: Option<String>,
+    /// The most significant finding for this crate, if any.
+    pub drift: Option<Drift>,
+}
+
+/// Summarize each `expected` crate against the project, taking its drift
+/// from the analysis `findings`.
+pub(crate) fn crate_statuses(
+    expected: &BTreeMap<String, CrateSpec>,
+    project: &ProjectDeps,
+    findings: &[crate::drift::Finding],
+) -> Vec<CrateStatus> {
+    let worst = crate::drift::worst_by_crate(findings);
+    expected
+        .iter()
+        .filter(|(_, spec)| !spec.version.is_empty())
+        .map(|(name, spec)| CrateStatus {
+            name: name.clone(),
+            expected: spec.version.clone(),
+            actual: project.version_of(name, spec.dep_kind).map(String::from),
+            drift: worst.get(name.as_str()).map(|&kind| kind.into()),
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct StatusOutput {
+    /// `clean`, `warnings` or `errors`: the worst finding overall.
+    pub status: Outcome,
+    pub packs: Vec<PackStatus>,
+    /// `not-registered` findings for packs with metadata but no
+    /// `[build-dependencies]` entry.
+    pub unregistered: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PackStatus {
+    pub name: String,
+    pub short_name: String,
+    /// The version requirement from `[build-dependencies]`.
+    pub registered_version: String,
+    /// The version of the spec that was loaded.
+    pub installed_version: String,
+    pub active_features: Vec<String>,
+    /// Registry release information; `None` for local sources and `--path`.
+    pub release: Option<PackRelease>,
+    pub crates: Vec<CrateStatus>,
+    pub findings: Vec<Finding>,
+    /// Pinned crates, and crates the sync policy keeps behind.
+    pub held: Vec<Hold>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PackRelease {
+    pub latest_version: Option<String>,
+    pub yanked: bool,
+    pub outdated: bool,
+    /// Crate changes from the installed to the latest release.
+    pub changes: Vec<CrateChange>,
+    /// Set when the registry could not be queried.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "kebab-case")]
+pub(crate) enum CrateChange {
+    Added {
+        name: String,
+        version: String,
+    },
+    Removed {
+        name: String,
+        version: String,
+    },
+    VersionChanged {
+        name: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl From<&bphelper_manifest::CrateChange> for CrateChange {
+    fn from(change: &bphelper_manifest::CrateChange) -> Self {
+        match change.clone() {
+            bphelper_manifest::CrateChange::Added { name, version } => {
+                CrateChange::Added { name, version }
+            }
+            bphelper_manifest::CrateChange::Removed { name, version } => {
+                CrateChange::Removed { name, version }
+            }
+            bphelper_manifest::CrateChange::VersionChanged { name, from, to } => {
+                CrateChange::VersionChanged { name, from, to }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct CheckOutput {
+    /// True when every pack loaded and there are no findings.
+    pub ok: bool,
+    /// `clean`, `warnings` or `errors`; matches the exit status.
+    pub status: Outcome,
+    pub packs: Vec<PackCheck>,
+    /// `not-registered` findings, as for `status`.
+    pub unregistered: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PackCheck {
+    pub name: String,
+    pub version: Option<String>,
+    /// Set when the pack's spec could not be loaded.
+    pub error: Option<String>,
+    pub crates: Vec<CrateStatus>,
+    pub findings: Vec<Finding>,
+    /// As for `status`.
+    pub held: Vec<Hold>,
+}
+
+// ============================================================================
+// workspace runs
+// ============================================================================
+
+/// `status` or `check` with `--workspace` or `-p`: one document for every
+/// selected member, each carrying the fields of the single-project document.
+#[derive(Debug, Serialize)]
+pub(crate) struct WorkspaceOutput<T> {
+    /// True when every member is clean.
+    pub ok: bool,
+    /// The worst status across members; matches `check`'s exit status.
+    pub status: Outcome,
+    pub members: Vec<MemberOutput<T>>,
+    /// Selected members with no battery packs installed.
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct MemberOutput<T> {
+    pub package: String,
+    pub manifest_path: String,
+    #[serde(flatten)]
+    pub report: T,
+}
+
+// ============================================================================
+// tree
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TreeOutput {
+    pub packs: Vec<TreePack>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TreePack {
+    pub name: String,
+    pub version: String,
+    pub features: Vec<TreeFeature>,
+    pub no_longer_curated: Vec<TreeCrate>,
+    /// Set when a nested pack's spec could not be loaded.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TreeFeature {
+    pub name: String,
+    pub crates: Vec<TreeCrate>,
+    /// Nested battery packs the feature enables.
+    pub packs: Vec<TreePack>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TreeCrate {
+    pub name: String,
+    /// The dependency section the pack puts the crate in.
+    pub section: Option<&'static str>,
+    pub recommended: Option<String>,
+    /// The version in the project's manifest.
+    pub actual: Option<String>,
+    /// The most significant finding for this crate, if any.
+    pub drift: Option<Drift>,
+    pub findings: Vec<Finding>,
+}
+
+impl From<&crate::tree::PackNode> for TreePack {
+    fn from(pack: &crate::tree::PackNode) -> Self {
+        Self {
+            name: pack.name.clone(),
+            version: pack.version.clone(),
+            features: pack
+                .features
+                .iter()
+                .map(|feature| TreeFeature {
+                    name: feature.name.clone(),
+                    crates: feature.crates.iter().map(Into::into).collect(),
+                    packs: feature.packs.iter().map(Into::into).collect(),
+                })
+                .collect(),
+            no_longer_curated: pack.no_longer_curated.iter().map(Into::into).collect(),
+            error: pack.error.clone(),
+        }
+    }
+}
+
+impl From<&crate::tree::CrateNode> for TreeCrate {
+    fn from(krate: &crate::tree::CrateNode) -> Self {
+        Self {
+            name: krate.name.clone(),
+            section: krate.dep_kind.map(crate::manifest::dep_kind_section),
+            recommended: krate.recommended.clone(),
+            actual: krate.declared.clone(),
+            drift: krate.findings.iter().map(|f| f.kind).min().map(Into::into),
+            findings: findings(&krate.findings),
+        }
+    }
+}
+
+// ============================================================================
+// validate
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ValidateOutput {
+    pub name: String,
+    pub valid: bool,
+    pub errors: usize,
+    pub warnings: usize,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Set when a template failed to generate, build or test.
+    pub template_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Diagnostic {
+    pub severity: Severity,
+    pub rule: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+impl From<&bphelper_manifest::Diagnostic> for Diagnostic {
+    fn from(d: &bphelper_manifest::Diagnostic) -> Self {
+        Self {
+            severity: match d.severity {
+                bphelper_manifest::Severity::Error => Severity::Error,
+                bphelper_manifest::Severity::Warning => Severity::Warning,
+            },
+            rule: d.rule.to_string(),
+            message: d.message.clone(),
+        }
+    }
+}
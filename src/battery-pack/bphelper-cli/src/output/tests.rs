@@ -0,0 +1,262 @@
+//! Tests for the JSON output schema.
+
+use super::*;
+use snapbox::{assert_data_eq, str};
+
+// [verify cli.json.versioned]
+#[test]
+fn render_wraps_data_in_versioned_envelope() {
+    let output = ListOutput { packs: Vec::new() };
+    let json: serde_json::Value = serde_json::from_str(&render("list", &output).unwrap()).unwrap();
+
+    assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["kind"], "list");
+    assert_eq!(json["packs"], serde_json::json!([]));
+}
+
+#[test]
+fn render_rejects_non_objects() {
+    assert!(render("list", &vec![1, 2, 3]).is_err());
+}
+
+// [verify cli.json.drift]
+#[test]
+fn crate_statuses_take_the_worst_finding() {
+    let spec = bphelper_manifest::parse_battery_pack(
+        r#"
+[package]
+name = "demo-battery-pack"
+version = "0.1.0"
+
+[dependencies]
+anyhow = { version = "1.0.80", optional = true }
+clap = { version = "4.5", features = ["derive"], optional = true }
+local = { version = "0.1", optional = true }
+thiserror = { version = "2", optional = true }
+
+[features]
+default = ["anyhow", "clap", "local", "thiserror"]
+"#,
+    )
+    .unwrap();
+    let user: toml::Value = toml::from_str(
+        r#"
+[dependencies]
+anyhow = "1.0.70"
+clap = { version = "4.5.1", features = ["derive"] }
+local = { path = "../local" }
+"#,
+    )
+    .unwrap();
+    let project = ProjectDeps::from_manifests(&user, None);
+    let active: BTreeSet<String> = ["default".to_string()].into();
+    let expected = spec.resolve_for_features(&active);
+    let findings = crate::drift::analyze(
+        &project,
+        &crate::drift::PackState {
+            name: "demo-battery-pack",
+            spec: &spec,
+            active_features: &active,
+            managed_deps: None,
+            pinned: &BTreeSet::new(),
+        },
+        crate::manifest::SyncPolicy::default(),
+    );
+
+    let statuses = crate_statuses(&expected, &project, &findings);
+
+    let drift: Vec<(&str, Option<&str>, Option<Drift>)> = statuses
+        .iter()
+        .map(|c| (c.name.as_str(), c.actual.as_deref(), c.drift))
+        .collect();
+    assert_eq!(
+        drift,
+        [
+            ("anyhow", Some("1.0.70"), Some(Drift::Outdated)),
+            ("clap", Some("4.5.1"), None),
+            // Present without a version (e.g. a path dependency): not drift.
+            ("local", None, None),
+            ("thiserror", None, Some(Drift::MissingCrate)),
+        ]
+    );
+}
+
+// [verify cli.json.status]
+// [verify cli.pins.report]
+#[test]
+fn status_output_schema() {
+    let output = StatusOutput {
+        status: Outcome::Errors,
+        packs: vec![PackStatus {
+            name: "cli-battery-pack".into(),
+            short_name: "cli".into(),
+            registered_version: "0.4".into(),
+            installed_version: "0.4.1".into(),
+            active_features: vec!["default".into()],
+            release: Some(PackRelease {
+                latest_version: Some("0.5.0".into()),
+                yanked: false,
+                outdated: true,
+                changes: vec![
+                    CrateChange::Added {
+                        name: "indicatif".into(),
+                        version: "0.17".into(),
+                    },
+                    CrateChange::VersionChanged {
+                        name: "clap".into(),
+                        from: "4.4".into(),
+                        to: "4.5".into(),
+                    },
+                ],
+                error: None,
+            }),
+            crates: vec![CrateStatus {
+                name: "clap".into(),
+                expected: "4.4".into(),
+                actual: None,
+                drift: Some(Drift::MissingCrate),
+            }],
+            findings: vec![Finding {
+                pack: "cli-battery-pack".into(),
+                drift: Drift::MissingCrate,
+                severity: Severity::Error,
+                crate_name: Some("clap".into()),
+                expected: Some("4.4".into()),
+                actual: None,
+                message: "clap: missing from [dependencies]".into(),
+            }],
+            held: vec![Hold {
+                crate_name: "tokio".into(),
+                reason: HoldReason::Pinned,
+                policy: None,
+                actual: "=1.38.0".into(),
+                recommended: "1.40".into(),
+                behind: true,
+                message: "tokio: pinned at =1.38.0 (1.40 recommended)".into(),
+            }],
+        }],
+        unregistered: Vec::new(),
+    };
+
+    assert_data_eq!(
+        render("status", &output).unwrap(),
+        str![[r#"
+{
+  "schema_version": 2,
+  "kind": "status",
+  "status": "errors",
+  "packs": [
+    {
+      "name": "cli-battery-pack",
+      "short_name": "cli",
+      "registered_version": "0.4",
+      "installed_version": "0.4.1",
+      "active_features": [
+        "default"
+      ],
+      "release": {
+        "latest_version": "0.5.0",
+        "yanked": false,
+        "outdated": true,
+        "changes": [
+          {
+            "change": "added",
+            "name": "indicatif",
+            "version": "0.17"
+          },
+          {
+            "change": "version-changed",
+            "name": "clap",
+            "from": "4.4",
+            "to": "4.5"
+          }
+        ],
+        "error": null
+      },
+      "crates": [
+        {
+          "name": "clap",
+          "expected": "4.4",
+          "actual": null,
+          "drift": "missing-crate"
+        }
+      ],
+      "findings": [
+        {
+          "pack": "cli-battery-pack",
+          "drift": "missing-crate",
+          "severity": "error",
+          "crate": "clap",
+          "expected": "4.4",
+          "actual": null,
+          "message": "clap: missing from [dependencies]"
+        }
+      ],
+      "held": [
+        {
+          "crate": "tokio",
+          "reason": "pinned",
+          "policy": null,
+          "actual": "=1.38.0",
+          "recommended": "1.40",
+          "behind": true,
+          "message": "tokio: pinned at =1.38.0 (1.40 recommended)"
+        }
+      ]
+    }
+  ],
+  "unregistered": []
+}
+"#]]
+    );
+}
+
+// [verify cli.json.validate]
+#[test]
+fn diagnostics_keep_severity_and_rule() {
+    let diag = bphelper_manifest::Diagnostic {
+        severity: bphelper_manifest::Severity::Warning,
+        rule: "format.crate.keyword",
+        message: "missing keyword".into(),
+    };
+    let json = serde_json::to_value(Diagnostic::from(&diag)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "severity": "warning",
+            "rule": "format.crate.keyword",
+            "message": "missing keyword",
+        })
+    );
+}
+
+// [verify cli.json.show]
+#[test]
+fn show_output_marks_installed_state() {
+    let detail = crate::registry::fetch_battery_pack_detail(
+        "fancy",
+        Some(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../../tests/fixtures/fancy-battery-pack")
+                .to_str()
+                .unwrap(),
+        ),
+    )
+    .unwrap();
+    let managed: BTreeSet<String> = ["clap".to_string()].into();
+    let active: BTreeSet<String> = ["default".to_string()].into();
+
+    let output = ShowOutput::new(&detail, &managed, &active);
+
+    let clap = output.crates.iter().find(|c| c.name == "clap").unwrap();
+    assert!(clap.installed);
+    let console = output.crates.iter().find(|c| c.name == "console").unwrap();
+    assert!(!console.installed);
+    let default = output
+        .features
+        .iter()
+        .find(|f| f.name == "default")
+        .unwrap();
+    assert!(default.active);
+    assert!(output.templates.iter().any(|t| t.name == "default"));
+}
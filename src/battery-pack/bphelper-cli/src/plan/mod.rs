@@ -0,0 +1,170 @@
+//! One dependency plan over every installed battery pack.
+//!
+//! Packs may recommend the same crate with different versions, features or
+//! dependency kinds. Writing each pack's view in turn lets whichever pack
+//! comes last win, so `sync` and `add` first merge them with
+//! [`bphelper_manifest::merge_crate_specs`] (the `manifest.merge.*` rules)
+//! and then apply the merged plan to the manifests in a single pass.
+
+#[cfg(test)]
+mod tests;
+
+use crate::manifest::{
+    Relocation, SyncPolicy, add_dep_to_table, add_workspace_ref, dep_kind_section, relocate_dep,
+    sync_dep_in_table_with_policy,
+};
+use bphelper_manifest::{CrateSpec, DepKind, MergedCrateSpec};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The merged dependency plan for a set of packs.
+#[derive(Debug, Default)]
+pub(crate) struct DepPlan {
+    crates: BTreeMap<String, MergedCrateSpec>,
+    pinned: BTreeSet<String>,
+}
+
+/// One edit made while applying a [`DepPlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Change {
+    /// Added to a package dependency section.
+    Added(String),
+    /// Updated in a package dependency section.
+    Updated(String),
+    /// Added to `[workspace.dependencies]`.
+    AddedToWorkspace(String),
+    /// Updated in `[workspace.dependencies]`.
+    UpdatedInWorkspace(String),
+    /// A `workspace = true` reference added to a package section.
+    Referenced(String, DepKind),
+    /// Moved between package dependency sections.
+    Moved(String, Relocation),
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added(name) => write!(f, "+ {}", name),
+            Change::Updated(name) => write!(f, "~ {}", name),
+            Change::AddedToWorkspace(name) => write!(f, "+ {} (added to workspace)", name),
+            Change::UpdatedInWorkspace(name) => write!(f, "~ {} (updated in workspace)", name),
+            Change::Referenced(name, kind) => {
+                write!(f, "+ {} ([{}], workspace = true)", name, kind)
+            }
+            Change::Moved(name, moved) => write!(f, "> {} ({})", name, moved),
+        }
+    }
+}
+
+impl DepPlan {
+    /// Merge each pack's resolved crates; a crate pinned by any pack stays
+    /// pinned.
+    // [impl manifest.merge.version]
+    // [impl manifest.merge.features]
+    // [impl manifest.merge.dep-kind]
+    pub(crate) fn new<'a>(
+        packs: impl IntoIterator<Item = (&'a BTreeMap<String, CrateSpec>, &'a BTreeSet<String>)>,
+    ) -> Self {
+        let mut specs = Vec::new();
+        let mut pinned = BTreeSet::new();
+        for (expected, pack_pinned) in packs {
+            specs.push(expected.clone());
+            pinned.extend(pack_pinned.iter().cloned());
+        }
+        DepPlan {
+            crates: bphelper_manifest::merge_crate_specs(&specs),
+            pinned,
+        }
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.crates.contains_key(name)
+    }
+
+    /// The merged spec for `name`, as written to the `kind` section.
+    pub(crate) fn spec(&self, name: &str, kind: DepKind) -> Option<CrateSpec> {
+        self.crates.get(name).map(|merged| CrateSpec {
+            version: merged.version.clone(),
+            features: merged.features.clone(),
+            dep_kind: kind,
+            optional: merged.optional,
+        })
+    }
+
+    /// Apply the plan to the package manifest and, in a workspace, the
+    /// workspace manifest. With `only`, just those crates are touched.
+    /// Existing entries are only upgraded (as `policy` allows) and given
+    /// missing features, never replaced; only crates in `relocate` are
+    /// moved between sections.
+    // [impl manifest.deps.existing]
+    // [impl manifest.deps.workspace]
+    // [impl manifest.deps.no-workspace]
+    // [impl cli.add.dep-kind]
+    // [impl cli.sync.dep-kind]
+    pub(crate) fn apply(
+        &self,
+        user_doc: &mut toml_edit::DocumentMut,
+        mut ws_doc: Option<&mut toml_edit::DocumentMut>,
+        policy: SyncPolicy,
+        only: Option<&BTreeSet<String>>,
+        relocate: &BTreeSet<String>,
+    ) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for (name, merged) in &self.crates {
+            if only.is_some_and(|only| !only.contains(name)) {
+                continue;
+            }
+            // [impl cli.pins.metadata]
+            let policy = if self.pinned.contains(name) {
+                SyncPolicy::NeverUpgrade
+            } else {
+                policy
+            };
+            let kinds = &merged.dep_kinds;
+
+            if relocate.contains(name)
+                && let Some(moved) = relocate_dep(user_doc, name, kinds)
+            {
+                changes.push(Change::Moved(name.clone(), moved));
+            }
+
+            if let Some(ws_doc) = ws_doc.as_deref_mut() {
+                let spec = self.spec(name, kinds[0]).expect("planned crate");
+                if let Some(ws_table) = ws_doc["workspace"]["dependencies"]
+                    .or_insert(toml_edit::Item::Table(toml_edit::Table::new()))
+                    .as_table_mut()
+                {
+                    if !ws_table.contains_key(name) {
+                        add_dep_to_table(ws_table, name, &spec);
+                        changes.push(Change::AddedToWorkspace(name.clone()));
+                    } else if sync_dep_in_table_with_policy(ws_table, name, &spec, policy) {
+                        changes.push(Change::UpdatedInWorkspace(name.clone()));
+                    }
+                }
+            }
+
+            for &kind in kinds {
+                let Some(table) = user_doc[dep_kind_section(kind)]
+                    .or_insert(toml_edit::Item::Table(toml_edit::Table::new()))
+                    .as_table_mut()
+                else {
+                    continue;
+                };
+                if ws_doc.is_some() {
+                    if !table.contains_key(name) {
+                        add_workspace_ref(table, name);
+                        changes.push(Change::Referenced(name.clone(), kind));
+                    }
+                } else {
+                    let spec = self.spec(name, kind).expect("planned crate");
+                    if !table.contains_key(name) {
+                        add_dep_to_table(table, name, &spec);
+                        changes.push(Change::Added(name.clone()));
+                    } else if sync_dep_in_table_with_policy(table, name, &spec, policy) {
+                        changes.push(Change::Updated(name.clone()));
+                    }
+                }
+            }
+        }
+        changes
+    }
+}
@@ -0,0 +1,139 @@
+//! Tests for the merged dependency plan.
+
+use super::*;
+
+fn spec(version: &str, features: &[&str], dep_kind: DepKind) -> CrateSpec {
+    CrateSpec {
+        version: version.into(),
+        features: features.iter().map(|s| s.to_string()).collect(),
+        dep_kind,
+        optional: false,
+    }
+}
+
+fn pack(crates: &[(&str, CrateSpec)]) -> BTreeMap<String, CrateSpec> {
+    crates
+        .iter()
+        .map(|(name, spec)| (name.to_string(), spec.clone()))
+        .collect()
+}
+
+fn doc(input: &str) -> toml_edit::DocumentMut {
+    input.parse().unwrap()
+}
+
+/// Two packs that disagree on `tokio`'s version and features and on
+/// `insta`'s dependency kind.
+fn packs() -> [BTreeMap<String, CrateSpec>; 2] {
+    [
+        pack(&[
+            ("tokio", spec("1.38", &["macros"], DepKind::Normal)),
+            ("insta", spec("1", &[], DepKind::Dev)),
+        ]),
+        pack(&[
+            ("tokio", spec("1.40", &["rt"], DepKind::Normal)),
+            ("insta", spec("1", &[], DepKind::Build)),
+        ]),
+    ]
+}
+
+// [verify manifest.merge.plan]
+// [verify manifest.merge.version]
+// [verify manifest.merge.features]
+// [verify manifest.merge.dep-kind]
+#[test]
+fn the_outcome_does_not_depend_on_pack_order() {
+    let [a, b] = packs();
+    let none = BTreeSet::new();
+    let mut outputs = Vec::new();
+    for order in [[&a, &b], [&b, &a]] {
+        let plan = DepPlan::new(order.map(|p| (p, &none)));
+        let mut user = doc("[dependencies]\ntokio = \"1.38\"\n");
+        plan.apply(&mut user, None, SyncPolicy::default(), None, &none);
+        outputs.push(user.to_string());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+    assert_eq!(
+        outputs[0],
+        "[dependencies]\n\
+         tokio = { version = \"1.40\", features = [\"macros\", \"rt\"] }\n\n\
+         [dev-dependencies]\ninsta = \"1\"\n\n\
+         [build-dependencies]\ninsta = \"1\"\n"
+    );
+}
+
+// [verify manifest.merge.plan]
+// [verify manifest.deps.workspace]
+#[test]
+fn workspace_entries_are_written_once_and_referenced_per_kind() {
+    let [a, b] = packs();
+    let none = BTreeSet::new();
+    let plan = DepPlan::new([(&a, &none), (&b, &none)]);
+    let mut user = doc("[package]\nname = \"app\"\n");
+    let mut ws = doc("[workspace]\nmembers = [\"app\"]\n");
+    let changes = plan.apply(&mut user, Some(&mut ws), SyncPolicy::default(), None, &none);
+
+    assert!(changes.contains(&Change::Referenced("insta".into(), DepKind::Build)));
+    assert_eq!(
+        ws.to_string(),
+        "[workspace]\nmembers = [\"app\"]\n\n\
+         [workspace.dependencies]\n\
+         insta = \"1\"\n\
+         tokio = { version = \"1.40\", features = [\"macros\", \"rt\"] }\n"
+    );
+    assert_eq!(
+        user.to_string(),
+        "[package]\nname = \"app\"\n\n\
+         [dev-dependencies]\ninsta = { workspace = true }\n\n\
+         [build-dependencies]\ninsta = { workspace = true }\n\n\
+         [dependencies]\ntokio = { workspace = true }\n"
+    );
+}
+
+// [verify manifest.deps.existing]
+#[test]
+fn only_limits_the_crates_touched_and_pins_hold() {
+    let [a, b] = packs();
+    let pinned: BTreeSet<String> = ["tokio".to_string()].into();
+    let none = BTreeSet::new();
+    let plan = DepPlan::new([(&a, &pinned), (&b, &none)]);
+    let mut user = doc("[dependencies]\ntokio = { version = \"1.2\", features = [\"full\"] }\n");
+    let only: BTreeSet<String> = ["tokio".to_string()].into();
+    let changes = plan.apply(&mut user, None, SyncPolicy::default(), Some(&only), &none);
+
+    assert_eq!(changes, [Change::Updated("tokio".into())]);
+    assert_eq!(
+        user.to_string(),
+        "[dependencies]\ntokio = { version = \"1.2\", features = [\"full\", \"macros\", \"rt\"] }\n"
+    );
+    assert!(plan.contains("insta"));
+}
+
+// [verify cli.sync.dep-kind]
+#[test]
+fn only_relocatable_crates_are_moved() {
+    let [a, _] = packs();
+    let none = BTreeSet::new();
+    let plan = DepPlan::new([(&a, &none)]);
+    let input = "[dependencies]\ninsta = \"1.34\"\n";
+
+    let mut user = doc(input);
+    plan.apply(&mut user, None, SyncPolicy::default(), None, &none);
+    assert_eq!(
+        user.to_string(),
+        "[dependencies]\ninsta = \"1.34\"\n\
+         tokio = { version = \"1.38\", features = [\"macros\"] }\n\n\
+         [dev-dependencies]\ninsta = \"1\"\n"
+    );
+
+    let mut user = doc(input);
+    let managed: BTreeSet<String> = ["insta".to_string()].into();
+    let changes = plan.apply(&mut user, None, SyncPolicy::default(), None, &managed);
+    assert!(changes.contains(&Change::Moved(
+        "insta".into(),
+        Relocation {
+            from: vec![DepKind::Normal],
+            to: vec![DepKind::Dev],
+        }
+    )));
+}
@@ -0,0 +1,140 @@
+//! Support for `cargo bp sync --prune`: working out what each installed pack
+//! used to manage but no longer curates.
+
+#[cfg(test)]
+mod tests;
+
+use bphelper_manifest::CrateSpec;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What the project records about one installed pack, plus its current
+/// resolution.
+pub(crate) struct PackRecord<'a> {
+    /// The pack's resolved crates for its active features.
+    pub expected: &'a BTreeMap<String, CrateSpec>,
+    /// `managed-deps` from the pack's metadata, if recorded.
+    pub managed_deps: Option<&'a BTreeSet<String>>,
+    /// `managed-features` from the pack's metadata: the Cargo features sync
+    /// added to each crate on the pack's behalf.
+    pub managed_features: &'a BTreeMap<String, BTreeSet<String>>,
+}
+
+/// What pruning does for one pack.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PackPrune {
+    /// Stale crates no other pack manages or resolves to: removed from the
+    /// manifest.
+    pub remove: BTreeSet<String>,
+    /// Stale crates another pack still uses: kept in the manifest, but no
+    /// longer recorded as this pack's.
+    pub release: BTreeSet<String>,
+    /// Cargo features this pack added that neither it nor any other pack
+    /// asks for any more, by crate.
+    pub features: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl PackPrune {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.remove.is_empty() && self.release.is_empty() && self.features.is_empty()
+    }
+
+    /// Every crate the pack no longer curates.
+    pub(crate) fn stale(&self) -> impl Iterator<Item = &String> {
+        self.remove.iter().chain(&self.release)
+    }
+}
+
+/// Plan the pruning for each pack; the result lines up with `packs`.
+// [impl cli.sync.prune]
+// [impl cli.sync.prune-shared]
+// [impl cli.sync.prune-features]
+pub(crate) fn plan(packs: &[PackRecord<'_>]) -> Vec<PackPrune> {
+    packs
+        .iter()
+        .enumerate()
+        .map(|(i, pack)| {
+            let others: Vec<&PackRecord<'_>> = packs
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other)| other)
+                .collect();
+            let claimed = |name: &str| {
+                others.iter().any(|other| {
+                    other.expected.contains_key(name)
+                        || other.managed_deps.is_some_and(|m| m.contains(name))
+                })
+            };
+
+            let (release, remove): (BTreeSet<String>, BTreeSet<String>) = pack
+                .managed_deps
+                .into_iter()
+                .flatten()
+                .filter(|name| !pack.expected.contains_key(*name))
+                .cloned()
+                .partition(|name| claimed(name));
+
+            let features = pack
+                .managed_features
+                .iter()
+                .filter(|(name, _)| !remove.contains(*name))
+                .filter_map(|(name, recorded)| {
+                    let wanted: BTreeSet<&String> = pack
+                        .expected
+                        .get(name)
+                        .into_iter()
+                        .chain(others.iter().filter_map(|o| o.expected.get(name)))
+                        .flat_map(|spec| &spec.features)
+                        .collect();
+                    let stale: BTreeSet<String> = recorded
+                        .iter()
+                        .filter(|f| !wanted.contains(f))
+                        .cloned()
+                        .collect();
+                    (!stale.is_empty()).then(|| (name.clone(), stale))
+                })
+                .collect();
+
+            PackPrune {
+                remove,
+                release,
+                features,
+            }
+        })
+        .collect()
+}
+
+/// The features sync records for a pack: what it recorded before, less what
+/// was pruned, plus those of the pack's features that this sync `inserted`.
+/// Features the user had already declared are never recorded.
+pub(crate) fn managed_features(
+    recorded: &BTreeMap<String, BTreeSet<String>>,
+    expected: &BTreeMap<String, CrateSpec>,
+    inserted: &BTreeMap<String, BTreeSet<String>>,
+    pruned: Option<&PackPrune>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let mut features: BTreeMap<String, BTreeSet<String>> = recorded
+        .iter()
+        .filter(|(name, _)| pruned.is_none_or(|p| !p.stale().any(|s| s == *name)))
+        .map(|(name, recorded)| {
+            let stale = pruned.and_then(|p| p.features.get(name));
+            let kept = recorded
+                .iter()
+                .filter(|f| stale.is_none_or(|s| !s.contains(*f)))
+                .cloned()
+                .collect();
+            (name.clone(), kept)
+        })
+        .collect();
+    for (name, spec) in expected {
+        let Some(inserted) = inserted.get(name) else {
+            continue;
+        };
+        features
+            .entry(name.clone())
+            .or_default()
+            .extend(spec.features.intersection(inserted).cloned());
+    }
+    features.retain(|_, f| !f.is_empty());
+    features
+}
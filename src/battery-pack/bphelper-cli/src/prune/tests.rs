@@ -0,0 +1,136 @@
+//! Tests for prune planning.
+
+use super::*;
+use bphelper_manifest::DepKind;
+
+fn spec(features: &[&str]) -> CrateSpec {
+    CrateSpec {
+        version: "1".into(),
+        features: features.iter().map(|s| s.to_string()).collect(),
+        dep_kind: DepKind::Normal,
+        optional: false,
+    }
+}
+
+fn set(items: &[&str]) -> BTreeSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+fn crates(items: &[(&str, &[&str])]) -> BTreeMap<String, CrateSpec> {
+    items
+        .iter()
+        .map(|(name, features)| (name.to_string(), spec(features)))
+        .collect()
+}
+
+fn features(items: &[(&str, &[&str])]) -> BTreeMap<String, BTreeSet<String>> {
+    items
+        .iter()
+        .map(|(name, features)| (name.to_string(), set(features)))
+        .collect()
+}
+
+// [verify cli.sync.prune]
+// [verify cli.sync.prune-shared]
+#[test]
+fn stale_crates_are_removed_unless_another_pack_uses_them() {
+    let cli_expected = crates(&[("clap", &["derive"])]);
+    let cli_managed = set(&["clap", "console", "indicatif", "anyhow"]);
+    let error_expected = crates(&[("anyhow", &[])]);
+    let error_managed = set(&["anyhow"]);
+    let tui_expected = crates(&[("console", &[])]);
+    let none = BTreeMap::new();
+    let packs = [
+        PackRecord {
+            expected: &cli_expected,
+            managed_deps: Some(&cli_managed),
+            managed_features: &none,
+        },
+        PackRecord {
+            expected: &error_expected,
+            managed_deps: Some(&error_managed),
+            managed_features: &none,
+        },
+        PackRecord {
+            expected: &tui_expected,
+            managed_deps: None,
+            managed_features: &none,
+        },
+    ];
+
+    let plan = plan(&packs);
+
+    assert_eq!(plan[0].remove, set(&["indicatif"]));
+    assert_eq!(plan[0].release, set(&["anyhow", "console"]));
+    assert!(plan[1].is_empty());
+    assert!(plan[2].is_empty());
+}
+
+// [verify cli.sync.prune-features]
+#[test]
+fn features_no_pack_asks_for_are_stale() {
+    let cli_expected = crates(&[("clap", &["derive"]), ("serde", &[])]);
+    let cli_managed = set(&["clap", "serde"]);
+    let cli_features = features(&[("clap", &["derive", "env"]), ("serde", &["derive", "rc"])]);
+    let other_expected = crates(&[("serde", &["rc"])]);
+    let none = BTreeMap::new();
+    let packs = [
+        PackRecord {
+            expected: &cli_expected,
+            managed_deps: Some(&cli_managed),
+            managed_features: &cli_features,
+        },
+        PackRecord {
+            expected: &other_expected,
+            managed_deps: None,
+            managed_features: &none,
+        },
+    ];
+
+    let plan = plan(&packs);
+
+    assert!(plan[0].remove.is_empty());
+    assert_eq!(
+        plan[0].features,
+        features(&[("clap", &["env"]), ("serde", &["derive"])])
+    );
+
+    let recorded = managed_features(&cli_features, &cli_expected, &none, Some(&plan[0]));
+    assert_eq!(
+        recorded,
+        features(&[("clap", &["derive"]), ("serde", &["rc"])])
+    );
+}
+
+#[test]
+fn features_of_removed_crates_are_not_listed_separately() {
+    let expected = crates(&[]);
+    let managed = set(&["indicatif"]);
+    let recorded = features(&[("indicatif", &["improved_unicode"])]);
+    let packs = [PackRecord {
+        expected: &expected,
+        managed_deps: Some(&managed),
+        managed_features: &recorded,
+    }];
+
+    let plan = plan(&packs);
+
+    assert_eq!(plan[0].remove, set(&["indicatif"]));
+    assert!(plan[0].features.is_empty());
+    let none = BTreeMap::new();
+    assert!(managed_features(&recorded, &expected, &none, Some(&plan[0])).is_empty());
+    assert_eq!(
+        managed_features(&recorded, &expected, &none, None),
+        recorded
+    );
+}
+
+#[test]
+fn only_inserted_features_are_recorded() {
+    let expected = crates(&[("clap", &["derive", "env"]), ("serde", &["derive"])]);
+    // `derive` on clap was already declared by hand; serde was untouched.
+    let inserted = features(&[("clap", &["env"]), ("serde", &[])]);
+
+    let recorded = managed_features(&BTreeMap::new(), &expected, &inserted, None);
+    assert_eq!(recorded, features(&[("clap", &["env"])]));
+}
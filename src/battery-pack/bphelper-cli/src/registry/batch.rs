@@ -0,0 +1,209 @@
+//! Concurrent spec loading for commands that visit every installed pack.
+//!
+//! `status`, `sync` and `check` each need one spec per installed battery
+//! pack, and on the registry path every spec means a tarball download. This
+//! module fans that work out over a small worker pool, memoizes parsed specs
+//! by pack name and version for the life of the process, and shows a
+//! progress line on stderr while it runs.
+
+use anyhow::{Context, Result};
+use bphelper_manifest::BatteryPackSpec;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::{CrateSource, load_installed_bp_spec};
+
+/// Upper bound on concurrent spec fetches. The work is network-bound, so this
+/// is about being polite to the registry rather than matching CPU count.
+pub(crate) const MAX_FETCH_WORKERS: usize = 8;
+
+/// Apply `f` to every item on up to `workers` threads, keeping input order.
+// [impl cli.fetch.parallel]
+pub(crate) fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    workers: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let workers = workers.clamp(1, items.len().max(1));
+    if workers == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let slots: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else { break };
+                    let result = f(item);
+                    *slots[index].lock().unwrap() = Some(result);
+                }
+            });
+        }
+    });
+    slots
+        .into_iter()
+        .map(|slot| slot.into_inner().unwrap().expect("every item is processed"))
+        .collect()
+}
+
+/// Run `f` over `items` on the fetch pool, with a progress line labelled
+/// `label` while it runs.
+pub(crate) fn fetch_all<T: Sync, R: Send>(
+    label: &str,
+    items: &[T],
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let progress = Progress::new(label, items.len());
+    parallel_map(items, MAX_FETCH_WORKERS, |item| {
+        let result = f(item);
+        progress.tick();
+        result
+    })
+}
+
+// ============================================================================
+// Progress
+// ============================================================================
+
+/// A single overwritten `label (n/total)` line on stderr.
+///
+/// Only drawn when stderr is a terminal and there is more than one item, so
+/// piped output and tests see nothing.
+// [impl cli.fetch.progress]
+struct Progress<'a> {
+    term: Option<console::Term>,
+    label: &'a str,
+    total: usize,
+    done: AtomicUsize,
+}
+
+impl<'a> Progress<'a> {
+    fn new(label: &'a str, total: usize) -> Self {
+        let term = console::Term::stderr();
+        let progress = Self {
+            term: (term.is_term() && total > 1).then_some(term),
+            label,
+            total,
+            done: AtomicUsize::new(0),
+        };
+        progress.draw(0);
+        progress
+    }
+
+    fn tick(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        self.draw(done);
+    }
+
+    fn draw(&self, done: usize) {
+        if let Some(term) = &self.term {
+            let _ = term.clear_line();
+            let _ = term.write_str(&format!("{} ({}/{})...", self.label, done, self.total));
+        }
+    }
+}
+
+impl Drop for Progress<'_> {
+    fn drop(&mut self) {
+        if let Some(term) = &self.term {
+            let _ = term.clear_line();
+        }
+    }
+}
+
+// ============================================================================
+// Spec memo
+// ============================================================================
+
+/// One memo entry; its lock is held while the spec loads.
+type MemoSlot = Arc<Mutex<Option<BatteryPackSpec>>>;
+
+/// Parsed specs keyed by `(crate name, version)`.
+///
+/// Each key has its own lock, so concurrent requests for the same release
+/// wait for one load instead of downloading it twice. Failures are not
+/// remembered; the next caller tries again.
+#[derive(Default)]
+pub(crate) struct SpecMemo {
+    entries: Mutex<HashMap<(String, String), MemoSlot>>,
+}
+
+impl SpecMemo {
+    pub(crate) fn get_or_load(
+        &self,
+        crate_name: &str,
+        version: &str,
+        load: impl FnOnce() -> Result<BatteryPackSpec>,
+    ) -> Result<BatteryPackSpec> {
+        let entry = self
+            .entries
+            .lock()
+            .unwrap()
+            .entry((crate_name.to_string(), version.to_string()))
+            .or_default()
+            .clone();
+        let mut slot = entry.lock().unwrap();
+        if let Some(spec) = slot.as_ref() {
+            return Ok(spec.clone());
+        }
+        let spec = load()?;
+        *slot = Some(spec.clone());
+        Ok(spec)
+    }
+}
+
+/// Look up `crate_name@version` in the process-wide memo, loading it with
+/// `load` on a miss.
+// [impl cli.fetch.memo]
+pub(crate) fn memoized_spec(
+    crate_name: &str,
+    version: &str,
+    load: impl FnOnce() -> Result<BatteryPackSpec>,
+) -> Result<BatteryPackSpec> {
+    static MEMO: OnceLock<SpecMemo> = OnceLock::new();
+    MEMO.get_or_init(SpecMemo::default)
+        .get_or_load(crate_name, version, load)
+}
+
+// ============================================================================
+// Batch loaders
+// ============================================================================
+
+/// Load the installed spec of every pack in `bp_names`, in order.
+///
+/// Like [`load_installed_bp_spec`], but concurrent, and on the registry path
+/// the dependency graph is resolved with a single `cargo metadata` run. Each
+/// pack gets its own result so one failure doesn't hide the others; the outer
+/// error is for the dependency graph itself.
+pub(crate) fn load_installed_bp_specs(
+    bp_names: &[String],
+    path: Option<&str>,
+    source: &CrateSource,
+) -> Result<Vec<Result<BatteryPackSpec>>> {
+    let label = "Resolving battery packs";
+    if path.is_some() || !matches!(source, CrateSource::Registry) {
+        return Ok(fetch_all(label, bp_names, |bp_name| {
+            load_installed_bp_spec(bp_name, path, source)
+        }));
+    }
+
+    let resolved = crate::manifest::resolve_battery_pack_manifests(bp_names)?;
+    Ok(fetch_all(label, bp_names, |bp_name| {
+        let (version, manifest_path) = resolved.get(bp_name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Battery pack '{}' not found in dependency graph. Is it in [build-dependencies]?",
+                bp_name
+            )
+        })?;
+        memoized_spec(bp_name, version, || {
+            let manifest_content = std::fs::read_to_string(manifest_path)
+                .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+            bphelper_manifest::parse_battery_pack(&manifest_content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse battery pack '{}': {}", bp_name, e))
+        })
+    }))
+}
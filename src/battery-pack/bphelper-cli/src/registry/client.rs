@@ -0,0 +1,236 @@
+//! HTTP access for registry and forge requests.
+//!
+//! Everything in `registry` that talks to the network goes through the
+//! [`RegistryClient`] trait, so tests can substitute an in-memory client.
+//! The default [`HttpClient`] adds timeouts, retries with exponential
+//! backoff on 429/5xx responses and transport errors, and token auth.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// A minimal HTTP GET interface for registry lookups.
+pub(crate) trait RegistryClient: Send + Sync {
+    /// Fetch `url`. Non-success statuses are returned, not turned into errors;
+    /// `Err` means no response could be obtained at all.
+    fn get(&self, url: &str) -> Result<HttpResponse>;
+}
+
+/// A fully-read HTTP response.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+    /// Parsed `Retry-After` header (seconds form only).
+    pub retry_after: Option<Duration>,
+}
+
+impl HttpResponse {
+    pub(crate) fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub(crate) fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body).context("invalid JSON in response body")
+    }
+
+    /// Whether a request that got this response is worth retrying.
+    fn is_retryable(&self) -> bool {
+        self.status == 429 || (500..600).contains(&self.status)
+    }
+}
+
+// ============================================================================
+// Retries
+// ============================================================================
+
+/// How many times to retry, and how long to wait between attempts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    /// Retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each one after that.
+    pub base_delay: Duration,
+    /// Upper bound on any single delay, including server-requested ones.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self.base_delay.saturating_mul(1 << retry.min(16));
+        retry_after.unwrap_or(backoff).min(self.max_delay)
+    }
+}
+
+/// The outcome of a single request attempt.
+pub(crate) enum Attempt {
+    Response(HttpResponse),
+    /// A transport failure that may succeed on retry (timeout, connection reset).
+    Transient(anyhow::Error),
+    /// A failure that retrying won't fix (bad URL, TLS misconfiguration).
+    Fatal(anyhow::Error),
+}
+
+/// Run `attempt` until it succeeds, fails permanently, or retries run out.
+///
+/// When retries run out on a retryable status, the last response is returned
+/// so the caller can report the status.
+// [impl cli.http.retry]
+pub(crate) fn send_with_retries(
+    policy: &RetryPolicy,
+    mut sleep: impl FnMut(Duration),
+    mut attempt: impl FnMut() -> Attempt,
+) -> Result<HttpResponse> {
+    let mut retry = 0;
+    loop {
+        let retry_after = match attempt() {
+            Attempt::Response(response) => {
+                if !response.is_retryable() || retry >= policy.max_retries {
+                    return Ok(response);
+                }
+                response.retry_after
+            }
+            Attempt::Transient(err) => {
+                if retry >= policy.max_retries {
+                    return Err(err);
+                }
+                None
+            }
+            Attempt::Fatal(err) => return Err(err),
+        };
+        sleep(policy.delay(retry, retry_after));
+        retry += 1;
+    }
+}
+
+// ============================================================================
+// Default reqwest-backed client
+// ============================================================================
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An `Authorization` header value to send to URLs with a given prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AuthRule {
+    pub url_prefix: String,
+    pub header: String,
+}
+
+/// Build auth rules from the environment.
+///
+/// `CARGO_BP_REGISTRY_TOKEN` is sent verbatim, as cargo does, to
+/// `registry_prefixes`. Cargo's own `CARGO_REGISTRY_TOKEN` is a publish token
+/// and is never read.
+// [impl cli.http.auth]
+pub(crate) fn auth_rules_from_env(
+    env: impl Fn(&str) -> Option<String>,
+    registry_prefixes: &[&str],
+) -> Vec<AuthRule> {
+    let Some(token) = env("CARGO_BP_REGISTRY_TOKEN").filter(|v| !v.trim().is_empty()) else {
+        return Vec::new();
+    };
+    registry_prefixes
+        .iter()
+        .map(|prefix| AuthRule {
+            url_prefix: format!("{}/", prefix.trim_end_matches('/')),
+            header: token.trim().to_string(),
+        })
+        .collect()
+}
+
+/// Find the `Authorization` header for `url`, if any rule matches.
+pub(crate) fn auth_header_for<'a>(rules: &'a [AuthRule], url: &str) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| url.starts_with(&rule.url_prefix))
+        .map(|rule| rule.header.as_str())
+}
+
+/// The production client: reqwest with timeouts, retries and auth.
+pub(crate) struct HttpClient {
+    inner: reqwest::blocking::Client,
+    retry: RetryPolicy,
+    auth: Vec<AuthRule>,
+}
+
+impl HttpClient {
+    // [impl cli.http.timeout]
+    pub(crate) fn new(auth: Vec<AuthRule>) -> Result<Self> {
+        let inner = reqwest::blocking::Client::builder()
+            .user_agent("cargo-bp (https://github.com/battery-pack-rs/battery-pack)")
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .context("failed to build HTTP client")?;
+        Ok(Self {
+            inner,
+            retry: RetryPolicy::default(),
+            auth,
+        })
+    }
+
+    fn attempt(&self, url: &str) -> Attempt {
+        let mut request = self.inner.get(url);
+        if let Some(header) = auth_header_for(&self.auth, url) {
+            request = request.header(reqwest::header::AUTHORIZATION, header);
+        }
+        let response = match request.send() {
+            Ok(response) => response,
+            Err(e) if e.is_timeout() || e.is_connect() => {
+                return Attempt::Transient(e.into());
+            }
+            Err(e) => return Attempt::Fatal(e.into()),
+        };
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        match response.bytes() {
+            Ok(body) => Attempt::Response(HttpResponse {
+                status,
+                body: body.to_vec(),
+                retry_after,
+            }),
+            // The body was cut off mid-read; the next attempt may get it all.
+            Err(e) => Attempt::Transient(e.into()),
+        }
+    }
+}
+
+impl RegistryClient for HttpClient {
+    fn get(&self, url: &str) -> Result<HttpResponse> {
+        send_with_retries(&self.retry, std::thread::sleep, || self.attempt(url))
+            .with_context(|| format!("request to {} failed", url))
+    }
+}
+
+// A failure to build is kept as a string so every caller gets the same error.
+static CLIENT: OnceLock<std::result::Result<HttpClient, String>> = OnceLock::new();
+
+/// The process-wide client used by the registry functions.
+pub(crate) fn client() -> Result<&'static dyn RegistryClient> {
+    let client = CLIENT.get_or_init(|| {
+        let endpoints = super::endpoints();
+        let auth = auth_rules_from_env(|var| std::env::var(var).ok(), &endpoints.token_endpoints());
+        HttpClient::new(auth).map_err(|e| format!("{e:#}"))
+    });
+    match client {
+        Ok(client) => Ok(client),
+        Err(e) => Err(anyhow::anyhow!("{}", e)),
+    }
+}
@@ -14,17 +14,64 @@ use tar::Archive;
 
 use crate::manifest::resolve_battery_pack_manifest;
 
+mod batch;
+mod client;
+
+pub(crate) use batch::{fetch_all, load_installed_bp_specs};
+pub(crate) use client::{RegistryClient, client};
+
 const CRATES_IO_API: &str = "https://crates.io/api/v1/crates";
 const CRATES_IO_CDN: &str = "https://static.crates.io/crates";
 
-fn http_client() -> &'static reqwest::blocking::Client {
-    static CLIENT: std::sync::OnceLock<reqwest::blocking::Client> = std::sync::OnceLock::new();
-    CLIENT.get_or_init(|| {
-        reqwest::blocking::Client::builder()
-            .user_agent("cargo-bp (https://github.com/battery-pack-rs/battery-pack)")
-            .build()
-            .expect("failed to build HTTP client")
-    })
+/// Registry endpoints, overridable via the `registry.*` config keys.
+struct RegistryEndpoints {
+    api: String,
+    download: String,
+    /// Whether `api` was configured to something other than crates.io.
+    custom_api: bool,
+}
+
+impl RegistryEndpoints {
+    fn new(api: Option<&str>, download: Option<&str>) -> Self {
+        let api = api
+            .unwrap_or(CRATES_IO_API)
+            .trim_end_matches('/')
+            .to_string();
+        Self {
+            custom_api: api != CRATES_IO_API,
+            api,
+            download: download
+                .unwrap_or(CRATES_IO_CDN)
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+
+    /// The endpoints that get `CARGO_BP_REGISTRY_TOKEN`: only an API the
+    /// user configured. crates.io needs no token to read, and downloads are
+    /// usually served by a CDN.
+    // [impl cli.http.auth]
+    fn token_endpoints(&self) -> Vec<&str> {
+        if self.custom_api {
+            vec![self.api.as_str()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+static ENDPOINTS: std::sync::OnceLock<RegistryEndpoints> = std::sync::OnceLock::new();
+
+/// Override the registry API and download endpoints for this process.
+///
+/// Must be called before the first registry request; later calls are ignored.
+// [impl cli.config.registry]
+pub(crate) fn configure_endpoints(api: Option<&str>, download: Option<&str>) {
+    let _ = ENDPOINTS.set(RegistryEndpoints::new(api, download));
+}
+
+fn endpoints() -> &'static RegistryEndpoints {
+    ENDPOINTS.get_or_init(|| RegistryEndpoints::new(None, None))
 }
 
 // [impl cli.source.flag]
@@ -53,6 +100,14 @@ struct VersionInfo {
 #[derive(Deserialize)]
 struct SearchResponse {
     crates: Vec<SearchCrate>,
+    #[serde(default)]
+    meta: SearchMeta,
+}
+
+#[derive(Deserialize, Default)]
+struct SearchMeta {
+    #[serde(default)]
+    total: u64,
 }
 
 #[derive(Deserialize)]
@@ -60,8 +115,42 @@ struct SearchCrate {
     name: String,
     max_version: String,
     description: Option<String>,
+    #[serde(default)]
+    downloads: u64,
+    #[serde(default)]
+    recent_downloads: Option<u64>,
+    #[serde(default)]
+    updated_at: Option<String>,
+    // crates.io leaves these null in search results, but alternative
+    // registries may fill them in.
+    #[serde(default)]
+    keywords: Option<Vec<String>>,
+    #[serde(default)]
+    categories: Option<Vec<String>>,
+}
+
+/// The parts of the per-crate endpoint (`{api}/{name}`) that search results
+/// leave out.
+#[derive(Deserialize)]
+struct CrateTagsResponse {
+    #[serde(rename = "crate")]
+    krate: CrateTags,
+}
+
+#[derive(Deserialize)]
+struct CrateTags {
+    #[serde(default)]
+    keywords: Option<Vec<String>>,
+    #[serde(default)]
+    categories: Option<Vec<String>>,
 }
 
+/// Page size for registry searches (the crates.io maximum).
+const SEARCH_PAGE_SIZE: usize = 100;
+
+/// Upper bound on search pages, so a misbehaving registry can't loop forever.
+const MAX_SEARCH_PAGES: u32 = 50;
+
 /// Backward-compatible alias for `bphelper_manifest::TemplateSpec`.
 pub(crate) type TemplateConfig = bphelper_manifest::TemplateSpec;
 
@@ -81,20 +170,83 @@ pub(crate) struct Owner {
 }
 
 // ============================================================================
-// GitHub API types
+// Published VCS info
 // ============================================================================
 
+/// The `.cargo_vcs_info.json` file that `cargo package` writes into every
+/// crate published from a git checkout.
 #[derive(Deserialize)]
-struct GitHubTreeResponse {
-    tree: Vec<GitHubTreeEntry>,
+struct CargoVcsInfo {
+    git: Option<CargoVcsGit>,
     #[serde(default)]
-    #[allow(dead_code)]
-    truncated: bool,
+    path_in_vcs: String,
 }
 
 #[derive(Deserialize)]
-struct GitHubTreeEntry {
-    path: String,
+struct CargoVcsGit {
+    sha1: String,
+}
+
+/// Where a battery pack's files live in its source repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VcsLocation {
+    /// Commit the crate was published from, if known.
+    pub commit: Option<String>,
+    /// Directory of the crate relative to the repository root ("" for the root).
+    pub path_in_vcs: String,
+}
+
+impl VcsLocation {
+    /// Repository path of a file or directory given relative to the crate root.
+    pub(crate) fn repo_path(&self, relative: &str) -> String {
+        let relative = relative.trim_start_matches("./").trim_matches('/');
+        let base = self.path_in_vcs.trim_matches('/');
+        if base.is_empty() {
+            relative.to_string()
+        } else {
+            format!("{}/{}", base, relative)
+        }
+    }
+}
+
+/// Work out where a published crate lives in its repository, from the
+/// exact commit and `path_in_vcs` in its `.cargo_vcs_info.json`.
+// [impl cli.show.vcs-links]
+pub(crate) fn read_vcs_info(crate_dir: &Path) -> Option<VcsLocation> {
+    let content = fs::read_to_string(crate_dir.join(".cargo_vcs_info.json")).ok()?;
+    let info: CargoVcsInfo = serde_json::from_str(&content).ok()?;
+    Some(VcsLocation {
+        commit: info.git.map(|g| g.sha1),
+        path_in_vcs: info.path_in_vcs,
+    })
+}
+
+/// Work out where a local crate (`--path` or `--crate-source`) lives in its
+/// repository, without the network.
+///
+/// Falls back from [`read_vcs_info`] to the enclosing git work tree, with no
+/// commit pinned. Downloaded crates must not use this: whatever repository
+/// encloses the download directory has nothing to do with the crate.
+// [impl cli.show.vcs-links]
+pub(crate) fn read_vcs_location(crate_dir: &Path) -> Option<VcsLocation> {
+    if crate_dir.join(".cargo_vcs_info.json").exists() {
+        return read_vcs_info(crate_dir);
+    }
+
+    let crate_dir = crate_dir.canonicalize().ok()?;
+    let root = crate_dir
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())?;
+    let relative = crate_dir.strip_prefix(root).ok()?;
+    let path_in_vcs = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some(VcsLocation {
+        commit: None,
+        path_in_vcs,
+    })
 }
 
 // ============================================================================
@@ -108,6 +260,91 @@ pub(crate) struct BatteryPackSummary {
     pub short_name: String,
     pub version: String,
     pub description: String,
+    /// All-time downloads (0 for local sources).
+    pub downloads: u64,
+    /// Downloads in the last 90 days, when the registry reports them.
+    pub recent_downloads: Option<u64>,
+    /// RFC 3339 timestamp of the last publish, when known.
+    pub updated_at: Option<String>,
+    /// `None` when the registry reports no keywords, even per crate.
+    pub keywords: Option<Vec<String>>,
+    /// `None` when the registry reports no categories, even per crate.
+    pub categories: Option<Vec<String>>,
+}
+
+/// Sort order for `cargo bp list` and the TUI list screen.
+// [impl cli.list.sort]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum ListSort {
+    /// Most downloaded first.
+    #[default]
+    Downloads,
+    /// Most downloaded in the last 90 days first.
+    Recent,
+    /// Alphabetical by name.
+    Name,
+}
+
+impl ListSort {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ListSort::Downloads => "downloads",
+            ListSort::Recent => "recent",
+            ListSort::Name => "name",
+        }
+    }
+
+    /// The next sort order, for cycling through them in the TUI.
+    pub(crate) fn next(self) -> Self {
+        match self {
+            ListSort::Downloads => ListSort::Recent,
+            ListSort::Recent => ListSort::Name,
+            ListSort::Name => ListSort::Downloads,
+        }
+    }
+
+    /// Sort `packs` in place. Ties always fall back to the name so output
+    /// is stable across runs.
+    pub(crate) fn apply(self, packs: &mut [BatteryPackSummary]) {
+        match self {
+            ListSort::Downloads => packs.sort_by(|a, b| {
+                b.downloads
+                    .cmp(&a.downloads)
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+            ListSort::Recent => packs.sort_by(|a, b| {
+                b.recent_downloads
+                    .unwrap_or(0)
+                    .cmp(&a.recent_downloads.unwrap_or(0))
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+            ListSort::Name => packs.sort_by(|a, b| a.name.cmp(&b.name)),
+        }
+    }
+}
+
+/// What to list: name filter, keyword filter, and sort order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ListQuery {
+    /// Substring of the battery pack name.
+    pub filter: Option<String>,
+    /// Keyword the battery pack must carry (in addition to `battery-pack`).
+    pub keyword: Option<String>,
+    pub sort: ListSort,
+}
+
+impl ListQuery {
+    /// Short human-readable description of the active filters, if any.
+    pub(crate) fn describe_filters(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(f) = &self.filter {
+            parts.push(format!("matching '{}'", f));
+        }
+        if let Some(k) = &self.keyword {
+            parts.push(format!("with keyword '{}'", k));
+        }
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
 }
 
 /// Detailed battery pack info
@@ -118,12 +355,17 @@ pub(crate) struct BatteryPackDetail {
     pub version: String,
     pub description: String,
     pub repository: Option<String>,
+    /// Commit the battery pack was published from, used to pin repository links.
+    pub commit: Option<String>,
     pub owners: Vec<OwnerInfo>,
     pub crates: Vec<String>,
     pub extends: Vec<String>,
     pub features: BTreeMap<String, Vec<String>>,
     pub templates: Vec<TemplateInfo>,
     pub examples: Vec<ExampleInfo>,
+    /// Secondary lookups that failed, such as the owners; the rest of the
+    /// detail is still shown.
+    pub warnings: Vec<String>,
 }
 
 #[derive(Clone)]
@@ -147,7 +389,7 @@ pub(crate) struct TemplateInfo {
     pub path: String,
     pub description: Option<String>,
     /// Full path in the repository (e.g., "src/cli-battery-pack/templates/simple")
-    /// Resolved by searching the GitHub tree API
+    /// Resolved from the crate's VCS info, if the template was packaged
     pub repo_path: Option<String>,
 }
 
@@ -156,7 +398,7 @@ pub(crate) struct ExampleInfo {
     pub name: String,
     pub description: Option<String>,
     /// Full path in the repository (e.g., "src/cli-battery-pack/examples/mini-grep.rs")
-    /// Resolved by searching the GitHub tree API
+    /// Resolved from the crate's VCS info
     pub repo_path: Option<String>,
 }
 
@@ -166,19 +408,51 @@ pub(crate) struct CrateMetadata {
 
 /// Look up a crate on crates.io and return its metadata
 pub(crate) fn lookup_crate(crate_name: &str) -> Result<CrateMetadata> {
-    let client = http_client();
+    lookup_crate_with(client()?, crate_name)
+}
+
+pub(crate) fn lookup_crate_with(
+    client: &dyn RegistryClient,
+    crate_name: &str,
+) -> Result<CrateMetadata> {
+    let versions = fetch_published_versions_with(client, crate_name)?;
+
+    // Find the latest non-yanked version
+    let version = versions
+        .iter()
+        .find(|v| !v.yanked)
+        .map(|v| v.num.clone())
+        .ok_or_else(|| anyhow::anyhow!("No non-yanked versions found for '{}'", crate_name))?;
 
-    let url = format!("{}/{}", CRATES_IO_API, crate_name);
+    Ok(CrateMetadata { version })
+}
+
+/// A published release of a crate, as listed by the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PublishedVersion {
+    pub num: String,
+    pub yanked: bool,
+}
+
+/// List every published version of a crate, newest first.
+pub(crate) fn fetch_published_versions(crate_name: &str) -> Result<Vec<PublishedVersion>> {
+    fetch_published_versions_with(client()?, crate_name)
+}
+
+pub(crate) fn fetch_published_versions_with(
+    client: &dyn RegistryClient,
+    crate_name: &str,
+) -> Result<Vec<PublishedVersion>> {
+    let url = format!("{}/{}", endpoints().api, crate_name);
     let response = client
         .get(&url)
-        .send()
         .with_context(|| format!("Failed to query crates.io for '{}'", crate_name))?;
 
-    if !response.status().is_success() {
+    if !response.is_success() {
         bail!(
             "Crate '{}' not found on crates.io (status: {})",
             crate_name,
-            response.status()
+            response.status
         );
     }
 
@@ -186,15 +460,55 @@ pub(crate) fn lookup_crate(crate_name: &str) -> Result<CrateMetadata> {
         .json()
         .with_context(|| format!("Failed to parse crates.io response for '{}'", crate_name))?;
 
-    // Find the latest non-yanked version
-    let version = parsed
+    Ok(parsed
         .versions
+        .into_iter()
+        .map(|v| PublishedVersion {
+            num: v.num,
+            yanked: v.yanked,
+        })
+        .collect())
+}
+
+/// How a project's registered battery pack version compares to the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PackVersionStatus {
+    /// Newest non-yanked release, if any.
+    pub latest: Option<String>,
+    /// The installed release has been yanked.
+    pub yanked: bool,
+    /// The latest release falls outside the registered version requirement.
+    pub outdated: bool,
+}
+
+/// Compare a registered requirement (`"0.4"`) and the installed release
+/// (`"0.4.1"`) against the registry's version list.
+// [impl cli.status.pack-yanked]
+// [impl cli.status.pack-outdated]
+pub(crate) fn pack_version_status(
+    registered: &str,
+    installed: &str,
+    published: &[PublishedVersion],
+) -> PackVersionStatus {
+    let yanked = published.iter().any(|v| v.yanked && v.num == installed);
+
+    let latest = published
         .iter()
-        .find(|v| !v.yanked)
-        .map(|v| v.num.clone())
-        .ok_or_else(|| anyhow::anyhow!("No non-yanked versions found for '{}'", crate_name))?;
+        .filter(|v| !v.yanked)
+        .filter_map(|v| semver::Version::parse(&v.num).ok())
+        .filter(|v| v.pre.is_empty())
+        .max();
 
-    Ok(CrateMetadata { version })
+    let outdated = match (&latest, semver::VersionReq::parse(registered)) {
+        (Some(latest), Ok(req)) => !req.matches(latest),
+        _ => false,
+    };
+
+    PackVersionStatus {
+        latest: latest.map(|v| v.to_string()),
+        yanked,
+        outdated,
+    }
 }
 
 /// Download a crate tarball and extract it to a temp directory
@@ -202,36 +516,40 @@ pub(crate) fn download_and_extract_crate(
     crate_name: &str,
     version: &str,
 ) -> Result<tempfile::TempDir> {
-    let client = http_client();
+    download_and_extract_crate_with(client()?, crate_name, version)
+}
 
+pub(crate) fn download_and_extract_crate_with(
+    client: &dyn RegistryClient,
+    crate_name: &str,
+    version: &str,
+) -> Result<tempfile::TempDir> {
     // Download from CDN: https://static.crates.io/crates/{name}/{name}-{version}.crate
     let url = format!(
         "{}/{}/{}-{}.crate",
-        CRATES_IO_CDN, crate_name, crate_name, version
+        endpoints().download,
+        crate_name,
+        crate_name,
+        version
     );
 
     let response = client
         .get(&url)
-        .send()
         .with_context(|| format!("Failed to download crate from {}", url))?;
 
-    if !response.status().is_success() {
+    if !response.is_success() {
         bail!(
             "Failed to download '{}' version {} (status: {})",
             crate_name,
             version,
-            response.status()
+            response.status
         );
     }
 
-    let bytes = response
-        .bytes()
-        .with_context(|| "Failed to read crate tarball")?;
-
     // Create temp directory and extract
     let temp_dir = tempfile::tempdir().with_context(|| "Failed to create temp directory")?;
 
-    let decoder = GzDecoder::new(&bytes[..]);
+    let decoder = GzDecoder::new(&response.body[..]);
     let mut archive = Archive::new(decoder);
     archive
         .unpack(temp_dir.path())
@@ -244,10 +562,39 @@ pub(crate) fn fetch_bp_spec_from_registry(
     crate_name: &str,
 ) -> Result<(String, bphelper_manifest::BatteryPackSpec)> {
     let crate_info = lookup_crate(crate_name)?;
-    let temp_dir = download_and_extract_crate(crate_name, &crate_info.version)?;
-    let crate_dir = temp_dir
-        .path()
-        .join(format!("{}-{}", crate_name, crate_info.version));
+    let spec = batch::memoized_spec(crate_name, &crate_info.version, || {
+        let (manifest_content, spec) =
+            fetch_bp_manifest_at_version(crate_name, &crate_info.version)?;
+
+        // Cache the manifest dynamically for autocomplete
+        let cache_dir = crate::completions::get_cache_dir();
+        if fs::create_dir_all(&cache_dir).is_ok() {
+            let cache_file = cache_dir.join(format!("{}_spec.toml", crate_name));
+            let _ = fs::write(&cache_file, &manifest_content);
+        }
+
+        Ok(spec)
+    })?;
+
+    Ok((crate_info.version, spec))
+}
+
+/// Download a specific release of a battery pack and parse its spec.
+pub(crate) fn fetch_bp_spec_at_version(
+    crate_name: &str,
+    version: &str,
+) -> Result<bphelper_manifest::BatteryPackSpec> {
+    batch::memoized_spec(crate_name, version, || {
+        fetch_bp_manifest_at_version(crate_name, version).map(|(_, spec)| spec)
+    })
+}
+
+fn fetch_bp_manifest_at_version(
+    crate_name: &str,
+    version: &str,
+) -> Result<(String, bphelper_manifest::BatteryPackSpec)> {
+    let temp_dir = download_and_extract_crate(crate_name, version)?;
+    let crate_dir = temp_dir.path().join(format!("{}-{}", crate_name, version));
 
     let manifest_path = crate_dir.join("Cargo.toml");
     let manifest_content = fs::read_to_string(&manifest_path)
@@ -256,14 +603,7 @@ pub(crate) fn fetch_bp_spec_from_registry(
     let spec = bphelper_manifest::parse_battery_pack(&manifest_content)
         .map_err(|e| anyhow::anyhow!("Failed to parse battery pack '{}': {}", crate_name, e))?;
 
-    // Cache the manifest dynamically for autocomplete
-    let cache_dir = crate::completions::get_cache_dir();
-    if fs::create_dir_all(&cache_dir).is_ok() {
-        let cache_file = cache_dir.join(format!("{}_spec.toml", crate_name));
-        let _ = fs::write(&cache_file, &manifest_content);
-    }
-
-    Ok((crate_info.version, spec))
+    Ok((manifest_content, spec))
 }
 
 // ============================================================================
@@ -443,6 +783,8 @@ pub(crate) fn load_installed_bp_spec(
 
 pub(crate) struct InstalledPack {
     pub short_name: String,
+    /// The version requirement registered in `[build-dependencies]`, or the
+    /// spec's own version when the entry has none (e.g. a path dependency).
     pub version: String,
     pub spec: bphelper_manifest::BatteryPackSpec,
     pub active_features: BTreeSet<String>,
@@ -450,43 +792,53 @@ pub(crate) struct InstalledPack {
 
 pub(crate) fn fetch_battery_pack_list(
     source: &CrateSource,
-    filter: Option<&str>,
+    query: &ListQuery,
 ) -> Result<Vec<BatteryPackSummary>> {
-    match source {
-        CrateSource::Registry => fetch_battery_pack_list_from_registry(filter),
-        CrateSource::Local(path) => discover_local_battery_packs(path, filter),
-    }
+    let mut packs = match source {
+        CrateSource::Registry => {
+            let client = client()?;
+            let mut packs = fetch_battery_pack_list_from_registry(client, query)?;
+            fill_missing_tags(client, &mut packs);
+            packs
+        }
+        CrateSource::Local(path) => discover_local_battery_packs(path, query)?,
+    };
+    query.sort.apply(&mut packs);
+    Ok(packs)
 }
 
-fn fetch_battery_pack_list_from_registry(filter: Option<&str>) -> Result<Vec<BatteryPackSummary>> {
-    let client = http_client();
-
-    // Build the search URL with keyword filter
-    let url = match filter {
-        Some(q) => format!(
-            "{CRATES_IO_API}?q={}&keyword=battery-pack&per_page=50",
-            urlencoding::encode(q)
+// [impl cli.list.paginate]
+fn fetch_battery_pack_list_from_registry(
+    client: &dyn RegistryClient,
+    query: &ListQuery,
+) -> Result<Vec<BatteryPackSummary>> {
+    // Build the search URL. `all_keywords` takes a space-separated list, so
+    // an extra keyword filter is applied server-side alongside `battery-pack`.
+    let api = &endpoints().api;
+    let mut base = match &query.keyword {
+        Some(k) => format!(
+            "{api}?all_keywords={}&per_page={SEARCH_PAGE_SIZE}",
+            urlencoding::encode(&format!("battery-pack {}", k))
         ),
-        None => format!("{CRATES_IO_API}?keyword=battery-pack&per_page=50"),
+        None => format!("{api}?keyword=battery-pack&per_page={SEARCH_PAGE_SIZE}"),
     };
+    if let Some(q) = &query.filter {
+        base.push_str(&format!("&q={}", urlencoding::encode(q)));
+    }
 
-    let response = client
-        .get(&url)
-        .send()
-        .context("Failed to query crates.io")?;
+    let crates = collect_search_pages(|page| {
+        let url = format!("{base}&page={page}");
+        let response = client.get(&url).context("Failed to query crates.io")?;
 
-    if !response.status().is_success() {
-        bail!(
-            "Failed to list battery packs (status: {})",
-            response.status()
-        );
-    }
+        if !response.is_success() {
+            bail!("Failed to list battery packs (status: {})", response.status);
+        }
 
-    let parsed: SearchResponse = response.json().context("Failed to parse response")?;
+        response.json().context("Failed to parse response")
+    })?;
 
     // Filter to only crates whose name ends with "-battery-pack"
-    let battery_packs = parsed
-        .crates
+    let battery_packs = crates
         .into_iter()
         .filter(|c| c.name.ends_with("-battery-pack"))
         .map(|c| BatteryPackSummary {
@@ -494,14 +846,61 @@ fn fetch_battery_pack_list_from_registry(filter: Option<&str>) -> Result<Vec<Bat
             name: c.name,
             version: c.max_version,
             description: c.description.unwrap_or_default(),
+            downloads: c.downloads,
+            recent_downloads: c.recent_downloads,
+            updated_at: c.updated_at,
+            keywords: c.keywords,
+            categories: c.categories,
         })
         .collect();
 
     Ok(battery_packs)
 }
 
+/// Look up keywords and categories the search results left out (crates.io
+/// never includes them there) from each pack's own endpoint. A pack whose
+/// lookup fails keeps `None`, which the output reports as unknown.
+// [impl cli.list.tags]
+fn fill_missing_tags(client: &dyn RegistryClient, packs: &mut [BatteryPackSummary]) {
+    let missing: Vec<usize> = (0..packs.len())
+        .filter(|&i| packs[i].keywords.is_none() || packs[i].categories.is_none())
+        .collect();
+    let names: Vec<&str> = missing.iter().map(|&i| packs[i].name.as_str()).collect();
+    let tags = batch::parallel_map(&names, batch::MAX_FETCH_WORKERS, |name| {
+        let url = format!("{}/{}", endpoints().api, name);
+        let response = client.get(&url).ok().filter(|r| r.is_success())?;
+        response.json::<CrateTagsResponse>().ok().map(|r| r.krate)
+    });
+    for (i, tags) in missing.into_iter().zip(tags) {
+        let Some(tags) = tags else { continue };
+        let pack = &mut packs[i];
+        pack.keywords = pack.keywords.take().or(tags.keywords);
+        pack.categories = pack.categories.take().or(tags.categories);
+    }
+}
+
+/// Fetch search pages (1-based) until the registry runs out of results.
+///
+/// Stops on a short page, once `meta.total` results have been seen (when the
+/// registry reports it), or after [`MAX_SEARCH_PAGES`] pages.
+fn collect_search_pages(
+    mut fetch_page: impl FnMut(u32) -> Result<SearchResponse>,
+) -> Result<Vec<SearchCrate>> {
+    let mut all = Vec::new();
+    for page in 1..=MAX_SEARCH_PAGES {
+        let response = fetch_page(page)?;
+        let count = response.crates.len();
+        all.extend(response.crates);
+        let total = response.meta.total;
+        if count < SEARCH_PAGE_SIZE || (total > 0 && all.len() as u64 >= total) {
+            break;
+        }
+    }
+    Ok(all)
+}
+
 pub(crate) fn update_cache() -> Result<()> {
-    let packs = fetch_battery_pack_list_from_registry(None)?;
+    let packs = fetch_battery_pack_list_from_registry(client()?, &ListQuery::default())?;
     let pack_names: Vec<String> = packs.into_iter().map(|p| p.name).collect();
 
     let cache_dir = crate::completions::get_cache_dir();
@@ -515,7 +914,7 @@ pub(crate) fn update_cache() -> Result<()> {
 
 pub(crate) fn discover_local_battery_packs(
     workspace_dir: &Path,
-    filter: Option<&str>,
+    query: &ListQuery,
 ) -> Result<Vec<BatteryPackSummary>> {
     let manifest_path = workspace_dir.join("Cargo.toml");
     let metadata = cargo_metadata::MetadataCommand::new()
@@ -528,18 +927,24 @@ pub(crate) fn discover_local_battery_packs(
         .packages
         .iter()
         .filter(|pkg| pkg.name.ends_with("-battery-pack"))
-        .filter(|pkg| {
-            if let Some(q) = filter {
-                short_name(&pkg.name).contains(q)
-            } else {
-                true
-            }
+        .filter(|pkg| match &query.filter {
+            Some(q) => short_name(&pkg.name).contains(q.as_str()),
+            None => true,
+        })
+        .filter(|pkg| match &query.keyword {
+            Some(k) => pkg.keywords.iter().any(|kw| kw == k),
+            None => true,
         })
         .map(|pkg| BatteryPackSummary {
             short_name: short_name(&pkg.name).to_string(),
             name: pkg.name.to_string(),
             version: pkg.version.to_string(),
             description: pkg.description.clone().unwrap_or_default(),
+            downloads: 0,
+            recent_downloads: None,
+            updated_at: None,
+            keywords: Some(pkg.keywords.clone()),
+            categories: Some(pkg.categories.clone()),
         })
         .collect();
 
@@ -623,6 +1028,15 @@ pub(crate) fn short_name(crate_name: &str) -> &str {
         .unwrap_or(crate_name)
 }
 
+/// Format a download count compactly: 950, 12.3k, 4.5M.
+pub(crate) fn format_downloads(n: u64) -> String {
+    match n {
+        0..1_000 => n.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", n as f64 / 1_000.0),
+        _ => format!("{:.1}M", n as f64 / 1_000_000.0),
+    }
+}
+
 /// Convert "cli" to "cli-battery-pack" (adds suffix if not already present)
 /// Special case: "battery-pack" stays as "battery-pack" (not "battery-pack-battery-pack")
 // [impl cli.name.resolve]
@@ -660,10 +1074,17 @@ pub(crate) fn fetch_battery_pack_detail(
     let spec = bphelper_manifest::parse_battery_pack(&manifest_content)
         .map_err(|e| anyhow::anyhow!("Failed to parse battery pack: {}", e))?;
 
-    // Fetch owners from crates.io
-    let owners = fetch_owners(&crate_name)?;
+    // Fetch owners from crates.io. They're only informational, so a failed
+    // lookup is a warning rather than an error.
+    let (owners, warning) = match client().and_then(|client| fetch_owners(client, &crate_name)) {
+        Ok(owners) => (owners, None),
+        Err(e) => (Vec::new(), Some(format!("{e:#}"))),
+    };
 
-    build_battery_pack_detail(&crate_dir, &spec, owners)
+    let vcs = read_vcs_info(&crate_dir);
+    let mut detail = build_battery_pack_detail(&crate_dir, &spec, owners, vcs)?;
+    detail.warnings.extend(warning);
+    Ok(detail)
 }
 
 /// Fetch detailed battery pack info from a local path
@@ -676,17 +1097,19 @@ fn fetch_battery_pack_detail_from_path(path: &str) -> Result<BatteryPackDetail>
     let spec = bphelper_manifest::parse_battery_pack(&manifest_content)
         .map_err(|e| anyhow::anyhow!("Failed to parse battery pack: {}", e))?;
 
-    build_battery_pack_detail(crate_dir, &spec, Vec::new())
+    build_battery_pack_detail(crate_dir, &spec, Vec::new(), read_vcs_location(crate_dir))
 }
 
 /// Build `BatteryPackDetail` from a parsed `BatteryPackSpec`.
 ///
-/// Derives extends/crates from the spec's crate keys, fetches repo tree for
-/// template path resolution, and scans for examples.
+/// Derives extends/crates from the spec's crate keys, resolves repository
+/// paths from `vcs` (where the crate lives in its repository), and scans
+/// for examples.
 pub(crate) fn build_battery_pack_detail(
     crate_dir: &Path,
     spec: &bphelper_manifest::BatteryPackSpec,
     owners: Vec<Owner>,
+    vcs: Option<VcsLocation>,
 ) -> Result<BatteryPackDetail> {
     // Split visible (non-hidden) crate keys into battery packs (extends) and regular crates
     // [impl format.hidden.effect]
@@ -701,17 +1124,16 @@ pub(crate) fn build_battery_pack_detail(
         .collect();
     let crates: Vec<String> = crates_raw.into_iter().map(|s| s.to_string()).collect();
 
-    // Fetch the GitHub repository tree to resolve paths
-    let repo_tree = spec.repository.as_ref().and_then(|r| fetch_github_tree(r));
-
-    // Convert templates with resolved repo paths
+    // Convert templates with resolved repo paths. Only link templates that
+    // actually shipped in the crate.
     let templates = spec
         .templates
         .iter()
         .map(|(name, tmpl)| {
-            let repo_path = repo_tree
+            let repo_path = vcs
                 .as_ref()
-                .and_then(|tree| find_template_path(tree, &tmpl.path));
+                .filter(|_| crate_dir.join(&tmpl.path).is_dir())
+                .map(|vcs| vcs.repo_path(&tmpl.path));
             TemplateInfo {
                 name: name.clone(),
                 path: tmpl.path.clone(),
@@ -722,7 +1144,7 @@ pub(crate) fn build_battery_pack_detail(
         .collect();
 
     // Scan examples directory
-    let examples = scan_examples(crate_dir, repo_tree.as_deref());
+    let examples = scan_examples(crate_dir, vcs.as_ref());
 
     // Build features map (sorted, visible crates only)
     let features: BTreeMap<String, Vec<String>> = spec
@@ -745,27 +1167,30 @@ pub(crate) fn build_battery_pack_detail(
         version: spec.version.clone(),
         description: spec.description.clone(),
         repository: spec.repository.clone(),
+        commit: vcs.and_then(|v| v.commit),
         owners: owners.into_iter().map(OwnerInfo::from).collect(),
         crates,
         extends,
         features,
         templates,
         examples,
+        warnings: Vec::new(),
     })
 }
 
-fn fetch_owners(crate_name: &str) -> Result<Vec<Owner>> {
-    let client = http_client();
-
-    let url = format!("{}/{}/owners", CRATES_IO_API, crate_name);
+// [impl cli.http.errors]
+fn fetch_owners(client: &dyn RegistryClient, crate_name: &str) -> Result<Vec<Owner>> {
+    let url = format!("{}/{}/owners", endpoints().api, crate_name);
     let response = client
         .get(&url)
-        .send()
         .with_context(|| format!("Failed to fetch owners for '{}'", crate_name))?;
 
-    if !response.status().is_success() {
-        // Not fatal - just return empty
-        return Ok(Vec::new());
+    if !response.is_success() {
+        bail!(
+            "Failed to fetch owners for '{}' (status: {})",
+            crate_name,
+            response.status
+        );
     }
 
     let parsed: OwnersResponse = response
@@ -775,7 +1200,7 @@ fn fetch_owners(crate_name: &str) -> Result<Vec<Owner>> {
     Ok(parsed.users)
 }
 
-fn scan_examples(crate_dir: &std::path::Path, repo_tree: Option<&[String]>) -> Vec<ExampleInfo> {
+fn scan_examples(crate_dir: &std::path::Path, vcs: Option<&VcsLocation>) -> Vec<ExampleInfo> {
     let examples_dir = crate_dir.join("examples");
     if !examples_dir.exists() {
         return Vec::new();
@@ -790,7 +1215,7 @@ fn scan_examples(crate_dir: &std::path::Path, repo_tree: Option<&[String]>) -> V
                 && let Some(name) = path.file_stem().and_then(|s| s.to_str())
             {
                 let description = extract_example_description(&path);
-                let repo_path = repo_tree.and_then(|tree| find_example_path(tree, name));
+                let repo_path = vcs.map(|vcs| vcs.repo_path(&format!("examples/{}.rs", name)));
                 examples.push(ExampleInfo {
                     name: name.to_string(),
                     description,
@@ -825,49 +1250,6 @@ fn extract_example_description(path: &std::path::Path) -> Option<String> {
     None
 }
 
-fn fetch_github_tree(repository: &str) -> Option<Vec<String>> {
-    // Parse GitHub URL: https://github.com/owner/repo
-    let gh_path = repository
-        .strip_prefix("https://github.com/")
-        .or_else(|| repository.strip_prefix("http://github.com/"))?;
-    let gh_path = gh_path.strip_suffix(".git").unwrap_or(gh_path);
-    let gh_path = gh_path.trim_end_matches('/');
-
-    let client = http_client();
-
-    // Fetch the tree recursively using the main branch
-    let url = format!(
-        "https://api.github.com/repos/{}/git/trees/main?recursive=1",
-        gh_path
-    );
-
-    let response = client.get(&url).send().ok()?;
-    if !response.status().is_success() {
-        return None;
-    }
-
-    let tree_response: GitHubTreeResponse = response.json().ok()?;
-
-    // Extract all paths (both blobs/files and trees/directories)
-    Some(tree_response.tree.into_iter().map(|e| e.path).collect())
-}
-
-/// Find the full repository path for an example file.
-/// Searches the tree for a file matching "examples/{name}.rs".
-pub(crate) fn find_example_path(tree: &[String], example_name: &str) -> Option<String> {
-    let suffix = format!("examples/{}.rs", example_name);
-    tree.iter().find(|path| path.ends_with(&suffix)).cloned()
-}
-
-/// Find the full repository path for a template directory.
-/// Searches the tree for a path matching "templates/{name}" or "{name}".
-pub(crate) fn find_template_path(tree: &[String], template_path: &str) -> Option<String> {
-    // The template path from config might be "templates/simple" or just the relative path
-    tree.iter()
-        .find(|path| path.ends_with(template_path))
-        .cloned()
-}
-
 /// A resolved battery pack crate directory. Owns the temp dir (if any) to keep it alive.
 pub(crate) struct ResolvedCrate {
     pub dir: PathBuf,
@@ -16,7 +16,7 @@
 //   - cli.list.query           — lists available battery packs
 //   - cli.list.filter          — filters results by name pattern
 
-use super::CrateSource;
+use super::{CrateSource, ListQuery, ListSort};
 use snapbox::{assert_data_eq, str};
 use std::path::PathBuf;
 
@@ -32,6 +32,13 @@ fn fixtures_dir() -> PathBuf {
         .join("tests/fixtures")
 }
 
+fn filter(q: &str) -> ListQuery {
+    ListQuery {
+        filter: Some(q.to_string()),
+        ..ListQuery::default()
+    }
+}
+
 fn format_summaries(packs: &[super::BatteryPackSummary]) -> String {
     packs
         .iter()
@@ -45,7 +52,7 @@ fn format_summaries(packs: &[super::BatteryPackSummary]) -> String {
 #[test]
 fn list_discovers_local_battery_packs() {
     let source = CrateSource::Local(fixtures_dir());
-    let packs = super::fetch_battery_pack_list(&source, None).unwrap();
+    let packs = super::fetch_battery_pack_list(&source, &ListQuery::default()).unwrap();
     let formatted = format_summaries(&packs);
     assert!(
         formatted.contains("basic-battery-pack"),
@@ -73,7 +80,7 @@ managed-battery-pack 0.2.0 -- A test battery pack for bp-managed resolution"#]]
 #[test]
 fn list_filter_narrows_results() {
     let source = CrateSource::Local(fixtures_dir());
-    let packs = super::fetch_battery_pack_list(&source, Some("basic")).unwrap();
+    let packs = super::fetch_battery_pack_list(&source, &filter("basic")).unwrap();
     let formatted = format_summaries(&packs);
     assert_eq!(packs.len(), 1, "Expected exactly 1 result");
     assert_data_eq!(
@@ -86,7 +93,7 @@ fn list_filter_narrows_results() {
 #[test]
 fn list_filter_no_match_returns_empty() {
     let source = CrateSource::Local(fixtures_dir());
-    let packs = super::fetch_battery_pack_list(&source, Some("nonexistent")).unwrap();
+    let packs = super::fetch_battery_pack_list(&source, &filter("nonexistent")).unwrap();
     assert!(packs.is_empty());
 }
 
@@ -94,7 +101,7 @@ fn list_filter_no_match_returns_empty() {
 #[test]
 fn list_invalid_workspace_path_errors() {
     let source = CrateSource::Local(PathBuf::from("/nonexistent/path"));
-    let result = super::fetch_battery_pack_list(&source, None);
+    let result = super::fetch_battery_pack_list(&source, &ListQuery::default());
     assert!(result.is_err());
 }
 
@@ -102,7 +109,7 @@ fn list_invalid_workspace_path_errors() {
 #[test]
 fn list_short_names_are_correct() {
     let source = CrateSource::Local(fixtures_dir());
-    let packs = super::fetch_battery_pack_list(&source, None).unwrap();
+    let packs = super::fetch_battery_pack_list(&source, &ListQuery::default()).unwrap();
     let short_names: Vec<&str> = packs.iter().map(|bp| bp.short_name.as_str()).collect();
     assert_eq!(short_names.len(), 4, "Expected 4 packs");
     assert!(short_names.contains(&"basic"), "Expected 'basic'");
@@ -111,6 +118,147 @@ fn list_short_names_are_correct() {
     assert!(short_names.contains(&"managed"), "Expected 'managed'");
 }
 
+// [verify cli.list.keyword]
+#[test]
+fn list_keyword_filters_local_packs() {
+    let source = CrateSource::Local(fixtures_dir());
+    let query = ListQuery {
+        keyword: Some("battery-pack".to_string()),
+        ..ListQuery::default()
+    };
+    let packs = super::fetch_battery_pack_list(&source, &query).unwrap();
+    let names: Vec<&str> = packs.iter().map(|bp| bp.short_name.as_str()).collect();
+    // broken-battery-pack deliberately omits the keyword.
+    assert_eq!(names, ["basic", "fancy", "managed"]);
+    assert!(
+        packs[0]
+            .keywords
+            .as_ref()
+            .is_some_and(|k| k.contains(&"battery-pack".to_string()))
+    );
+
+    let query = ListQuery {
+        keyword: Some("no-such-keyword".to_string()),
+        ..ListQuery::default()
+    };
+    assert!(
+        super::fetch_battery_pack_list(&source, &query)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+fn summary(name: &str, downloads: u64, recent: Option<u64>) -> super::BatteryPackSummary {
+    super::BatteryPackSummary {
+        name: format!("{name}-battery-pack"),
+        short_name: name.to_string(),
+        version: "0.1.0".to_string(),
+        description: String::new(),
+        downloads,
+        recent_downloads: recent,
+        updated_at: None,
+        keywords: Some(Vec::new()),
+        categories: Some(Vec::new()),
+    }
+}
+
+fn sorted_names(sort: ListSort) -> Vec<String> {
+    let mut packs = vec![
+        summary("cli", 500, Some(10)),
+        summary("async", 500, Some(300)),
+        summary("web", 9000, None),
+    ];
+    sort.apply(&mut packs);
+    packs.into_iter().map(|bp| bp.short_name).collect()
+}
+
+// [verify cli.list.sort]
+#[test]
+fn list_sort_orders() {
+    assert_eq!(sorted_names(ListSort::Downloads), ["web", "async", "cli"]);
+    assert_eq!(sorted_names(ListSort::Recent), ["async", "cli", "web"]);
+    assert_eq!(sorted_names(ListSort::Name), ["async", "cli", "web"]);
+}
+
+// [verify cli.list.sort]
+#[test]
+fn list_sort_cycles_through_all_orders() {
+    let mut sort = ListSort::default();
+    assert_eq!(sort, ListSort::Downloads);
+    for expected in [ListSort::Recent, ListSort::Name, ListSort::Downloads] {
+        sort = sort.next();
+        assert_eq!(sort, expected);
+    }
+}
+
+fn search_page(names: std::ops::Range<usize>, total: u64) -> super::SearchResponse {
+    let crates = names
+        .map(|i| {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("p{i}-battery-pack"),
+                "max_version": "1.0.0",
+                "description": null,
+                "downloads": i,
+                "recent_downloads": null,
+                "updated_at": "2026-01-02T03:04:05.000000+00:00",
+                "keywords": null,
+                "categories": null,
+            }))
+            .unwrap()
+        })
+        .collect();
+    super::SearchResponse {
+        crates,
+        meta: super::SearchMeta { total },
+    }
+}
+
+// [verify cli.list.paginate]
+#[test]
+fn list_pagination_follows_pages_until_short_page() {
+    let page_size = super::SEARCH_PAGE_SIZE;
+    let mut requested = Vec::new();
+    let crates = super::collect_search_pages(|page| {
+        requested.push(page);
+        let start = (page as usize - 1) * page_size;
+        let len = if page < 3 { page_size } else { 7 };
+        Ok(search_page(start..start + len, 0))
+    })
+    .unwrap();
+
+    assert_eq!(requested, [1, 2, 3]);
+    assert_eq!(crates.len(), 2 * page_size + 7);
+}
+
+// [verify cli.list.paginate]
+#[test]
+fn list_pagination_stops_at_reported_total() {
+    let page_size = super::SEARCH_PAGE_SIZE;
+    let total = 2 * page_size as u64;
+    let mut requested = 0;
+    let crates = super::collect_search_pages(|page| {
+        requested += 1;
+        let start = (page as usize - 1) * page_size;
+        Ok(search_page(start..start + page_size, total))
+    })
+    .unwrap();
+
+    assert_eq!(requested, 2, "must not request an empty third page");
+    assert_eq!(crates.len() as u64, total);
+}
+
+// [verify cli.list.paginate]
+#[test]
+fn list_pagination_propagates_errors() {
+    let result = super::collect_search_pages(|page| {
+        if page == 2 {
+            anyhow::bail!("boom");
+        }
+        Ok(search_page(0..super::SEARCH_PAGE_SIZE, 1000))
+    });
+    assert!(result.is_err());
+}
+
 // --- from show.rs ---
 
 // Integration tests for `cargo bp show`.
@@ -152,6 +300,133 @@ fn show_detail_no_hidden_returns_all_crates() {
     assert_eq!(detail.crates.len(), 3);
 }
 
+/// A published-looking battery pack: one packaged template, one template
+/// that was excluded from the tarball, and an example.
+fn write_packaged_pack(dir: &Path, vcs_info: Option<&str>) {
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        r#"[package]
+name = "vcs-battery-pack"
+version = "1.0.0"
+repository = "https://github.com/example/packs"
+
+[dependencies]
+anyhow = "1"
+
+[package.metadata.battery.templates]
+simple = { path = "templates/simple" }
+missing = { path = "templates/missing" }
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(dir.join("templates/simple")).unwrap();
+    std::fs::create_dir_all(dir.join("examples")).unwrap();
+    std::fs::write(dir.join("examples/demo.rs"), "//! Demo\nfn main() {}\n").unwrap();
+    if let Some(info) = vcs_info {
+        std::fs::write(dir.join(".cargo_vcs_info.json"), info).unwrap();
+    }
+}
+
+fn template_repo_path<'a>(detail: &'a super::BatteryPackDetail, name: &str) -> Option<&'a str> {
+    detail
+        .templates
+        .iter()
+        .find(|t| t.name == name)
+        .and_then(|t| t.repo_path.as_deref())
+}
+
+// [verify cli.show.vcs-links]
+#[test]
+fn show_detail_links_come_from_cargo_vcs_info() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_packaged_pack(
+        tmp.path(),
+        Some(
+            r#"{
+  "git": { "sha1": "0123456789abcdef0123456789abcdef01234567" },
+  "path_in_vcs": "packs/vcs-battery-pack"
+}"#,
+        ),
+    );
+
+    let detail = super::fetch_battery_pack_detail("vcs", tmp.path().to_str()).unwrap();
+
+    assert_eq!(
+        detail.commit.as_deref(),
+        Some("0123456789abcdef0123456789abcdef01234567")
+    );
+    assert_eq!(
+        template_repo_path(&detail, "simple"),
+        Some("packs/vcs-battery-pack/templates/simple")
+    );
+    assert_eq!(
+        template_repo_path(&detail, "missing"),
+        None,
+        "templates not in the tarball must not be linked"
+    );
+    assert_eq!(
+        detail.examples[0].repo_path.as_deref(),
+        Some("packs/vcs-battery-pack/examples/demo.rs")
+    );
+}
+
+// [verify cli.show.vcs-links]
+#[test]
+fn show_detail_links_from_repo_root_crate() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_packaged_pack(
+        tmp.path(),
+        Some(r#"{"git": {"sha1": "abc123", "dirty": true}, "path_in_vcs": ""}"#),
+    );
+
+    let detail = super::fetch_battery_pack_detail("vcs", tmp.path().to_str()).unwrap();
+
+    assert_eq!(
+        template_repo_path(&detail, "simple"),
+        Some("templates/simple")
+    );
+    assert_eq!(detail.commit.as_deref(), Some("abc123"));
+}
+
+// [verify cli.show.vcs-links]
+#[test]
+fn show_detail_local_checkout_uses_git_work_tree() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tmp.path().join(".git")).unwrap();
+    let pack_dir = tmp.path().join("src/vcs-battery-pack");
+    std::fs::create_dir_all(&pack_dir).unwrap();
+    write_packaged_pack(&pack_dir, None);
+
+    let detail = super::fetch_battery_pack_detail("vcs", pack_dir.to_str()).unwrap();
+
+    assert_eq!(detail.commit, None, "no commit is pinned for a checkout");
+    assert_eq!(
+        template_repo_path(&detail, "simple"),
+        Some("src/vcs-battery-pack/templates/simple")
+    );
+}
+
+// [verify cli.show.vcs-links]
+#[test]
+fn vcs_location_without_repository_is_none() {
+    let tmp = tempfile::tempdir().unwrap();
+    // A tempdir is not inside a git work tree, and has no vcs info.
+    assert_eq!(super::read_vcs_location(tmp.path()), None);
+}
+
+// [verify cli.show.vcs-links]
+#[test]
+fn downloaded_crate_ignores_enclosing_git_work_tree() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tmp.path().join(".git")).unwrap();
+    let pack_dir = tmp.path().join("registry/src/vcs-battery-pack-0.1.0");
+    std::fs::create_dir_all(&pack_dir).unwrap();
+    write_packaged_pack(&pack_dir, None);
+
+    assert_eq!(super::read_vcs_info(&pack_dir), None);
+    assert!(super::read_vcs_location(&pack_dir).is_some());
+}
+
 // --- from bp_managed.rs ---
 
 // Tests for bp-managed dependency resolution.
@@ -464,3 +739,463 @@ managed-battery-pack = { features = ["default"] }
 "#]]
     );
 }
+
+// --- HTTP client ---
+
+// Tests for the `RegistryClient` layer: retries, auth, and registry
+// functions running against an in-memory client.
+
+use super::client::{
+    Attempt, AuthRule, HttpResponse, RetryPolicy, auth_header_for, auth_rules_from_env,
+    send_with_retries,
+};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+fn response(status: u16, body: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: body.as_bytes().to_vec(),
+        retry_after: None,
+    }
+}
+
+/// Serves canned responses by URL and records every request.
+#[derive(Default)]
+struct MemoryClient {
+    responses: BTreeMap<String, HttpResponse>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl MemoryClient {
+    fn with(mut self, url: &str, status: u16, body: &str) -> Self {
+        self.responses
+            .insert(url.to_string(), response(status, body));
+        self
+    }
+}
+
+impl super::RegistryClient for MemoryClient {
+    fn get(&self, url: &str) -> anyhow::Result<HttpResponse> {
+        self.requests.lock().unwrap().push(url.to_string());
+        Ok(self
+            .responses
+            .get(url)
+            .cloned()
+            .unwrap_or_else(|| response(404, "")))
+    }
+}
+
+/// Run `send_with_retries` over a scripted sequence of attempts, returning
+/// the result and the delays it slept for.
+fn run_script(script: Vec<Attempt>) -> (anyhow::Result<HttpResponse>, Vec<Duration>, usize) {
+    let mut script = script.into_iter();
+    let mut attempts = 0;
+    let mut sleeps = Vec::new();
+    let result = send_with_retries(
+        &RetryPolicy::default(),
+        |d| sleeps.push(d),
+        || {
+            attempts += 1;
+            script.next().expect("script ran out of attempts")
+        },
+    );
+    (result, sleeps, attempts)
+}
+
+// [verify cli.http.retry]
+#[test]
+fn retries_server_errors_with_exponential_backoff() {
+    let (result, sleeps, attempts) = run_script(vec![
+        Attempt::Response(response(503, "")),
+        Attempt::Transient(anyhow::anyhow!("connection reset")),
+        Attempt::Response(response(200, "ok")),
+    ]);
+
+    assert_eq!(result.unwrap().status, 200);
+    assert_eq!(attempts, 3);
+    assert_eq!(
+        sleeps,
+        [Duration::from_millis(500), Duration::from_millis(1000)]
+    );
+}
+
+// [verify cli.http.retry]
+#[test]
+fn retry_after_is_honored_and_capped() {
+    let mut limited = response(429, "");
+    limited.retry_after = Some(Duration::from_secs(2));
+    let mut very_limited = response(429, "");
+    very_limited.retry_after = Some(Duration::from_secs(3600));
+
+    let (result, sleeps, _) = run_script(vec![
+        Attempt::Response(limited),
+        Attempt::Response(very_limited),
+        Attempt::Response(response(200, "")),
+    ]);
+
+    assert!(result.is_ok());
+    assert_eq!(sleeps, [Duration::from_secs(2), Duration::from_secs(30)]);
+}
+
+// [verify cli.http.retry]
+#[test]
+fn client_errors_and_fatal_failures_are_not_retried() {
+    let (result, sleeps, _) = run_script(vec![Attempt::Response(response(404, ""))]);
+    assert_eq!(result.unwrap().status, 404);
+    assert!(sleeps.is_empty());
+
+    let (result, sleeps, _) = run_script(vec![Attempt::Fatal(anyhow::anyhow!("bad url"))]);
+    assert!(result.is_err());
+    assert!(sleeps.is_empty());
+}
+
+// [verify cli.http.retry]
+#[test]
+fn retries_give_up_after_the_limit() {
+    let (result, sleeps, attempts) = run_script(
+        (0..4)
+            .map(|_| Attempt::Response(response(502, "")))
+            .collect(),
+    );
+    assert_eq!(result.unwrap().status, 502, "last response is returned");
+    assert_eq!(attempts, 4);
+    assert_eq!(sleeps.len(), 3);
+
+    let (result, _, attempts) = run_script(
+        (0..4)
+            .map(|_| Attempt::Transient(anyhow::anyhow!("timed out")))
+            .collect(),
+    );
+    assert!(result.is_err());
+    assert_eq!(attempts, 4);
+}
+
+// [verify cli.http.auth]
+#[test]
+fn auth_tokens_only_go_to_their_hosts() {
+    let env = |var: &str| match var {
+        "CARGO_BP_REGISTRY_TOKEN" => Some("bp-secret".to_string()),
+        _ => None,
+    };
+    let rules = auth_rules_from_env(env, &["https://crates.example.com/api/v1/crates"]);
+
+    assert_eq!(
+        auth_header_for(&rules, "https://api.github.com/repos/o/r"),
+        None
+    );
+    assert_eq!(
+        auth_header_for(&rules, "https://crates.example.com/api/v1/crates/foo"),
+        Some("bp-secret")
+    );
+    assert_eq!(
+        auth_header_for(&rules, "https://crates.example.com/api/v1/crates-evil/foo"),
+        None
+    );
+    assert_eq!(auth_header_for(&rules, "https://gitlab.com/o/r"), None);
+}
+
+// [verify cli.http.auth]
+#[test]
+fn default_endpoints_get_no_token() {
+    let env = |var: &str| match var {
+        "CARGO_BP_REGISTRY_TOKEN" => Some("bp-secret".to_string()),
+        "CARGO_REGISTRY_TOKEN" => Some("publish-secret".to_string()),
+        _ => None,
+    };
+    let endpoints = super::RegistryEndpoints::new(None, None);
+    let rules = auth_rules_from_env(env, &endpoints.token_endpoints());
+
+    assert_eq!(
+        auth_header_for(&rules, "https://crates.io/api/v1/crates/demo-battery-pack"),
+        None
+    );
+    assert_eq!(
+        auth_header_for(
+            &rules,
+            "https://static.crates.io/crates/demo/demo-1.0.0.crate"
+        ),
+        None
+    );
+}
+
+// [verify cli.http.auth]
+#[test]
+fn registry_token_skips_download_endpoint() {
+    let env = |var: &str| match var {
+        "CARGO_BP_REGISTRY_TOKEN" => Some("bp-secret".to_string()),
+        _ => None,
+    };
+    let endpoints = super::RegistryEndpoints::new(
+        Some("https://crates.example.com/api/v1/crates/"),
+        Some("https://cdn.example.com/crates"),
+    );
+    let rules = auth_rules_from_env(env, &endpoints.token_endpoints());
+
+    assert_eq!(
+        auth_header_for(&rules, "https://crates.example.com/api/v1/crates/foo"),
+        Some("bp-secret")
+    );
+    assert_eq!(
+        auth_header_for(&rules, "https://cdn.example.com/crates/foo/foo-1.0.0.crate"),
+        None
+    );
+}
+
+// [verify cli.http.auth]
+#[test]
+fn cargo_publish_token_is_never_sent() {
+    let env = |var: &str| match var {
+        "CARGO_REGISTRY_TOKEN" => Some("publish-secret".to_string()),
+        _ => None,
+    };
+    let rules = auth_rules_from_env(env, &["https://crates.example.com/api/v1/crates"]);
+    assert!(rules.is_empty());
+}
+
+// [verify cli.http.auth]
+#[test]
+fn no_tokens_means_no_auth() {
+    let rules: Vec<AuthRule> = auth_rules_from_env(|_| None, &["https://crates.io/api/v1/crates"]);
+    assert!(rules.is_empty());
+}
+
+// [verify cli.http.errors]
+#[test]
+fn lookup_crate_goes_through_the_client() {
+    let client = MemoryClient::default().with(
+        "https://crates.io/api/v1/crates/demo-battery-pack",
+        200,
+        r#"{"versions": [{"num": "2.0.0", "yanked": true}, {"num": "1.5.0", "yanked": false}]}"#,
+    );
+
+    let meta = super::lookup_crate_with(&client, "demo-battery-pack").unwrap();
+
+    assert_eq!(meta.version, "1.5.0");
+    assert_eq!(
+        *client.requests.lock().unwrap(),
+        ["https://crates.io/api/v1/crates/demo-battery-pack"]
+    );
+}
+
+// [verify cli.http.errors]
+#[test]
+fn owner_lookup_failures_are_reported() {
+    let client = MemoryClient::default().with(
+        "https://crates.io/api/v1/crates/demo-battery-pack/owners",
+        500,
+        "",
+    );
+    let Err(err) = super::fetch_owners(&client, "demo-battery-pack") else {
+        panic!("expected owner lookup to fail");
+    };
+    assert!(err.to_string().contains("status: 500"), "{err:#}");
+
+    let client = MemoryClient::default().with(
+        "https://crates.io/api/v1/crates/demo-battery-pack/owners",
+        200,
+        r#"{"users": [{"login": "alice", "name": null}]}"#,
+    );
+    let owners = super::fetch_owners(&client, "demo-battery-pack").unwrap();
+    assert_eq!(owners.len(), 1);
+}
+
+// [verify cli.list.paginate]
+#[test]
+fn registry_listing_pages_through_the_client() {
+    let base = "https://crates.io/api/v1/crates?keyword=battery-pack&per_page=100";
+    let first_page: Vec<_> = (0..super::SEARCH_PAGE_SIZE)
+        .map(|i| format!(r#"{{"name": "p{i}-battery-pack", "max_version": "1.0.0", "description": null, "downloads": {i}}}"#))
+        .collect();
+    let client = MemoryClient::default()
+        .with(
+            &format!("{base}&page=1"),
+            200,
+            &format!(r#"{{"crates": [{}], "meta": {{"total": 101}}}}"#, first_page.join(",")),
+        )
+        .with(
+            &format!("{base}&page=2"),
+            200,
+            r#"{"crates": [{"name": "last-battery-pack", "max_version": "0.1.0", "description": "Last", "downloads": 1000}, {"name": "not-a-pack", "max_version": "1.0.0", "description": null}], "meta": {"total": 101}}"#,
+        );
+
+    let packs =
+        super::fetch_battery_pack_list_from_registry(&client, &ListQuery::default()).unwrap();
+
+    assert_eq!(client.requests.lock().unwrap().len(), 2);
+    assert_eq!(packs.len(), super::SEARCH_PAGE_SIZE + 1);
+    assert!(packs.iter().all(|p| p.name.ends_with("-battery-pack")));
+}
+
+// [verify cli.list.tags]
+#[test]
+fn missing_tags_are_looked_up_per_crate() {
+    let client = MemoryClient::default().with(
+        "https://crates.io/api/v1/crates/a-battery-pack",
+        200,
+        r#"{"crate": {"keywords": ["battery-pack", "cli"], "categories": ["command-line-utilities"]}}"#,
+    );
+    let mut packs = vec![summary("a", 0, None), summary("b", 0, None)];
+    for pack in &mut packs {
+        pack.keywords = None;
+        pack.categories = None;
+    }
+    let mut reported = summary("c", 0, None);
+    reported.keywords = Some(vec!["battery-pack".to_string()]);
+    packs.push(reported);
+
+    super::fill_missing_tags(&client, &mut packs);
+
+    assert_eq!(
+        packs[0].keywords.as_deref(),
+        Some(&["battery-pack".to_string(), "cli".to_string()][..])
+    );
+    assert_eq!(
+        packs[0].categories.as_deref(),
+        Some(&["command-line-utilities".to_string()][..])
+    );
+    // b's lookup fails: still unknown rather than empty.
+    assert!(packs[1].keywords.is_none());
+    // c came with its tags; nothing to look up.
+    assert_eq!(client.requests.lock().unwrap().len(), 2);
+}
+
+// --- pack release status ---
+
+fn published(versions: &[(&str, bool)]) -> Vec<super::PublishedVersion> {
+    versions
+        .iter()
+        .map(|(num, yanked)| super::PublishedVersion {
+            num: num.to_string(),
+            yanked: *yanked,
+        })
+        .collect()
+}
+
+// [verify cli.status.pack-yanked]
+#[test]
+fn pack_status_flags_yanked_install() {
+    let versions = published(&[("0.4.2", false), ("0.4.1", true), ("0.4.0", false)]);
+
+    let status = super::pack_version_status("0.4", "0.4.1", &versions);
+    assert!(status.yanked);
+    assert!(!status.outdated, "0.4.2 still matches ^0.4");
+
+    let status = super::pack_version_status("0.4", "0.4.2", &versions);
+    assert!(!status.yanked);
+}
+
+// [verify cli.status.pack-outdated]
+#[test]
+fn pack_status_flags_incompatible_latest() {
+    let versions = published(&[
+        ("0.6.0", true),
+        ("0.5.1", false),
+        ("0.5.0-rc.1", false),
+        ("0.4.2", false),
+    ]);
+
+    let status = super::pack_version_status("0.4", "0.4.2", &versions);
+    assert_eq!(status.latest.as_deref(), Some("0.5.1"));
+    assert!(status.outdated);
+
+    let status = super::pack_version_status("0.5", "0.5.1", &versions);
+    assert!(!status.outdated, "yanked and pre-releases don't count");
+}
+
+#[test]
+fn pack_status_with_unparsable_requirement_is_not_outdated() {
+    let versions = published(&[("1.0.0", false)]);
+    let status = super::pack_version_status("not a version", "0.1.0", &versions);
+    assert_eq!(status.latest.as_deref(), Some("1.0.0"));
+    assert!(!status.outdated);
+}
+
+#[test]
+fn published_versions_keep_yanked_entries() {
+    let client = MemoryClient::default().with(
+        "https://crates.io/api/v1/crates/demo-battery-pack",
+        200,
+        r#"{"versions": [{"num": "2.0.0", "yanked": true}, {"num": "1.5.0", "yanked": false}]}"#,
+    );
+
+    let versions = super::fetch_published_versions_with(&client, "demo-battery-pack").unwrap();
+
+    assert_eq!(versions, published(&[("2.0.0", true), ("1.5.0", false)]));
+}
+
+// --- concurrent spec loading ---
+
+// [verify cli.fetch.parallel]
+#[test]
+fn parallel_map_keeps_order_and_bounds_workers() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let items: Vec<usize> = (0..32).collect();
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+
+    let results = super::batch::parallel_map(&items, 4, |n| {
+        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+        peak.fetch_max(now, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(2));
+        running.fetch_sub(1, Ordering::SeqCst);
+        n * 2
+    });
+
+    assert_eq!(results, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+    let peak = peak.into_inner();
+    assert!((1..=4).contains(&peak), "peak concurrency was {peak}");
+}
+
+#[test]
+fn parallel_map_handles_empty_input() {
+    let items: Vec<u8> = Vec::new();
+    assert!(super::batch::parallel_map(&items, 8, |n| *n).is_empty());
+}
+
+// [verify cli.fetch.memo]
+#[test]
+fn spec_memo_loads_each_release_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let memo = super::batch::SpecMemo::default();
+    let loads = AtomicUsize::new(0);
+    let load = |version: &str| {
+        loads.fetch_add(1, Ordering::SeqCst);
+        bphelper_manifest::parse_battery_pack(&format!(
+            "[package]\nname = \"demo-battery-pack\"\nversion = \"{version}\"\n"
+        ))
+        .map_err(|e| anyhow::anyhow!("{e}"))
+    };
+
+    let names: Vec<&str> = vec!["0.1.0"; 8];
+    super::batch::parallel_map(&names, 4, |version| {
+        memo.get_or_load("demo-battery-pack", version, || load(version))
+            .unwrap()
+    });
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    let spec = memo
+        .get_or_load("demo-battery-pack", "0.2.0", || load("0.2.0"))
+        .unwrap();
+    assert_eq!(spec.version, "0.2.0");
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn spec_memo_does_not_remember_failures() {
+    let memo = super::batch::SpecMemo::default();
+    assert!(
+        memo.get_or_load("demo-battery-pack", "0.1.0", || anyhow::bail!("offline"))
+            .is_err()
+    );
+    let spec = memo.get_or_load("demo-battery-pack", "0.1.0", || {
+        bphelper_manifest::parse_battery_pack(
+            "[package]\nname = \"demo-battery-pack\"\nversion = \"0.1.0\"\n",
+        )
+        .map_err(|e| anyhow::anyhow!("{e}"))
+    });
+    assert!(spec.is_ok());
+}
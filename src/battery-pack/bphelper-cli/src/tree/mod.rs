@@ -0,0 +1,464 @@
+//! Support for `cargo bp tree`: the whole project as installed packs, their
+//! active features, and the crates those features bring in.
+//!
+//! Nested packs (a pack curating another `*-battery-pack` crate) appear as
+//! children of the feature that enables them. Drift comes from the same
+//! [`crate::drift`] analysis `status` and `check` run, so the tree
+//! highlights exactly what they report.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::Result;
+use bphelper_manifest::{BatteryPackSpec, DepKind};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use crate::drift::{Finding, Severity};
+use crate::manifest::dep_kind_section;
+use crate::registry::short_name;
+
+/// Output format for `cargo bp tree`.
+// [impl cli.tree.formats]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum TreeFormat {
+    /// An indented tree.
+    #[default]
+    Text,
+    /// A Graphviz digraph.
+    Dot,
+    /// A JSON document, like `--message-format json` elsewhere.
+    Json,
+}
+
+/// A battery pack and what it contributes to the project.
+#[derive(Debug, Clone)]
+pub(crate) struct PackNode {
+    pub name: String,
+    /// The registered requirement, or the parent pack's requirement for a
+    /// nested pack.
+    pub version: String,
+    /// Active features, in the order the pack groups its crates.
+    pub features: Vec<FeatureNode>,
+    /// Crates the pack used to manage but no longer curates.
+    pub no_longer_curated: Vec<CrateNode>,
+    /// Why a nested pack's spec couldn't be loaded.
+    pub error: Option<String>,
+}
+
+/// An active feature and the crates it brings in.
+#[derive(Debug, Clone)]
+pub(crate) struct FeatureNode {
+    pub name: String,
+    pub crates: Vec<CrateNode>,
+    /// Nested battery packs this feature enables.
+    pub packs: Vec<PackNode>,
+}
+
+/// A curated crate as the pack recommends it and the project declares it.
+#[derive(Debug, Clone)]
+pub(crate) struct CrateNode {
+    pub name: String,
+    /// `None` for crates the pack no longer curates.
+    pub dep_kind: Option<DepKind>,
+    pub recommended: Option<String>,
+    /// The version in the project's manifest, if declared.
+    pub declared: Option<String>,
+    /// Drift findings for this crate; always empty inside nested packs.
+    pub findings: Vec<Finding>,
+}
+
+impl CrateNode {
+    /// The most severe finding's severity, if any.
+    pub(crate) fn severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+}
+
+/// An installed pack: its spec and how the project uses it.
+pub(crate) struct InstalledNode<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    pub spec: &'a BatteryPackSpec,
+    pub active_features: &'a BTreeSet<String>,
+    /// Drift findings from [`crate::drift::analyze`].
+    pub findings: &'a [Finding],
+}
+
+/// Nested packs are only followed this deep.
+const MAX_DEPTH: usize = 8;
+
+/// Build the tree for one installed pack. `declared` maps crate names to
+/// the versions the project's manifest declares; `load` fetches the specs
+/// of nested packs.
+// [impl cli.tree.structure]
+// [impl cli.tree.drift]
+pub(crate) fn build(
+    pack: &InstalledNode<'_>,
+    declared: &BTreeMap<String, String>,
+    load: &dyn Fn(&str) -> Result<BatteryPackSpec>,
+) -> PackNode {
+    let mut node = pack_node(
+        pack.name,
+        pack.version,
+        pack.spec,
+        pack.active_features,
+        declared,
+        pack.findings,
+        load,
+        &mut vec![pack.name.to_string()],
+    );
+    node.no_longer_curated = pack
+        .findings
+        .iter()
+        .filter(|f| f.kind == crate::drift::DriftKind::NoLongerCurated)
+        .filter_map(|f| {
+            let name = f.crate_name.clone()?;
+            Some(CrateNode {
+                declared: declared.get(&name).cloned(),
+                name,
+                dep_kind: None,
+                recommended: None,
+                findings: vec![f.clone()],
+            })
+        })
+        .collect();
+    node
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pack_node(
+    name: &str,
+    version: &str,
+    spec: &BatteryPackSpec,
+    active_features: &BTreeSet<String>,
+    declared: &BTreeMap<String, String>,
+    findings: &[Finding],
+    load: &dyn Fn(&str) -> Result<BatteryPackSpec>,
+    ancestors: &mut Vec<String>,
+) -> PackNode {
+    let resolved = spec.resolve_for_features(active_features);
+    let all = active_features.iter().any(|f| f == "all");
+    let is_active = |feature: &str| {
+        all || active_features.contains(feature)
+            || (feature == "default" && active_features.is_empty())
+    };
+
+    let mut features: Vec<FeatureNode> = Vec::new();
+    for (group, crate_name, _, _) in spec.all_crates_with_grouping() {
+        let Some(crate_spec) = resolved.get(&crate_name) else {
+            continue;
+        };
+        // A crate is grouped under the first feature listing it; if that
+        // one is off, show it under an active feature that brings it in.
+        let group = if is_active(&group) {
+            group
+        } else {
+            active_features
+                .iter()
+                .find(|f| {
+                    spec.features
+                        .get(*f)
+                        .is_some_and(|crates| crates.contains(&crate_name))
+                })
+                .cloned()
+                .unwrap_or(group)
+        };
+        let feature = match features.iter().position(|f| f.name == group) {
+            Some(i) => &mut features[i],
+            None => {
+                features.push(FeatureNode {
+                    name: group,
+                    crates: Vec::new(),
+                    packs: Vec::new(),
+                });
+                features.last_mut().expect("just pushed")
+            }
+        };
+
+        // [impl cli.tree.nested]
+        if crate_name.ends_with("-battery-pack") {
+            feature.packs.push(nested_pack(
+                &crate_name,
+                &crate_spec.version,
+                &crate_spec.features,
+                declared,
+                load,
+                ancestors,
+            ));
+            continue;
+        }
+
+        feature.crates.push(CrateNode {
+            declared: declared.get(&crate_name).cloned(),
+            findings: findings
+                .iter()
+                .filter(|f| f.crate_name.as_deref() == Some(crate_name.as_str()))
+                .cloned()
+                .collect(),
+            dep_kind: Some(crate_spec.dep_kind),
+            recommended: Some(crate_spec.version.clone()).filter(|v| !v.is_empty()),
+            name: crate_name,
+        });
+    }
+
+    PackNode {
+        name: name.to_string(),
+        version: version.to_string(),
+        features,
+        no_longer_curated: Vec::new(),
+        error: None,
+    }
+}
+
+/// A nested pack, resolved for the Cargo features its parent enables on it.
+fn nested_pack(
+    name: &str,
+    version: &str,
+    features: &BTreeSet<String>,
+    declared: &BTreeMap<String, String>,
+    load: &dyn Fn(&str) -> Result<BatteryPackSpec>,
+    ancestors: &mut Vec<String>,
+) -> PackNode {
+    let unloaded = |error: String| PackNode {
+        name: name.to_string(),
+        version: version.to_string(),
+        features: Vec::new(),
+        no_longer_curated: Vec::new(),
+        error: Some(error),
+    };
+    if ancestors.iter().any(|a| a == name) {
+        return unloaded(format!("{} includes itself", name));
+    }
+    if ancestors.len() >= MAX_DEPTH {
+        return unloaded("nested too deeply".to_string());
+    }
+    let spec = match load(name) {
+        Ok(spec) => spec,
+        Err(e) => return unloaded(format!("{:#}", e)),
+    };
+
+    ancestors.push(name.to_string());
+    let node = pack_node(
+        name,
+        version,
+        &spec,
+        features,
+        declared,
+        &[],
+        load,
+        ancestors,
+    );
+    ancestors.pop();
+    node
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Render packs as an indented tree.
+pub(crate) fn render_text(packs: &[PackNode]) -> String {
+    let mut out = String::new();
+    for pack in packs {
+        let _ = writeln!(out, "{}", pack_label(pack));
+        write_pack_children(&mut out, pack, "");
+    }
+    out
+}
+
+fn pack_label(pack: &PackNode) -> String {
+    use console::style;
+
+    let label = format!(
+        "{} ({})",
+        style(short_name(&pack.name)).bold(),
+        style(&pack.version).dim()
+    );
+    match &pack.error {
+        Some(error) => format!("{} {} {}", label, style("✗").red(), error),
+        None => label,
+    }
+}
+
+fn write_pack_children(out: &mut String, pack: &PackNode, prefix: &str) {
+    let stale = (!pack.no_longer_curated.is_empty()).then(|| FeatureNode {
+        name: "(no longer curated)".to_string(),
+        crates: pack.no_longer_curated.clone(),
+        packs: Vec::new(),
+    });
+    let groups: Vec<&FeatureNode> = pack.features.iter().chain(stale.as_ref()).collect();
+
+    for (i, feature) in groups.iter().enumerate() {
+        let last = i + 1 == groups.len();
+        let _ = writeln!(out, "{}{}{}", prefix, branch(last), feature.name);
+        let child_prefix = format!("{}{}", prefix, indent(last));
+
+        let count = feature.crates.len() + feature.packs.len();
+        for (j, krate) in feature.crates.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{}{}{}",
+                child_prefix,
+                branch(j + 1 == count),
+                crate_label(krate)
+            );
+        }
+        for (j, nested) in feature.packs.iter().enumerate() {
+            let last = feature.crates.len() + j + 1 == count;
+            let _ = writeln!(
+                out,
+                "{}{}{}",
+                child_prefix,
+                branch(last),
+                pack_label(nested)
+            );
+            write_pack_children(out, nested, &format!("{}{}", child_prefix, indent(last)));
+        }
+    }
+}
+
+fn crate_label(krate: &CrateNode) -> String {
+    use console::style;
+
+    let mut label = krate.name.clone();
+    if let Some(version) = &krate.declared {
+        label.push(' ');
+        label.push_str(version);
+    }
+    if let Some(kind) = krate.dep_kind {
+        let _ = write!(
+            label,
+            " {}",
+            style(format!("[{}]", dep_kind_section(kind))).dim()
+        );
+    }
+    if let Some(severity) = krate.severity() {
+        let marker = match severity {
+            Severity::Warning => style("⚠").yellow(),
+            Severity::Error => style("✗").red(),
+        };
+        let prefix = format!("{}: ", krate.name);
+        let messages: Vec<&str> = krate
+            .findings
+            .iter()
+            .map(|f| f.message.strip_prefix(&prefix).unwrap_or(&f.message))
+            .collect();
+        let _ = write!(label, " {} {}", marker, messages.join("; "));
+    }
+    label
+}
+
+fn branch(last: bool) -> &'static str {
+    if last { "└── " } else { "├── " }
+}
+
+fn indent(last: bool) -> &'static str {
+    if last { "    " } else { "│   " }
+}
+
+/// Render packs as a Graphviz digraph. Crates and packs get one node each,
+/// so a crate several packs share has several incoming edges.
+pub(crate) fn render_dot(packs: &[PackNode]) -> String {
+    let mut nodes: BTreeMap<String, String> = BTreeMap::new();
+    let mut edges: BTreeSet<(String, String)> = BTreeSet::new();
+    for pack in packs {
+        dot_pack(pack, &mut nodes, &mut edges);
+    }
+
+    let mut out = String::from("digraph battery_packs {\n    rankdir=LR;\n");
+    for (id, attrs) in &nodes {
+        let _ = writeln!(out, "    \"{}\" [{}];", dot_escape(id), attrs);
+    }
+    for (from, to) in &edges {
+        let _ = writeln!(
+            out,
+            "    \"{}\" -> \"{}\";",
+            dot_escape(from),
+            dot_escape(to)
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn dot_pack(
+    pack: &PackNode,
+    nodes: &mut BTreeMap<String, String>,
+    edges: &mut BTreeSet<(String, String)>,
+) {
+    let pack_id = format!("pack:{}", pack.name);
+    let color = if pack.error.is_some() {
+        ", color=red"
+    } else {
+        ""
+    };
+    nodes.insert(
+        pack_id.clone(),
+        format!(
+            "label=\"{} {}\", shape=folder{}",
+            dot_escape(&pack.name),
+            dot_escape(&pack.version),
+            color
+        ),
+    );
+
+    for feature in &pack.features {
+        let feature_id = format!("feature:{}/{}", pack.name, feature.name);
+        nodes.insert(
+            feature_id.clone(),
+            format!("label=\"{}\", shape=ellipse", dot_escape(&feature.name)),
+        );
+        edges.insert((pack_id.clone(), feature_id.clone()));
+
+        for krate in &feature.crates {
+            let crate_id = dot_crate(krate, nodes);
+            edges.insert((feature_id.clone(), crate_id));
+        }
+        for nested in &feature.packs {
+            dot_pack(nested, nodes, edges);
+            edges.insert((feature_id.clone(), format!("pack:{}", nested.name)));
+        }
+    }
+    for krate in &pack.no_longer_curated {
+        let crate_id = dot_crate(krate, nodes);
+        edges.insert((pack_id.clone(), crate_id));
+    }
+}
+
+fn dot_crate(krate: &CrateNode, nodes: &mut BTreeMap<String, String>) -> String {
+    let id = format!("crate:{}", krate.name);
+    let mut label = dot_escape(&krate.name);
+    if let Some(version) = &krate.declared {
+        let _ = write!(label, " {}", dot_escape(version));
+    }
+    if let Some(kind) = krate.dep_kind {
+        let _ = write!(label, "\\n[{}]", dep_kind_section(kind));
+    }
+    let color = match krate.severity() {
+        Some(Severity::Error) => ", color=red",
+        Some(Severity::Warning) => ", color=orange",
+        None => "",
+    };
+    // A crate several packs share is drawn in the color of its worst drift.
+    let existing = nodes.get(&id).map(|attrs| {
+        if attrs.ends_with("color=red") {
+            Some(Severity::Error)
+        } else if attrs.ends_with("color=orange") {
+            Some(Severity::Warning)
+        } else {
+            None
+        }
+    });
+    if existing.is_none_or(|worst| krate.severity() > worst) {
+        nodes.insert(
+            id.clone(),
+            format!("label=\"{}\", shape=box{}", label, color),
+        );
+    }
+    id
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,198 @@
+//! Tests for the project tree.
+
+use super::*;
+use crate::drift::{PackState, ProjectDeps};
+
+const CLI: &str = r#"
+[package]
+name = "cli-battery-pack"
+version = "0.5.0"
+keywords = ["battery-pack"]
+
+[dependencies]
+clap = { version = "4.5", features = ["derive"], optional = true }
+console = { version = "0.15.8", optional = true }
+indicatif = { version = "0.17", optional = true }
+error-battery-pack = { version = "0.3", optional = true }
+
+[dev-dependencies]
+snapbox = "0.6"
+
+[features]
+default = ["clap", "console", "error-battery-pack"]
+progress = ["indicatif", "console"]
+"#;
+
+const ERROR: &str = r#"
+[package]
+name = "error-battery-pack"
+version = "0.3.0"
+keywords = ["battery-pack"]
+
+[dependencies]
+anyhow = "1"
+"#;
+
+const PROJECT: &str = r#"
+[dependencies]
+clap = { version = "4.5", features = ["derive"] }
+console = "0.15.2"
+anyhow = "1.0.86"
+
+[dev-dependencies]
+snapbox = "0.6"
+"#;
+
+fn spec(manifest: &str) -> BatteryPackSpec {
+    bphelper_manifest::parse_battery_pack(manifest).unwrap()
+}
+
+fn load(name: &str) -> Result<BatteryPackSpec> {
+    match name {
+        "error-battery-pack" => Ok(spec(ERROR)),
+        _ => anyhow::bail!("no such pack: {}", name),
+    }
+}
+
+fn tree(features: &[&str]) -> PackNode {
+    let cli = spec(CLI);
+    let active: BTreeSet<String> = features.iter().map(|s| s.to_string()).collect();
+    let raw: toml::Value = toml::from_str(PROJECT).unwrap();
+    let project = ProjectDeps::from_manifests(&raw, None);
+    let findings = crate::drift::analyze(
+        &project,
+        &PackState {
+            name: "cli-battery-pack",
+            spec: &cli,
+            active_features: &active,
+            managed_deps: None,
+            pinned: &BTreeSet::new(),
+        },
+        crate::manifest::SyncPolicy::default(),
+    );
+    let declared: BTreeMap<String, String> = project
+        .deps
+        .iter()
+        .filter_map(|(name, decls)| Some((name.clone(), decls[0].version.clone()?)))
+        .collect();
+    build(
+        &InstalledNode {
+            name: "cli-battery-pack",
+            version: "0.5",
+            spec: &cli,
+            active_features: &active,
+            findings: &findings,
+        },
+        &declared,
+        &load,
+    )
+}
+
+fn crate_names(feature: &FeatureNode) -> Vec<&str> {
+    feature.crates.iter().map(|c| c.name.as_str()).collect()
+}
+
+// [verify cli.tree.structure]
+// [verify cli.tree.nested]
+#[test]
+fn groups_crates_under_active_features_and_nests_packs() {
+    let pack = tree(&["default", "progress"]);
+
+    let features: Vec<&str> = pack.features.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(features, ["default", "progress"]);
+    assert_eq!(
+        crate_names(&pack.features[0]),
+        ["clap", "console", "snapbox"]
+    );
+    assert_eq!(crate_names(&pack.features[1]), ["indicatif"]);
+
+    let nested = &pack.features[0].packs[0];
+    assert_eq!(nested.name, "error-battery-pack");
+    assert_eq!(nested.version, "0.3");
+    assert_eq!(crate_names(&nested.features[0]), ["anyhow"]);
+    assert_eq!(
+        nested.features[0].crates[0].declared.as_deref(),
+        Some("1.0.86")
+    );
+}
+
+#[test]
+fn inactive_features_are_left_out() {
+    let pack = tree(&[]);
+    let features: Vec<&str> = pack.features.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(features, ["default"]);
+}
+
+// [verify cli.tree.drift]
+#[test]
+fn crates_carry_their_drift() {
+    let pack = tree(&["default", "progress"]);
+
+    let console = &pack.features[0].crates[1];
+    assert_eq!(console.declared.as_deref(), Some("0.15.2"));
+    assert_eq!(console.severity(), Some(Severity::Warning));
+    let indicatif = &pack.features[1].crates[0];
+    assert_eq!(indicatif.declared, None);
+    assert_eq!(indicatif.severity(), Some(Severity::Error));
+    assert_eq!(pack.features[0].crates[0].severity(), None);
+}
+
+#[test]
+fn unloadable_nested_packs_are_reported() {
+    let cli = spec(CLI);
+    let active = BTreeSet::new();
+    let pack = build(
+        &InstalledNode {
+            name: "cli-battery-pack",
+            version: "0.5",
+            spec: &cli,
+            active_features: &active,
+            findings: &[],
+        },
+        &BTreeMap::new(),
+        &|name| anyhow::bail!("offline: {}", name),
+    );
+    let nested = &pack.features[0].packs[0];
+    assert_eq!(nested.error.as_deref(), Some("offline: error-battery-pack"));
+}
+
+// [verify cli.tree.formats]
+#[test]
+fn text_rendering_draws_the_tree() {
+    console::set_colors_enabled(false);
+    let text = render_text(&[tree(&["default", "progress"])]);
+    assert_eq!(
+        text,
+        "\
+cli (0.5)
+├── default
+│   ├── clap 4.5 [dependencies]
+│   ├── console 0.15.2 [dependencies] ⚠ 0.15.2 → 0.15.8 recommended
+│   ├── snapbox 0.6 [dev-dependencies]
+│   └── error (0.3)
+│       └── default
+│           └── anyhow 1.0.86 [dependencies]
+└── progress
+    └── indicatif [dependencies] ✗ missing from [dependencies]
+"
+    );
+}
+
+// [verify cli.tree.formats]
+#[test]
+fn dot_rendering_shares_crate_nodes() {
+    let dot = render_dot(&[tree(&["default", "progress"])]);
+    assert!(dot.starts_with("digraph battery_packs {"), "{dot}");
+    assert!(
+        dot.contains("\"crate:console\" [label=\"console 0.15.2\\n[dependencies]\", shape=box, color=orange];"),
+        "{dot}"
+    );
+    assert!(
+        dot.contains("\"feature:cli-battery-pack/default\" -> \"pack:error-battery-pack\";"),
+        "{dot}"
+    );
+    assert!(
+        dot.contains("\"feature:cli-battery-pack/progress\" -> \"crate:indicatif\";"),
+        "{dot}"
+    );
+}
@@ -2,10 +2,11 @@
 #[cfg(test)]
 mod tests;
 
+use crate::forge::Repository;
 use crate::manifest::{find_installed_bp_names, find_user_manifest};
 use crate::registry::{
-    BatteryPackDetail, BatteryPackSummary, CrateSource, fetch_battery_pack_detail,
-    fetch_battery_pack_list,
+    BatteryPackDetail, BatteryPackSummary, CrateSource, ListQuery, fetch_battery_pack_detail,
+    fetch_battery_pack_list, format_downloads,
 };
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
@@ -34,8 +35,8 @@ pub(crate) struct ShowOpts<'a> {
 }
 
 /// Run the TUI starting from the list view
-pub(crate) fn run_list(source: CrateSource, filter: Option<String>) -> Result<()> {
-    let app = App::new_list(source, filter);
+pub(crate) fn run_list(source: CrateSource, query: ListQuery) -> Result<()> {
+    let app = App::new_list(source, query);
     app.run()
 }
 
@@ -80,6 +81,7 @@ fn run_preview(opts: ShowOpts<'_>) -> Result<()> {
         pending_action: None,
         in_project: false,
         installed_bp_names: Vec::new(),
+        list_query: ListQuery::default(),
     };
     app.run()
 }
@@ -97,6 +99,8 @@ struct App {
     pending_action: Option<PendingAction>,
     in_project: bool,
     installed_bp_names: Vec<String>,
+    /// Query for the list screen, restored when navigating back to it.
+    list_query: ListQuery,
 }
 
 enum Screen {
@@ -124,7 +128,7 @@ struct LoadingState {
 
 enum LoadingTarget {
     List {
-        filter: Option<String>,
+        query: ListQuery,
     },
     Detail {
         name: String,
@@ -136,7 +140,25 @@ enum LoadingTarget {
 struct ListScreen {
     items: Vec<BatteryPackSummary>,
     list_state: ListState,
-    filter: Option<String>,
+    query: ListQuery,
+}
+
+impl ListScreen {
+    /// Switch to the next sort order, keeping the selected pack selected.
+    // [impl tui.browse.sort]
+    fn cycle_sort(&mut self) {
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .map(|bp| bp.name.clone());
+        self.query.sort = self.query.sort.next();
+        self.query.sort.apply(&mut self.items);
+        if let Some(name) = selected {
+            self.list_state
+                .select(self.items.iter().position(|bp| bp.name == name));
+        }
+    }
 }
 
 struct DetailScreen {
@@ -155,12 +177,12 @@ enum DetailItem {
     Crate(String),
     /// An extended battery pack - opens crates.io
     Extends(String),
-    /// A template - opens GitHub tree URL (stores local path and resolved repo path)
+    /// A template - opens repository tree URL (stores local path and resolved repo path)
     Template {
         _path: String,
         repo_path: Option<String>,
     },
-    /// An example - opens GitHub blob URL (stores name and resolved repo path)
+    /// An example - opens repository blob URL (stores name and resolved repo path)
     Example {
         _name: String,
         repo_path: Option<String>,
@@ -353,19 +375,22 @@ fn detect_project_state() -> (bool, Vec<String>) {
 }
 
 impl App {
-    fn new_list(source: CrateSource, filter: Option<String>) -> Self {
+    fn new_list(source: CrateSource, query: ListQuery) -> Self {
         let (in_project, installed_bp_names) = detect_project_state();
         Self {
             source,
             pack_path: None,
             screen: Screen::Loading(LoadingState {
                 message: "Loading battery packs...".to_string(),
-                target: LoadingTarget::List { filter },
+                target: LoadingTarget::List {
+                    query: query.clone(),
+                },
             }),
             should_quit: false,
             pending_action: None,
             in_project,
             installed_bp_names,
+            list_query: query,
         }
     }
 
@@ -386,6 +411,7 @@ impl App {
             pending_action: None,
             in_project,
             installed_bp_names,
+            list_query: ListQuery::default(),
         }
     }
 
@@ -458,27 +484,25 @@ impl App {
         };
 
         match state.target {
-            LoadingTarget::List { filter } => {
-                match fetch_battery_pack_list(&self.source, filter.as_deref()) {
-                    Ok(items) => {
-                        let mut list_state = ListState::default();
-                        if !items.is_empty() {
-                            list_state.select(Some(0));
-                        }
-                        self.screen = Screen::List(ListScreen {
-                            items,
-                            list_state,
-                            filter,
-                        });
-                    }
-                    Err(e) => {
-                        self.screen = Screen::Error(ErrorScreen {
-                            message: format!("{e}"),
-                            retry_target: LoadingTarget::List { filter },
-                        });
+            LoadingTarget::List { query } => match fetch_battery_pack_list(&self.source, &query) {
+                Ok(items) => {
+                    let mut list_state = ListState::default();
+                    if !items.is_empty() {
+                        list_state.select(Some(0));
                     }
+                    self.screen = Screen::List(ListScreen {
+                        items,
+                        list_state,
+                        query,
+                    });
                 }
-            }
+                Err(e) => {
+                    self.screen = Screen::Error(ErrorScreen {
+                        message: format!("{e}"),
+                        retry_target: LoadingTarget::List { query },
+                    });
+                }
+            },
             LoadingTarget::Detail {
                 name,
                 path,
@@ -599,6 +623,7 @@ impl App {
             None,
             Quit,
             ListSelect(usize),
+            ListSort,
             ListUp,
             ListDown,
             DetailNext,
@@ -606,10 +631,12 @@ impl App {
             OpenCratesIoUrl(String),
             OpenTemplate {
                 repository: Option<String>,
+                commit: Option<String>,
                 repo_path: Option<String>,
             },
             OpenExample {
                 repository: Option<String>,
+                commit: Option<String>,
                 repo_path: Option<String>,
             },
             DetailOpenCratesIo(String),
@@ -656,6 +683,7 @@ impl App {
                         Action::None
                     }
                 }
+                KeyCode::Char('s') => Action::ListSort,
                 KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
                 _ => Action::None,
             },
@@ -680,6 +708,7 @@ impl App {
                                 repo_path,
                             } => Action::OpenTemplate {
                                 repository: state.detail.repository.clone(),
+                                commit: state.detail.commit.clone(),
                                 repo_path,
                             },
                             DetailItem::Example {
@@ -687,6 +716,7 @@ impl App {
                                 repo_path,
                             } => Action::OpenExample {
                                 repository: state.detail.repository.clone(),
+                                commit: state.detail.commit.clone(),
                                 repo_path,
                             },
                             DetailItem::ActionOpenCratesIo => {
@@ -839,6 +869,12 @@ impl App {
                     list_nav(&mut state.list_state, state.items.len(), true);
                 }
             }
+            Action::ListSort => {
+                if let Screen::List(state) = &mut self.screen {
+                    state.cycle_sort();
+                    self.list_query = state.query.clone();
+                }
+            }
             Action::ListSelect(selected) => {
                 if let Screen::List(state) = &self.screen
                     && let Some(bp) = state.items.get(selected)
@@ -869,20 +905,22 @@ impl App {
             }
             Action::OpenTemplate {
                 repository,
+                commit,
                 repo_path,
             } => {
                 let url = match repo_path {
-                    Some(path) => build_github_url(repository.as_deref(), &path),
+                    Some(path) => build_tree_url(repository.as_deref(), commit.as_deref(), &path),
                     None => repository.unwrap_or_else(|| "https://crates.io".to_string()),
                 };
                 self.pending_action = Some(PendingAction::OpenUrl { url });
             }
             Action::OpenExample {
                 repository,
+                commit,
                 repo_path,
             } => {
                 let url = match repo_path {
-                    Some(path) => build_github_blob_url(repository.as_deref(), &path),
+                    Some(path) => build_blob_url(repository.as_deref(), commit.as_deref(), &path),
                     None => repository.unwrap_or_else(|| "https://crates.io".to_string()),
                 };
                 self.pending_action = Some(PendingAction::OpenUrl { url });
@@ -933,7 +971,9 @@ impl App {
                 if came_from_list {
                     self.screen = Screen::Loading(LoadingState {
                         message: "Loading battery packs...".to_string(),
-                        target: LoadingTarget::List { filter: None },
+                        target: LoadingTarget::List {
+                            query: self.list_query.clone(),
+                        },
                     });
                 } else {
                     self.should_quit = true;
@@ -1157,7 +1197,7 @@ impl App {
 /// Build a `ListItem` for a battery pack summary row (shared by list and browse views).
 fn bp_summary_list_item(bp: &BatteryPackSummary) -> ListItem<'_> {
     let desc = bp.description.lines().next().unwrap_or("");
-    let line = Line::from(vec![
+    let mut spans = vec![
         Span::styled(
             format!("{:<20}", bp.short_name),
             Style::default().fg(Color::Green).bold(),
@@ -1168,9 +1208,17 @@ fn bp_summary_list_item(bp: &BatteryPackSummary) -> ListItem<'_> {
             Style::default().fg(Color::DarkGray),
         ),
         Span::raw("  "),
-        Span::raw(desc),
-    ]);
-    ListItem::new(line)
+    ];
+    // Local sources have no download counts; don't show a column of zeros.
+    if bp.downloads > 0 {
+        spans.push(Span::styled(
+            format!("↓{:>7}", format_downloads(bp.downloads)),
+            Style::default().fg(Color::Cyan),
+        ));
+        spans.push(Span::raw("  "));
+    }
+    spans.push(Span::raw(desc));
+    ListItem::new(Line::from(spans))
 }
 
 fn render_loading(frame: &mut Frame, state: &LoadingState) {
@@ -1220,10 +1268,14 @@ fn render_list(frame: &mut Frame, state: &mut ListScreen) {
     .areas(area);
 
     // Header
-    let title = match &state.filter {
-        Some(f) => format!("Battery Packs (filter: {})", f),
-        None => "Battery Packs".to_string(),
-    };
+    let mut title = "Battery Packs".to_string();
+    if let Some(f) = &state.query.filter {
+        title.push_str(&format!(" (filter: {})", f));
+    }
+    if let Some(k) = &state.query.keyword {
+        title.push_str(&format!(" (keyword: {})", k));
+    }
+    title.push_str(&format!(" — sorted by {}", state.query.sort.as_str()));
     frame.render_widget(
         Paragraph::new(title)
             .style(Style::default().bold())
@@ -1248,7 +1300,7 @@ fn render_list(frame: &mut Frame, state: &mut ListScreen) {
 
     // Footer
     frame.render_widget(
-        Paragraph::new("↑↓/jk Navigate | Enter Select | q Quit")
+        Paragraph::new("↑↓/jk Navigate | Enter Select | s Sort | q Quit")
             .style(Style::default().white().on_dark_gray()),
         footer,
     );
@@ -1326,6 +1378,14 @@ fn render_detail(frame: &mut Frame, state: &DetailScreen) {
         lines.push(Line::from(""));
     }
 
+    for warning in &detail.warnings {
+        lines.push(Line::styled(
+            format!("warning: {}", warning),
+            Style::default().fg(Color::Yellow),
+        ));
+        lines.push(Line::from(""));
+    }
+
     if !detail.owners.is_empty() {
         lines.push(Line::styled("Authors:", Style::default().bold()));
         for owner in &detail.owners {
@@ -1658,42 +1718,26 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     h_area
 }
 
-/// Build a GitHub tree URL for a directory path.
-/// If repository is set and looks like a GitHub URL, construct a tree URL.
-/// Otherwise fall back to a crates.io search or just open the repo root.
-fn build_github_url(repository: Option<&str>, path: &str) -> String {
-    build_github_ref_url(repository, "tree", path)
+/// Build a tree URL for a directory in the pack's repository.
+fn build_tree_url(repository: Option<&str>, commit: Option<&str>, path: &str) -> String {
+    build_repo_url(repository, |repo| repo.tree_url(commit, path))
 }
 
-/// Build a GitHub blob URL for a file path.
-fn build_github_blob_url(repository: Option<&str>, path: &str) -> String {
-    build_github_ref_url(repository, "blob", path)
+/// Build a blob URL for a file in the pack's repository.
+fn build_blob_url(repository: Option<&str>, commit: Option<&str>, path: &str) -> String {
+    build_repo_url(repository, |repo| repo.blob_url(commit, path))
 }
 
-/// Build a GitHub URL with the specified ref type (tree or blob).
-fn build_github_ref_url(repository: Option<&str>, ref_type: &str, path: &str) -> String {
+/// Build a repository link on whichever forge hosts the pack. Falls back to
+/// the repository URL itself when the forge isn't recognized.
+// [impl tui.browse.forge-links]
+fn build_repo_url(repository: Option<&str>, link: impl FnOnce(&Repository) -> String) -> String {
     match repository {
-        Some(repo) => {
-            // Try to parse GitHub URL: https://github.com/owner/repo
-            if let Some(gh_path) = repo
-                .strip_prefix("https://github.com/")
-                .or_else(|| repo.strip_prefix("http://github.com/"))
-            {
-                // Remove trailing .git if present
-                let gh_path = gh_path.strip_suffix(".git").unwrap_or(gh_path);
-                // Remove trailing slash
-                let gh_path = gh_path.trim_end_matches('/');
-                // Construct URL with main branch
-                format!("https://github.com/{}/{}/main/{}", gh_path, ref_type, path)
-            } else {
-                // Not a GitHub URL, just open the repository URL
-                repo.to_string()
-            }
-        }
-        None => {
-            // No repository, can't construct URL
-            // Fall back to nothing useful - this shouldn't happen in practice
-            "https://crates.io".to_string()
-        }
+        Some(url) => match Repository::parse(url) {
+            Some(repo) => link(&repo),
+            None => url.to_string(),
+        },
+        // No repository, can't construct URL
+        None => "https://crates.io".to_string(),
     }
 }
@@ -13,7 +13,9 @@ fn make_detail(crates: &[&str], templates: &[&str], examples: &[&str]) -> Batter
         version: "1.0.0".to_string(),
         description: "Test battery pack".to_string(),
         repository: Some("https://github.com/test/test".to_string()),
+        commit: None,
         owners: Vec::new(),
+        warnings: Vec::new(),
         crates: crates.iter().map(|s| s.to_string()).collect(),
         extends: Vec::new(),
         features: std::collections::BTreeMap::new(),
@@ -111,6 +113,7 @@ fn make_app(screen: Screen) -> App {
         pending_action: None,
         in_project: true,
         installed_bp_names: Vec::new(),
+        list_query: ListQuery::default(),
     }
 }
 
@@ -121,6 +124,11 @@ fn make_summary(short_name: &str, version: &str, desc: &str) -> BatteryPackSumma
         short_name: short_name.to_string(),
         version: version.to_string(),
         description: desc.to_string(),
+        downloads: 0,
+        recent_downloads: None,
+        updated_at: None,
+        keywords: Some(Vec::new()),
+        categories: Some(Vec::new()),
     }
 }
 
@@ -155,7 +163,7 @@ fn list_j_k_navigation() {
             make_summary("b", "1.0.0", "Pack B"),
         ],
         list_state,
-        filter: None,
+        query: ListQuery::default(),
     }));
 
     app.handle_key(KeyCode::Char('j')); // down
@@ -173,7 +181,7 @@ fn list_q_quits() {
     let mut app = make_app(Screen::List(ListScreen {
         items: vec![make_summary("a", "1.0.0", "")],
         list_state,
-        filter: None,
+        query: ListQuery::default(),
     }));
 
     app.handle_key(KeyCode::Char('q'));
@@ -188,13 +196,87 @@ fn list_esc_quits() {
     let mut app = make_app(Screen::List(ListScreen {
         items: Vec::new(),
         list_state,
-        filter: None,
+        query: ListQuery::default(),
     }));
 
     app.handle_key(KeyCode::Esc);
     assert!(app.should_quit);
 }
 
+/// [verify tui.browse.sort]
+#[test]
+fn list_s_cycles_sort_and_keeps_selection() {
+    let mut a = make_summary("a", "1.0.0", "Pack A");
+    a.downloads = 10;
+    a.recent_downloads = Some(5);
+    let mut b = make_summary("b", "1.0.0", "Pack B");
+    b.downloads = 100;
+    let mut list_state = ListState::default();
+    list_state.select(Some(1)); // "a"
+    let mut app = make_app(Screen::List(ListScreen {
+        items: vec![b, a],
+        list_state,
+        query: ListQuery::default(),
+    }));
+
+    app.handle_key(KeyCode::Char('s'));
+
+    let state = unwrap_list_screen(&app);
+    assert_eq!(state.query.sort, crate::registry::ListSort::Recent);
+    let names: Vec<&str> = state
+        .items
+        .iter()
+        .map(|bp| bp.short_name.as_str())
+        .collect();
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(
+        state.list_state.selected(),
+        Some(0),
+        "selection follows 'a'"
+    );
+    assert_eq!(app.list_query.sort, crate::registry::ListSort::Recent);
+}
+
+/// [verify tui.browse.list]
+#[test]
+fn list_renders_downloads_and_sort() {
+    let mut a = make_summary("web", "1.2.0", "Web things");
+    a.downloads = 12_345;
+    let mut list_state = ListState::default();
+    list_state.select(Some(0));
+    let mut app = make_app(Screen::List(ListScreen {
+        items: vec![a],
+        list_state,
+        query: ListQuery {
+            keyword: Some("http".to_string()),
+            ..ListQuery::default()
+        },
+    }));
+
+    let output = render_app_to_string(&mut app, 90, 8);
+    assert!(output.contains("12.3k"), "{output}");
+    assert!(output.contains("keyword: http"), "{output}");
+    assert!(output.contains("sorted by downloads"), "{output}");
+}
+
+/// [verify cli.http.errors]
+#[test]
+fn detail_renders_without_owners_on_warning() {
+    let mut detail = make_detail(&["serde"], &[], &[]);
+    detail.warnings = vec!["owners lookup failed (status: 404)".to_string()];
+    let mut app = make_app(Screen::Detail(DetailScreen {
+        detail: Rc::new(detail),
+        selected_index: 0,
+        came_from_list: false,
+        in_project: true,
+        is_installed: false,
+    }));
+
+    let output = render_app_to_string(&mut app, 80, 14);
+    assert!(output.contains("status: 404"), "{output}");
+    assert!(output.contains("serde"), "{output}");
+}
+
 // --- Detail screen key handling ---
 
 /// [verify tui.nav.keyboard]
@@ -305,7 +387,9 @@ fn render_app_to_string(app: &mut App, width: u16, height: u16) -> String {
 fn app_renders_loading_screen_without_io() {
     let mut app = make_app(Screen::Loading(LoadingState {
         message: "Loading battery packs...".to_string(),
-        target: LoadingTarget::List { filter: None },
+        target: LoadingTarget::List {
+            query: ListQuery::default(),
+        },
     }));
     let output = render_app_to_string(&mut app, 60, 10);
     assert!(
@@ -343,7 +427,9 @@ fn network_non_blocking_is_architectural() {
 fn error_screen_renders_message() {
     let mut app = make_app(Screen::Error(ErrorScreen {
         message: "connection refused".to_string(),
-        retry_target: LoadingTarget::List { filter: None },
+        retry_target: LoadingTarget::List {
+            query: ListQuery::default(),
+        },
     }));
     let output = render_app_to_string(&mut app, 60, 10);
     assert!(output.contains("Error"), "Expected 'Error' in output");
@@ -366,20 +452,23 @@ fn error_screen_enter_retries() {
     let mut app = make_app(Screen::Error(ErrorScreen {
         message: "timeout".to_string(),
         retry_target: LoadingTarget::List {
-            filter: Some("test".to_string()),
+            query: ListQuery {
+                filter: Some("test".to_string()),
+                ..ListQuery::default()
+            },
         },
     }));
 
     app.handle_key(KeyCode::Enter);
 
     let Screen::Loading(LoadingState {
-        target: LoadingTarget::List { filter },
+        target: LoadingTarget::List { query },
         ..
     }) = &app.screen
     else {
         panic!("expected Screen::Loading(LoadingTarget::List)");
     };
-    assert_eq!(filter.as_deref(), Some("test"));
+    assert_eq!(query.filter.as_deref(), Some("test"));
 }
 
 /// [verify tui.network.error]
@@ -388,7 +477,9 @@ fn error_screen_enter_retries() {
 fn error_screen_r_retries() {
     let mut app = make_app(Screen::Error(ErrorScreen {
         message: "timeout".to_string(),
-        retry_target: LoadingTarget::List { filter: None },
+        retry_target: LoadingTarget::List {
+            query: ListQuery::default(),
+        },
     }));
 
     app.handle_key(KeyCode::Char('r'));
@@ -401,7 +492,9 @@ fn error_screen_r_retries() {
 fn error_screen_esc_quits() {
     let mut app = make_app(Screen::Error(ErrorScreen {
         message: "error".to_string(),
-        retry_target: LoadingTarget::List { filter: None },
+        retry_target: LoadingTarget::List {
+            query: ListQuery::default(),
+        },
     }));
 
     app.handle_key(KeyCode::Esc);
@@ -414,7 +507,9 @@ fn error_screen_esc_quits() {
 fn error_screen_q_quits() {
     let mut app = make_app(Screen::Error(ErrorScreen {
         message: "error".to_string(),
-        retry_target: LoadingTarget::List { filter: None },
+        retry_target: LoadingTarget::List {
+            query: ListQuery::default(),
+        },
     }));
 
     app.handle_key(KeyCode::Char('q'));
@@ -570,3 +665,47 @@ fn preview_standalone_renders_header_and_content() {
         output
     );
 }
+
+/// [verify cli.show.vcs-links]
+/// Repository links are pinned to the published commit, or follow the
+/// default branch when no commit is known.
+#[test]
+fn github_links_pin_published_commit() {
+    let repo = Some("https://github.com/example/packs.git");
+    assert_eq!(
+        build_tree_url(repo, Some("abc123"), "packs/x/templates/simple"),
+        "https://github.com/example/packs/tree/abc123/packs/x/templates/simple"
+    );
+    assert_eq!(
+        build_blob_url(repo, None, "examples/demo.rs"),
+        "https://github.com/example/packs/blob/HEAD/examples/demo.rs"
+    );
+}
+
+/// [verify tui.browse.forge-links]
+/// Packs on non-GitHub forges get links too; unknown hosts fall back to
+/// the repository URL.
+#[test]
+fn repo_links_follow_the_forge() {
+    assert_eq!(
+        build_tree_url(
+            Some("https://gitlab.com/group/sub/packs"),
+            Some("abc123"),
+            "templates/simple"
+        ),
+        "https://gitlab.com/group/sub/packs/-/tree/abc123/templates/simple"
+    );
+    assert_eq!(
+        build_blob_url(
+            Some("https://codeberg.org/me/packs"),
+            Some("abc123"),
+            "examples/demo.rs"
+        ),
+        "https://codeberg.org/me/packs/src/commit/abc123/examples/demo.rs"
+    );
+    assert_eq!(
+        build_blob_url(Some("https://example.com/me/packs"), None, "x.rs"),
+        "https://example.com/me/packs"
+    );
+    assert_eq!(build_blob_url(None, None, "x.rs"), "https://crates.io");
+}
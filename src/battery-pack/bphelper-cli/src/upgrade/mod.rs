@@ -0,0 +1,182 @@
+//! Support for `cargo bp upgrade`: picking a newer battery pack release and
+//! describing what changes between the installed release and that one.
+//!
+//! The command itself lives with the other subcommands in `commands`; this
+//! module holds the parts that don't touch the project's manifests.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result};
+use bphelper_manifest::BatteryPackSpec;
+use std::path::Path;
+
+use crate::registry::{
+    CrateSource, PublishedVersion, download_and_extract_crate, fetch_published_versions,
+    find_local_battery_pack_dir,
+};
+
+/// Releases newer than the one a project has installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UpgradeCandidates {
+    /// The version the project is on: the installed release if it satisfies
+    /// the registered requirement, else the requirement's lower bound.
+    pub current: semver::Version,
+    /// Newest release that still satisfies the registered requirement.
+    pub compatible: Option<semver::Version>,
+    /// Newest release overall.
+    pub latest: Option<semver::Version>,
+}
+
+impl UpgradeCandidates {
+    /// The release to move to, or `None` if already up to date.
+    pub(crate) fn target(&self, breaking: bool) -> Option<&semver::Version> {
+        if breaking {
+            self.latest.as_ref()
+        } else {
+            self.compatible.as_ref()
+        }
+    }
+}
+
+/// Find the releases a pack registered as `registered` and installed at
+/// `installed` could move to. Yanked releases and pre-releases are never
+/// candidates. Returns `None` when `registered` isn't a usable requirement.
+// [impl cli.upgrade.compatible]
+// [impl cli.upgrade.breaking]
+pub(crate) fn upgrade_candidates(
+    registered: &str,
+    installed: &str,
+    published: &[PublishedVersion],
+) -> Option<UpgradeCandidates> {
+    let req = semver::VersionReq::parse(registered).ok()?;
+    let current = semver::Version::parse(installed)
+        .ok()
+        .filter(|v| req.matches(v))
+        .or_else(|| requirement_floor(registered))?;
+
+    let newer: Vec<semver::Version> = published
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| semver::Version::parse(&v.num).ok())
+        .filter(|v| v.pre.is_empty() && *v > current)
+        .collect();
+
+    Some(UpgradeCandidates {
+        compatible: newer.iter().filter(|v| req.matches(v)).max().cloned(),
+        latest: newer.into_iter().max(),
+        current,
+    })
+}
+
+/// The lowest version a plain requirement like `"0.4"` or `"^1.2.3"` admits.
+fn requirement_floor(registered: &str) -> Option<semver::Version> {
+    let bare = registered.trim().trim_start_matches(['^', '~', '=']).trim();
+    let mut parts = bare.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some(semver::Version::new(major, minor, patch))
+}
+
+/// Published versions of a pack. A local source only knows the one version
+/// in its workspace.
+pub(crate) fn available_versions(
+    source: &CrateSource,
+    crate_name: &str,
+) -> Result<Vec<PublishedVersion>> {
+    match source {
+        CrateSource::Registry => fetch_published_versions(crate_name),
+        CrateSource::Local(workspace_dir) => {
+            let crate_dir = find_local_battery_pack_dir(workspace_dir, crate_name)?;
+            let release = read_release(&crate_dir, crate_name)?;
+            Ok(vec![PublishedVersion {
+                num: release.spec.version,
+                yanked: false,
+            }])
+        }
+    }
+}
+
+/// A battery pack release: its spec and the changelog it ships, if any.
+pub(crate) struct Release {
+    pub spec: BatteryPackSpec,
+    pub changelog: Option<String>,
+}
+
+/// Load `crate_name` at `version` from `source`, along with its
+/// `CHANGELOG.md`.
+pub(crate) fn fetch_release(
+    source: &CrateSource,
+    crate_name: &str,
+    version: &str,
+) -> Result<Release> {
+    match source {
+        CrateSource::Registry => {
+            let temp_dir = download_and_extract_crate(crate_name, version)?;
+            let crate_dir = temp_dir.path().join(format!("{}-{}", crate_name, version));
+            read_release(&crate_dir, crate_name)
+        }
+        CrateSource::Local(workspace_dir) => {
+            let crate_dir = find_local_battery_pack_dir(workspace_dir, crate_name)?;
+            read_release(&crate_dir, crate_name)
+        }
+    }
+}
+
+fn read_release(crate_dir: &Path, crate_name: &str) -> Result<Release> {
+    let manifest_path = crate_dir.join("Cargo.toml");
+    let manifest_content = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let spec = bphelper_manifest::parse_battery_pack(&manifest_content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse battery pack '{}': {}", crate_name, e))?;
+    let changelog = std::fs::read_to_string(crate_dir.join("CHANGELOG.md")).ok();
+    Ok(Release { spec, changelog })
+}
+
+/// The sections of a Keep a Changelog style `changelog` for releases after
+/// `from`, up to and including `to`, newest first as they appear.
+///
+/// Release headings are level-two headings whose first word is the version,
+/// optionally bracketed or linked: `## [0.5.0](...) - 2026-04-03`,
+/// `## 0.5.0` or `## v0.5.0`. Other sections, like `[Unreleased]`, are
+/// skipped.
+// [impl cli.upgrade.changelog]
+pub(crate) fn changelog_between(
+    changelog: &str,
+    from: &semver::Version,
+    to: &semver::Version,
+) -> String {
+    let mut sections: Vec<Vec<&str>> = Vec::new();
+    let mut in_range = false;
+    for line in changelog.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            in_range = heading_version(heading).is_some_and(|v| v > *from && v <= *to);
+            if in_range {
+                sections.push(vec![line]);
+            }
+            continue;
+        }
+        if line.starts_with("# ") {
+            in_range = false;
+        }
+        if in_range && let Some(section) = sections.last_mut() {
+            section.push(line);
+        }
+    }
+
+    sections
+        .iter()
+        .map(|lines| lines.join("\n").trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn heading_version(heading: &str) -> Option<semver::Version> {
+    let word = heading.trim_start().trim_start_matches('[');
+    let end = word
+        .find(|c: char| c == ']' || c == '(' || c.is_whitespace())
+        .unwrap_or(word.len());
+    let word = &word[..end];
+    semver::Version::parse(word.strip_prefix('v').unwrap_or(word)).ok()
+}
@@ -0,0 +1,118 @@
+//! Tests for upgrade planning.
+
+use super::*;
+
+fn published(versions: &[(&str, bool)]) -> Vec<PublishedVersion> {
+    versions
+        .iter()
+        .map(|(num, yanked)| PublishedVersion {
+            num: num.to_string(),
+            yanked: *yanked,
+        })
+        .collect()
+}
+
+fn v(s: &str) -> semver::Version {
+    semver::Version::parse(s).unwrap()
+}
+
+// [verify cli.upgrade.compatible]
+// [verify cli.upgrade.breaking]
+#[test]
+fn candidates_split_compatible_and_breaking() {
+    let versions = published(&[
+        ("0.6.0", false),
+        ("0.5.2", true),
+        ("0.5.1", false),
+        ("0.5.0", false),
+        ("0.7.0-rc.1", false),
+    ]);
+    let candidates = upgrade_candidates("0.5", "0.5.0", &versions).unwrap();
+
+    assert_eq!(candidates.current, v("0.5.0"));
+    assert_eq!(candidates.compatible, Some(v("0.5.1")));
+    assert_eq!(candidates.latest, Some(v("0.6.0")));
+    assert_eq!(candidates.target(false), Some(&v("0.5.1")));
+    assert_eq!(candidates.target(true), Some(&v("0.6.0")));
+}
+
+#[test]
+fn up_to_date_has_no_target() {
+    let versions = published(&[("1.2.0", false), ("1.1.0", false)]);
+    let candidates = upgrade_candidates("1.2", "1.2.0", &versions).unwrap();
+    assert_eq!(candidates.target(false), None);
+    assert_eq!(candidates.target(true), None);
+}
+
+#[test]
+fn current_falls_back_to_the_requirement_floor() {
+    // A local source "installs" whatever its workspace has, which may not
+    // match the registration at all.
+    let versions = published(&[("0.2.0", false)]);
+    let candidates = upgrade_candidates("0.1", "0.2.0", &versions).unwrap();
+
+    assert_eq!(candidates.current, v("0.1.0"));
+    assert_eq!(candidates.compatible, None);
+    assert_eq!(candidates.latest, Some(v("0.2.0")));
+}
+
+#[test]
+fn unparsable_requirements_are_rejected() {
+    assert!(upgrade_candidates("not a version", "1.0.0", &[]).is_none());
+    assert!(upgrade_candidates(">=1, <2", "0.1.0", &[]).is_none());
+}
+
+const CHANGELOG: &str = "\
+# Changelog
+
+All notable changes to this project will be documented in this file.
+
+## [Unreleased]
+
+- not yet released
+
+## [0.6.0](https://example.com/compare/v0.5.1...v0.6.0) - 2026-04-21
+
+### Added
+
+- shell completions
+
+## [0.5.1](https://example.com/compare/v0.5.0...v0.5.1) - 2026-04-13
+
+### Other
+
+- upgrade ratatui
+
+## v0.5.0
+
+- move snapbox to dev-dependencies
+";
+
+// [verify cli.upgrade.changelog]
+#[test]
+fn changelog_sections_cover_the_upgraded_range() {
+    let notes = changelog_between(CHANGELOG, &v("0.5.0"), &v("0.6.0"));
+    assert_eq!(
+        notes,
+        "\
+## [0.6.0](https://example.com/compare/v0.5.1...v0.6.0) - 2026-04-21
+
+### Added
+
+- shell completions
+
+## [0.5.1](https://example.com/compare/v0.5.0...v0.5.1) - 2026-04-13
+
+### Other
+
+- upgrade ratatui"
+    );
+}
+
+// [verify cli.upgrade.changelog]
+#[test]
+fn changelog_accepts_bare_and_v_prefixed_headings() {
+    let notes = changelog_between(CHANGELOG, &v("0.4.0"), &v("0.5.0"));
+    assert_eq!(notes, "## v0.5.0\n\n- move snapbox to dev-dependencies");
+    assert_eq!(changelog_between(CHANGELOG, &v("0.6.0"), &v("0.7.0")), "");
+}
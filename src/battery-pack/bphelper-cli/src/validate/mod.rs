@@ -1,6 +1,7 @@
 //! Battery pack validation: structure checks and template compilation.
 
 use anyhow::{Context, Result, bail};
+use std::io::Write;
 use std::path::Path;
 
 // ============================================================================
@@ -9,7 +10,10 @@ use std::path::Path;
 
 // [impl cli.validate.purpose]
 // [impl cli.validate.default-path]
-pub(crate) fn validate_battery_pack_cmd(path: Option<&str>) -> Result<()> {
+pub(crate) fn validate_battery_pack_cmd(
+    path: Option<&str>,
+    format: crate::output::MessageFormat,
+) -> Result<()> {
     let crate_root = match path {
         Some(p) => std::path::PathBuf::from(p),
         None => std::env::current_dir().context("failed to get current directory")?,
@@ -46,6 +50,10 @@ pub(crate) fn validate_battery_pack_cmd(path: Option<&str>) -> Result<()> {
     let mut report = spec.validate_spec();
     report.merge(bphelper_manifest::validate_on_disk(&spec, &crate_root));
 
+    if format.is_json() {
+        return print_validate_json(&spec.name, &report, &crate_root);
+    }
+
     // [impl cli.validate.clean]
     if report.is_clean() {
         validate_templates(crate_root.to_str().unwrap_or("."))?;
@@ -86,6 +94,50 @@ pub(crate) fn validate_battery_pack_cmd(path: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// JSON flavor of `validate`: diagnostics and the template result go into a
+/// single document on stdout, template progress goes to stderr, and the exit
+/// status matches the human output.
+// [impl cli.json.validate]
+fn print_validate_json(
+    name: &str,
+    report: &bphelper_manifest::ValidationReport,
+    crate_root: &Path,
+) -> Result<()> {
+    let diagnostics: Vec<crate::output::Diagnostic> =
+        report.diagnostics.iter().map(Into::into).collect();
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == crate::output::Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+
+    // Templates are only built once the manifest itself is sound.
+    let template_error = if errors == 0 {
+        validate_templates_with_log(crate_root.to_str().unwrap_or("."), &mut std::io::stderr())
+            .err()
+            .map(|e| format!("{:#}", e))
+    } else {
+        None
+    };
+
+    let valid = errors == 0 && template_error.is_none();
+    crate::output::print(
+        "validate",
+        &crate::output::ValidateOutput {
+            name: name.to_string(),
+            valid,
+            errors,
+            warnings,
+            diagnostics,
+            template_error,
+        },
+    )?;
+    if !valid {
+        bail!("validation failed");
+    }
+    Ok(())
+}
+
 /// Validate that each template in a battery pack generates a project that compiles
 /// and passes tests.
 ///
@@ -99,6 +151,11 @@ pub(crate) fn validate_battery_pack_cmd(path: Option<&str>) -> Result<()> {
 // [impl cli.validate.templates]
 // [impl cli.validate.templates.cache]
 pub fn validate_templates(manifest_dir: &str) -> Result<()> {
+    validate_templates_with_log(manifest_dir, &mut std::io::stdout())
+}
+
+/// [`validate_templates`], writing progress messages to `log`.
+fn validate_templates_with_log(manifest_dir: &str, log: &mut dyn Write) -> Result<()> {
     let manifest_dir = Path::new(manifest_dir);
     let cargo_toml = manifest_dir.join("Cargo.toml");
     let content = std::fs::read_to_string(&cargo_toml)
@@ -113,7 +170,7 @@ pub fn validate_templates(manifest_dir: &str) -> Result<()> {
 
     if spec.templates.is_empty() {
         // [impl cli.validate.templates.none]
-        println!("no templates to validate");
+        writeln!(log, "no templates to validate")?;
         return Ok(());
     }
 
@@ -126,7 +183,7 @@ pub fn validate_templates(manifest_dir: &str) -> Result<()> {
     let shared_target_dir = metadata.target_directory.join("bp-validate");
 
     for (name, template) in &spec.templates {
-        println!("validating template '{name}'...");
+        writeln!(log, "validating template '{name}'...")?;
 
         let tmp = tempfile::tempdir().context("failed to create temp directory")?;
 
@@ -178,14 +235,15 @@ pub fn validate_templates(manifest_dir: &str) -> Result<()> {
             String::from_utf8_lossy(&output.stderr)
         );
 
-        println!("template '{name}' ok");
+        writeln!(log, "template '{name}' ok")?;
     }
 
-    println!(
+    writeln!(
+        log,
         "all {} template(s) for '{}' validated successfully",
         spec.templates.len(),
         crate_name
-    );
+    )?;
     Ok(())
 }
 
@@ -4,6 +4,8 @@ use indoc::indoc;
 use snapbox::{assert_data_eq, str};
 use std::path::PathBuf;
 
+use crate::output::MessageFormat;
+
 fn fixtures_dir() -> PathBuf {
     let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
     manifest_dir
@@ -21,7 +23,8 @@ fn fixtures_dir() -> PathBuf {
 #[test]
 fn validate_basic_fixture_is_clean() {
     let fixture = fixtures_dir().join("basic-battery-pack");
-    let result = super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()));
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Human);
     assert!(result.is_ok(), "basic-battery-pack should validate cleanly");
 }
 
@@ -30,7 +33,8 @@ fn validate_basic_fixture_is_clean() {
 #[test]
 fn validate_fancy_fixture_is_clean() {
     let fixture = fixtures_dir().join("fancy-battery-pack");
-    let result = super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()));
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Human);
     assert!(result.is_ok(), "fancy-battery-pack should validate cleanly");
 }
 
@@ -40,7 +44,8 @@ fn validate_fancy_fixture_is_clean() {
 #[test]
 fn validate_broken_fixture_fails() {
     let fixture = fixtures_dir().join("broken-battery-pack");
-    let result = super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()));
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Human);
     assert!(
         result.is_err(),
         "broken-battery-pack should fail validation"
@@ -54,7 +59,8 @@ fn validate_broken_fixture_fails() {
 fn validate_workspace_manifest_fails() {
     let fixture = fixtures_dir();
     // The fixtures directory itself has a workspace Cargo.toml
-    let result = super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()));
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Human);
     assert!(result.is_err(), "workspace manifest should fail");
     let err = result.unwrap_err().to_string();
     assert_data_eq!(
@@ -69,7 +75,7 @@ Run this from a battery pack crate directory, or use --path to point to one.
 // [verify cli.validate.no-package]
 #[test]
 fn validate_nonexistent_path_fails() {
-    let result = super::validate_battery_pack_cmd(Some("/nonexistent/path"));
+    let result = super::validate_battery_pack_cmd(Some("/nonexistent/path"), MessageFormat::Human);
     assert!(result.is_err(), "nonexistent path should fail");
 }
 
@@ -78,7 +84,8 @@ fn validate_nonexistent_path_fails() {
 fn validate_uses_path_argument() {
     // Verify --path correctly targets a specific directory rather than cwd
     let fixture = fixtures_dir().join("basic-battery-pack");
-    let result = super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()));
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Human);
     assert!(
         result.is_ok(),
         "explicit --path to a valid fixture should succeed"
@@ -92,7 +99,7 @@ fn validate_defaults_to_current_directory() {
     let fixture = fixtures_dir().join("fancy-battery-pack");
     let original_dir = std::env::current_dir().unwrap();
     std::env::set_current_dir(&fixture).unwrap();
-    let result = super::validate_battery_pack_cmd(None);
+    let result = super::validate_battery_pack_cmd(None, MessageFormat::Human);
     std::env::set_current_dir(&original_dir).unwrap();
     assert!(
         result.is_ok(),
@@ -105,7 +112,8 @@ fn validate_defaults_to_current_directory() {
 #[test]
 fn validate_fixture_without_repository_warns_but_passes() {
     let fixture = fixtures_dir().join("basic-battery-pack");
-    let result = super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()));
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Human);
     assert!(
         result.is_ok(),
         "basic-battery-pack should pass validation (warnings only): {:?}",
@@ -117,7 +125,8 @@ fn validate_fixture_without_repository_warns_but_passes() {
 #[test]
 fn validate_fixture_with_repository_no_warning() {
     let fixture = fixtures_dir().join("fancy-battery-pack");
-    let result = super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()));
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Human);
     assert!(
         result.is_ok(),
         "fancy-battery-pack should validate cleanly: {:?}",
@@ -210,3 +219,12 @@ test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out;
 "#]]
     );
 }
+
+// [verify cli.json.validate]
+#[test]
+fn validate_json_reports_failure_exit() {
+    let fixture = fixtures_dir().join("broken-battery-pack");
+    let result =
+        super::validate_battery_pack_cmd(Some(fixture.to_str().unwrap()), MessageFormat::Json);
+    assert!(result.is_err(), "JSON mode keeps the failing exit status");
+}
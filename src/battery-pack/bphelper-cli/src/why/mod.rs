@@ -0,0 +1,191 @@
+//! Support for `cargo bp why`: tracing a dependency back to the battery
+//! packs, and the features within them, that put it in the manifest.
+
+#[cfg(test)]
+mod tests;
+
+use bphelper_manifest::{BatteryPackSpec, CrateSpec, DepKind, MergedCrateSpec};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::drift::{DeclaredDep, PackState, ProjectDeps};
+
+/// Why a pack's resolved spec includes a crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Reason {
+    /// An active feature lists it. `default` covers the implicit default
+    /// set (every non-optional crate) when the pack has no `default`
+    /// feature.
+    Feature(String),
+    /// The `all` sentinel is active.
+    AllFeatures,
+    /// Dev- and build-dependencies aren't feature-gated.
+    Always(DepKind),
+}
+
+/// What one pack says about the crate.
+#[derive(Debug, Clone)]
+pub(crate) struct Recommendation {
+    pub pack: String,
+    pub pack_version: String,
+    pub spec: CrateSpec,
+    /// Why the active features include the crate; empty when they don't.
+    pub reasons: Vec<Reason>,
+    /// Inactive features that would bring the crate in.
+    pub available_via: Vec<String>,
+    /// Whether this pack's version is the one the merged plan uses.
+    pub wins: bool,
+}
+
+impl Recommendation {
+    pub(crate) fn is_active(&self) -> bool {
+        !self.reasons.is_empty()
+    }
+}
+
+/// Who owns the crate's entry in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Ownership {
+    /// Recorded in these packs' `managed-deps`; sync and `rm` maintain it.
+    Managed(Vec<String>),
+    /// Declared, but no pack manages it.
+    User,
+    /// Not declared in the manifest at all.
+    Absent,
+}
+
+/// Everything `cargo bp why` reports about one crate.
+#[derive(Debug, Clone)]
+pub(crate) struct Explanation {
+    pub crate_name: String,
+    pub declared: Vec<DeclaredDep>,
+    pub ownership: Ownership,
+    /// One entry per installed pack that curates the crate.
+    pub recommendations: Vec<Recommendation>,
+    /// The crate's entry in the plan merged across every installed pack,
+    /// if any pack's active features include it.
+    pub merged: Option<MergedCrateSpec>,
+}
+
+/// Explain where `crate_name` comes from.
+// [impl cli.why.packs]
+// [impl cli.why.winner]
+// [impl cli.why.ownership]
+pub(crate) fn explain(
+    crate_name: &str,
+    project: &ProjectDeps,
+    packs: &[PackState<'_>],
+) -> Explanation {
+    let resolved: Vec<BTreeMap<String, CrateSpec>> = packs
+        .iter()
+        .map(|pack| pack.spec.resolve_for_features(pack.active_features))
+        .collect();
+    let merged = bphelper_manifest::merge_crate_specs(&resolved).remove(crate_name);
+
+    let recommendations = packs
+        .iter()
+        .zip(&resolved)
+        .filter_map(|(pack, resolved)| {
+            let active = resolved.get(crate_name);
+            let spec = active.or_else(|| pack.spec.crates.get(crate_name))?;
+            Some(Recommendation {
+                pack: pack.name.to_string(),
+                pack_version: pack.spec.version.clone(),
+                spec: spec.clone(),
+                reasons: if active.is_some() {
+                    reasons(pack.spec, pack.active_features, crate_name)
+                } else {
+                    Vec::new()
+                },
+                available_via: available_via(pack.spec, pack.active_features, crate_name),
+                wins: active
+                    .zip(merged.as_ref())
+                    .is_some_and(|(spec, merged)| spec.version == merged.version),
+            })
+        })
+        .collect();
+
+    let declared = project.deps.get(crate_name).cloned().unwrap_or_default();
+    let managers: Vec<String> = packs
+        .iter()
+        .filter(|pack| pack.managed_deps.is_some_and(|m| m.contains(crate_name)))
+        .map(|pack| pack.name.to_string())
+        .collect();
+    let ownership = if !managers.is_empty() {
+        Ownership::Managed(managers)
+    } else if declared.is_empty() {
+        Ownership::Absent
+    } else {
+        Ownership::User
+    };
+
+    Explanation {
+        crate_name: crate_name.to_string(),
+        declared,
+        ownership,
+        recommendations,
+        merged,
+    }
+}
+
+/// Why `active_features` resolve to include `crate_name`. Mirrors
+/// [`BatteryPackSpec::resolve_for_features`].
+fn reasons(
+    spec: &BatteryPackSpec,
+    active_features: &BTreeSet<String>,
+    crate_name: &str,
+) -> Vec<Reason> {
+    if active_features.iter().any(|f| f == "all") {
+        return vec![Reason::AllFeatures];
+    }
+
+    let mut reasons = Vec::new();
+    let in_default = match spec.features.get("default") {
+        Some(crates) => crates.contains(crate_name),
+        None => spec.crates.get(crate_name).is_some_and(|c| !c.optional),
+    };
+    if default_active(active_features) && in_default {
+        reasons.push(Reason::Feature("default".to_string()));
+    }
+    for feature in active_features {
+        if feature != "default"
+            && spec
+                .features
+                .get(feature)
+                .is_some_and(|crates| crates.contains(crate_name))
+        {
+            reasons.push(Reason::Feature(feature.clone()));
+        }
+    }
+    if let Some(crate_spec) = spec.crates.get(crate_name)
+        && crate_spec.dep_kind != DepKind::Normal
+        && !spec.is_hidden(crate_name)
+    {
+        reasons.push(Reason::Always(crate_spec.dep_kind));
+    }
+    reasons
+}
+
+/// Features that list `crate_name` but aren't active.
+fn available_via(
+    spec: &BatteryPackSpec,
+    active_features: &BTreeSet<String>,
+    crate_name: &str,
+) -> Vec<String> {
+    if active_features.iter().any(|f| f == "all") {
+        return Vec::new();
+    }
+    spec.features
+        .iter()
+        .filter(|(feature, crates)| {
+            let active = active_features.contains(*feature)
+                || (*feature == "default" && default_active(active_features));
+            !active && crates.contains(crate_name)
+        })
+        .map(|(feature, _)| feature.clone())
+        .collect()
+}
+
+/// No features recorded means the default set.
+fn default_active(active_features: &BTreeSet<String>) -> bool {
+    active_features.is_empty() || active_features.contains("default")
+}
@@ -0,0 +1,145 @@
+//! Tests for dependency provenance.
+
+use super::*;
+
+const CLI: &str = r#"
+[package]
+name = "cli-battery-pack"
+version = "0.5.0"
+keywords = ["battery-pack"]
+
+[dependencies]
+clap = { version = "4.5", features = ["derive"], optional = true }
+console = { version = "0.15.8", optional = true }
+indicatif = { version = "0.17", optional = true }
+
+[dev-dependencies]
+snapbox = "0.6"
+
+[features]
+default = ["clap", "console"]
+progress = ["indicatif", "console"]
+"#;
+
+const ERROR: &str = r#"
+[package]
+name = "error-battery-pack"
+version = "0.3.0"
+keywords = ["battery-pack"]
+
+[dependencies]
+anyhow = "1"
+console = "0.15.2"
+"#;
+
+fn spec(manifest: &str) -> BatteryPackSpec {
+    bphelper_manifest::parse_battery_pack(manifest).unwrap()
+}
+
+fn set(items: &[&str]) -> BTreeSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+fn project(manifest: &str) -> ProjectDeps {
+    ProjectDeps::from_manifests(&toml::from_str(manifest).unwrap(), None)
+}
+
+// [verify cli.why.packs]
+// [verify cli.why.winner]
+#[test]
+fn names_every_pack_and_the_winning_version() {
+    let (cli, error) = (spec(CLI), spec(ERROR));
+    let (cli_features, error_features) = (set(&["default", "progress"]), set(&[]));
+    let managed = set(&["console"]);
+    let packs = [
+        PackState {
+            name: "cli-battery-pack",
+            spec: &cli,
+            active_features: &cli_features,
+            managed_deps: Some(&managed),
+            pinned: &BTreeSet::new(),
+        },
+        PackState {
+            name: "error-battery-pack",
+            spec: &error,
+            active_features: &error_features,
+            managed_deps: None,
+            pinned: &BTreeSet::new(),
+        },
+    ];
+    let project = project("[dependencies]\nconsole = \"0.15.8\"\n");
+
+    let why = explain("console", &project, &packs);
+
+    assert_eq!(why.merged.unwrap().version, "0.15.8");
+    let [cli, error] = &why.recommendations[..] else {
+        panic!("{:?}", why.recommendations);
+    };
+    assert_eq!(
+        cli.reasons,
+        [
+            Reason::Feature("default".into()),
+            Reason::Feature("progress".into())
+        ]
+    );
+    assert!(cli.wins);
+    assert_eq!(error.reasons, [Reason::Feature("default".into())]);
+    assert_eq!(error.spec.version, "0.15.2");
+    assert!(!error.wins);
+    assert_eq!(
+        why.ownership,
+        Ownership::Managed(vec!["cli-battery-pack".into()])
+    );
+}
+
+// [verify cli.why.packs]
+#[test]
+fn inactive_crates_list_the_features_that_would_add_them() {
+    let cli = spec(CLI);
+    let features = set(&[]);
+    let packs = [PackState {
+        name: "cli-battery-pack",
+        spec: &cli,
+        active_features: &features,
+        managed_deps: None,
+        pinned: &BTreeSet::new(),
+    }];
+
+    let why = explain("indicatif", &project("[dependencies]\n"), &packs);
+
+    assert!(why.merged.is_none());
+    assert!(!why.recommendations[0].is_active());
+    assert_eq!(why.recommendations[0].available_via, ["progress"]);
+    assert_eq!(why.ownership, Ownership::Absent);
+
+    let why = explain("snapbox", &project("[dependencies]\n"), &packs);
+    assert_eq!(
+        why.recommendations[0].reasons,
+        [Reason::Always(DepKind::Dev)]
+    );
+}
+
+// [verify cli.why.ownership]
+#[test]
+fn unmanaged_declarations_are_user_owned() {
+    let cli = spec(CLI);
+    let features = set(&["all"]);
+    let managed = set(&["clap"]);
+    let packs = [PackState {
+        name: "cli-battery-pack",
+        spec: &cli,
+        active_features: &features,
+        managed_deps: Some(&managed),
+        pinned: &BTreeSet::new(),
+    }];
+    let project = project("[dependencies]\nconsole = \"0.15\"\nserde = \"1\"\n");
+
+    let console = explain("console", &project, &packs);
+    assert_eq!(console.ownership, Ownership::User);
+    assert_eq!(console.recommendations[0].reasons, [Reason::AllFeatures]);
+
+    let serde = explain("serde", &project, &packs);
+    assert_eq!(serde.ownership, Ownership::User);
+    assert!(serde.recommendations.is_empty());
+    assert_eq!(serde.declared[0].version.as_deref(), Some("1"));
+}
@@ -0,0 +1,91 @@
+//! Workspace member selection: cargo-style `--workspace`, `-p/--package` and
+//! `--exclude` for commands that act on one manifest.
+//!
+//! Without any of the flags a command runs on the manifest in the current
+//! directory, as it always has. With them it runs once per selected member,
+//! enumerated through `cargo metadata`.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+
+/// Which workspace members a command runs on.
+// [impl cli.workspace.select]
+#[derive(Debug, Clone, Default, clap::Args)]
+pub(crate) struct MemberSelection {
+    /// Run on every member of the workspace
+    #[arg(long)]
+    pub workspace: bool,
+
+    /// Run on this workspace member (repeatable)
+    #[arg(long = "package", short = 'p', value_name = "SPEC")]
+    pub package: Vec<String>,
+
+    /// Leave this member out of --workspace (repeatable)
+    #[arg(long, value_name = "SPEC", requires = "workspace")]
+    pub exclude: Vec<String>,
+}
+
+impl MemberSelection {
+    /// True when any of the flags were given.
+    pub(crate) fn is_explicit(&self) -> bool {
+        self.workspace || !self.package.is_empty()
+    }
+
+    /// The members of the workspace containing `project_dir` that this
+    /// selection picks, in `cargo metadata` order.
+    pub(crate) fn resolve(&self, project_dir: &Path) -> Result<Vec<Member>> {
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .current_dir(project_dir)
+            .no_deps()
+            .exec()
+            .context("Failed to run `cargo metadata`")?;
+        let members = metadata
+            .workspace_packages()
+            .into_iter()
+            .map(|package| Member {
+                name: package.name.to_string(),
+                dir: package
+                    .manifest_path
+                    .parent()
+                    .map(|dir| dir.as_std_path().to_path_buf())
+                    .unwrap_or_else(|| metadata.workspace_root.clone().into()),
+            })
+            .collect();
+        self.select(members)
+    }
+
+    /// Pick members by name. Naming a package that isn't a member is an
+    /// error, so typos don't silently shrink the run.
+    pub(crate) fn select(&self, members: Vec<Member>) -> Result<Vec<Member>> {
+        for name in self.package.iter().chain(&self.exclude) {
+            if !members.iter().any(|m| m.name == *name) {
+                bail!("package '{}' is not a member of this workspace", name);
+            }
+        }
+        let selected: Vec<Member> = members
+            .into_iter()
+            .filter(|m| {
+                if self.workspace {
+                    !self.exclude.contains(&m.name)
+                } else {
+                    self.package.contains(&m.name)
+                }
+            })
+            .collect();
+        if selected.is_empty() {
+            bail!("no workspace members selected");
+        }
+        Ok(selected)
+    }
+}
+
+/// One selected workspace member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Member {
+    pub name: String,
+    /// The directory holding the member's `Cargo.toml`.
+    pub dir: PathBuf,
+}
@@ -0,0 +1,67 @@
+//! Tests for workspace member selection.
+
+use super::*;
+use clap::Parser;
+
+#[derive(Parser)]
+struct Args {
+    #[command(flatten)]
+    members: MemberSelection,
+}
+
+fn parse(args: &[&str]) -> Result<MemberSelection, clap::Error> {
+    Args::try_parse_from(std::iter::once("test").chain(args.iter().copied()))
+        .map(|args| args.members)
+}
+
+fn members() -> Vec<Member> {
+    ["app", "core", "cli"]
+        .iter()
+        .map(|name| Member {
+            name: name.to_string(),
+            dir: PathBuf::from("crates").join(name),
+        })
+        .collect()
+}
+
+fn names(members: &[Member]) -> Vec<&str> {
+    members.iter().map(|m| m.name.as_str()).collect()
+}
+
+// [verify cli.workspace.select]
+#[test]
+fn workspace_selects_every_member_but_excluded_ones() {
+    let selection = parse(&["--workspace", "--exclude", "core"]).unwrap();
+    assert!(selection.is_explicit());
+    assert_eq!(names(&selection.select(members()).unwrap()), ["app", "cli"]);
+}
+
+// [verify cli.workspace.select]
+#[test]
+fn packages_select_named_members() {
+    let selection = parse(&["-p", "cli", "--package", "app"]).unwrap();
+    assert_eq!(names(&selection.select(members()).unwrap()), ["app", "cli"]);
+    assert!(!parse(&[]).unwrap().is_explicit());
+}
+
+// [verify cli.workspace.select]
+#[test]
+fn unknown_packages_and_exclude_without_workspace_are_rejected() {
+    let err = parse(&["-p", "nope"])
+        .unwrap()
+        .select(members())
+        .unwrap_err();
+    assert!(err.to_string().contains("'nope'"), "{err}");
+    assert!(parse(&["--exclude", "core"]).is_err());
+
+    let everything = parse(&[
+        "--workspace",
+        "--exclude",
+        "app",
+        "--exclude",
+        "core",
+        "--exclude",
+        "cli",
+    ]);
+    assert!(everything.unwrap().select(members()).is_err());
+}
@@ -426,6 +426,66 @@ impl BatteryPackSpec {
     }
 }
 
+// ============================================================================
+// Version-to-version changes
+// ============================================================================
+
+/// A change to a visible curated crate between two releases of a battery pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrateChange {
+    /// The newer release curates a crate the older one didn't.
+    Added { name: String, version: String },
+    /// The newer release no longer curates this crate.
+    Removed { name: String, version: String },
+    /// The recommended version changed.
+    VersionChanged {
+        name: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl CrateChange {
+    /// The crate this change is about.
+    pub fn name(&self) -> &str {
+        match self {
+            CrateChange::Added { name, .. }
+            | CrateChange::Removed { name, .. }
+            | CrateChange::VersionChanged { name, .. } => name,
+        }
+    }
+}
+
+impl BatteryPackSpec {
+    /// List the visible crates added, removed, or re-versioned in `newer`
+    /// relative to `self`, ordered by crate name.
+    pub fn diff_crates(&self, newer: &BatteryPackSpec) -> Vec<CrateChange> {
+        let old = self.visible_crates();
+        let new = newer.visible_crates();
+        let names: BTreeSet<&str> = old.keys().chain(new.keys()).copied().collect();
+
+        names
+            .into_iter()
+            .filter_map(|name| match (old.get(name), new.get(name)) {
+                (None, Some(spec)) => Some(CrateChange::Added {
+                    name: name.to_string(),
+                    version: spec.version.clone(),
+                }),
+                (Some(spec), None) => Some(CrateChange::Removed {
+                    name: name.to_string(),
+                    version: spec.version.clone(),
+                }),
+                (Some(a), Some(b)) if a.version != b.version => Some(CrateChange::VersionChanged {
+                    name: name.to_string(),
+                    from: a.version.clone(),
+                    to: b.version.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 // ============================================================================
 // Glob matching (minimal, for hidden dep patterns)
 // ============================================================================
@@ -989,6 +1049,66 @@ fn validate_templates_on_disk(
 mod tests {
     use super::*;
 
+    // -- Version diff tests --
+
+    #[test]
+    fn diff_crates_reports_added_removed_and_bumped() {
+        let old = parse_battery_pack(
+            r#"
+            [package]
+            name = "test-battery-pack"
+            version = "0.1.0"
+
+            [dependencies]
+            clap = "3"
+            console = "0.15"
+            serde = "1"
+            internal = "1"
+
+            [package.metadata.battery-pack]
+            hidden = ["internal"]
+        "#,
+        )
+        .unwrap();
+        let new = parse_battery_pack(
+            r#"
+            [package]
+            name = "test-battery-pack"
+            version = "0.2.0"
+
+            [dependencies]
+            clap = "4"
+            indicatif = "0.17"
+            serde = "1"
+            internal = "2"
+
+            [package.metadata.battery-pack]
+            hidden = ["internal"]
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            old.diff_crates(&new),
+            vec![
+                CrateChange::VersionChanged {
+                    name: "clap".to_string(),
+                    from: "3".to_string(),
+                    to: "4".to_string(),
+                },
+                CrateChange::Removed {
+                    name: "console".to_string(),
+                    version: "0.15".to_string(),
+                },
+                CrateChange::Added {
+                    name: "indicatif".to_string(),
+                    version: "0.17".to_string(),
+                },
+            ]
+        );
+        assert!(new.diff_crates(&new).is_empty());
+    }
+
     // -- Parsing tests --
 
     #[test]
@@ -0,0 +1,142 @@
+//! Tests for adding several battery packs in one `cargo bp add`.
+
+mod common;
+
+fn project() -> tempfile::TempDir {
+    common::project("[package]\nname = \"test-project\"\nversion = \"0.1.0\"\nedition = \"2021\"\n")
+}
+
+fn add(project: &tempfile::TempDir, args: &[&str]) -> (bool, String, String) {
+    let (code, stdout, stderr) = common::output(
+        common::cargo_bp()
+            .current_dir(project.path())
+            .args([
+                "bp",
+                "--crate-source",
+                &common::fixtures_dir().to_string_lossy(),
+            ])
+            .arg("add")
+            .args(args),
+    );
+    (code == Some(0), stdout, stderr)
+}
+
+// [verify cli.add.multiple]
+// [verify cli.add.pack-features]
+#[test]
+fn several_packs_are_added_in_one_go() {
+    let project = project();
+    let (ok, stdout, stderr) = add(
+        &project,
+        &[
+            "basic",
+            "fancy:indicators",
+            "managed:",
+            "-F",
+            "fancy/default",
+        ],
+    );
+    assert!(ok, "{stdout}\n{stderr}");
+    assert!(stdout.contains("Adding 3 battery packs:"), "{stdout}");
+    assert!(
+        stdout.contains("fancy-battery-pack (default, indicators):"),
+        "{stdout}"
+    );
+
+    let manifest = common::read(project.path(), "Cargo.toml");
+    let doc: toml::Table = manifest.parse().unwrap();
+    for name in ["anyhow", "thiserror", "clap", "indicatif", "console"] {
+        assert!(
+            doc["dependencies"].get(name).is_some(),
+            "{name}: {manifest}"
+        );
+    }
+    assert!(doc["dev-dependencies"].get("insta").is_some(), "{manifest}");
+    let packs = doc["package"]["metadata"]["battery-pack"]
+        .as_table()
+        .unwrap();
+    let names: Vec<&str> = packs.keys().map(String::as_str).collect();
+    assert_eq!(
+        names,
+        [
+            "basic-battery-pack",
+            "fancy-battery-pack",
+            "managed-battery-pack"
+        ]
+    );
+}
+
+// [verify cli.add.multiple]
+#[test]
+fn crates_of_the_first_pack_are_still_crates() {
+    let project = project();
+    let (ok, stdout, stderr) = add(&project, &["basic", "thiserror"]);
+    assert!(ok, "{stdout}\n{stderr}");
+    assert!(!stdout.contains("Adding"), "{stdout}");
+
+    let manifest = common::read(project.path(), "Cargo.toml");
+    assert!(manifest.contains("thiserror"), "{manifest}");
+    assert!(!manifest.contains("anyhow"), "{manifest}");
+}
+
+// [verify cli.add.pack-features]
+#[test]
+fn features_for_another_pack_add_it() {
+    let project = project();
+    let (ok, stdout, stderr) = add(&project, &["basic", "-F", "fancy/indicators"]);
+    assert!(ok, "{stdout}\n{stderr}");
+    assert!(stdout.contains("Adding 2 battery packs:"), "{stdout}");
+    assert!(
+        stdout.contains("fancy-battery-pack (default, indicators):"),
+        "{stdout}"
+    );
+}
+
+/// A crate source with `cli`, `logging` and `error` packs, one crate each.
+fn named_packs() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("Cargo.toml"),
+        "[workspace]\nmembers = [\"cli-battery-pack\", \"logging-battery-pack\", \"error-battery-pack\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    for (pack, krate) in [("cli", "clap"), ("logging", "tracing"), ("error", "anyhow")] {
+        let pack_dir = dir.path().join(format!("{pack}-battery-pack"));
+        std::fs::create_dir_all(pack_dir.join("src")).unwrap();
+        std::fs::write(pack_dir.join("src/lib.rs"), "").unwrap();
+        std::fs::write(
+            pack_dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{pack}-battery-pack\"\nversion = \"0.1.0\"\nedition = \"2021\"\nkeywords = [\"battery-pack\"]\n\n\
+                 [dependencies]\n{krate} = {{ version = \"1\", optional = true }}\n\n\
+                 [features]\ndefault = [\"{krate}\"]\n"
+            ),
+        )
+        .unwrap();
+    }
+    dir
+}
+
+// [verify cli.add.multiple]
+#[test]
+fn bare_pack_names_are_added_together() {
+    let packs = named_packs();
+    let project = project();
+    let (code, stdout, stderr) = common::output(
+        common::cargo_bp()
+            .current_dir(project.path())
+            .args(["bp", "--crate-source", &packs.path().to_string_lossy()])
+            .args(["add", "cli", "logging", "error"]),
+    );
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(stdout.contains("Adding 3 battery packs:"), "{stdout}");
+
+    let manifest = common::read(project.path(), "Cargo.toml");
+    let doc: toml::Table = manifest.parse().unwrap();
+    for name in ["clap", "tracing", "anyhow"] {
+        assert!(
+            doc["dependencies"].get(name).is_some(),
+            "{name}: {manifest}"
+        );
+    }
+}
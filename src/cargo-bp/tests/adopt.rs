@@ -0,0 +1,91 @@
+//! Tests for `cargo bp adopt`.
+
+mod common;
+
+/// A project that already uses two of basic-battery-pack's crates, one of
+/// them at an older version than the pack recommends.
+fn project() -> tempfile::TempDir {
+    common::project(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "1"
+eyre = "0.5"
+serde = "1"
+"#,
+    )
+}
+
+fn adopt(project: &tempfile::TempDir, args: &[&str]) -> (bool, String, String) {
+    let fixtures = common::fixtures_dir().to_string_lossy().into_owned();
+    let args: Vec<&str> = ["--crate-source", &fixtures, "adopt", "basic"]
+        .into_iter()
+        .chain(args.iter().copied())
+        .collect();
+    let (code, stdout, stderr) = common::run(project.path(), &args);
+    (code == Some(0), stdout, stderr)
+}
+
+fn manifest(project: &tempfile::TempDir) -> toml::Table {
+    toml::from_str(&common::read(project.path(), "Cargo.toml")).unwrap()
+}
+
+// [verify cli.adopt.register]
+// [verify cli.adopt.report]
+#[test]
+fn adopt_registers_the_pack_without_touching_dependencies() {
+    let project = project();
+    let (ok, stdout, stderr) = adopt(&project, &[]);
+    assert!(ok, "adopt failed: {stderr}");
+    assert!(stdout.contains("eyre: 0.5 → 0.6 recommended"), "{stdout}");
+
+    let manifest = manifest(&project);
+    assert!(
+        manifest["build-dependencies"]
+            .get("basic-battery-pack")
+            .is_some()
+    );
+    let entry = &manifest["package"]["metadata"]["battery-pack"]["basic-battery-pack"];
+    assert_eq!(entry["features"], toml::Value::from(vec!["all-errors"]));
+    assert_eq!(
+        entry["managed-deps"],
+        toml::Value::from(vec!["anyhow", "eyre"])
+    );
+    let deps = manifest["dependencies"].as_table().unwrap();
+    assert_eq!(deps["eyre"].as_str(), Some("0.5"));
+    assert!(!deps.contains_key("thiserror"));
+
+    let (ok, _, stderr) = adopt(&project, &[]);
+    assert!(!ok);
+    assert!(stderr.contains("already installed"), "{stderr}");
+}
+
+// [verify cli.adopt.sync]
+#[test]
+fn adopt_with_sync_applies_the_recommendations() {
+    let project = project();
+    let (ok, _, stderr) = adopt(&project, &["--sync"]);
+    assert!(ok, "adopt --sync failed: {stderr}");
+
+    let manifest = manifest(&project);
+    let deps = manifest["dependencies"].as_table().unwrap();
+    assert_eq!(deps["eyre"].as_str(), Some("0.6"));
+    assert!(deps.contains_key("thiserror"));
+    assert_eq!(deps["serde"].as_str(), Some("1"));
+}
+
+#[test]
+fn adopt_refuses_when_nothing_overlaps() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"p\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1\"\n",
+    )
+    .unwrap();
+    let (ok, _, stderr) = adopt(&dir, &[]);
+    assert!(!ok);
+    assert!(stderr.contains("cargo bp add basic"), "{stderr}");
+}
@@ -0,0 +1,88 @@
+//! Tests for `cargo bp check` drift findings and exit codes.
+
+mod common;
+
+use common::{basic_fixture, run};
+
+/// A project that registers basic-battery-pack with the given dependencies.
+fn project(dependencies: &str) -> tempfile::TempDir {
+    common::project(&format!(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+{dependencies}
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+"#
+    ))
+}
+
+/// Run `cargo bp check --path <fixture>`; returns the exit code and stdout.
+fn check(project: &tempfile::TempDir) -> (Option<i32>, String) {
+    let (code, stdout, _) = run(project.path(), &["check", "--path", &basic_fixture()]);
+    (code, stdout)
+}
+
+// [verify cli.drift.exit-codes]
+// [verify cli.check.path]
+#[test]
+fn check_exits_zero_when_clean() {
+    let project = project("anyhow = \"1\"\nthiserror = \"2\"");
+    let (code, stdout) = check(&project);
+    assert_eq!(code, Some(0), "{stdout}");
+    assert!(stdout.contains("✅ OK"), "{stdout}");
+}
+
+// [verify cli.drift.exit-codes]
+#[test]
+fn check_exits_two_on_warnings() {
+    let project = project("anyhow = \"0.9\"\nthiserror = \"2\"");
+    let (code, stdout) = check(&project);
+    assert_eq!(code, Some(2), "{stdout}");
+    assert!(stdout.contains("anyhow: 0.9 → 1 recommended"), "{stdout}");
+}
+
+// [verify cli.drift.exit-codes]
+// [verify cli.check.output]
+#[test]
+fn check_exits_three_on_errors() {
+    let project = project("anyhow = \"1\"");
+    let (code, stdout) = check(&project);
+    assert_eq!(code, Some(3), "{stdout}");
+    assert!(
+        stdout.contains("thiserror: missing from [dependencies]"),
+        "{stdout}"
+    );
+}
+
+// [verify cli.json.check]
+#[test]
+fn check_json_reports_status_and_findings() {
+    let project = project("anyhow = \"0.9\"");
+    let (code, stdout, _) = run(
+        project.path(),
+        &[
+            "check",
+            "--path",
+            &basic_fixture(),
+            "--message-format",
+            "json",
+        ],
+    );
+    assert_eq!(code, Some(3));
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["status"], "errors");
+    assert_eq!(json["ok"], false);
+    let drift: Vec<&str> = json["packs"][0]["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["drift"].as_str().unwrap())
+        .collect();
+    assert_eq!(drift, ["outdated", "missing-crate"]);
+}
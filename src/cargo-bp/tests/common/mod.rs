@@ -0,0 +1,76 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate uses only some of these.
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use std::path::{Path, PathBuf};
+
+pub fn cargo_bp() -> Command {
+    Command::new(assert_cmd::cargo::cargo_bin!("cargo-bp"))
+}
+
+/// The repository's `tests/fixtures` directory.
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("tests/fixtures")
+}
+
+/// The repository's `battery-pack` crate, whose templates `cargo bp new`
+/// uses.
+pub fn battery_pack_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("battery-pack")
+}
+
+/// The basic-battery-pack fixture, ready to pass to `--path`.
+pub fn basic_fixture() -> String {
+    fixtures_dir()
+        .join("basic-battery-pack")
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// A temporary project with the given `Cargo.toml`.
+pub fn project(manifest: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+    dir
+}
+
+/// Read a file from a project.
+pub fn read(dir: &Path, file: &str) -> String {
+    std::fs::read_to_string(dir.join(file)).unwrap()
+}
+
+/// Run `command`; returns its exit code, stdout and stderr.
+pub fn output(command: &mut Command) -> (Option<i32>, String, String) {
+    let output = command.output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+/// Run `cargo bp <args>` in `dir`.
+pub fn run(dir: &Path, args: &[&str]) -> (Option<i32>, String, String) {
+    output(cargo_bp().current_dir(dir).arg("bp").args(args))
+}
+
+/// Run `cargo bp <args> --path <basic-battery-pack>` in `dir`.
+pub fn run_basic(dir: &Path, args: &[&str]) -> (Option<i32>, String, String) {
+    output(
+        cargo_bp()
+            .current_dir(dir)
+            .arg("bp")
+            .args(args)
+            .args(["--path", &basic_fixture()]),
+    )
+}
@@ -0,0 +1,38 @@
+//! Tests for `cargo bp config` with a broken config file.
+
+mod common;
+
+// [verify cli.config.broken]
+#[test]
+fn config_set_runs_despite_an_unknown_key() {
+    let project = common::project(
+        "[package]\nname = \"test-project\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    );
+    let home = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join(".cargo-bp.toml"),
+        "tagret = \"package\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        common::output(
+            common::cargo_bp()
+                .current_dir(project.path())
+                .env("XDG_CONFIG_HOME", home.path())
+                .arg("bp")
+                .args(args),
+        )
+    };
+
+    let (code, _, stderr) = run(&["status"]);
+    assert_ne!(code, Some(0));
+    assert!(stderr.contains("tagret"), "{stderr}");
+
+    let (code, stdout, stderr) = run(&["config", "set", "target", "package"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(stderr.contains("warning:"), "{stderr}");
+    assert!(
+        common::read(project.path(), ".cargo-bp.toml").contains("target = \"package\""),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,77 @@
+//! Tests for `cargo bp doctor`.
+
+mod common;
+
+const MANIFEST: &str = r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "1"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["default"]
+"#;
+
+const BUILD_RS: &str = "fn main() {\n    basic_battery_pack::validate();\n    \
+                        old_battery_pack::validate();\n}\n";
+
+/// A project with old-format metadata and a leftover `validate()` call.
+fn project() -> tempfile::TempDir {
+    let dir = common::project(MANIFEST);
+    std::fs::write(dir.path().join("build.rs"), BUILD_RS).unwrap();
+    dir
+}
+
+fn doctor(project: &tempfile::TempDir, args: &[&str]) -> (bool, String, String) {
+    let args: Vec<&str> = ["doctor"].into_iter().chain(args.iter().copied()).collect();
+    let (code, stdout, stderr) = common::run_basic(project.path(), &args);
+    (code == Some(0), stdout, stderr)
+}
+
+fn read(project: &tempfile::TempDir, file: &str) -> String {
+    common::read(project.path(), file)
+}
+
+// [verify cli.doctor.checks]
+// [verify cli.doctor.confirm]
+#[test]
+fn doctor_reports_without_fixing_when_non_interactive() {
+    let project = project();
+    let (ok, stdout, stderr) = doctor(&project, &[]);
+    assert!(!ok);
+    assert!(stdout.contains("no managed-deps"), "stdout: {stdout}");
+    assert!(
+        stdout.contains("old_battery_pack::validate()"),
+        "stdout: {stdout}"
+    );
+    assert!(
+        stderr.contains("2 problem(s) left unfixed"),
+        "stderr: {stderr}"
+    );
+    assert_eq!(read(&project, "Cargo.toml"), MANIFEST);
+    assert_eq!(read(&project, "build.rs"), BUILD_RS);
+}
+
+// [verify cli.doctor.fix]
+#[test]
+fn doctor_fix_repairs_metadata_and_build_rs() {
+    let project = project();
+    let (ok, stdout, stderr) = doctor(&project, &["--fix"]);
+    assert!(ok, "doctor --fix failed: {stderr}");
+    assert!(stdout.contains("Fixed 2 of 2"), "stdout: {stdout}");
+
+    // thiserror is curated but not in the manifest, so it isn't claimed.
+    assert!(read(&project, "Cargo.toml").contains("managed-deps = [\"anyhow\"]"));
+    let build_rs = read(&project, "build.rs");
+    assert!(build_rs.contains("basic_battery_pack::validate();"));
+    assert!(!build_rs.contains("old_battery_pack"));
+
+    let (ok, stdout, _) = doctor(&project, &[]);
+    assert!(ok);
+    assert!(stdout.contains("No problems found."), "stdout: {stdout}");
+}
@@ -0,0 +1,154 @@
+//! Tests for `cargo bp new battery-pack --from <project>`.
+
+mod common;
+
+use std::path::Path;
+
+/// A workspace whose `app` member inherits some of its dependencies.
+fn write_project(root: &Path) {
+    std::fs::write(
+        root.join("Cargo.toml"),
+        r#"[workspace]
+members = ["app", "util"]
+resolver = "2"
+
+[workspace.dependencies]
+serde = { version = "1.0", features = ["derive"] }
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(root.join("app/src")).unwrap();
+    std::fs::write(
+        root.join("app/Cargo.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "1"
+serde = { workspace = true }
+serde_json = "1"
+util = { path = "../util" }
+
+[dev-dependencies]
+insta = "1"
+
+[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+"#,
+    )
+    .unwrap();
+    std::fs::write(root.join("app/src/lib.rs"), "").unwrap();
+    std::fs::create_dir_all(root.join("util/src")).unwrap();
+    std::fs::write(
+        root.join("util/Cargo.toml"),
+        r#"[package]
+name = "util"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde_json = { version = "1.0.100", features = ["raw_value"] }
+"#,
+    )
+    .unwrap();
+    std::fs::write(root.join("util/src/lib.rs"), "").unwrap();
+}
+
+fn extract(project: &Path, out: &Path, extra: &[&str]) -> assert_cmd::assert::Assert {
+    common::cargo_bp()
+        .args(["bp", "new", "battery-pack", "--name", "demo", "--path"])
+        .arg(common::battery_pack_root())
+        .arg("--from")
+        .arg(project)
+        .args(extra)
+        .current_dir(out)
+        .assert()
+}
+
+// [verify cli.new.from]
+// [verify cli.new.from.read]
+// [verify cli.new.from.features]
+// [verify cli.new.from.layout]
+#[test]
+fn extract_workspace_into_pack() {
+    let project = tempfile::tempdir().unwrap();
+    write_project(project.path());
+    let out = tempfile::tempdir().unwrap();
+
+    let output = extract(
+        project.path(),
+        out.path(),
+        &["--group", "json=serde,serde_json"],
+    )
+    .success()
+    .get_output()
+    .clone();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("skipping 'winapi'"), "{stderr}");
+
+    let pack = out.path().join("demo-battery-pack");
+    for file in ["build.rs", "docs.handlebars.md", "src/lib.rs"] {
+        assert!(pack.join(file).exists(), "{file} missing");
+    }
+    let manifest: toml::Value =
+        toml::from_str(&std::fs::read_to_string(pack.join("Cargo.toml")).unwrap()).unwrap();
+
+    let deps = &manifest["dependencies"];
+    assert_eq!(deps["serde"]["version"].as_str(), Some("1.0"));
+    assert_eq!(
+        deps["serde"]["features"],
+        toml::Value::Array(vec!["derive".into()])
+    );
+    assert_eq!(deps["serde"]["optional"].as_bool(), Some(true));
+    // The highest requirement and both members' features are kept.
+    assert_eq!(deps["serde_json"]["version"].as_str(), Some("1.0.100"));
+    assert_eq!(
+        deps["serde_json"]["features"],
+        toml::Value::Array(vec!["raw_value".into()])
+    );
+    assert!(deps.get("util").is_none());
+    assert!(deps.get("winapi").is_none());
+    assert_eq!(
+        manifest["dev-dependencies"]["insta"]["version"].as_str(),
+        Some("1")
+    );
+
+    let features = &manifest["features"];
+    assert_eq!(
+        features["default"],
+        toml::Value::Array(vec!["anyhow".into()])
+    );
+    assert_eq!(
+        features["json"],
+        toml::Value::Array(vec!["serde".into(), "serde_json".into()])
+    );
+    assert_eq!(
+        manifest["package"]["keywords"],
+        toml::Value::Array(vec!["battery-pack".into()])
+    );
+    assert_eq!(
+        manifest["package"]["metadata"]["battery-pack"]["hidden"],
+        toml::Value::Array(vec!["battery-pack".into()])
+    );
+}
+
+// [verify cli.new.from.features]
+#[test]
+fn extract_rejects_grouping_unknown_crate() {
+    let project = tempfile::tempdir().unwrap();
+    write_project(project.path());
+    let out = tempfile::tempdir().unwrap();
+
+    let output = extract(project.path(), out.path(), &["--group", "cli=clap"])
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("'clap' is not one of the extracted crates"),
+        "{stderr}"
+    );
+    assert!(!out.path().join("demo-battery-pack").exists());
+}
@@ -0,0 +1,109 @@
+//! Integration tests for `--message-format json`.
+
+use assert_cmd::Command;
+use std::path::Path;
+
+fn cargo_bp() -> Command {
+    Command::new(assert_cmd::cargo::cargo_bin!("cargo-bp"))
+}
+
+fn fixtures_dir() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("tests/fixtures")
+}
+
+fn run_json(cmd: &mut Command) -> serde_json::Value {
+    let output = cmd.output().expect("failed to run cargo-bp");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!(
+            "stdout is not JSON ({e}):\n{}",
+            String::from_utf8_lossy(&output.stdout)
+        )
+    })
+}
+
+// [verify cli.json.list]
+#[test]
+fn list_json_from_local_source() {
+    let json = run_json(cargo_bp().args([
+        "bp",
+        "--crate-source",
+        &fixtures_dir().to_string_lossy(),
+        "list",
+        "--non-interactive",
+        "--message-format",
+        "json",
+    ]));
+
+    assert_eq!(json["schema_version"], 2);
+    assert_eq!(json["kind"], "list");
+    let names: Vec<&str> = json["packs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"basic-battery-pack"), "{names:?}");
+}
+
+// [verify cli.json.status]
+#[test]
+fn status_json_reports_crate_drift() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join("Cargo.toml"),
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "0.9"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+"#,
+    )
+    .unwrap();
+
+    let fixture = fixtures_dir().join("basic-battery-pack");
+    let json = run_json(cargo_bp().current_dir(project.path()).args([
+        "bp",
+        "status",
+        "--path",
+        &fixture.to_string_lossy(),
+        "--message-format",
+        "json",
+    ]));
+
+    assert_eq!(json["kind"], "status");
+    let pack = &json["packs"][0];
+    assert_eq!(pack["name"], "basic-battery-pack");
+    assert_eq!(pack["registered_version"], "0.1");
+    assert_eq!(pack["release"], serde_json::Value::Null);
+
+    let drift: Vec<(&str, &str)> = pack["crates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["name"].as_str().unwrap(),
+                c["drift"].as_str().unwrap_or("none"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        drift,
+        [("anyhow", "outdated"), ("thiserror", "missing-crate")]
+    );
+}
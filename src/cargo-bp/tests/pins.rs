@@ -0,0 +1,114 @@
+//! Tests for pinned crates and the sync policy.
+
+mod common;
+
+use common::{basic_fixture, cargo_bp, output};
+
+/// A project holding `anyhow` back with a pin and `thiserror` with an
+/// exact requirement; basic-battery-pack recommends 1 and 2.
+fn project() -> tempfile::TempDir {
+    common::project(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "0.1"
+thiserror = "=1.0.50"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["default"]
+pinned = ["anyhow"]
+"#,
+    )
+}
+
+fn run(project: &tempfile::TempDir, args: &[&str]) -> (Option<i32>, String, String) {
+    output(
+        cargo_bp()
+            .current_dir(project.path())
+            .env("CARGO_BP_SYNC_POLICY", "allow-breaking")
+            .arg("bp")
+            .args(args)
+            .args(["--path", &basic_fixture()]),
+    )
+}
+
+// [verify cli.pins.metadata]
+// [verify cli.pins.exact]
+#[test]
+fn sync_leaves_pinned_versions_alone() {
+    let project = project();
+    let (code, stdout, stderr) = run(&project, &["sync"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+
+    let manifest = std::fs::read_to_string(project.path().join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("anyhow = \"0.1\""), "{manifest}");
+    assert!(manifest.contains("thiserror = \"=1.0.50\""), "{manifest}");
+    assert!(manifest.contains("pinned = [\"anyhow\"]"), "{manifest}");
+}
+
+// [verify cli.pins.report]
+#[test]
+fn check_reports_pins_apart_from_drift() {
+    let project = project();
+    let (code, stdout, stderr) = run(&project, &["check"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(stdout.contains("✅ OK"), "{stdout}");
+    assert!(
+        stdout.contains("anyhow: pinned at 0.1 (1 recommended)"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("thiserror: exact requirement =1.0.50 (2 recommended)"),
+        "{stdout}"
+    );
+
+    let (_, stdout, _) = run(&project, &["status", "--message-format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["status"], "clean");
+    let held: Vec<(&str, &str)> = json["packs"][0]["held"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| (h["crate"].as_str().unwrap(), h["reason"].as_str().unwrap()))
+        .collect();
+    assert_eq!(held, [("anyhow", "pinned"), ("thiserror", "exact")]);
+}
+
+// [verify cli.pins.policy]
+#[test]
+fn status_honors_the_sync_policy() {
+    let dir = common::project(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "0.1"
+thiserror = "2"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["default"]
+"#,
+    );
+    let (code, stdout, _) = output(
+        cargo_bp()
+            .current_dir(dir.path())
+            .env("CARGO_BP_SYNC_POLICY", "compatible-only")
+            .args(["bp", "check", "--path", &basic_fixture()]),
+    );
+    assert_eq!(code, Some(0), "{stdout}");
+    assert!(
+        stdout.contains("anyhow: kept at 0.1 by sync policy compatible-only (1 recommended)"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,162 @@
+//! Tests for `cargo bp sync --prune`.
+
+mod common;
+
+/// A project whose basic-battery-pack metadata still records `indicatif`
+/// and anyhow's `backtrace` feature, neither of which the pack curates
+/// any more. `rc` on serde was added by hand.
+fn project() -> tempfile::TempDir {
+    common::project(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = { version = "1", features = ["backtrace", "std"] }
+indicatif = "0.17"
+serde = { version = "1", features = ["rc"] }
+thiserror = "2"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["default"]
+managed-deps = ["anyhow", "indicatif", "thiserror"]
+managed-features = { anyhow = ["backtrace"] }
+"#,
+    )
+}
+
+fn run(project: &tempfile::TempDir, args: &[&str]) -> (Option<i32>, String, String) {
+    common::run_basic(project.path(), args)
+}
+
+fn manifest(project: &tempfile::TempDir) -> String {
+    common::read(project.path(), "Cargo.toml")
+}
+
+#[test]
+fn sync_without_prune_removes_nothing() {
+    let project = project();
+    let (code, stdout, stderr) = run(&project, &["sync"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+
+    let manifest = manifest(&project);
+    assert!(manifest.contains("indicatif = \"0.17\""), "{manifest}");
+    assert!(manifest.contains("\"backtrace\""), "{manifest}");
+}
+
+// [verify cli.sync.prune]
+#[test]
+fn prune_removes_crates_the_pack_no_longer_curates() {
+    let project = project();
+    let (code, stdout, _) = run(&project, &["sync", "--prune", "--check"]);
+    assert_ne!(code, Some(0));
+    assert!(stdout.contains("-indicatif = \"0.17\""), "{stdout}");
+    assert!(manifest(&project).contains("indicatif"));
+
+    let (code, stdout, stderr) = run(&project, &["sync", "--prune"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(stdout.contains("  - indicatif"), "{stdout}");
+
+    let manifest = manifest(&project);
+    assert!(!manifest.contains("indicatif"), "{manifest}");
+    assert!(manifest.contains("serde = "), "{manifest}");
+    assert!(
+        manifest.contains("managed-deps = [\"anyhow\", \"thiserror\"]"),
+        "{manifest}"
+    );
+    // Features are only pruned on request.
+    assert!(manifest.contains("\"backtrace\""), "{manifest}");
+}
+
+// [verify cli.sync.prune-features]
+#[test]
+fn prune_features_drops_only_recorded_features() {
+    let project = project();
+    assert!(run(&project, &["sync", "--prune-features"]).0 != Some(0));
+
+    let (code, stdout, stderr) = run(&project, &["sync", "--prune", "--prune-features"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+
+    let manifest = manifest(&project);
+    assert!(
+        manifest.contains("anyhow = { version = \"1\", features = [\"std\"] }"),
+        "{manifest}"
+    );
+    assert!(manifest.contains("features = [\"rc\"]"), "{manifest}");
+    assert!(!manifest.contains("managed-features"), "{manifest}");
+}
+
+/// A one-crate pack, curating anyhow with `features`.
+fn write_pack(dir: &std::path::Path, features: &str) {
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "basic-battery-pack"
+version = "0.1.0"
+edition = "2021"
+keywords = ["battery-pack"]
+
+[dependencies]
+anyhow = {{ version = "1", features = [{features}], optional = true }}
+
+[features]
+default = ["anyhow"]
+"#
+        ),
+    )
+    .unwrap();
+}
+
+// [verify cli.sync.prune-features]
+#[test]
+fn prune_features_keeps_features_the_user_declared() {
+    let pack = tempfile::tempdir().unwrap();
+    write_pack(pack.path(), r#""backtrace", "std""#);
+    let project = common::project(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = { version = "1", features = ["backtrace"] }
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["default"]
+managed-deps = ["anyhow"]
+"#,
+    );
+    let pack_path = pack.path().to_string_lossy().into_owned();
+    let sync = |args: &[&str]| {
+        let args: Vec<&str> = args.iter().copied().chain(["--path", &pack_path]).collect();
+        common::run(project.path(), &args)
+    };
+
+    let (code, stdout, stderr) = sync(&["sync"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(
+        manifest(&project).contains("managed-features = { anyhow = [\"std\"] }"),
+        "{}",
+        manifest(&project)
+    );
+
+    // The pack drops both features; only the one sync added goes.
+    write_pack(pack.path(), "");
+    let (code, stdout, stderr) = sync(&["sync", "--prune", "--prune-features"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    let manifest = manifest(&project);
+    assert!(
+        manifest.contains("anyhow = { version = \"1\", features = [\"backtrace\"] }"),
+        "{manifest}"
+    );
+}
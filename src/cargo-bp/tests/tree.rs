@@ -0,0 +1,75 @@
+//! Tests for `cargo bp tree`.
+
+mod common;
+
+use common::{basic_fixture, run};
+
+/// A project with basic-battery-pack's `all-errors` feature and an
+/// outdated `anyhow`.
+fn project() -> tempfile::TempDir {
+    common::project(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "0.9"
+thiserror = "2"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["all-errors"]
+"#,
+    )
+}
+
+fn tree(project: &tempfile::TempDir, format: &str) -> String {
+    let (code, stdout, stderr) = run(
+        project.path(),
+        &["tree", "--path", &basic_fixture(), "--format", format],
+    );
+    assert_eq!(code, Some(0), "{stderr}");
+    stdout
+}
+
+// [verify cli.tree.structure]
+// [verify cli.tree.drift]
+#[test]
+fn tree_text_shows_features_crates_and_drift() {
+    let project = project();
+    let text = tree(&project, "text");
+    assert_eq!(
+        text,
+        "\
+basic (0.1)
+└── all-errors
+    ├── anyhow 0.9 [dependencies] ⚠ 0.9 → 1 recommended
+    ├── eyre [dependencies] ✗ missing from [dependencies]
+    └── thiserror 2 [dependencies]
+"
+    );
+}
+
+// [verify cli.tree.formats]
+#[test]
+fn tree_dot_and_json() {
+    let project = project();
+
+    let dot = tree(&project, "dot");
+    assert!(dot.starts_with("digraph battery_packs {"), "{dot}");
+    assert!(
+        dot.contains("\"feature:basic-battery-pack/all-errors\" -> \"crate:eyre\";"),
+        "{dot}"
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&tree(&project, "json")).unwrap();
+    assert_eq!(json["kind"], "tree");
+    let crates = &json["packs"][0]["features"][0]["crates"];
+    assert_eq!(crates[0]["name"], "anyhow");
+    assert_eq!(crates[0]["actual"], "0.9");
+    assert_eq!(crates[0]["drift"], "outdated");
+    assert_eq!(crates[1]["drift"], "missing-crate");
+}
@@ -0,0 +1,86 @@
+//! Tests for `cargo bp undo`.
+
+mod common;
+
+use common::basic_fixture;
+
+const MANIFEST: &str = r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["default"]
+"#;
+
+/// A project with basic-battery-pack registered but none of its crates.
+fn project() -> tempfile::TempDir {
+    common::project(MANIFEST)
+}
+
+fn run(project: &tempfile::TempDir, args: &[&str]) -> (bool, String, String) {
+    let (code, stdout, stderr) = common::run(project.path(), args);
+    (code == Some(0), stdout, stderr)
+}
+
+fn sync(project: &tempfile::TempDir) {
+    let (ok, _, stderr) = run(project, &["sync", "--path", &basic_fixture()]);
+    assert!(ok, "sync failed: {stderr}");
+}
+
+fn manifest(project: &tempfile::TempDir) -> String {
+    common::read(project.path(), "Cargo.toml")
+}
+
+// [verify cli.undo.snapshot]
+// [verify cli.undo.restore]
+#[test]
+fn undo_restores_the_manifest_sync_changed() {
+    let project = project();
+    sync(&project);
+    assert!(manifest(&project).contains("anyhow"));
+
+    let (ok, stdout, stderr) = run(&project, &["undo"]);
+    assert!(ok, "undo failed: {stderr}");
+    assert!(stdout.contains("cargo bp sync"), "stdout: {stdout}");
+    assert_eq!(manifest(&project), MANIFEST);
+
+    let (ok, _, stderr) = run(&project, &["undo"]);
+    assert!(!ok);
+    assert!(stderr.contains("Nothing to undo"), "stderr: {stderr}");
+}
+
+// [verify cli.undo.refuse-changed]
+#[test]
+fn undo_refuses_when_the_file_changed_since() {
+    let project = project();
+    sync(&project);
+    let edited = manifest(&project) + "\n[features]\nextra = []\n";
+    std::fs::write(project.path().join("Cargo.toml"), &edited).unwrap();
+
+    let (ok, _, stderr) = run(&project, &["undo"]);
+    assert!(!ok);
+    assert!(
+        stderr.contains("Cargo.toml has changed since"),
+        "stderr: {stderr}"
+    );
+    assert_eq!(manifest(&project), edited);
+}
+
+// [verify cli.undo.list]
+#[test]
+fn undo_list_shows_the_command_and_its_files() {
+    let project = project();
+    let (ok, stdout, _) = run(&project, &["undo", "--list"]);
+    assert!(ok);
+    assert!(stdout.contains("No cargo bp snapshots"), "stdout: {stdout}");
+
+    sync(&project);
+    let (ok, stdout, stderr) = run(&project, &["undo", "--list"]);
+    assert!(ok, "undo --list failed: {stderr}");
+    assert!(stdout.contains("cargo bp sync --path"), "stdout: {stdout}");
+    assert!(stdout.contains("modified  Cargo.toml"), "stdout: {stdout}");
+}
@@ -0,0 +1,91 @@
+//! Tests for `cargo bp upgrade` against a local battery pack source.
+
+use assert_cmd::Command;
+use std::path::Path;
+
+fn cargo_bp() -> Command {
+    Command::new(assert_cmd::cargo::cargo_bin!("cargo-bp"))
+}
+
+fn fixtures_dir() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("tests/fixtures")
+}
+
+/// A project registering basic-battery-pack 0.0.5; the local source has 0.1.0.
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("Cargo.toml"),
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "1"
+
+[build-dependencies]
+basic-battery-pack = "0.0.5"
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+fn upgrade(project: &tempfile::TempDir, extra: &[&str]) -> String {
+    let output = cargo_bp()
+        .current_dir(project.path())
+        .args(["bp", "--crate-source", &fixtures_dir().to_string_lossy()])
+        .args(["upgrade", "basic"])
+        .args(extra)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(
+        output.status.success(),
+        "stdout: {stdout}\nstderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    stdout
+}
+
+// [verify cli.upgrade.compatible]
+#[test]
+fn upgrade_stays_within_the_registered_requirement() {
+    let project = project();
+    let before = std::fs::read_to_string(project.path().join("Cargo.toml")).unwrap();
+
+    let stdout = upgrade(&project, &[]);
+
+    assert!(stdout.contains("basic 0.0.5 is up to date"), "{stdout}");
+    assert!(stdout.contains("--breaking"), "{stdout}");
+    let after = std::fs::read_to_string(project.path().join("Cargo.toml")).unwrap();
+    assert_eq!(before, after);
+}
+
+// [verify cli.upgrade.breaking]
+// [verify cli.upgrade.changelog]
+// [verify cli.upgrade.sync]
+#[test]
+fn breaking_upgrade_updates_registration_and_syncs() {
+    let project = project();
+
+    let stdout = upgrade(&project, &["--breaking"]);
+
+    assert!(stdout.contains("basic 0.0.5 → 0.1.0"), "{stdout}");
+    assert!(stdout.contains("## [0.1.0] - 2026-01-01"), "{stdout}");
+    let manifest = std::fs::read_to_string(project.path().join("Cargo.toml")).unwrap();
+    assert!(
+        manifest.contains("basic-battery-pack = \"0.1.0\""),
+        "{manifest}"
+    );
+    assert!(
+        manifest.contains("thiserror"),
+        "sync should add thiserror:\n{manifest}"
+    );
+}
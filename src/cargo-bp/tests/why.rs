@@ -0,0 +1,78 @@
+//! Tests for `cargo bp why`.
+
+mod common;
+
+use common::{basic_fixture, run};
+
+/// A project using basic-battery-pack's default crates, plus `serde`.
+fn project() -> tempfile::TempDir {
+    common::project(
+        r#"[package]
+name = "test-project"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "1"
+thiserror = "2"
+serde = "1"
+
+[build-dependencies]
+basic-battery-pack = "0.1"
+
+[package.metadata.battery-pack.basic-battery-pack]
+features = ["default"]
+managed-deps = ["anyhow", "thiserror"]
+"#,
+    )
+}
+
+fn why(project: &tempfile::TempDir, crate_name: &str) -> (bool, String, String) {
+    let (code, stdout, stderr) = run(
+        project.path(),
+        &["why", crate_name, "--path", &basic_fixture()],
+    );
+    (code == Some(0), stdout, stderr)
+}
+
+// [verify cli.why.packs]
+// [verify cli.why.winner]
+// [verify cli.why.ownership]
+#[test]
+fn why_traces_a_managed_crate_to_its_pack() {
+    let project = project();
+    let (ok, stdout, stderr) = why(&project, "anyhow");
+    assert!(ok, "{stderr}");
+    assert!(stdout.contains("declared in [dependencies]: 1"), "{stdout}");
+    assert!(stdout.contains("managed by basic"), "{stdout}");
+    assert!(
+        stdout.contains("basic 0.1.0 recommends 1 in [dependencies] via feature default"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("→ 1 in [dependencies] (from basic)"),
+        "{stdout}"
+    );
+}
+
+// [verify cli.why.packs]
+// [verify cli.why.ownership]
+#[test]
+fn why_reports_user_owned_and_inactive_crates() {
+    let project = project();
+    let (ok, stdout, _) = why(&project, "serde");
+    assert!(ok);
+    assert!(stdout.contains("user-owned"), "{stdout}");
+
+    let (ok, stdout, _) = why(&project, "eyre");
+    assert!(ok);
+    assert!(stdout.contains("not in Cargo.toml"), "{stdout}");
+    assert!(stdout.contains("enable all-errors to add it"), "{stdout}");
+
+    let (ok, _, stderr) = why(&project, "tokio");
+    assert!(!ok);
+    assert!(
+        stderr.contains("no installed battery pack curates it"),
+        "{stderr}"
+    );
+}
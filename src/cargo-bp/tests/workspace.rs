@@ -0,0 +1,114 @@
+//! Tests for `--workspace`, `-p` and `--exclude`.
+
+mod common;
+
+use common::run_basic as run;
+use std::path::Path;
+
+fn member(root: &Path, name: &str, deps: &str, packs: &str) {
+    let dir = root.join("crates").join(name);
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [dependencies]\n{deps}\n{packs}"
+        ),
+    )
+    .unwrap();
+}
+
+/// A workspace where `app` is in sync with basic-battery-pack, `lib` is
+/// missing one of its crates, and `plain` has no battery packs.
+fn workspace() -> tempfile::TempDir {
+    let dir = common::project("[workspace]\nmembers = [\"crates/*\"]\nresolver = \"2\"\n");
+    let packs = "\n[build-dependencies]\nbasic-battery-pack = \"0.1\"\n\n\
+                 [package.metadata.battery-pack.basic-battery-pack]\n\
+                 features = [\"default\"]\n";
+    member(
+        dir.path(),
+        "app",
+        "anyhow = \"1\"\nthiserror = \"2\"",
+        packs,
+    );
+    member(dir.path(), "lib", "anyhow = \"1\"", packs);
+    member(dir.path(), "plain", "serde = \"1\"", "");
+    dir
+}
+
+// [verify cli.workspace.select]
+// [verify cli.workspace.combined]
+#[test]
+fn check_workspace_combines_members_into_one_document() {
+    let ws = workspace();
+    let (code, stdout, stderr) = run(
+        ws.path(),
+        &["check", "--workspace", "--message-format", "json"],
+    );
+    assert_eq!(code, Some(3), "{stdout}\n{stderr}");
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["kind"], "check");
+    assert_eq!(json["ok"], false);
+    assert_eq!(json["status"], "errors");
+    let members: Vec<(&str, &str)> = json["members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| {
+            (
+                m["package"].as_str().unwrap(),
+                m["status"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(members, [("app", "clean"), ("lib", "errors")]);
+    assert_eq!(json["skipped"], serde_json::json!(["plain"]));
+
+    let (code, stdout, stderr) = run(ws.path(), &["check", "--workspace", "--exclude", "lib"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(stdout.contains("app (crates/app)"), "{stdout}");
+    assert!(!stdout.contains("lib (crates/lib)"), "{stdout}");
+    assert!(
+        stdout.contains("Workspace: 1 member(s): 1 clean (1 without battery packs skipped)"),
+        "{stdout}"
+    );
+}
+
+// [verify cli.workspace.combined]
+#[test]
+fn sync_check_workspace_reports_every_member() {
+    let ws = workspace();
+    // Make `app` drift too, so two members are out of sync.
+    let app = ws.path().join("crates/app/Cargo.toml");
+    let content = std::fs::read_to_string(&app).unwrap();
+    std::fs::write(&app, content.replace("thiserror = \"2\"\n", "")).unwrap();
+
+    let (code, stdout, stderr) = run(ws.path(), &["sync", "--check", "--workspace"]);
+    assert_ne!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(stdout.contains("app (crates/app)"), "{stdout}");
+    assert!(stdout.contains("lib (crates/lib)"), "{stdout}");
+    assert!(
+        stderr.contains("2 workspace member(s) are out of sync"),
+        "{stderr}"
+    );
+}
+
+// [verify cli.workspace.each]
+#[test]
+fn sync_package_only_touches_the_selected_member() {
+    let ws = workspace();
+    let (code, stdout, stderr) = run(ws.path(), &["sync", "-p", "lib"]);
+    assert_eq!(code, Some(0), "{stdout}\n{stderr}");
+    assert!(stdout.contains("lib (crates/lib)"), "{stdout}");
+
+    let lib = std::fs::read_to_string(ws.path().join("crates/lib/Cargo.toml")).unwrap();
+    assert!(lib.contains("thiserror"), "{lib}");
+    let plain = std::fs::read_to_string(ws.path().join("crates/plain/Cargo.toml")).unwrap();
+    assert!(!plain.contains("anyhow"), "{plain}");
+
+    let (code, _, stderr) = run(ws.path(), &["status", "-p", "nope"]);
+    assert_ne!(code, Some(0));
+    assert!(stderr.contains("'nope' is not a member"), "{stderr}");
+}